@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::{fs, io, path, time};
+
+use crate::error::Error;
+use crate::source::{SourceEntry, SourceFileType, SourceMetadata, WalkSource};
+use crate::tree;
+
+/// [`WalkSource`] for the entries of a `.zip`, `.tar` or `.tar.gz` archive.
+///
+/// The entries of the archive are read once when creating the source, their contents are never
+/// extracted. The top level of the archive is the empty path or `.`, i.e., matching the archive
+/// with [`Builder::build_source`](crate::Builder::build_source) relative to the top level
+/// yields the names of the entries. Entries with absolute names or names that contain `..`
+/// components are skipped. Modification times are only provided for the entries of `.tar`
+/// archives. Requires the `archive` feature.
+///
+/// ```no_run
+/// # fn example() -> Result<(), String> {
+/// let archive = globmatch::ArchiveSource::open("release.tar.gz")?;
+/// let wasm: Vec<_> = globmatch::Builder::new("dist/**/*.wasm")
+///     .build_source("", &archive)?
+///     .flatten()
+///     .collect();
+///
+/// assert!(!wasm.is_empty(), "missing wasm modules");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveSource {
+    /// Normalized names of all entries, including the implicit directories.
+    entries: BTreeMap<path::PathBuf, SourceMetadata>,
+}
+
+impl ArchiveSource {
+    /// Reads the entries of the archive at the given path.
+    ///
+    /// The format is determined by the extension of the file, i.e., `.zip`, `.tar`, `.tar.gz`
+    /// or `.tgz`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the file cannot be read or if its format is not supported.
+    pub fn open<P>(p: P) -> Result<ArchiveSource, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let p = p.as_ref();
+        let name = p.file_name().unwrap_or_default().to_string_lossy();
+        let name = name.to_lowercase();
+        let file = || {
+            fs::File::open(p)
+                .map(io::BufReader::new)
+                .map_err(|err| archive_error(p, err))
+        };
+        if name.ends_with(".zip") {
+            ArchiveSource::from_zip(file()?)
+        } else if name.ends_with(".tar") {
+            ArchiveSource::from_tar(file()?)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            ArchiveSource::from_tar_gz(file()?)
+        } else {
+            Err(Error::walk(format!(
+                "Failed to read archive {}: Unsupported format",
+                p.to_string_lossy()
+            )))
+        }
+    }
+
+    /// Reads the entries of a `.zip` archive.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the archive cannot be read.
+    pub fn from_zip<R>(reader: R) -> Result<ArchiveSource, Error>
+    where
+        R: io::Read + io::Seek,
+    {
+        let zip_error = |err: zip::result::ZipError| archive_error("<zip>", err);
+        let mut zip = zip::ZipArchive::new(reader).map_err(zip_error)?;
+        let mut source = ArchiveSource::default();
+        for i in 0..zip.len() {
+            let entry = zip.by_index_raw(i).map_err(zip_error)?;
+            let file_type = if entry.is_dir() {
+                SourceFileType::Dir
+            } else if entry.is_symlink() {
+                SourceFileType::Symlink
+            } else {
+                SourceFileType::File
+            };
+            if let Some(name) = entry.enclosed_name() {
+                source.insert(&name, file_type, entry.size(), None);
+            }
+        }
+        Ok(source)
+    }
+
+    /// Reads the entries of a `.tar` archive.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the archive cannot be read.
+    pub fn from_tar<R>(reader: R) -> Result<ArchiveSource, Error>
+    where
+        R: io::Read,
+    {
+        let tar_error = |err: io::Error| archive_error("<tar>", err);
+        let mut tar = tar::Archive::new(reader);
+        let mut source = ArchiveSource::default();
+        for entry in tar.entries().map_err(tar_error)? {
+            let entry = entry.map_err(tar_error)?;
+            let header = entry.header();
+            let file_type = match header.entry_type() {
+                tar::EntryType::Directory => SourceFileType::Dir,
+                tar::EntryType::Symlink => SourceFileType::Symlink,
+                tar::EntryType::Regular | tar::EntryType::Continuous => SourceFileType::File,
+                // hard links are yielded like the files they link to
+                tar::EntryType::Link => SourceFileType::File,
+                // extended headers are consumed by `tar` and never yielded
+                _ => SourceFileType::Other,
+            };
+            let modified = header
+                .mtime()
+                .ok()
+                .map(|secs| time::UNIX_EPOCH + time::Duration::from_secs(secs));
+            let name = entry.path().map_err(tar_error)?;
+            source.insert(&name, file_type, entry.size(), modified);
+        }
+        Ok(source)
+    }
+
+    /// Reads the entries of a gzip compressed `.tar` archive.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the archive cannot be read.
+    pub fn from_tar_gz<R>(reader: R) -> Result<ArchiveSource, Error>
+    where
+        R: io::Read,
+    {
+        ArchiveSource::from_tar(flate2::read::GzDecoder::new(reader))
+    }
+
+    /// Provides the number of entries within the archive, including the implicit directories.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether the archive does not contain any entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts the entry with the given name and all of its ancestors as directories.
+    fn insert(
+        &mut self,
+        name: &path::Path,
+        file_type: SourceFileType,
+        len: u64,
+        modified: Option<time::SystemTime>,
+    ) {
+        // names such as `a/../b` are rejected even though they do not escape the top level
+        if name
+            .components()
+            .any(|c| matches!(c, path::Component::ParentDir))
+        {
+            return;
+        }
+        let key = tree::key(name);
+        let escapes = key
+            .components()
+            .any(|c| !matches!(c, path::Component::Normal(_)));
+        if key.as_os_str().is_empty() || escapes {
+            return;
+        }
+        for ancestor in key.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            self.entries
+                .entry(path::PathBuf::from(ancestor))
+                .or_insert(SourceMetadata {
+                    file_type: SourceFileType::Dir,
+                    len: 0,
+                    modified: None,
+                });
+        }
+        let metadata = SourceMetadata {
+            file_type,
+            len,
+            modified,
+        };
+        self.entries.insert(key, metadata);
+    }
+}
+
+impl WalkSource for ArchiveSource {
+    fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>> {
+        let key = tree::key(dir);
+        if self.metadata(dir)?.file_type != SourceFileType::Dir {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a directory",
+            ));
+        }
+        // all descendants of a directory directly follow the directory itself
+        let entries = self
+            .entries
+            .range::<path::Path, _>((Bound::Excluded(key.as_path()), Bound::Unbounded))
+            .take_while(|(p, _)| p.starts_with(&key))
+            .filter(|(p, _)| p.parent() == Some(key.as_path()))
+            .map(|(p, metadata)| SourceEntry {
+                path: dir.join(p.file_name().unwrap_or_default()),
+                metadata: *metadata,
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    fn metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        let key = tree::key(p);
+        if key.as_os_str().is_empty() {
+            return Ok(SourceMetadata {
+                file_type: SourceFileType::Dir,
+                len: 0,
+                modified: None,
+            });
+        }
+        self.entries
+            .get(&key)
+            .copied()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        self.metadata(p)
+    }
+}
+
+fn archive_error<P, E>(p: P, err: E) -> Error
+where
+    P: AsRef<path::Path>,
+    E: std::fmt::Display,
+{
+    Error::walk(format!(
+        "Failed to read archive {}: {err}",
+        p.as_ref().to_string_lossy()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Builder};
+    use std::io::Write;
+
+    const NAMES: [&str; 6] = [
+        "dist/app.wasm",
+        "dist/lib/core.wasm",
+        "dist/lib/core.js",
+        "README.md",
+        "../escape.wasm",
+        "dist/../dist/smuggled.wasm",
+    ];
+
+    fn zip() -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(io::Cursor::new(vec![]));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.add_directory("empty/", options).unwrap();
+        for name in NAMES {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar() -> Vec<u8> {
+        let mut tar = tar::Builder::new(vec![]);
+        for name in NAMES.iter().filter(|name| !name.contains("..")) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(name.len() as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, name, name.as_bytes()).unwrap();
+        }
+        tar.into_inner().unwrap()
+    }
+
+    fn wasm(source: &ArchiveSource) -> Result<Vec<path::PathBuf>, String> {
+        let mut paths: Vec<_> = Builder::new("dist/**/*.wasm")
+            .build_source("", source)?
+            .collect::<Result<_, _>>()?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    #[test]
+    fn zip_archive() -> Result<(), String> {
+        let source = ArchiveSource::from_zip(io::Cursor::new(zip()))?;
+        assert_eq!(4 + 3, source.len());
+        assert_eq!(
+            vec![
+                path::PathBuf::from("dist/app.wasm"),
+                path::PathBuf::from("dist/lib/core.wasm"),
+            ],
+            wasm(&source)?
+        );
+        assert_eq!(
+            "dist/lib/core.js".len() as u64,
+            source.metadata("./dist/lib/core.js".as_ref()).unwrap().len
+        );
+
+        let paths: Vec<_> = Builder::new("*")
+            .file_type(crate::FileType::Dirs)
+            .build_source(".", &source)?
+            .flatten()
+            .collect();
+        assert_eq!(2, paths.len()); // `dist` and `empty`, but not the top level
+        Ok(())
+    }
+
+    #[test]
+    fn tar_archive() -> Result<(), String> {
+        let tar = tar();
+        let source = ArchiveSource::from_tar(tar.as_slice())?;
+        assert_eq!(4 + 2, source.len());
+        assert_eq!(2, wasm(&source)?.len());
+
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::fast());
+        gz.write_all(&tar).map_err(|err| err.to_string())?;
+        let gz = gz.finish().map_err(|err| err.to_string())?;
+        assert_eq!(source, ArchiveSource::from_tar_gz(gz.as_slice())?);
+
+        let tmp = utils::TempDir::new("archive")?;
+        let p = tmp.write("archive.TGZ", &gz)?;
+        assert_eq!(source, ArchiveSource::open(&p)?);
+
+        assert!(ArchiveSource::open("archive.rar").is_err());
+        assert!(ArchiveSource::from_zip(io::Cursor::new(tar)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::sync::{atomic, Arc};
+
+/// Handle to cancel a running walk, e.g., from a UI or a server that aborts a scan of a huge or
+/// network-mounted tree, see [`Matcher::cancel_on`](crate::Matcher::cancel_on).
+///
+/// All clones of a handle share the same state, i.e., cancelling one clone cancels all walks that
+/// have been configured with any of its clones. The iterators check the handle before each
+/// walked entry, and thus between directory reads. Once cancelled, an iterator yields a single
+/// [`Error::Cancelled`](crate::Error::Cancelled) and ends.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let handle = globmatch::CancellationHandle::new();
+/// let mut paths = globmatch::Builder::new("test-files/**/*.txt")
+///     .build(env!("CARGO_MANIFEST_DIR"))?
+///     .cancel_on(handle.clone())
+///     .into_iter();
+///
+/// assert!(paths.next().is_some_and(|p| p.is_ok()));
+/// handle.cancel();
+/// assert_eq!(Some(Err(globmatch::Error::Cancelled)), paths.next());
+/// assert_eq!(None, paths.next());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationHandle(Arc<atomic::AtomicBool>);
+
+impl CancellationHandle {
+    /// Creates a new handle that has not been cancelled.
+    pub fn new() -> CancellationHandle {
+        CancellationHandle::default()
+    }
+
+    /// Cancels all walks using this handle or any of its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::SeqCst);
+    }
+
+    /// Checks whether the handle has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,363 @@
+use std::{ffi, path, sync};
+
+use crate::error::Error;
+use crate::pattern;
+
+/// Glob with a capture group for each wildcard, see [`Glob::captures`](crate::Glob::captures).
+///
+/// The capture groups are inserted into the regular expression that `globset` generates for the
+/// glob, such that the captures are consistent with the compiled glob and its options. The
+/// regular expression is only compiled when the first path is captured.
+#[derive(Debug)]
+pub(crate) struct CaptureGlob {
+    translated: Option<Translated>,
+    /// Maximum number of path components matched by a globstar, see
+    /// [`Builder::globstar_max_depth`](crate::Builder::globstar_max_depth).
+    max_depth: Option<usize>,
+    compiled: sync::OnceLock<Option<regex::bytes::Regex>>,
+}
+
+/// Regular expression of a glob with the capture groups.
+#[derive(Debug)]
+struct Translated {
+    re: String,
+    /// Number of capture groups, in the order of the wildcards within the glob.
+    captures: usize,
+    /// Indices of the capture groups of regular expression segments.
+    segments: Vec<usize>,
+    /// Indices of the capture groups of globstars.
+    globstars: Vec<usize>,
+}
+
+impl CaptureGlob {
+    /// Creates the capture groups for the glob, which is compiled using the provided function.
+    ///
+    /// Numeric ranges must have been replaced by alternates, and nested alternates must have
+    /// been flattened. No values are captured if the glob cannot be compiled.
+    pub(crate) fn new<F>(glob: &str, max_depth: Option<usize>, compile: F) -> CaptureGlob
+    where
+        F: FnOnce(&str) -> Result<globset::Glob, Error>,
+    {
+        CaptureGlob {
+            translated: pattern::hybrid_regex(glob, compile)
+                .ok()
+                .and_then(|(re, segments)| translate(&re, &segments)),
+            max_depth,
+            compiled: sync::OnceLock::new(),
+        }
+    }
+
+    /// Creates a [`CaptureGlob`] that does not capture any values.
+    pub(crate) fn none() -> CaptureGlob {
+        CaptureGlob {
+            translated: None,
+            max_depth: None,
+            compiled: sync::OnceLock::new(),
+        }
+    }
+
+    /// Provides the values captured by the wildcards of the glob, `None` if the path is not a
+    /// match, if the glob cannot be translated or if any value is not valid UTF-8.
+    pub(crate) fn captures(&self, p: &path::Path) -> Option<Vec<String>> {
+        self.captures_os(p)?
+            .into_iter()
+            .map(|v| v.into_string().ok())
+            .collect()
+    }
+
+    /// Provides the values captured by the wildcards of the glob, `None` if the path is not a
+    /// match or if the glob cannot be translated.
+    pub(crate) fn captures_os(&self, p: &path::Path) -> Option<Vec<ffi::OsString>> {
+        let translated = self.translated.as_ref()?;
+        let re = self
+            .compiled
+            .get_or_init(|| regex::bytes::Regex::new(&translated.re).ok())
+            .as_ref()?;
+        let bytes = pattern::path_bytes(p);
+        let captures = re.captures(&bytes)?;
+
+        let values: Vec<_> = (0..translated.captures)
+            .map(|n| captures.name(&name(n)).map_or(&b""[..], |m| m.as_bytes()))
+            .collect();
+        // each regular expression segment must match exactly one path component
+        if translated
+            .segments
+            .iter()
+            .any(|&n| values[n].contains(&b'/'))
+        {
+            return None;
+        }
+        if let Some(max_depth) = self.max_depth {
+            let depth = |v: &[u8]| v.split(|b| *b == b'/').filter(|c| !c.is_empty()).count();
+            if translated
+                .globstars
+                .iter()
+                .any(|&n| depth(values[n]) > max_depth)
+            {
+                return None;
+            }
+        }
+        values.into_iter().map(os_string).collect()
+    }
+
+    /// Provides the number of values that are captured for each matching path, `None` if the
+    /// glob cannot be translated.
+    pub(crate) fn len(&self) -> Option<usize> {
+        self.translated.as_ref().map(|t| t.captures)
+    }
+}
+
+/// Converts the captured bytes of a path, see [`pattern::path_bytes`].
+#[cfg(unix)]
+fn os_string(v: &[u8]) -> Option<ffi::OsString> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(ffi::OsStr::from_bytes(v).to_os_string())
+}
+
+/// Converts the captured bytes of a path, see [`pattern::path_bytes`].
+#[cfg(not(unix))]
+fn os_string(v: &[u8]) -> Option<ffi::OsString> {
+    String::from_utf8(v.to_vec()).ok().map(ffi::OsString::from)
+}
+
+/// Name of the `n`-th capture group.
+fn name(n: usize) -> String {
+    format!("globmatchcapture{n}")
+}
+
+/// Provides the length of the group or character class that starts at the beginning of `re`,
+/// including the closing bracket, `None` if it is not closed.
+fn group_len(re: &str) -> Option<usize> {
+    let bytes = re.as_bytes();
+    let mut depth = 0;
+    let mut class = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' if !class => {
+                class = true;
+                depth += 1;
+            }
+            b']' if class => {
+                class = false;
+                depth -= 1;
+            }
+            b'(' if !class => depth += 1,
+            b')' if !class => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+        if depth == 0 {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Inserts a named capture group for each wildcard into the regular expression `re` that
+/// `globset` generated for a glob, where the regular expression `segments` are replaced by
+/// their [`pattern::placeholder`].
+///
+/// Alternates are captured as a whole, wildcards within alternates are not captured.
+fn translate(re: &str, segments: &[&str]) -> Option<Translated> {
+    // globset generates `(?-u)`, optionally followed by `(?i)`, and anchors the expression
+    let start = re.find('^')?;
+    let (flags, body) = (&re[..start], re[start + 1..].strip_suffix('$')?);
+
+    let mut out = String::new();
+    let mut captures = 0;
+    let mut captured = vec![];
+    let mut globstars = vec![];
+    let mut open = |out: &mut String| {
+        out.push_str(&format!("(?P<{}>", name(captures)));
+        captures += 1;
+        captures - 1
+    };
+
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        let len = if let Some(tail) = rest.strip_prefix("(?:/|/.*/)") {
+            // `/**/` matches any number of path components, including none
+            out.push_str("/(?:");
+            globstars.push(open(&mut out));
+            out.push_str(".*)/)?");
+            rest.len() - tail.len()
+        } else if let Some(tail) = rest.strip_prefix("(?:/?|.*/)") {
+            // a leading `**/` matches any number of path components, including none
+            out.push_str("(?:/?|");
+            globstars.push(open(&mut out));
+            out.push_str(".*)/)");
+            rest.len() - tail.len()
+        } else if rest.starts_with(".*") || rest.starts_with("[^/]*") {
+            // `.*` is generated for a trailing globstar `/**`, since separators are literal
+            let len = match rest.starts_with(".*") {
+                true => 2,
+                false => 5,
+            };
+            let n = open(&mut out);
+            if len == 2 {
+                globstars.push(n);
+            }
+            out.push_str(&rest[..len]);
+            out.push(')');
+            len
+        } else if c == '[' || rest.starts_with("(?:") {
+            // character classes and alternates
+            let len = group_len(rest)?;
+            open(&mut out);
+            out.push_str(&rest[..len]);
+            out.push(')');
+            len
+        } else if c == '.' {
+            open(&mut out);
+            out.push_str(".)");
+            1
+        } else if c == '\\' {
+            // escaped literals, e.g., `\.` or `\xc3`
+            let len = match rest[1..].starts_with('x') {
+                true => 4,
+                false => 1 + rest[1..].chars().next()?.len_utf8(),
+            };
+            out.push_str(rest.get(..len)?);
+            len
+        } else if let Some((i, segment)) = segments
+            .iter()
+            .enumerate()
+            .find(|(i, _)| rest.starts_with(&pattern::placeholder(*i)))
+        {
+            captured.push(open(&mut out));
+            out.push_str(segment);
+            out.push(')');
+            pattern::placeholder(i).len()
+        } else {
+            out.push(c);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+
+    // regular expression segments within alternates are not captured
+    for (i, segment) in segments.iter().enumerate() {
+        out = out.replace(&pattern::placeholder(i), &format!("(?:{segment})"));
+    }
+    Some(Translated {
+        re: format!("(?s){flags}^{out}$"),
+        captures,
+        segments: captured,
+        globstars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captures(glob: &str, p: &str) -> Option<Vec<String>> {
+        crate::Builder::new(glob)
+            .backslash_escape(true)
+            .capture_for(glob)
+            .captures(path::Path::new(p))
+    }
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(
+            Some(vec!["net".to_string(), "tcp".to_string()]),
+            captures("src/**/*_test.rs", "src/net/tcp_test.rs")
+        );
+        assert_eq!(
+            Some(vec!["".to_string(), "tcp".to_string()]),
+            captures("src/**/*_test.rs", "src/tcp_test.rs")
+        );
+        assert_eq!(
+            Some(vec!["a/b".to_string(), "c".to_string()]),
+            captures("**/?.txt", "a/b/c.txt")
+        );
+        assert_eq!(
+            Some(vec!["x".to_string(), "1".to_string()]),
+            captures("a[!0-9]/file[0-9].txt", "ax/file1.txt")
+        );
+        assert_eq!(None, captures("a[!0-9]/file[0-9].txt", "a1/file1.txt"));
+        assert_eq!(
+            Some(vec!["x/y.md".to_string()]),
+            captures("docs/**", "docs/x/y.md")
+        );
+        assert_eq!(None, captures("*.rs", "src/lib.rs"));
+        assert_eq!(
+            Some(vec!["sumé".to_string()]),
+            captures("ré*.txt", "résumé.txt")
+        );
+        assert_eq!(None, captures("src/[a", "src/a"));
+    }
+
+    #[test]
+    fn alternates_and_segments() {
+        assert_eq!(
+            Some(vec!["lib".to_string(), "rs".to_string()]),
+            captures("src/*.{rs,toml}", "src/lib.rs")
+        );
+        assert_eq!(
+            Some(vec!["a/x.c".to_string()]),
+            captures("{a/*.c,b}", "a/x.c")
+        );
+        assert_eq!(
+            Some(vec!["v12".to_string(), "x".to_string()]),
+            captures("lib/<re:v[0-9]+>/*.rs", "lib/v12/x.rs")
+        );
+        assert_eq!(None, captures("lib/<re:.*>/*.rs", "lib/a/b/x.rs"));
+        assert_eq!(
+            Some(vec!["09".to_string()]),
+            captures("report_{08..10}.csv", "report_09.csv")
+        );
+        assert_eq!(None, captures("report_{08..10}.csv", "report_11.csv"));
+        assert_eq!(
+            Some(vec!["a".to_string()]),
+            captures(r"\*/*.txt", "*/a.txt")
+        );
+        assert_eq!(None, captures(r"\*/*.txt", "b/a.txt"));
+        let insensitive = crate::Builder::new("*.TXT")
+            .case_sensitive(false)
+            .capture_for("*.TXT");
+        assert_eq!(
+            Some(vec!["a".to_string()]),
+            insensitive.captures(path::Path::new("a.txt"))
+        );
+    }
+
+    #[test]
+    fn builder_options() {
+        let glob = "src/**/*{.rs,}";
+        let mut builder = crate::Builder::new(glob);
+        assert_eq!(
+            None,
+            builder
+                .capture_for(glob)
+                .captures(path::Path::new("src/lib"))
+        );
+        builder.empty_alternates(true);
+        assert_eq!(
+            Some(vec!["".to_string(), "lib".to_string(), "".to_string()]),
+            builder
+                .capture_for(glob)
+                .captures(path::Path::new("src/lib"))
+        );
+
+        builder.globstar_max_depth(1);
+        let captures = builder.capture_for(glob);
+        assert_eq!(3, captures.len().unwrap());
+        assert!(captures.captures(path::Path::new("src/a/x.rs")).is_some());
+        assert_eq!(None, captures.captures(path::Path::new("src/a/b/x.rs")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let p = path::Path::new(std::ffi::OsStr::from_bytes(b"src/\xff.rs"));
+        let glob = crate::Builder::new("src/*.rs").capture_for("src/*.rs");
+        assert_eq!(None, glob.captures(p));
+        assert_eq!(1, glob.len().unwrap());
+    }
+}
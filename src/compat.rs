@@ -0,0 +1,7 @@
+//! Compatibility layers for users of other crates.
+//!
+//! The modules within this module mirror the API of other crates, implemented on top of this
+//! crate, such that projects can switch to the relative-root semantics of `globmatch` with
+//! minimal changes.
+
+pub mod globwalk;
@@ -0,0 +1,338 @@
+//! Compatibility layer for users of the [globwalk][globwalk] crate.
+//!
+//! This module mirrors the shape of the `GlobWalkerBuilder` of [globwalk][globwalk]: A base
+//! directory, a list of patterns, depth limits and file types. Patterns are resolved relative to
+//! the base directory as described for [`Builder::build`], i.e., in contrast to globwalk the
+//! patterns may contain relative path components such as `../`. Patterns starting with `!`
+//! exclude all paths (relative to the base directory) that match the remaining pattern.
+//!
+//! The main difference to [globwalk][globwalk] is that the [`GlobWalker`] yields paths instead of
+//! `walkdir::DirEntry`s, and that all errors are reported as [`Error`].
+//!
+//! ```
+//! use globmatch::compat::globwalk::{FileType, GlobWalkerBuilder};
+//!
+//! # fn example() -> Result<(), String> {
+//! let base_dir = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+//! let paths: Vec<_> = GlobWalkerBuilder::from_patterns(&base_dir, &["**/*.txt", "!a/a0/**"])
+//!     .max_depth(3)
+//!     .file_type(FileType::FILE)
+//!     .build()?
+//!     .flatten()
+//!     .collect();
+//!
+//! assert_eq!(6, paths.len());
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+//!
+//! [globwalk]: https://docs.rs/globwalk
+
+use std::{collections, ops, path};
+
+use crate::{Builder, Error, Glob, IterAll, SourceFileType};
+
+/// File types that are yielded by the [`GlobWalker`], see [`GlobWalkerBuilder::file_type`].
+///
+/// File types can be combined, e.g., `FileType::FILE | FileType::DIR`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileType(u8);
+
+impl FileType {
+    /// Regular files.
+    pub const FILE: FileType = FileType(0b001);
+    /// Directories.
+    pub const DIR: FileType = FileType(0b010);
+    /// Symbolic links.
+    pub const SYMLINK: FileType = FileType(0b100);
+
+    /// Checks whether all file types of `other` are contained in this [`FileType`].
+    pub fn contains(&self, other: FileType) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for FileType {
+    fn default() -> Self {
+        FileType::FILE | FileType::DIR | FileType::SYMLINK
+    }
+}
+
+impl ops::BitOr for FileType {
+    type Output = FileType;
+
+    fn bitor(self, rhs: FileType) -> FileType {
+        FileType(self.0 | rhs.0)
+    }
+}
+
+/// Builder for a [`GlobWalker`], mirroring `globwalk::GlobWalkerBuilder`.
+#[derive(Clone, Debug)]
+pub struct GlobWalkerBuilder {
+    base_dir: path::PathBuf,
+    patterns: Vec<String>,
+    min_depth: usize,
+    max_depth: usize,
+    case_insensitive: bool,
+    file_type: FileType,
+}
+
+impl GlobWalkerBuilder {
+    /// Creates a new builder for a single `pattern` relative to the `base_dir`.
+    pub fn new<P, S>(base_dir: P, pattern: S) -> GlobWalkerBuilder
+    where
+        P: AsRef<path::Path>,
+        S: AsRef<str>,
+    {
+        GlobWalkerBuilder::from_patterns(base_dir, &[pattern])
+    }
+
+    /// Creates a new builder for a list of `patterns` relative to the `base_dir`.
+    ///
+    /// Patterns starting with `!` exclude paths, refer to the [module documentation](self).
+    pub fn from_patterns<P, S>(base_dir: P, patterns: &[S]) -> GlobWalkerBuilder
+    where
+        P: AsRef<path::Path>,
+        S: AsRef<str>,
+    {
+        GlobWalkerBuilder {
+            base_dir: path::PathBuf::from(base_dir.as_ref()),
+            patterns: patterns.iter().map(|p| p.as_ref().to_string()).collect(),
+            min_depth: 0,
+            max_depth: usize::MAX,
+            case_insensitive: false,
+            file_type: FileType::default(),
+        }
+    }
+
+    /// Only yields paths with at least the given depth, relative to the base directory.
+    pub fn min_depth(mut self, depth: usize) -> GlobWalkerBuilder {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Only yields paths up to the given depth, relative to the base directory, e.g., `1`
+    /// only yields the direct children of the base directory.
+    pub fn max_depth(mut self, depth: usize) -> GlobWalkerBuilder {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Toggle whether the patterns match case insensitive or not.
+    pub fn case_insensitive(mut self, yes: bool) -> GlobWalkerBuilder {
+        self.case_insensitive = yes;
+        self
+    }
+
+    /// Only yields paths of the given file types.
+    pub fn file_type(mut self, file_type: FileType) -> GlobWalkerBuilder {
+        self.file_type = file_type;
+        self
+    }
+
+    /// Builds the [`GlobWalker`].
+    ///
+    /// The depth limits are applied while walking, i.e., folders below the maximum depth are not
+    /// walked at all.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::build`] and [`Builder::build_glob`], e.g., if any of the patterns
+    /// cannot be compiled or if the base directory does not exist.
+    pub fn build(self) -> Result<GlobWalker, Error> {
+        let mut iters = vec![];
+        let mut excludes = vec![];
+
+        for pattern in &self.patterns {
+            if let Some(exclude) = pattern.strip_prefix('!') {
+                excludes.push(
+                    Builder::new(exclude)
+                        .case_sensitive(!self.case_insensitive)
+                        .build_glob()?
+                        .into_owned(),
+                );
+                continue;
+            }
+
+            let mut builder = Builder::new(pattern);
+            builder.case_sensitive(!self.case_insensitive);
+            // the depth limits are relative to the base directory, but the walk starts at the
+            // resolved root, paths outside of the base directory are only checked when yielded
+            let (root, _, _) = builder.resolve(&self.base_dir, &crate::WalkDirSource)?;
+            if let Some(offset) = depth_below(&self.base_dir, &root) {
+                if offset > self.max_depth {
+                    continue;
+                }
+                builder.min_depth(self.min_depth.saturating_sub(offset));
+                if self.max_depth != usize::MAX {
+                    builder.max_depth(self.max_depth - offset);
+                }
+            }
+            iters.push(builder.build(&self.base_dir)?.into_iter());
+        }
+
+        Ok(GlobWalker {
+            base_dir: self.base_dir,
+            min_depth: self.min_depth,
+            max_depth: self.max_depth,
+            file_type: self.file_type,
+            iters: iters.into_iter(),
+            current: None,
+            excludes,
+            seen: collections::HashSet::new(),
+        })
+    }
+}
+
+/// Iterator over all paths matching the patterns of a [`GlobWalkerBuilder`].
+///
+/// Each path is only yielded once, even if it matches more than one pattern.
+#[derive(Debug)]
+pub struct GlobWalker {
+    base_dir: path::PathBuf,
+    min_depth: usize,
+    max_depth: usize,
+    file_type: FileType,
+    iters: std::vec::IntoIter<IterAll<path::PathBuf>>,
+    current: Option<IterAll<path::PathBuf>>,
+    excludes: Vec<Glob<'static>>,
+    seen: collections::HashSet<path::PathBuf>,
+}
+
+/// Provides the number of path components of `root` below the `base_dir`, or `None` if the
+/// `root` is not located within the `base_dir`.
+fn depth_below(base_dir: &path::Path, root: &path::Path) -> Option<usize> {
+    let relative = root.strip_prefix(base_dir).ok()?;
+    relative.components().try_fold(0, |depth, c| match c {
+        path::Component::Normal(_) => Some(depth + 1),
+        path::Component::CurDir => Some(depth),
+        _ => None,
+    })
+}
+
+impl GlobWalker {
+    /// Checks whether the given path of the walked type should be yielded by the walker.
+    fn accept(&mut self, path: &path::Path, file_type: SourceFileType) -> bool {
+        // paths that are not within the base directory, e.g., for patterns such as `../*.txt`
+        // are matched as provided
+        let relative = path.strip_prefix(&self.base_dir).unwrap_or(path);
+        let depth = relative.components().count();
+        if depth < self.min_depth || depth > self.max_depth {
+            return false;
+        }
+        if self.excludes.iter().any(|glob| glob.is_match(relative)) {
+            return false;
+        }
+
+        let file_type = match file_type {
+            SourceFileType::Symlink => FileType::SYMLINK,
+            SourceFileType::Dir => FileType::DIR,
+            SourceFileType::File | SourceFileType::Other => FileType::FILE,
+        };
+        self.file_type.contains(file_type) && self.seen.insert(path::PathBuf::from(path))
+    }
+}
+
+impl Iterator for GlobWalker {
+    type Item = Result<path::PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.current.is_none() {
+                self.current = Some(self.iters.next()?);
+            }
+
+            // notice that the current iterator has been set above
+            match self.current.as_mut().unwrap().next_typed() {
+                None => self.current = None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok((path, file_type))) if self.accept(&path, file_type) => {
+                    return Some(Ok(path))
+                }
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+/// Creates a [`GlobWalker`] for a single `pattern` relative to the current working directory,
+/// mirroring `globwalk::glob`.
+///
+/// # Errors
+///
+/// Refer to [`GlobWalkerBuilder::build`].
+pub fn glob<S>(pattern: S) -> Result<GlobWalker, Error>
+where
+    S: AsRef<str>,
+{
+    GlobWalkerBuilder::new(".", pattern).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globwalk() -> Result<(), String> {
+        let base_dir = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let paths: Vec<_> = GlobWalkerBuilder::from_patterns(&base_dir, &["**/*.txt", "a/**"])
+            .build()?
+            .flatten()
+            .collect();
+        // `a/**` yields `a` itself, its directories and the markdown file
+        assert_eq!(6 + 2 + 1 + 1 + 3 + 1, paths.len());
+
+        let paths: Vec<_> = GlobWalkerBuilder::from_patterns(&base_dir, &["**/*.txt", "a/**"])
+            .file_type(FileType::FILE)
+            .min_depth(3)
+            .build()?
+            .flatten()
+            .collect();
+        assert_eq!(5 + 1, paths.len());
+
+        let paths: Vec<_> = GlobWalkerBuilder::new(&base_dir, "**/a*")
+            .case_insensitive(true)
+            .file_type(FileType::DIR)
+            .build()?
+            .flatten()
+            .collect();
+        assert_eq!(1 + 3, paths.len());
+
+        let paths: Vec<_> = GlobWalkerBuilder::from_patterns(&base_dir, &["**/*.txt", "!.*/**"])
+            .max_depth(2)
+            .build()?
+            .flatten()
+            .collect();
+        assert_eq!(2, paths.len());
+
+        // the depth limits are forwarded to the walk relative to the resolved root
+        let patterns = ["**/*.txt", "a/**", "a/a0/*", "../c-simple/b/*"];
+        let walk = |min: usize, max: usize| -> Result<Vec<_>, String> {
+            let mut paths: Vec<_> = GlobWalkerBuilder::from_patterns(&base_dir, &patterns)
+                .min_depth(min)
+                .max_depth(max)
+                .build()?
+                .collect::<Result<_, _>>()?;
+            paths.sort();
+            Ok(paths)
+        };
+        let all = walk(0, usize::MAX)?;
+        for (min, max) in [(0, 1), (1, 2), (2, 3), (3, 3), (4, 10)] {
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|p| {
+                    let depth = p.strip_prefix(&base_dir).unwrap().components().count();
+                    depth >= min && depth <= max
+                })
+                .cloned()
+                .collect();
+            assert_eq!(expected, walk(min, max)?, "depth {min}..={max}");
+        }
+
+        assert!(GlobWalkerBuilder::new(&base_dir, "a[").build().is_err());
+        assert!(GlobWalkerBuilder::new(&base_dir, "!a[").build().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::path;
+
+use crate::{suggest, utils, Builder};
+
+/// Suggests completions for a partially typed glob, similar to shell tab-completion.
+///
+/// The `partial` glob is split at the last path separator: The leading part is resolved
+/// relative to `root` and may contain wildcards, e.g., `src/*/`. All entries of the matching
+/// directories that start with the trailing part (which may contain wildcards as well) are
+/// provided as completion. Each completion consists of the leading part as typed by the user and
+/// the name of the entry. Directories are suffixed with a `/` such that the completion can be
+/// continued.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = env!("CARGO_MANIFEST_DIR");
+///
+/// let completions = globmatch::complete(root, "test-files/c-simple/a/a")?;
+/// assert_eq!(
+///     vec![
+///         "test-files/c-simple/a/a0/",
+///         "test-files/c-simple/a/a1/",
+///         "test-files/c-simple/a/a2/"
+///     ],
+///     completions
+/// );
+///
+/// let completions = globmatch::complete(root, "test-files/c-simple/a/*/a0_")?;
+/// assert_eq!(3, completions.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Refer to [`Builder::build`], e.g., if the leading part of the glob cannot be compiled.
+pub fn complete<P>(root: P, partial: &str) -> Result<Vec<String>, String>
+where
+    P: AsRef<path::Path>,
+{
+    let (parent, prefix) = match partial.rsplit_once('/') {
+        Some((parent, prefix)) => (parent, prefix),
+        None => ("", partial),
+    };
+
+    let dirs: Vec<_> = if parent.is_empty() {
+        vec![path::PathBuf::from(root.as_ref())]
+    } else if parent.split('/').all(suggest::is_literal) {
+        vec![root.as_ref().join(parent)]
+    } else {
+        Builder::new(parent)
+            .build(root.as_ref())?
+            .into_iter()
+            .flatten()
+            .collect()
+    };
+
+    let matcher = globset::GlobBuilder::new(&format!("{prefix}*"))
+        .literal_separator(true)
+        .build()
+        .map_err(|err| format!("'{}': {}", partial, utils::to_upper(err.kind().to_string())))?
+        .compile_matcher();
+
+    let mut completions: Vec<_> = dirs
+        .iter()
+        .filter_map(|dir| dir.read_dir().ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !matcher.is_match(&name) {
+                return None;
+            }
+
+            let separator = match entry.path().is_dir() {
+                true => "/",
+                false => "",
+            };
+            Some(match parent.is_empty() {
+                true => format!("{name}{separator}"),
+                false => format!("{parent}/{name}{separator}"),
+            })
+        })
+        .collect();
+
+    completions.sort_unstable();
+    completions.dedup();
+    Ok(completions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(vec!["some_file.txt"], complete(&root, "so")?);
+        assert_eq!(vec![".hidden/"], complete(&root, ".")?);
+        assert_eq!(vec!["a/a0/a0_2.md"], complete(&root, "a/a0/*.m")?);
+        assert_eq!(
+            vec!["a/*/a0_0.txt", "a/*/a0_1.txt", "a/*/a0_2.md"],
+            complete(&root, "a/*/a0_")?
+        );
+        assert_eq!(vec!["../c-simple/"], complete(&root, "../c-")?);
+        assert!(complete(&root, "x/")?.is_empty());
+        assert!(complete(&root, "a[/").is_err());
+        Ok(())
+    }
+}
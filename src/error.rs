@@ -1,41 +1,361 @@
 use std::fmt;
 use std::io;
+use std::path;
+use std::sync::Arc;
 
-/// Simple error type used by this facade.
+use crate::utils;
+
+/// Error type used by this facade.
+///
+/// The errors for building a [`Matcher`](crate::Matcher), [`Glob`](crate::Glob) or
+/// [`GlobSet`](crate::GlobSet) are distinguished from the errors that occur while walking the
+/// paths. All errors can be converted into a `String` containing the [`fmt::Display`] output.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Error(String);
+#[non_exhaustive]
+pub enum Error {
+    /// The glob is empty.
+    EmptyPattern,
+    /// The glob could not be compiled, or exceeds the configured
+    /// [`PatternLimits`](crate::PatternLimits).
+    InvalidPattern {
+        /// The glob that failed to compile, possibly shortened.
+        glob: String,
+        /// Description of the failure.
+        message: String,
+        /// The underlying error, if the glob has been rejected by `globset`.
+        source: Option<globset::Error>,
+    },
+    /// The template of a [`Rewriter`](crate::Rewriter) is invalid. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    InvalidTemplate {
+        /// The template.
+        template: String,
+        /// Description of the failure.
+        message: String,
+    },
+    /// The root directory does not exist.
+    RootNotFound {
+        /// The root directory.
+        root: path::PathBuf,
+        /// Description of the failure, e.g., the message of the operating system.
+        message: String,
+        /// The underlying error of the operating system.
+        source: IoError,
+    },
+    /// The glob is an absolute path, but must be relative to the root.
+    AbsolutePattern {
+        /// The absolute glob.
+        glob: String,
+        /// `true` if the glob has been rejected while resolving the root directory, e.g., by
+        /// [`Builder::build`](crate::Builder::build), and `false` if it has been rejected by
+        /// [`Builder::build_glob_set`](crate::Builder::build_glob_set).
+        resolve: bool,
+    },
+    /// The root directory could not be resolved for the glob, e.g., since the glob contains
+    /// relative path components that are not allowed.
+    Resolve {
+        /// Description of the failure.
+        message: String,
+        /// The underlying error, if the root could not be accessed.
+        source: Option<IoError>,
+    },
+    /// Generic failure while walking the paths, e.g., due to missing permissions.
+    Walk {
+        /// Description of the failure, including the path that could not be walked.
+        message: String,
+        /// The underlying error, if an operation of the file system failed.
+        source: Option<IoError>,
+    },
+    /// The resolved root directory no longer exists, e.g., since it has been deleted after the
+    /// [`Matcher`](crate::Matcher) has been built. This error is yielded only once, as the single
+    /// item of the iterator.
+    RootVanished(path::PathBuf),
+    /// The iteration has been aborted since the configured maximum number of entries has been
+    /// walked, see [`Builder::max_entries`](crate::Builder::max_entries). This error is yielded
+    /// only once, as the last item of the iterator.
+    EntryLimit(usize),
+    /// The iteration has been aborted since the configured maximum number of matches has been
+    /// yielded, see [`WalkLimits::max_matches`](crate::WalkLimits::max_matches). This error is
+    /// yielded only once, as the last item of the iterator.
+    MatchLimit(usize),
+    /// The iteration has been aborted since it took longer than the configured duration, see
+    /// [`WalkLimits::timeout`](crate::WalkLimits::timeout). This error is yielded only once, as
+    /// the last item of the iterator.
+    Timeout(std::time::Duration),
+    /// The iteration has been cancelled via a
+    /// [`CancellationHandle`](crate::CancellationHandle). This error is yielded only once, as
+    /// the last item of the iterator.
+    Cancelled,
+    /// A symbolic link points to one of its own ancestors, which would lead to an endless walk.
+    /// Only yielded if symbolic links are followed, see
+    /// [`Builder::follow_links`](crate::Builder::follow_links). The link itself is not walked.
+    SymlinkLoop {
+        /// The ancestor that the link points to.
+        ancestor: path::PathBuf,
+        /// The path of the link.
+        child: path::PathBuf,
+    },
+    /// The matching path is not valid UTF-8. Only yielded by the iterators that provide UTF-8
+    /// paths and by `Matcher::collect_reproducible`, the original path is retained. Requires the
+    /// `camino` or the `reproducible` feature.
+    #[cfg(any(feature = "camino", feature = "reproducible"))]
+    NotUtf8(path::PathBuf),
+    /// The glob or a walked path escapes the root of a jailed [`Matcher`](crate::Matcher), see
+    /// [`Builder::jail`](crate::Builder::jail). Contains the glob with `..` components, or the
+    /// path of the symbolic link that points outside the resolved root. Also provided by
+    /// `Matcher::collect_reproducible` for matching paths that are not located within the
+    /// resolved root.
+    OutsideRoot(path::PathBuf),
+    /// The option is not supported for the walked paths, e.g., ignore files for a
+    /// [`WalkSource`](crate::WalkSource) other than the file system.
+    Unsupported(String),
+}
+
+/// An I/O error that is shared by the clones of an [`Error`].
+///
+/// Two errors are considered equal if their [`io::ErrorKind`] and their messages are equal.
+#[derive(Clone, Debug)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    /// Provides the underlying I/O error.
+    pub fn inner(&self) -> &io::Error {
+        &self.0
+    }
+}
+
+impl From<io::Error> for IoError {
+    fn from(err: io::Error) -> Self {
+        IoError(Arc::new(err))
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Eq for IoError {}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 impl Error {
     /// Creates a new error string.
     pub fn new(err: &str) -> Error {
-        Error(err.to_string())
+        Error::walk(err)
+    }
+
+    /// Creates an [`Error::Walk`] without a source.
+    pub(crate) fn walk<S>(message: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::Walk {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Creates an [`Error::Walk`] for a failed operation of the file system.
+    pub(crate) fn walk_io<S>(message: S, source: io::Error) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::Walk {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Creates an [`Error::Resolve`] without a source.
+    pub(crate) fn resolve<S>(message: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::Resolve {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Creates an [`Error::InvalidPattern`] without a source.
+    pub(crate) fn pattern<S>(glob: &str, message: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::InvalidPattern {
+            glob: glob.to_string(),
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Creates an [`Error::InvalidTemplate`] for the given template.
+    #[cfg(feature = "regex")]
+    pub(crate) fn template<S>(template: &str, message: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::InvalidTemplate {
+            template: template.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates an [`Error::InvalidPattern`] for a glob that has been rejected by `globset`.
+    pub(crate) fn globset(glob: &str, source: globset::Error) -> Error {
+        Error::InvalidPattern {
+            glob: glob.to_string(),
+            message: utils::to_upper(source.kind().to_string()),
+            source: Some(source),
+        }
+    }
+
+    /// Creates an [`Error::RootNotFound`] for the given `root`.
+    pub(crate) fn root_not_found(root: &path::Path, inner: io::Error) -> Error {
+        Error::RootNotFound {
+            root: path::PathBuf::from(root),
+            message: utils::to_upper(inner.to_string()),
+            source: inner.into(),
+        }
+    }
+
+    /// Creates an error for a failure while resolving the given `root` for the `pattern`.
+    pub(crate) fn from_resolve(root: &path::Path, pattern: &str, inner: io::Error) -> Error {
+        match inner.kind() {
+            io::ErrorKind::NotFound => Error::root_not_found(root, inner),
+            io::ErrorKind::Unsupported if utils::is_absolute_pattern(pattern) => {
+                Error::AbsolutePattern {
+                    glob: pattern.to_string(),
+                    resolve: true,
+                }
+            }
+            _ => Error::Resolve {
+                message: utils::to_upper(inner.to_string()),
+                source: Some(inner.into()),
+            },
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            Error::EmptyPattern => write!(f, "Empty glob"),
+            Error::InvalidPattern { glob, message, .. } => write!(f, "'{glob}': {message}"),
+            #[cfg(feature = "regex")]
+            Error::InvalidTemplate { template, message } => {
+                write!(f, "Invalid template '{template}': {message}")
+            }
+            Error::RootNotFound { message, .. } => {
+                write!(f, "'Failed to resolve paths': {message}")
+            }
+            Error::AbsolutePattern { glob, resolve } => match resolve {
+                true => write!(f, "'Failed to resolve paths': '{glob}' is an absolute path"),
+                false => write!(f, "{glob}' is an absolute path"),
+            },
+            Error::Resolve { message, .. } => write!(f, "'Failed to resolve paths': {message}"),
+            Error::Walk { message, .. } => write!(f, "{message}"),
+            Error::RootVanished(root) => {
+                write!(f, "Root path {} no longer exists", root.to_string_lossy())
+            }
+            Error::EntryLimit(max) => {
+                write!(f, "Aborted after walking the maximum of {max} entries")
+            }
+            Error::MatchLimit(max) => {
+                write!(f, "Aborted after yielding the maximum of {max} matches")
+            }
+            Error::Timeout(timeout) => {
+                write!(f, "Aborted after exceeding the timeout of {timeout:?}")
+            }
+            Error::Cancelled => write!(f, "Cancelled the walk"),
+            Error::SymlinkLoop { ancestor, child } => write!(
+                f,
+                "Failed to walk path {}: Symbolic link loop to {}",
+                child.to_string_lossy(),
+                ancestor.to_string_lossy()
+            ),
+            #[cfg(any(feature = "camino", feature = "reproducible"))]
+            Error::NotUtf8(p) => {
+                write!(f, "Path {} is not valid UTF-8", p.to_string_lossy())
+            }
+            Error::OutsideRoot(p) => {
+                write!(f, "Path {} escapes the root", p.to_string_lossy())
+            }
+            Error::Unsupported(err) => write!(f, "Unsupported option: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidPattern {
+                source: Some(source),
+                ..
+            } => Some(source),
+            Error::RootNotFound { source, .. } => Some(source.inner()),
+            Error::Resolve {
+                source: Some(source),
+                ..
+            }
+            | Error::Walk {
+                source: Some(source),
+                ..
+            } => Some(source.inner()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for String {
+    fn from(err: Error) -> Self {
+        err.to_string()
+    }
+}
+
+impl Error {
+    /// Creates an error for a failure while walking the given `path` at the given `depth`.
+    pub(crate) fn from_io(path: &path::Path, depth: usize, inner: io::Error) -> Error {
+        let common = format!("Failed to walk path {}", path.to_string_lossy());
+        let message = match inner.kind() {
+            // walkdir yields the root itself at depth zero
+            io::ErrorKind::NotFound if depth == 0 => {
+                return Error::RootVanished(path::PathBuf::from(path))
+            }
+            io::ErrorKind::InvalidData => format!("{common}: Invalid data encountered: {inner}"),
+            io::ErrorKind::PermissionDenied => {
+                format!("{common}: Missing permissions to read entry: {inner}")
+            }
+            _ => format!("{common}: Unexpected error occurred: {inner}"),
+        };
+        Error::walk_io(message, inner)
     }
 }
 
 impl From<walkdir::Error> for Error {
     fn from(item: walkdir::Error) -> Self {
-        if let Some(path) = item.path() {
-            let common = format!("Failed to walk path {}", path.to_string_lossy());
-
-            if let Some(inner) = item.io_error() {
-                return match inner.kind() {
-                    io::ErrorKind::InvalidData => {
-                        Error(format!("{common}: Invalid data encountered: {inner}"))
-                    }
-                    io::ErrorKind::PermissionDenied => Error(format!(
-                        "{common}: Missing permissions to read entry: {inner}"
-                    )),
-                    _ => Error(format!("{common}: Unexpected error occurred: {inner}")),
-                };
-            }
-            return Error(format!("{common}: Unknown error occurred"));
-        }
-        Error("<unknown-path>: Unknown error occurred".to_string())
+        if let (Some(ancestor), Some(child)) = (item.loop_ancestor(), item.path()) {
+            return Error::SymlinkLoop {
+                ancestor: path::PathBuf::from(ancestor),
+                child: path::PathBuf::from(child),
+            };
+        }
+        let depth = item.depth();
+        if let Some(path) = item.path().map(path::PathBuf::from) {
+            if let Some(inner) = item.into_io_error() {
+                return Error::from_io(&path, depth, inner);
+            }
+            return Error::walk(format!(
+                "Failed to walk path {}: Unknown error occurred",
+                path.to_string_lossy()
+            ));
+        }
+        Error::walk("<unknown-path>: Unknown error occurred")
     }
 }
@@ -0,0 +1,180 @@
+use std::{fmt, path};
+
+use crate::{pattern, winpath};
+
+/// Explanation of the matching decision for a single path, see
+/// [`Matcher::explain`](crate::Matcher::explain).
+///
+/// The [`fmt::Display`] implementation provides a human readable summary, e.g., for logging.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchExplanation {
+    /// The path that has been explained.
+    pub path: path::PathBuf,
+    /// The resolved root of the [`Matcher`](crate::Matcher).
+    pub root: path::PathBuf,
+    /// The resolved glob that is matched against the relative path.
+    pub glob: String,
+    /// The path relative to the `root` that is matched against the `glob`, `None` if the
+    /// path is not within the `root`.
+    pub relative: Option<path::PathBuf>,
+    /// Whether the relative path matches the `glob`.
+    pub is_match: bool,
+    /// Whether the path is excluded, see [`Builder::exclude`](crate::Builder::exclude).
+    pub is_excluded: bool,
+    /// Index of the first component of the `relative` path at which the path can no longer
+    /// match the `glob`, `None` for matching paths.
+    pub failed_component: Option<usize>,
+    /// The component of the `glob` that has been compared against the failed component of the
+    /// path. This is `None` if the component is not known, e.g., after a globstar `**`, or if
+    /// the path has more components than the glob.
+    pub failed_glob_component: Option<String>,
+}
+
+impl fmt::Display for MatchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let relative = match &self.relative {
+            Some(relative) => relative,
+            None => {
+                return write!(
+                    f,
+                    "'{}' is not within the root '{}'",
+                    self.path.display(),
+                    self.root.display()
+                )
+            }
+        };
+        if self.is_match {
+            return match self.is_excluded {
+                true => write!(
+                    f,
+                    "'{}' matches '{}' but is excluded",
+                    relative.display(),
+                    self.glob
+                ),
+                false => write!(f, "'{}' matches '{}'", relative.display(), self.glob),
+            };
+        }
+
+        write!(f, "'{}' does not match '{}'", relative.display(), self.glob)?;
+        let component = self
+            .failed_component
+            .and_then(|i| relative.components().nth(i));
+        match (component, &self.failed_glob_component) {
+            (Some(c), Some(glob)) => write!(
+                f,
+                ": '{}' does not match '{glob}'",
+                c.as_os_str().to_string_lossy()
+            ),
+            (Some(c), None) => write!(
+                f,
+                ": no match possible at '{}'",
+                c.as_os_str().to_string_lossy()
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Explains the matching decision for the given `path`, see [`MatchExplanation`].
+pub(crate) fn explain(
+    root: &path::Path,
+    glob: &str,
+    matcher: &pattern::PatternSet,
+    path: &path::Path,
+) -> MatchExplanation {
+    let relative = match winpath::strip_prefix(path, root) {
+        // the root itself is a file, match on its name as if walking its parent
+        Some(relative) if relative.as_os_str().is_empty() && !root.is_dir() => {
+            root.file_name().map(path::PathBuf::from)
+        }
+        Some(relative) => Some(path::PathBuf::from(relative)),
+        None => None,
+    };
+
+    let is_match = relative.as_ref().is_some_and(|p| matcher.is_match(p));
+    let failed_component = match (&relative, is_match) {
+        (Some(relative), false) => {
+            // the first folder that cannot contain a match, otherwise the file name itself
+            let components: Vec<_> = relative.components().collect();
+            let failed = (1..components.len()).find_map(|n| {
+                let dir: path::PathBuf = components[..n].iter().collect();
+                match matcher.may_contain_match(&dir) {
+                    true => None,
+                    // the folder itself matches, the path has more components than the glob
+                    false if matcher.is_match(&dir) => Some(n),
+                    false => Some(n - 1),
+                }
+            });
+            failed.or_else(|| components.len().checked_sub(1))
+        }
+        _ => None,
+    };
+
+    // glob components are only known up to the first globstar
+    let failed_glob_component = failed_component.and_then(|i| {
+        glob.split('/')
+            .take_while(|c| *c != "**")
+            .nth(i)
+            .map(String::from)
+    });
+
+    MatchExplanation {
+        path: path::PathBuf::from(path),
+        root: path::PathBuf::from(root),
+        glob: String::from(glob),
+        is_excluded: matcher.is_excluded(path),
+        relative,
+        is_match,
+        failed_component,
+        failed_glob_component,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Builder;
+
+    #[test]
+    fn explain() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let matcher = Builder::new("**/a?/*.md")
+            .exclude("**/a0_2.*")
+            .build(&root)?;
+
+        let explanation = matcher.explain(format!("{root}/a/a0/a0_2.md"));
+        assert!(explanation.is_match && explanation.is_excluded);
+        assert_eq!(None, explanation.failed_component);
+        assert_eq!(
+            "'a/a0/a0_2.md' matches '**/a?/*.md' but is excluded",
+            explanation.to_string()
+        );
+
+        // the glob components after the globstar are unknown
+        let explanation = matcher.explain(format!("{root}/a/a0/a0_0.txt"));
+        assert_eq!(Some(2), explanation.failed_component);
+        assert_eq!(None, explanation.failed_glob_component);
+        assert_eq!(
+            "'a/a0/a0_0.txt' does not match '**/a?/*.md': no match possible at 'a0_0.txt'",
+            explanation.to_string()
+        );
+
+        let explanation = matcher.explain("/some/other/path");
+        assert_eq!(None, explanation.relative);
+        assert!(explanation.to_string().contains("is not within the root"));
+
+        // the path has more components than the glob
+        let matcher = Builder::new("a/*").build(&root)?;
+        let explanation = matcher.explain(format!("{root}/a/a0/a0_0.txt"));
+        assert_eq!(Some(1), explanation.failed_component);
+        assert_eq!(None, explanation.failed_glob_component);
+
+        let matcher = Builder::new("*.md").build(format!("{root}/some_file.txt"))?;
+        let explanation = matcher.explain(format!("{root}/some_file.txt"));
+        assert_eq!(
+            "'some_file.txt' does not match '*.md': 'some_file.txt' does not match '*.md'",
+            explanation.to_string()
+        );
+        Ok(())
+    }
+}
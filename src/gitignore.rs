@@ -0,0 +1,131 @@
+use std::path;
+use std::sync::Arc;
+
+use ignore::gitignore::Gitignore;
+
+/// Gitignore rules that apply to the entries of a walk, see
+/// [`Builder::respect_gitignore`](crate::Builder::respect_gitignore) and
+/// [`Builder::add_ignore_file_name`](crate::Builder::add_ignore_file_name).
+///
+/// The rules of `.gitignore` files are only applied within a git repository, i.e., if the root
+/// of the walk or any of its parents contains a `.git` folder. As for `git`, the rules of the
+/// global gitignore file, `.git/info/exclude` and all `.gitignore` files of the repository apply,
+/// where the rules of the `.gitignore` file that is closest to the entry take precedence. Custom
+/// ignore files are applied within the walked folders, independent of any repository.
+///
+/// The stack is cheap to clone, such that the parallel iterator can provide each walked folder
+/// with the rules of its ancestors.
+#[derive(Clone, Debug)]
+pub(crate) struct GitignoreStack {
+    /// Absolute path of the walk root, used to match entries independent of the given root.
+    root: path::PathBuf,
+    /// Rules in order of precedence, last one wins. The depth of the folder containing the
+    /// `.gitignore` file is `None` for rules that apply to the entire walk.
+    rules: Vec<(Option<usize>, Arc<Gitignore>)>,
+    /// Names of the ignore files that are loaded for each walked folder, last one wins.
+    names: Vec<String>,
+}
+
+impl GitignoreStack {
+    /// Collects the rules that apply to the walk of the given `root`, where `gitignore` enables
+    /// the `.gitignore` files and `names` are the names of custom ignore files.
+    ///
+    /// Provides `None` if there are no rules to apply, i.e., if no custom ignore files are used
+    /// and the `root` is not within a git repository.
+    pub(crate) fn new(
+        root: &path::Path,
+        gitignore: bool,
+        names: Vec<String>,
+    ) -> Option<GitignoreStack> {
+        let root = root.canonicalize().ok()?;
+        let repo = match gitignore {
+            true => root.ancestors().find(|dir| dir.join(".git").exists()),
+            false => None,
+        };
+
+        let mut rules = vec![];
+        let mut files = vec![];
+        if let Some(repo) = repo {
+            rules.push((None, Arc::new(Gitignore::global().0)));
+            rules.push((
+                None,
+                Arc::new(Gitignore::new(repo.join(".git/info/exclude")).0),
+            ));
+
+            // the `.gitignore` of the root itself is added once the root is walked
+            let mut parents: Vec<_> = root
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(repo))
+                .collect();
+            parents.reverse();
+            for dir in parents {
+                rules.push((None, Arc::new(Gitignore::new(dir.join(".gitignore")).0)));
+            }
+            files.push(".gitignore".to_string());
+        } else if names.is_empty() {
+            return None;
+        }
+
+        // custom ignore files take precedence over `.gitignore` files in the same folder
+        files.extend(names);
+        Some(GitignoreStack {
+            root,
+            rules,
+            names: files,
+        })
+    }
+
+    /// Checks whether the given entry at the given depth is ignored, where `root` is the root of
+    /// the walk.
+    ///
+    /// Entries must be checked in the order in which they are walked, since the `.gitignore`
+    /// files of all folders that are not ignored are collected during the walk.
+    pub(crate) fn is_ignored(
+        &mut self,
+        root: &path::Path,
+        entry: &path::Path,
+        depth: usize,
+        is_dir: bool,
+    ) -> bool {
+        while matches!(self.rules.last(), Some((Some(d), _)) if *d >= depth) {
+            self.rules.pop();
+        }
+
+        let path = match entry.strip_prefix(root) {
+            Ok(relative) => self.root.join(relative),
+            Err(_) => return false,
+        };
+
+        let ignored = depth > 0
+            && self
+                .rules
+                .iter()
+                .rev()
+                .map(|(_, rules)| rules.matched(&path, is_dir))
+                .find(|m| !m.is_none())
+                .is_some_and(|m| m.is_ignore());
+
+        if is_dir && !ignored {
+            for name in &self.names {
+                let file = path.join(name);
+                if file.is_file() {
+                    let rules = Gitignore::new(file).0;
+                    self.rules.push((Some(depth), Arc::new(rules)));
+                }
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outside_repository() {
+        assert!(GitignoreStack::new(path::Path::new("/"), true, vec![]).is_none());
+        assert!(GitignoreStack::new(path::Path::new("/"), true, vec![".ignore".into()]).is_some());
+    }
+}
@@ -0,0 +1,204 @@
+use std::{collections, fs, io, path, sync, sync::mpsc, thread};
+
+use crate::{Error, Matcher};
+
+/// Hash algorithms that are supported by [`Matcher::hash_matches`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HashAlgorithm {
+    /// SHA-256, as provided by the `sha2` crate.
+    Sha256,
+    /// BLAKE3, as provided by the `blake3` crate.
+    Blake3,
+}
+
+/// Streams the contents of the file through the hasher and provides the hex-encoded digest.
+fn hash_file(p: &path::Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    let mut file = fs::File::open(p)?;
+    let digest = match algorithm {
+        HashAlgorithm::Sha256 => {
+            use sha2::Digest;
+            let mut hasher = sha2::Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            hasher.finalize().to_vec()
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut file, &mut hasher)?;
+            hasher.finalize().as_bytes().to_vec()
+        }
+    };
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+impl<'a, P> Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Hashes the contents of all matching files, providing a manifest that maps each path to
+    /// its hex-encoded digest.
+    ///
+    /// The files are hashed while walking the paths, the contents are streamed through the
+    /// hasher such that files are never read into memory at once. Matching directories and
+    /// other entries that are not files are skipped.
+    ///
+    /// If the number of threads has been configured via
+    /// [`Builder::threads`](crate::Builder::threads), the paths are walked using the parallel
+    /// iterator and passed on to the same number of threads, which hash the files while the
+    /// walk continues.
+    ///
+    /// Requires the `hash` feature.
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that is encountered while walking the paths or while
+    /// reading a file.
+    pub fn hash_matches(
+        self,
+        algorithm: HashAlgorithm,
+    ) -> Result<collections::BTreeMap<path::PathBuf, String>, Error> {
+        // relative paths are yielded as configured, but the files are read via the root
+        let root = match self.walk.relative_paths {
+            true if self.root_path().is_file() => Some((self.root_path().to_path_buf(), true)),
+            true => Some((self.root_path().to_path_buf(), false)),
+            false => None,
+        };
+        let resolve = move |p: &path::Path| match &root {
+            Some((root, true)) => root.clone(),
+            Some((root, false)) => root.join(p),
+            None => path::PathBuf::from(p),
+        };
+        let resolve = &resolve;
+
+        let hash = |p: path::PathBuf| -> Result<_, Error> {
+            let digest = hash_file(&resolve(&p), algorithm).map_err(|err| {
+                let message = format!("Failed to hash file {}: {}", p.to_string_lossy(), err);
+                Error::walk_io(message, err)
+            })?;
+            Ok((p, digest))
+        };
+
+        let threads = match self.walk.threads {
+            Some(threads) if threads > 1 => threads,
+            _ => {
+                let mut manifest = collections::BTreeMap::new();
+                for p in self {
+                    let p = p?;
+                    if resolve(&p).is_file() {
+                        let (p, digest) = hash(p)?;
+                        manifest.insert(p, digest);
+                    }
+                }
+                return Ok(manifest);
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel::<path::PathBuf>();
+        let receiver = &sync::Mutex::new(receiver);
+        let hash = &hash;
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    s.spawn(move || {
+                        let mut hashed = vec![];
+                        loop {
+                            // the lock is released before hashing the file
+                            let next = receiver.lock().expect("hashing thread panicked").recv();
+                            match next {
+                                Ok(p) => hashed.push(hash(p)?),
+                                Err(_) => return Ok::<_, Error>(hashed),
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            let mut walked = Ok(());
+            for p in self.into_par_iter() {
+                match p {
+                    Ok(p) if !resolve(&p).is_file() => {}
+                    Ok(p) => {
+                        // all hashing threads stopped on an error, which is provided below
+                        if sender.send(p).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        walked = Err(err);
+                        break;
+                    }
+                }
+            }
+            drop(sender);
+
+            let hashed: Vec<_> = handles
+                .into_iter()
+                .map(|h| h.join().expect("hashing thread panicked"))
+                .collect();
+            walked?;
+            let mut manifest = collections::BTreeMap::new();
+            for entries in hashed {
+                manifest.extend(entries?);
+            }
+            Ok(manifest)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+
+    #[test]
+    fn hash_matches() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let manifest = Builder::new("**")
+            .build(&root)?
+            .hash_matches(HashAlgorithm::Sha256)
+            .map_err(|err| err.to_string())?;
+        assert_eq!(4 + 3 + 1 + 2, manifest.len());
+
+        let file = path::PathBuf::from(format!("{root}/some_file.txt"));
+        let contents = fs::read(&file).unwrap();
+
+        use sha2::Digest;
+        let expected: String = sha2::Sha256::digest(&contents)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(Some(&expected), manifest.get(&file));
+
+        let manifest = Builder::new("some_file.txt")
+            .build(&root)?
+            .hash_matches(HashAlgorithm::Blake3)
+            .map_err(|err| err.to_string())?;
+        assert_eq!(
+            Some(&blake3::hash(&contents).to_hex().to_string()),
+            manifest.get(&file)
+        );
+
+        let parallel = Builder::new("**")
+            .threads(3)
+            .build(&root)?
+            .hash_matches(HashAlgorithm::Sha256)
+            .map_err(|err| err.to_string())?;
+        let sequential = Builder::new("**")
+            .build(&root)?
+            .hash_matches(HashAlgorithm::Sha256)
+            .map_err(|err| err.to_string())?;
+        assert_eq!(sequential, parallel);
+
+        let relative = Builder::new("**")
+            .relative_paths(true)
+            .build(&root)?
+            .hash_matches(HashAlgorithm::Sha256)
+            .map_err(|err| err.to_string())?;
+        assert_eq!(sequential.len(), relative.len());
+        assert_eq!(
+            Some(&expected),
+            relative.get(path::Path::new("some_file.txt"))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::{ffi, path};
+
+/// Rule of a [`HiddenPolicy`], matched against the name of a single path component.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Rule {
+    Prefix(String),
+    Suffix(String),
+    Enclosed(String, String),
+    Name(String),
+}
+
+impl Rule {
+    fn is_hidden(&self, name: &[u8]) -> bool {
+        match self {
+            Rule::Prefix(prefix) => name.starts_with(prefix.as_bytes()),
+            Rule::Suffix(suffix) => name.ends_with(suffix.as_bytes()),
+            Rule::Enclosed(open, close) => {
+                name.len() >= open.len() + close.len()
+                    && name.starts_with(open.as_bytes())
+                    && name.ends_with(close.as_bytes())
+            }
+            Rule::Name(other) => name == other.as_bytes(),
+        }
+    }
+}
+
+/// Configurable convention for hidden files and folders.
+///
+/// This is the generalization of [`is_hidden_entry`](crate::is_hidden_entry) and
+/// [`is_hidden_path`](crate::is_hidden_path): The default policy hides all names that start with
+/// a dot, further rules can be registered, e.g., for `~$` temporary files of office suites,
+/// `#...#` backups of emacs or `__pycache__` folders. Rules are matched against the raw bytes of
+/// the names, such that names that are not valid unicode are supported as well.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let mut hidden = globmatch::HiddenPolicy::default();
+/// hidden.prefix("~$").enclosed("#", "#").name("__pycache__");
+///
+/// assert!(hidden.is_hidden_entry("docs/~$report.docx"));
+/// assert!(hidden.is_hidden_path("src/__pycache__/main.pyc"));
+/// assert!(!hidden.is_hidden_entry("notes/#1.md"));
+///
+/// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+///     .build(env!("CARGO_MANIFEST_DIR"))?
+///     .into_iter()
+///     .filter_entry(|p| !hidden.is_hidden_entry(p))
+///     .flatten()
+///     .collect();
+///
+/// assert_eq!(6 + 1, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HiddenPolicy {
+    rules: Vec<Rule>,
+}
+
+impl Default for HiddenPolicy {
+    /// Creates the policy that hides all names starting with a dot, e.g., `.git`.
+    fn default() -> Self {
+        HiddenPolicy {
+            rules: vec![Rule::Prefix(".".to_string())],
+        }
+    }
+}
+
+impl HiddenPolicy {
+    /// Creates a policy that does not hide any names, e.g., to only hide a custom set of names.
+    pub fn none() -> HiddenPolicy {
+        HiddenPolicy { rules: vec![] }
+    }
+
+    /// Hides all names that start with the given `prefix`, e.g., `~$`.
+    pub fn prefix<S>(&mut self, prefix: S) -> &mut HiddenPolicy
+    where
+        S: Into<String>,
+    {
+        self.rules.push(Rule::Prefix(prefix.into()));
+        self
+    }
+
+    /// Hides all names that end with the given `suffix`, e.g., `~` for backup files.
+    pub fn suffix<S>(&mut self, suffix: S) -> &mut HiddenPolicy
+    where
+        S: Into<String>,
+    {
+        self.rules.push(Rule::Suffix(suffix.into()));
+        self
+    }
+
+    /// Hides all names that start with `open` and end with `close`, e.g., `#` and `#` for
+    /// `#file.txt#`. The prefix and the suffix do not overlap, i.e., `#` itself is not hidden.
+    pub fn enclosed<S, T>(&mut self, open: S, close: T) -> &mut HiddenPolicy
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        self.rules.push(Rule::Enclosed(open.into(), close.into()));
+        self
+    }
+
+    /// Hides all entries with exactly the given name, e.g., `__pycache__`.
+    pub fn name<S>(&mut self, name: S) -> &mut HiddenPolicy
+    where
+        S: Into<String>,
+    {
+        self.rules.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Checks if the provided path is a hidden "entry", i.e., if its final path component is
+    /// hidden by any of the rules of this policy, see [`is_hidden_entry`](crate::is_hidden_entry).
+    pub fn is_hidden_entry<P>(&self, path: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        let name = path
+            .as_ref()
+            .file_name()
+            .unwrap_or_else(|| path.as_ref().as_os_str());
+        self.is_hidden_name(name)
+    }
+
+    /// Checks if the provided path has a path component that is hidden by any of the rules of
+    /// this policy, see [`is_hidden_path`](crate::is_hidden_path).
+    pub fn is_hidden_path<P>(&self, path: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        path.as_ref()
+            .components()
+            .any(|c| self.is_hidden_name(c.as_os_str()))
+    }
+
+    fn is_hidden_name(&self, name: &ffi::OsStr) -> bool {
+        let name = name.as_encoded_bytes();
+        self.rules.iter().any(|rule| rule.is_hidden(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_policy() {
+        let default = HiddenPolicy::default();
+        assert!(default.is_hidden_entry("a/.git"));
+        assert!(!default.is_hidden_entry(".git/config"));
+        assert!(default.is_hidden_path(".git/config"));
+        assert!(!HiddenPolicy::none().is_hidden_entry(".git"));
+
+        let mut policy = HiddenPolicy::none();
+        policy
+            .prefix("~$")
+            .suffix("~")
+            .enclosed("#", "#")
+            .name("__pycache__");
+        assert!(policy.is_hidden_entry("~$report.docx"));
+        assert!(policy.is_hidden_entry("main.rs~"));
+        assert!(policy.is_hidden_entry("#notes.org#"));
+        assert!(!policy.is_hidden_entry("#"));
+        assert!(!policy.is_hidden_entry("#notes.org"));
+        assert!(policy.is_hidden_path("pkg/__pycache__/mod.pyc"));
+        assert!(!policy.is_hidden_entry("__pycache__.py"));
+        assert!(!policy.is_hidden_entry(".git"));
+    }
+}
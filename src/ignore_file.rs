@@ -0,0 +1,431 @@
+use std::{borrow, fs, path};
+
+use crate::{Builder, Error, Glob, GlobSet};
+
+/// Single pattern of an ignore file, see [`Builder::from_ignore_file`].
+#[derive(Debug, Eq, PartialEq)]
+struct Line {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+/// Parses a line of an ignore file, providing `None` for blank lines and comments.
+fn parse_line(line: &str) -> Option<Line> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    if line.starts_with('#') {
+        return None;
+    }
+
+    // trailing spaces are ignored unless they are escaped with a backslash
+    let mut line = line.to_string();
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line.pop();
+    }
+    if line.ends_with("\\ ") {
+        line.truncate(line.len() - 2);
+        line.push(' ');
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.as_str()),
+    };
+    // a leading backslash escapes a literal `#` or `!`
+    let line = match line
+        .strip_prefix("\\#")
+        .or_else(|| line.strip_prefix("\\!"))
+    {
+        Some(rest) => format!("{}{rest}", &line[1..2]),
+        None => line.to_string(),
+    };
+
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line.as_str()),
+    };
+    let (anchored, glob) = match line.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (line.contains('/'), line),
+    };
+    if glob.is_empty() {
+        return None;
+    }
+
+    Some(Line {
+        glob: escape_extensions(glob),
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+/// Escapes the braces and `<` of the glob, which `git` matches literally, but which would be
+/// compiled as alternates and regular expression segments by this crate.
+fn escape_extensions(glob: &str) -> String {
+    let mut escaped = String::with_capacity(glob.len());
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                escaped.push(c);
+                escaped.extend(chars.next());
+            }
+            '[' => {
+                // classes are copied verbatim, a leading `]` is a member of the class
+                escaped.push(c);
+                if let Some(negation) = chars.next_if(|c| *c == '!' || *c == '^') {
+                    escaped.push(negation);
+                }
+                escaped.extend(chars.next_if_eq(&']'));
+                for c in chars.by_ref() {
+                    escaped.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '{' | '}' | '<' => escaped.extend(['[', c, ']']),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Builds the [`GlobSet`]s for the lines of an ignore file located in the folder `base`.
+fn parse(content: &str, base: &path::Path) -> Result<Vec<GlobSet<'static>>, Error> {
+    let base = match base.as_os_str().is_empty() {
+        true => String::new(),
+        false => globset::escape(&base.to_string_lossy()) + "/",
+    };
+
+    let mut sets = vec![];
+    for line in content.lines().filter_map(parse_line) {
+        let glob = match line.anchored {
+            true => format!("{base}{}", line.glob),
+            false => line.glob,
+        };
+
+        // the type of the matched path is not known, thus a pattern that is not restricted to
+        // directories matches the path itself and the contents of a directory of the same name
+        let mut globs = vec![format!("{glob}/**")];
+        if !line.dir_only {
+            globs.insert(0, glob);
+        }
+
+        for glob in globs {
+            let builder = Builder::new_owned(glob);
+            let mut set = match line.anchored {
+                true => builder.build_glob_set_anchored()?,
+                false => builder.build_glob_set()?,
+            };
+            set.negated = line.negated;
+            sets.push(set);
+        }
+    }
+    Ok(sets)
+}
+
+impl Builder<'static> {
+    /// Loads the patterns of a `.gitignore`-style file as a list of [`GlobSet`]s, e.g., to pass
+    /// them as `filter_entry` or `filter_post` to the functions in [`wrappers`](crate::wrappers).
+    ///
+    /// The file is parsed following the format of `.gitignore` files:
+    ///
+    /// - Blank lines and lines starting with `#` are skipped, trailing spaces are ignored unless
+    ///   they are escaped with a backslash. A leading `\#` or `\!` matches a literal `#` or `!`.
+    /// - A leading `!` negates the pattern, see [`GlobSet::is_negated`]. When matching the list
+    ///   using [`wrappers::is_match_ordered`](crate::wrappers::is_match_ordered), the last matching
+    ///   pattern decides, i.e., a negated pattern re-includes paths excluded by earlier patterns.
+    /// - A trailing `/` restricts the pattern to the contents of matching directories.
+    /// - A pattern with a leading or a middle `/` is anchored, i.e., it is matched relative to the
+    ///   folder containing the ignore file. All other patterns are matched in any folder.
+    /// - As for `git`, braces and `<re:...>` segments are matched literally.
+    ///
+    /// Since the filters do not access the file system, a pattern that is not restricted to
+    /// directories matches the path itself as well as the contents of a directory of the same
+    /// name. Other than `git`, a negated pattern also re-includes files within excluded
+    /// directories if the list is used as `filter_post`. The globs are matched case sensitive.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// use globmatch::wrappers;
+    ///
+    /// let root = env!("CARGO_MANIFEST_DIR");
+    /// let name = format!("globmatch-doc-{}.ignore", std::process::id());
+    /// let ignore = std::env::temp_dir().join(name);
+    /// std::fs::write(&ignore, "# skip some files\n*.md\n/b/\n").map_err(|err| err.to_string())?;
+    ///
+    /// let filter_post = globmatch::Builder::from_ignore_file(&ignore)?;
+    /// # std::fs::remove_file(&ignore).map_err(|err| err.to_string())?;
+    /// assert!(wrappers::is_match_ordered(&filter_post, "a/a0/a0_2.md"));
+    /// assert!(!wrappers::is_match_ordered(&filter_post, "a/a0/a0_0.txt"));
+    ///
+    /// let candidates = wrappers::build_matchers(&["test-files/c-simple/a/**/*.*"], &root)?;
+    /// let (paths, _) = wrappers::match_paths(candidates, None, Some(filter_post));
+    /// assert!(paths.iter().all(|p| p.extension().is_some_and(|ext| ext == "txt")));
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the file cannot be read and with the errors described for
+    /// [`Builder::build_glob_set`] if any of the patterns is invalid.
+    pub fn from_ignore_file<P>(path: P) -> Result<Vec<GlobSet<'static>>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let path = path.as_ref();
+        let content = read(path)?;
+        parse(&content, path.parent().unwrap_or(path::Path::new("")))
+    }
+}
+
+fn read(path: &path::Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| {
+        let message = format!(
+            "Failed to read ignore file {}: {err}",
+            path.to_string_lossy()
+        );
+        Error::walk_io(message, err)
+    })
+}
+
+/// Compiled pattern of a [`GitignoreSet`].
+#[derive(Debug)]
+struct Rule {
+    glob: Glob<'static>,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+/// Matcher implementing the semantics of `.gitignore` files for arbitrary paths.
+///
+/// Other than the filters provided by [`Builder::from_ignore_file`], this type follows the rules
+/// that `git` applies when deciding whether a path is ignored, without walking or accessing the
+/// file system:
+///
+/// - The last pattern matching a path decides, a negated pattern `!glob` re-includes the path.
+/// - A path within an ignored directory is ignored, regardless of any negated patterns since
+///   `git` does not descend into ignored directories.
+/// - Patterns with a leading or a middle `/` are anchored and matched against the full path,
+///   all other patterns are matched against the name of the path, i.e., in any folder.
+/// - Patterns with a trailing `/` only match directories.
+///
+/// Paths are matched relative to the folder containing the patterns, and since the type of a
+/// path cannot be determined without accessing the file system, the caller specifies whether
+/// a path is a directory. Paths with `..` components are never ignored, since they are not
+/// resolved and thus may point outside of the folder. The patterns are compiled using the glob
+/// syntax of this crate, with braces and `<re:...>` segments matched literally; the globs are
+/// matched case sensitive.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let set = globmatch::GitignoreSet::new("*.log\n!keep.log\n/build/\n")?;
+///
+/// assert!(set.is_ignored("logs/debug.log", false));
+/// assert!(!set.is_ignored("logs/keep.log", false));
+/// assert!(set.is_ignored("build", true));
+/// assert!(!set.is_ignored("build", false));
+/// assert!(set.is_ignored("build/keep.log", false));
+/// assert!(!set.is_ignored("src/build/main.rs", false));
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GitignoreSet {
+    rules: Vec<Rule>,
+}
+
+impl GitignoreSet {
+    /// Creates a [`GitignoreSet`] from the contents of a `.gitignore` file, refer to
+    /// [`Builder::from_ignore_file`] for the supported format.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the errors described for [`Builder::build_glob`] if any of the patterns is
+    /// invalid.
+    pub fn new(content: &str) -> Result<GitignoreSet, Error> {
+        let rules = content
+            .lines()
+            .filter_map(parse_line)
+            .map(|line| {
+                Ok(Rule {
+                    glob: Builder::new_owned(line.glob).build_glob()?,
+                    negated: line.negated,
+                    dir_only: line.dir_only,
+                    anchored: line.anchored,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(GitignoreSet { rules })
+    }
+
+    /// Creates a [`GitignoreSet`] from the provided `.gitignore` file.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Walk`] if the file cannot be read, refer to [`GitignoreSet::new`].
+    pub fn from_file<P>(path: P) -> Result<GitignoreSet, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        GitignoreSet::new(&read(path.as_ref())?)
+    }
+
+    /// Provides the number of patterns in the set, excluding blank lines and comments.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Checks whether the set does not contain any patterns.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Checks whether `git` would ignore the provided path, relative to the folder containing the
+    /// patterns. The flag `is_dir` specifies whether the path is a directory.
+    ///
+    /// Paths with `..` components are not resolved and thus never ignored.
+    pub fn is_ignored<P>(&self, path: P, is_dir: bool) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        let path = path.as_ref();
+        if path
+            .components()
+            .any(|c| matches!(c, path::Component::ParentDir))
+        {
+            return false;
+        }
+
+        let names: Vec<_> = path
+            .components()
+            .filter_map(|c| match c {
+                path::Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect();
+
+        // all parent folders are directories, `git` does not descend into ignored folders
+        (1..names.len()).any(|n| self.matched(&names[..n], true))
+            || (!names.is_empty() && self.matched(&names, is_dir))
+    }
+
+    fn matched(&self, names: &[borrow::Cow<'_, str>], is_dir: bool) -> bool {
+        let path = names.join("/");
+        let name = &names[names.len() - 1];
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| is_dir || !rule.dir_only)
+            .find(|rule| match rule.anchored {
+                true => rule.glob.is_match(&path),
+                false => rule.glob.is_match(name.as_ref()),
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+    use crate::wrappers::is_match_ordered;
+
+    #[test]
+    fn ignore_file_lines() {
+        assert_eq!(None, parse_line(""));
+        assert_eq!(None, parse_line("# comment"));
+        assert_eq!(None, parse_line("/"));
+
+        let line = parse_line("!/build/  ").unwrap();
+        assert_eq!("build", line.glob);
+        assert!(line.negated && line.dir_only && line.anchored);
+
+        let line = parse_line("doc/*.md\r").unwrap();
+        assert_eq!("doc/*.md", line.glob);
+        assert!(!line.negated && !line.dir_only && line.anchored);
+
+        assert_eq!("#file", parse_line("\\#file").unwrap().glob);
+        assert_eq!("!file", parse_line("\\!file").unwrap().glob);
+        assert_eq!("file ", parse_line("file\\ ").unwrap().glob);
+        assert!(!parse_line("*.log").unwrap().anchored);
+
+        // braces and regular expression segments are not supported by git
+        assert_eq!("[{]a,b[}].txt", parse_line("{a,b}.txt").unwrap().glob);
+        assert_eq!("[<]re:a+>/*", parse_line("<re:a+>/*").unwrap().glob);
+        assert_eq!("[{}]/\\{", parse_line("[{}]/\\{").unwrap().glob);
+        assert_eq!("[]{]/[!]}]", parse_line("[]{]/[!]}]").unwrap().glob);
+    }
+
+    #[test]
+    fn ignore_file() -> Result<(), String> {
+        let base = utils::TempDir::new("ignore-file")?;
+        let content = "# generated\n*.log\n!keep.log\ntarget/\n/docs/*.md\n";
+        let sets = Builder::from_ignore_file(base.write(".ignore", content)?)?;
+        assert_eq!(2 + 2 + 1 + 2, sets.len());
+
+        let is_match = |p: &str| is_match_ordered(&sets, base.join(p));
+        assert!(is_match("debug.log"));
+        assert!(is_match("sub/debug.log"));
+        assert!(!is_match("sub/keep.log"));
+        assert!(is_match("sub/target/main.o"));
+        assert!(!is_match("sub/target"));
+        assert!(is_match("docs/readme.md"));
+        assert!(is_match("docs/readme.md/content"));
+        assert!(!is_match("sub/docs/readme.md"));
+        assert!(!is_match("main.rs"));
+
+        let err = Builder::from_ignore_file(base.join("globmatch-missing.ignore"));
+        assert!(matches!(err, Err(Error::Walk { .. })));
+
+        // relative ignore files are anchored at the relative folder
+        let sets = parse("/a/*.txt\n", path::Path::new(""))?;
+        assert!(is_match_ordered(&sets, "a/a0.txt"));
+        assert!(!is_match_ordered(&sets, "b/a/a0.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn gitignore_set() -> Result<(), String> {
+        let content =
+            "# build outputs\n/target/\n*.o\n!main.o\nlogs/\n!logs/keep.txt\ndoc/**/*.tmp\n";
+        let set = GitignoreSet::new(content)?;
+        assert_eq!(6, set.len());
+        assert!(GitignoreSet::new("# only comments\n\n")?.is_empty());
+
+        assert!(set.is_ignored("target", true));
+        assert!(set.is_ignored("target/debug/app", false));
+        assert!(!set.is_ignored("target", false));
+        assert!(!set.is_ignored("sub/target/app", false));
+
+        assert!(set.is_ignored("a/b/lib.o", false));
+        assert!(!set.is_ignored("a/b/main.o", false));
+        assert!(set.is_ignored("./lib.o", false));
+
+        // files within ignored folders cannot be re-included
+        assert!(set.is_ignored("sub/logs", true));
+        assert!(set.is_ignored("logs/keep.txt", false));
+        assert!(!set.is_ignored("logs", false));
+
+        assert!(set.is_ignored("doc/a/b/x.tmp", false));
+        assert!(set.is_ignored("doc/x.tmp", false));
+        assert!(!set.is_ignored("src/doc/x.tmp", false));
+        assert!(!set.is_ignored("src/main.rs", false));
+        assert!(!set.is_ignored("", true));
+        assert!(!set.is_ignored("../lib.o", false));
+
+        let set = GitignoreSet::new("{a,b}.txt\n<re:x+>.log\n")?;
+        assert!(!set.is_ignored("a.txt", false));
+        assert!(set.is_ignored("{a,b}.txt", false));
+        assert!(!set.is_ignored("xx.log", false));
+        assert!(set.is_ignored("<re:x+>.log", false));
+        Ok(())
+    }
+}
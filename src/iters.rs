@@ -1,66 +1,626 @@
-use std::path;
+use std::{collections, fs, path, time};
 
+use crate::cancel::CancellationHandle;
 use crate::error::Error;
+use crate::observer::{Observer, Progress, SkipReason};
+use crate::options::{MetadataFilter, OnError, PathNormalization, WalkOptions};
+use crate::source::{SourceDirEntry, SourceFileType, SourceWalk, WalkSource};
+use crate::{pattern, utils};
+
+/// Entry of a walk, i.e., a `walkdir::DirEntry` or an entry of a [`WalkSource`].
+pub(crate) trait WalkEntry {
+    fn path(&self) -> &path::Path;
+    fn depth(&self) -> usize;
+    fn file_type(&self) -> SourceFileType;
+    fn path_is_symlink(&self) -> bool;
+    /// Checks whether the entry resolves to a path within the canonical `root`.
+    fn resolves_within(&self, root: &path::Path) -> bool;
+    fn is_metadata_match(&self, filter: &MetadataFilter) -> Result<bool, Error>;
+}
+
+impl WalkEntry for walkdir::DirEntry {
+    fn path(&self) -> &path::Path {
+        walkdir::DirEntry::path(self)
+    }
+
+    fn depth(&self) -> usize {
+        walkdir::DirEntry::depth(self)
+    }
+
+    fn file_type(&self) -> SourceFileType {
+        walkdir::DirEntry::file_type(self).into()
+    }
+
+    fn path_is_symlink(&self) -> bool {
+        walkdir::DirEntry::path_is_symlink(self)
+    }
+
+    fn resolves_within(&self, root: &path::Path) -> bool {
+        utils::is_within(walkdir::DirEntry::path(self), root)
+    }
+
+    fn is_metadata_match(&self, filter: &MetadataFilter) -> Result<bool, Error> {
+        let metadata = walkdir::DirEntry::metadata(self).map_err(Error::from)?;
+        Ok(filter.is_match(&metadata))
+    }
+}
+
+impl WalkEntry for SourceDirEntry {
+    fn path(&self) -> &path::Path {
+        &self.path
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn file_type(&self) -> SourceFileType {
+        self.metadata.file_type
+    }
+
+    fn path_is_symlink(&self) -> bool {
+        self.metadata.file_type == SourceFileType::Symlink
+    }
+
+    fn resolves_within(&self, _root: &path::Path) -> bool {
+        // the targets of the symbolic links of a source are unknown
+        false
+    }
+
+    fn is_metadata_match(&self, filter: &MetadataFilter) -> Result<bool, Error> {
+        Ok(filter.is_source_match(&self.metadata))
+    }
+}
+
+/// Recursive walk of an [`IterAll`], i.e., `walkdir::IntoIter` or a [`SourceWalk`].
+pub(crate) trait Walk {
+    type Entry: WalkEntry;
+
+    fn next_entry(&mut self) -> Option<Result<Self::Entry, Error>>;
+    fn skip_current_dir(&mut self);
+}
+
+impl Walk for walkdir::IntoIter {
+    type Entry = walkdir::DirEntry;
+
+    fn next_entry(&mut self) -> Option<Result<Self::Entry, Error>> {
+        self.next().map(|next| next.map_err(Error::from))
+    }
+
+    fn skip_current_dir(&mut self) {
+        walkdir::IntoIter::skip_current_dir(self);
+    }
+}
+
+impl<S> Walk for SourceWalk<S>
+where
+    S: WalkSource,
+{
+    type Entry = SourceDirEntry;
+
+    fn next_entry(&mut self) -> Option<Result<Self::Entry, Error>> {
+        SourceWalk::next_entry(self)
+    }
+
+    fn skip_current_dir(&mut self) {
+        SourceWalk::skip_current_dir(self);
+    }
+}
+
+/// State that is shared by the [`IterAll`] and [`IterFilter`] iterators.
+#[derive(Debug)]
+struct WalkState<P>
+where
+    P: AsRef<path::Path>,
+{
+    root: P,
+    matcher: pattern::PatternSet,
+    options: WalkOptions,
+    /// Number of entries that have been walked so far.
+    entries: usize,
+    /// Number of directories that have been entered so far.
+    dirs: usize,
+    /// Number of matches that have been found so far.
+    matches: usize,
+    /// Start of the walk, i.e., the time at which the first entry has been requested.
+    started: Option<time::Instant>,
+    /// Set once the iteration has been aborted, e.g., since a limit has been exceeded.
+    done: bool,
+    /// Set if the contents of the last walked directory should be skipped.
+    skip: bool,
+    observer: Observer,
+    /// Handles that abort the iteration once any of them is cancelled.
+    cancel: Vec<CancellationHandle>,
+    /// Canonicalized root, determined once the first path is canonicalized.
+    canonical_root: Option<Option<path::PathBuf>>,
+    /// Canonical root that walked paths must not escape, see [`IterAll::with_jail_root`].
+    jail_root: Option<Option<path::PathBuf>>,
+    /// Files that have been yielded so far, only recorded if duplicates are skipped.
+    seen: collections::HashSet<utils::FileId>,
+    #[cfg(feature = "ignore")]
+    gitignore: Option<crate::gitignore::GitignoreStack>,
+}
+
+impl<P> WalkState<P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Consistent implementation of the `next` functions for [`IterAll`] and [`IterFilter`].
+    ///
+    /// Returns `None` if the iterator should continue with the next entry.
+    fn match_next<E>(
+        &mut self,
+        next: Option<Result<E, Error>>,
+    ) -> Option<Option<Result<(E, path::PathBuf), Error>>>
+    where
+        E: WalkEntry,
+    {
+        if let Some(end) = self.check_next(next.is_some()) {
+            return Some(end.map(Err));
+        }
+        self.process_next(next)
+    }
+
+    /// Checks the cancellation and the limits of the walk and counts the walked entry, if any.
+    ///
+    /// This check runs for every walked entry, including the entries that are rejected by the
+    /// predicate of an [`IterFilter`]. Returns `None` if the entry should be processed, or the
+    /// error that must be yielded as the last item if the iteration ends.
+    fn check_next(&mut self, is_entry: bool) -> Option<Option<Error>> {
+        if self.done {
+            return Some(None);
+        }
+
+        if self.cancel.iter().any(CancellationHandle::is_cancelled) {
+            return Some(Some(self.abort(Error::Cancelled)));
+        }
+
+        let started = *self.started.get_or_insert_with(time::Instant::now);
+        if let Some(timeout) = self.options.timeout {
+            if started.elapsed() > timeout {
+                return Some(Some(self.abort(Error::Timeout(timeout))));
+            }
+        }
+
+        if is_entry {
+            self.entries += 1;
+            if let Some(max) = self.options.max_entries {
+                if self.entries > max {
+                    return Some(Some(self.abort(Error::EntryLimit(max))));
+                }
+            }
+            if self.observer.is_progress_due(self.entries) {
+                self.observer.on_progress(self.progress());
+            }
+        }
+        None
+    }
+
+    /// Matches the entry that has passed [`WalkState::check_next`], see
+    /// [`WalkState::match_next`].
+    fn process_next<E>(
+        &mut self,
+        next: Option<Result<E, Error>>,
+    ) -> Option<Option<Result<(E, path::PathBuf), Error>>>
+    where
+        E: WalkEntry,
+    {
+        match next {
+            None => {
+                self.finish();
+                Some(None)
+            }
+            Some(res) => match res {
+                Ok(dir) => {
+                    if self.is_ignored(&dir) {
+                        self.observer.on_skip(dir.path(), SkipReason::Ignored);
+                        self.skip = dir.file_type() == SourceFileType::Dir;
+                        return None;
+                    }
+                    if self.options.jail && !self.is_jailed(&dir) {
+                        // followed links are not walked either
+                        self.skip = dir.file_type() == SourceFileType::Dir;
+                        let err = Error::OutsideRoot(path::PathBuf::from(dir.path()));
+                        return self.on_error(err).map(|err| Some(Err(err)));
+                    }
+
+                    // assuming that walkdir doesn't create any paths that do not have the provided
+                    // prefix we can simply exclude such paths since matching on them will anyhow
+                    // be impossible
+                    let is_dir = dir.file_type() == SourceFileType::Dir;
+                    let p = match dir.depth() == 0 && !is_dir {
+                        // the root itself is a file, match on its name as if walking its parent
+                        true => path::Path::new(dir.path().file_name()?),
+                        false => dir.path().strip_prefix(&self.root).ok()?,
+                    };
+                    // println!("checking {:?} -- {}", p, matcher.is_match(p));
+
+                    if dir.depth() > 0 && is_dir {
+                        trace!(path = ?dir.path(), depth = dir.depth(), "entering directory");
+                        if self.matcher.is_pruned(dir.path()) {
+                            self.observer.on_skip(dir.path(), SkipReason::Excluded);
+                            self.skip = true;
+                            return None;
+                        }
+                        // the entry itself may still be a match, only its contents are skipped
+                        self.skip = !self.matcher.may_contain_match(p);
+                        if self.skip {
+                            trace!(path = ?dir.path(), "contents cannot match, skipping directory");
+                        }
+                    }
+                    if is_dir && !self.skip {
+                        self.dirs += 1;
+                    }
+                    // the relative path of the root is empty, which is only matched by globstars
+                    if dir.depth() == 0 && is_dir && !self.matcher.may_match_root() {
+                        return None;
+                    }
+
+                    trace!(
+                        path = ?dir.path(),
+                        relative = ?p,
+                        matched = self.matcher.is_match(p),
+                        "matching entry"
+                    );
+
+                    if self.matcher.is_match(p) && self.options.file_type.accepts(dir.file_type()) {
+                        if self.matcher.is_excluded(dir.path()) {
+                            self.observer.on_skip(dir.path(), SkipReason::Excluded);
+                            return None;
+                        }
+                        if !self.matcher.is_allowed(dir.path(), is_dir) {
+                            return None;
+                        }
+                        if self.options.metadata.is_active() {
+                            match dir.is_metadata_match(&self.options.metadata) {
+                                Ok(true) => {}
+                                Ok(false) => return None,
+                                Err(err) => return self.on_error(err).map(|err| Some(Err(err))),
+                            }
+                        }
+                        if self.options.dedup_files {
+                            let id = utils::file_id(dir.path(), self.options.follow_links);
+                            if id.is_some_and(|id| !self.seen.insert(id)) {
+                                self.observer.on_skip(dir.path(), SkipReason::Duplicate);
+                                return None;
+                            }
+                        }
+
+                        self.matches += 1;
+                        if let Some(max) = self.options.max_matches {
+                            if self.matches > max {
+                                return Some(Some(Err(self.abort(Error::MatchLimit(max)))));
+                            }
+                        }
+
+                        self.observer.on_match(dir.path());
+                        let relative = path::PathBuf::from(p);
+                        return Some(Some(Ok((dir, relative))));
+                    }
+                    None // iterator should continue
+                }
+                Err(err) => match self.options.missing_root_error(err) {
+                    Some(err) => self.on_error(err).map(|err| Some(Err(err))),
+                    None => None,
+                },
+            },
+        }
+    }
+
+    /// Provides the progress of the walk.
+    fn progress(&self) -> Progress {
+        Progress {
+            entries: self.entries,
+            dirs: self.dirs,
+            matches: self.matches,
+        }
+    }
+
+    /// Aborts the iteration, provides the error that must be yielded as the last item.
+    fn abort(&mut self, err: Error) -> Error {
+        self.observer.on_error(&err);
+        self.finish();
+        err
+    }
+
+    /// Ends the iteration, the final progress is reported once the walk ends for any reason.
+    fn finish(&mut self) {
+        if !self.done {
+            self.done = true;
+            self.observer.on_progress(self.progress());
+        }
+    }
+
+    /// Applies the configured error policy, provides the error if it must be yielded.
+    fn on_error(&mut self, err: Error) -> Option<Error> {
+        self.observer.on_error(&err);
+        match self.options.on_error {
+            OnError::Yield => Some(err),
+            OnError::Skip => None,
+            OnError::Abort => {
+                self.finish();
+                Some(err)
+            }
+        }
+    }
+
+    /// Checks whether the entry is excluded by a `.gitignore` file.
+    #[cfg(feature = "ignore")]
+    fn is_ignored<E>(&mut self, entry: &E) -> bool
+    where
+        E: WalkEntry,
+    {
+        let is_dir = entry.file_type() == SourceFileType::Dir;
+        match &mut self.gitignore {
+            Some(gitignore) => {
+                gitignore.is_ignored(self.root.as_ref(), entry.path(), entry.depth(), is_dir)
+            }
+            None => false,
+        }
+    }
+
+    /// Checks whether the entry is excluded by a `.gitignore` file.
+    #[cfg(not(feature = "ignore"))]
+    fn is_ignored<E>(&mut self, _entry: &E) -> bool
+    where
+        E: WalkEntry,
+    {
+        false
+    }
+
+    /// Checks whether the entry is contained within the jail root, see `jail`.
+    ///
+    /// Only symbolic links and paths with `..` components, which are only provided by custom
+    /// sources, may escape the root. The symbolic links of a source cannot be resolved and are
+    /// therefore never within the root.
+    fn is_jailed<E>(&mut self, entry: &E) -> bool
+    where
+        E: WalkEntry,
+    {
+        let p = entry.path();
+        let relative = p.strip_prefix(self.root.as_ref()).unwrap_or(p);
+        if relative
+            .components()
+            .any(|c| c == path::Component::ParentDir)
+        {
+            return false;
+        }
+        if !entry.path_is_symlink() {
+            return true;
+        }
+        let root = self
+            .jail_root
+            .get_or_insert_with(|| fs::canonicalize(self.root.as_ref()).ok());
+        root.as_deref()
+            .is_some_and(|root| entry.resolves_within(root))
+    }
+
+    /// Provides the path that is yielded for the matching entry.
+    ///
+    /// Returns `None` if the path cannot be normalized and the error is skipped, see
+    /// [`WalkOptions::yielded_path`].
+    fn yielded_path<E>(
+        &mut self,
+        entry: &E,
+        relative: &path::Path,
+    ) -> Option<Result<path::PathBuf, Error>>
+    where
+        E: WalkEntry,
+    {
+        let canonical = self.options.normalization == PathNormalization::Canonical;
+        // paths of walked symbolic links cannot be canonicalized by joining them to the root
+        if canonical && self.canonical_root.is_none() {
+            let root = match self.options.follow_links {
+                true => None,
+                false => fs::canonicalize(self.root.as_ref())
+                    .ok()
+                    .filter(|root| root.is_dir()),
+            };
+            self.canonical_root = Some(root);
+        }
+        let canonical_root = match entry.path_is_symlink() {
+            true => None,
+            false => self
+                .canonical_root
+                .as_ref()
+                .and_then(|root| root.as_deref()),
+        };
+
+        match self
+            .options
+            .yielded_path(entry.path(), relative, canonical_root)
+        {
+            Ok(p) => Some(Ok(p)),
+            Err(err) => self
+                .on_error(Error::from_io(entry.path(), entry.depth(), err))
+                .map(Err),
+        }
+    }
+
+    /// Provides whether the contents of the last walked directory should be skipped.
+    fn take_skip(&mut self) -> bool {
+        std::mem::take(&mut self.skip)
+    }
+}
+
+impl<P> Drop for WalkState<P>
+where
+    P: AsRef<path::Path>,
+{
+    fn drop(&mut self) {
+        // iterators that are dropped before the walk ends still report the final progress
+        if self.started.is_some() {
+            self.finish();
+        }
+    }
+}
+
+/// Matching entry yielded by [`IterRaw`].
+#[derive(Debug, Clone)]
+pub struct RawEntry {
+    /// The original entry provided by `walkdir`.
+    pub entry: walkdir::DirEntry,
+    /// The path of the entry relative to the root of the [`Matcher`](crate::Matcher), i.e.,
+    /// the path that has been matched against the glob.
+    pub relative: path::PathBuf,
+}
 
 /// Standard iterator created from a [`Matcher`](./struct.Matcher.html).
 ///
 /// This iterator iterates over all paths recursively without any filter. Use
 /// [`IterAll::filter_entry`] to create a more efficient [`IterFilter`] out of this iterator,
 /// e.g., do not walk any hidden folders such as `.git`.
+///
+/// By default the file system is walked using `walkdir`, see
+/// [`WalkDirSource`](crate::WalkDirSource). An iterator created via
+/// [`Builder::build_source`](crate::Builder::build_source) walks a [`WalkSource`] instead, the
+/// walk `W` is then a [`SourceWalk`]. Filters and entries that provide the `walkdir` entries
+/// are only available for the file system.
 #[derive(Debug)]
-pub struct IterAll<P>
+pub struct IterAll<P, W = walkdir::IntoIter>
 where
     P: AsRef<path::Path>,
 {
-    root: P,
-    iter: walkdir::IntoIter,
-    matcher: globset::GlobMatcher,
+    iter: W,
+    state: WalkState<P>,
 }
 
-impl<P> IterAll<P>
+impl<P, W> IterAll<P, W>
 where
     P: AsRef<path::Path>,
 {
     pub(crate) fn new(
         root: P,
-        iter: walkdir::IntoIter,
-        matcher: globset::GlobMatcher,
-    ) -> IterAll<P> {
+        iter: W,
+        matcher: pattern::PatternSet,
+        options: WalkOptions,
+        observer: Observer,
+        cancel: Option<CancellationHandle>,
+    ) -> IterAll<P, W> {
+        #[cfg(feature = "ignore")]
+        let gitignore = match options.gitignore {
+            true => crate::gitignore::GitignoreStack::new(root.as_ref(), true, vec![]),
+            false => None,
+        };
         IterAll {
-            root,
             iter,
-            matcher,
+            state: WalkState {
+                root,
+                matcher,
+                options,
+                entries: 0,
+                dirs: 0,
+                matches: 0,
+                started: None,
+                done: false,
+                skip: false,
+                observer,
+                cancel: cancel.into_iter().collect(),
+                canonical_root: None,
+                jail_root: None,
+                seen: collections::HashSet::new(),
+                #[cfg(feature = "ignore")]
+                gitignore,
+            },
         }
     }
+
+    /// Checks walked symbolic links against the given canonical root instead of the resolved
+    /// root, see [`Builder::jail`](crate::Builder::jail).
+    pub(crate) fn with_jail_root(mut self, root: Option<path::PathBuf>) -> IterAll<P, W> {
+        if root.is_some() {
+            self.state.jail_root = Some(root);
+        }
+        self
+    }
+
+    /// Additionally aborts the iteration once the given handle is cancelled, e.g., by the owner
+    /// of the iterator without affecting other walks that use the handle of the matcher.
+    #[cfg(feature = "async")]
+    pub(crate) fn cancel_on(mut self, handle: CancellationHandle) -> IterAll<P, W> {
+        self.state.cancel.push(handle);
+        self
+    }
+
+    /// Applies the ignore files with the given names within the walked folders, see
+    /// [`Builder::add_ignore_file_name`](crate::Builder::add_ignore_file_name).
+    #[cfg(feature = "ignore")]
+    pub(crate) fn with_ignore_files(mut self, names: Vec<String>) -> IterAll<P, W> {
+        if !names.is_empty() {
+            let root = self.state.root.as_ref();
+            let gitignore = self.state.options.gitignore;
+            self.state.gitignore = crate::gitignore::GitignoreStack::new(root, gitignore, names);
+        }
+        self
+    }
 }
 
-/// Helper function for a consistent implementation of the `next` functions for
-/// [`IterAll`] and [`IterFilter`].
-fn match_next<P>(
-    root: P,
-    next: Option<Result<walkdir::DirEntry, walkdir::Error>>,
-    matcher: &globset::GlobMatcher,
-) -> Option<Option<Result<path::PathBuf, Error>>>
+// the walk is an implementation detail, the functions of this block are private as well
+#[allow(private_bounds)]
+impl<P, W> IterAll<P, W>
 where
     P: AsRef<path::Path>,
+    W: Walk,
 {
-    match next {
-        None => Some(None),
-        Some(res) => match res {
-            Ok(dir) => {
-                // assuming that walkdir doesn't create any paths that do not have the provided
-                // prefix we can simply exclude such paths since matching on them will anyhow
-                // be impossible
-                let p = dir.path().strip_prefix(root).ok()?;
-                // println!("checking {:?} -- {}", p, matcher.is_match(p));
+    /// Provides the next matching entry and its path relative to the root.
+    fn next_match(&mut self) -> Option<Result<(W::Entry, path::PathBuf), Error>> {
+        loop {
+            let next = self.state.match_next(self.iter.next_entry());
+            if self.state.take_skip() {
+                self.iter.skip_current_dir();
+            }
+            match next {
+                None => continue,
+                Some(entry) => {
+                    return entry;
+                }
+            };
+        }
+    }
+
+    /// Provides the next matching path.
+    fn next_path(&mut self) -> Option<Result<path::PathBuf, Error>> {
+        self.next_walked()
+            .map(|next| next.map(|(_, yielded)| yielded))
+    }
+
+    /// Provides the next matching path together with the type of the walked entry.
+    pub(crate) fn next_typed(&mut self) -> Option<Result<(path::PathBuf, SourceFileType), Error>> {
+        loop {
+            match self.next_match()? {
+                Ok((entry, relative)) => {
+                    if let Some(p) = self.state.yielded_path(&entry, &relative) {
+                        return Some(p.map(|p| (p, entry.file_type())));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
 
-                if matcher.is_match(p) {
-                    return Some(Some(Ok(path::PathBuf::from(dir.path()))));
+    /// Provides the walked path of the next match together with the path that is yielded for it.
+    pub(crate) fn next_walked(&mut self) -> Option<Result<(path::PathBuf, path::PathBuf), Error>> {
+        loop {
+            match self.next_match()? {
+                Ok((entry, relative)) => {
+                    if let Some(p) = self.state.yielded_path(&entry, &relative) {
+                        return Some(p.map(|p| (entry.path().to_path_buf(), p)));
+                    }
                 }
-                None // iterator should continue
+                Err(err) => return Some(Err(err)),
             }
-            Err(err) => Some(Some(Err(err.into()))),
-        },
+        }
+    }
+}
+
+impl<P> IterAll<P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Provides the next matching entry.
+    fn next_entry(&mut self) -> Option<Result<RawEntry, Error>> {
+        self.next_match()
+            .map(|next| next.map(|(entry, relative)| RawEntry { entry, relative }))
     }
 }
 
@@ -71,14 +631,19 @@ where
     type Item = Result<path::PathBuf, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match match_next(&self.root, self.iter.next(), &self.matcher) {
-                None => continue,
-                Some(entry) => {
-                    return entry;
-                }
-            };
-        }
+        self.next_path()
+    }
+}
+
+impl<P, S> Iterator for IterAll<P, SourceWalk<S>>
+where
+    P: AsRef<path::Path>,
+    S: WalkSource,
+{
+    type Item = Result<path::PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_path()
     }
 }
 
@@ -98,29 +663,164 @@ where
     where
         PrePath: FnMut(&path::Path) -> bool,
     {
-        // TODO: instead of creating an IterFilter it should be possible to swap out the
-        // implementation and return an IterAll<walkdir::FilterEntry> ?
         IterFilter {
-            root: self.root,
-            iter: self.iter.filter_entry(move |entry| predicate(entry.path())),
-            matcher: self.matcher,
+            iter: self.iter,
+            predicate: move |entry: &walkdir::DirEntry| predicate(entry.path()),
+            state: self.state,
         }
     }
+
+    /// Transform the iterator into a [`IterFilter`] using the given predicate.
+    ///
+    /// Behaves like [`IterAll::filter_entry`] but the predicate receives an [`EntryInfo`],
+    /// which provides the depth and the file type of the entry, and its metadata on demand. This
+    /// allows to skip folders, e.g., by their depth without additional calls to the file system.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .filter_entry_full(|e| !(e.file_type().is_dir() && e.depth() > 0))
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(1, paths.len()); // only `some_file.txt` is not within a sub-folder
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn filter_entry_full<PreEntry>(
+        self,
+        mut predicate: PreEntry,
+    ) -> IterFilter<walkdir::IntoIter, P, impl FnMut(&walkdir::DirEntry) -> bool>
+    where
+        PreEntry: FnMut(&EntryInfo<'_>) -> bool,
+    {
+        IterFilter {
+            iter: self.iter,
+            predicate: move |entry: &walkdir::DirEntry| predicate(&EntryInfo { entry }),
+            state: self.state,
+        }
+    }
+}
+
+/// Walked entry passed to the predicate of [`IterAll::filter_entry_full`].
+///
+/// Provides the information that is known while walking the file system without additional
+/// calls to the file system, except for [`EntryInfo::metadata`].
+#[derive(Debug, Clone, Copy)]
+pub struct EntryInfo<'a> {
+    entry: &'a walkdir::DirEntry,
+}
+
+impl<'a> EntryInfo<'a> {
+    /// The path of the entry.
+    pub fn path(&self) -> &'a path::Path {
+        self.entry.path()
+    }
+
+    /// The depth of the entry relative to the root of the [`Matcher`](crate::Matcher), where
+    /// the root itself has the depth zero.
+    pub fn depth(&self) -> usize {
+        self.entry.depth()
+    }
+
+    /// The type of the entry, as determined while walking the file system.
+    pub fn file_type(&self) -> fs::FileType {
+        self.entry.file_type()
+    }
+
+    /// Queries the metadata of the entry.
+    ///
+    /// The metadata is only queried when calling this function, i.e., predicates that check,
+    /// e.g., the size of an entry should check the cheaper properties first.
+    pub fn metadata(&self) -> Result<fs::Metadata, Error> {
+        self.entry.metadata().map_err(Error::from)
+    }
 }
 
 /// Filtered iterator created via [`IterAll::filter_entry`].
 ///
 /// This iterator iterates over all paths recursively but applies the configured predicate
-/// to all paths.
+/// to all paths. Further predicates can be added using [`IterFilter::filter_entry`], e.g., to
+/// compose independent filters for hidden folders and build outputs.
 #[derive(Debug)]
 pub struct IterFilter<I, P, PreDir>
 where
     PreDir: FnMut(&walkdir::DirEntry) -> bool,
     P: AsRef<path::Path>,
 {
-    root: P,
-    iter: walkdir::FilterEntry<I, PreDir>,
-    matcher: globset::GlobMatcher,
+    iter: I,
+    predicate: PreDir,
+    state: WalkState<P>,
+}
+
+impl<PreDir, P> IterFilter<walkdir::IntoIter, P, PreDir>
+where
+    PreDir: FnMut(&walkdir::DirEntry) -> bool,
+    P: AsRef<path::Path>,
+{
+    /// Provides the number of entries that have been walked so far, including the entries that
+    /// have been skipped by the predicates. The contents of skipped folders are not walked and
+    /// thus not counted.
+    pub(crate) fn entries(&self) -> usize {
+        self.state.entries
+    }
+
+    /// Adds the given predicate to the predicates of this iterator.
+    ///
+    /// Only paths that satisfy all predicates are walked, see [`IterAll::filter_entry`]. The
+    /// predicates are applied in the order in which they have been added.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .filter_entry(|p| !globmatch::is_hidden_path(p))
+    ///     .filter_entry(|p| !p.ends_with("a0"))
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(3 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn filter_entry<PrePath>(
+        self,
+        mut predicate: PrePath,
+    ) -> IterFilter<walkdir::IntoIter, P, impl FnMut(&walkdir::DirEntry) -> bool>
+    where
+        PrePath: FnMut(&path::Path) -> bool,
+    {
+        let mut previous = self.predicate;
+        IterFilter {
+            iter: self.iter,
+            predicate: move |entry: &walkdir::DirEntry| previous(entry) && predicate(entry.path()),
+            state: self.state,
+        }
+    }
+
+    /// Adds the given predicate to the predicates of this iterator, see
+    /// [`IterAll::filter_entry_full`] and [`IterFilter::filter_entry`].
+    pub fn filter_entry_full<PreEntry>(
+        self,
+        mut predicate: PreEntry,
+    ) -> IterFilter<walkdir::IntoIter, P, impl FnMut(&walkdir::DirEntry) -> bool>
+    where
+        PreEntry: FnMut(&EntryInfo<'_>) -> bool,
+    {
+        let mut previous = self.predicate;
+        IterFilter {
+            iter: self.iter,
+            predicate: move |entry: &walkdir::DirEntry| {
+                previous(entry) && predicate(&EntryInfo { entry })
+            },
+            state: self.state,
+        }
+    }
 }
 
 impl<PreDir, P> Iterator for IterFilter<walkdir::IntoIter, P, PreDir>
@@ -132,12 +832,169 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match match_next(&self.root, self.iter.next(), &self.matcher) {
+            let next = self.iter.next();
+            // rejected entries are walked as well, i.e., they count towards the limits
+            if let Some(end) = self.state.check_next(next.is_some()) {
+                return end.map(Err);
+            }
+            // consistent with `walkdir::FilterEntry`, the contents of rejected folders are skipped
+            if let Some(Ok(entry)) = &next {
+                if !(self.predicate)(entry) {
+                    self.state
+                        .observer
+                        .on_skip(entry.path(), SkipReason::Filtered);
+                    if entry.file_type().is_dir() {
+                        self.iter.skip_current_dir();
+                    }
+                    continue;
+                }
+            }
+
+            let next = self
+                .state
+                .process_next(next.map(|next| next.map_err(Error::from)));
+            if self.state.take_skip() {
+                self.iter.skip_current_dir();
+            }
+            match next {
                 None => continue,
-                Some(entry) => {
-                    return entry;
+                Some(None) => return None,
+                Some(Some(Err(err))) => return Some(Err(err)),
+                Some(Some(Ok((entry, relative)))) => {
+                    if let Some(p) = self.state.yielded_path(&entry, &relative) {
+                        return Some(p);
+                    }
                 }
             };
         }
     }
 }
+
+/// Iterator yielding the raw `walkdir::DirEntry` for each match, created via
+/// [`Matcher::raw_entries`](crate::Matcher::raw_entries).
+#[derive(Debug)]
+pub struct IterRaw<P>
+where
+    P: AsRef<path::Path>,
+{
+    iter: IterAll<P>,
+}
+
+impl<P> IterRaw<P>
+where
+    P: AsRef<path::Path>,
+{
+    pub(crate) fn new(iter: IterAll<P>) -> IterRaw<P> {
+        IterRaw { iter }
+    }
+}
+
+impl<P> Iterator for IterRaw<P>
+where
+    P: AsRef<path::Path>,
+{
+    type Item = Result<RawEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_entry()
+    }
+}
+
+/// Matching entry yielded by [`IterEntries`].
+#[derive(Debug, Clone)]
+pub struct MatchEntry {
+    /// The matching path, as yielded by [`IterAll`], see
+    /// [`Builder::relative_paths`](crate::Builder::relative_paths) and
+    /// [`Builder::path_normalization`](crate::Builder::path_normalization).
+    pub path: path::PathBuf,
+    /// The depth of the entry relative to the root of the [`Matcher`](crate::Matcher), where
+    /// the root itself has the depth zero.
+    pub depth: usize,
+    /// The metadata of the entry. Symbolic links are not resolved unless they are followed, see
+    /// [`Builder::follow_links`](crate::Builder::follow_links).
+    pub metadata: fs::Metadata,
+    /// The type of the entry, as determined while walking the file system.
+    pub file_type: fs::FileType,
+}
+
+/// Iterator yielding a [`MatchEntry`] for each match, created via
+/// [`Matcher::into_entries`](crate::Matcher::into_entries).
+#[derive(Debug)]
+pub struct IterEntries<P>
+where
+    P: AsRef<path::Path>,
+{
+    iter: IterAll<P>,
+}
+
+impl<P> IterEntries<P>
+where
+    P: AsRef<path::Path>,
+{
+    pub(crate) fn new(iter: IterAll<P>) -> IterEntries<P> {
+        IterEntries { iter }
+    }
+}
+
+impl<P> Iterator for IterEntries<P>
+where
+    P: AsRef<path::Path>,
+{
+    type Item = Result<MatchEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, relative) = match self.iter.next_match()? {
+                Ok(next) => next,
+                Err(err) => return Some(Err(err)),
+            };
+            // consistent with the paths yielded by `IterAll`
+            let path = match self.iter.state.yielded_path(&entry, &relative) {
+                Some(Ok(path)) => path,
+                Some(Err(err)) => return Some(Err(err)),
+                None => continue,
+            };
+            // the metadata is only queried for matching entries
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(err) => match self.iter.state.on_error(err.into()) {
+                    Some(err) => return Some(Err(err)),
+                    None => continue,
+                },
+            };
+            return Some(Ok(MatchEntry {
+                depth: entry.depth(),
+                file_type: entry.file_type(),
+                metadata,
+                path,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builder, Error, WalkLimits};
+
+    #[test]
+    fn timeout() -> Result<(), String> {
+        let timeout = std::time::Duration::from_secs(60);
+        let mut iter = Builder::new("test-files/c-simple/**/*.txt")
+            .limit(WalkLimits {
+                timeout: Some(timeout),
+                ..Default::default()
+            })
+            .build(env!("CARGO_MANIFEST_DIR"))?
+            .into_iter();
+        // the timeout starts with the first item that is requested
+        assert!(iter.state.started.is_none());
+        assert!(iter.next().is_some_and(|p| p.is_ok()));
+
+        // instead of waiting for the timeout, the start of the walk is moved into the past
+        let started = iter.state.started.as_mut().unwrap();
+        *started = started.checked_sub(2 * timeout).ok_or("clock too early")?;
+        assert_eq!(Some(Err(Error::Timeout(timeout))), iter.next());
+        assert_eq!(None, iter.next());
+        Ok(())
+    }
+}
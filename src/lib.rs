@@ -86,12 +86,13 @@
 //! # Example: Specifying options and using `.filter_entry`.
 //!
 //! Similar to the builder pattern in [globset][globset] when using `globset::GlobBuilder`, this
-//! crate allows to pass options (currently just case sensitivity) to the builder.
+//! crate allows to pass options to the builder, e.g., the case sensitivity, exclude patterns,
+//! depth limits or whether symbolic links are followed. Refer to [`Builder`] for all options.
 //!
 //! In addition, the [`filter_entry`][filter_entry] function from [walkdir][walkdir] is accessible,
-//! but only as a single call (this crate does not implement a recursive iterator). This function
-//! allows filter files and folders *before* matching against the provided glob and therefore
-//! to efficiently exclude files and folders, e.g., hidden folders:
+//! and multiple calls can be chained. This function allows filter files and folders *before*
+//! matching against the provided glob and therefore to efficiently exclude files and folders,
+//! e.g., hidden folders:
 //!
 //! ```
 //! use globmatch;
@@ -123,10 +124,10 @@
 //! to further limit the files (e.g., based on a global blacklist).
 //!
 //! - [`Builder::build_glob`] to create a single [`Glob`] (caution: the builder only checks
-//!    that the pattern is not empty, but allows absolute paths).
+//!   that the pattern is not empty, but allows absolute paths).
 //! - [`Builder::build_glob_set`] to create a [`Glob`] matcher that contains two globs
 //!   `[glob, **/glob]` out of the specified `glob` parameter of [`Builder::new`]. The pattern
-//!    must not be an absolute path.
+//!   must not be an absolute path.
 //!
 //! ```
 //! use globmatch;
@@ -161,33 +162,149 @@
 #[cfg(doctest)]
 doc_comment::doctest!("../readme.md");
 
+use std::borrow;
+use std::convert;
 use std::path;
+use std::str;
 
+/// Emits a `tracing` event at the trace level if the `tracing` feature is enabled.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+/// Emits a `tracing` event at the debug level if the `tracing` feature is enabled.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+#[cfg(feature = "archive")]
+mod archive;
+mod cancel;
+#[cfg(feature = "regex")]
+mod capture;
+mod complete;
 mod error;
+mod explain;
+#[cfg(feature = "ignore")]
+mod gitignore;
+#[cfg(feature = "hash")]
+mod hash;
+mod hidden;
+mod ignore_file;
 mod iters;
+mod lint;
+mod live;
+mod multi;
+mod observer;
+mod options;
+mod parallel;
+mod pattern;
+mod predicate;
+#[cfg(feature = "regex")]
+mod rewrite;
+mod roots;
+mod search;
+mod source;
+#[cfg(feature = "async")]
+mod stream;
+mod suggest;
+mod tree;
+#[cfg(feature = "camino")]
+mod utf8;
 mod utils;
+mod winpath;
 
+pub mod compat;
+pub mod manifest;
+pub mod prelude;
+pub mod presets;
+pub mod snapshot;
 pub mod wrappers;
 
-pub use crate::error::Error;
-pub use crate::iters::{IterAll, IterFilter};
+#[cfg(feature = "archive")]
+pub use crate::archive::ArchiveSource;
+pub use crate::cancel::CancellationHandle;
+pub use crate::complete::complete;
+pub use crate::error::{Error, IoError};
+pub use crate::explain::MatchExplanation;
+#[cfg(feature = "hash")]
+pub use crate::hash::HashAlgorithm;
+pub use crate::hidden::HiddenPolicy;
+pub use crate::ignore_file::GitignoreSet;
+pub use crate::iters::{
+    EntryInfo, IterAll, IterEntries, IterFilter, IterRaw, MatchEntry, RawEntry,
+};
+pub use crate::lint::{LintKind, LintWarning};
+pub use crate::live::LiveMatcher;
+pub use crate::multi::{IterMulti, MultiMatch, MultiMatcher};
+pub use crate::observer::{Progress, SkipReason, WalkObserver};
+pub use crate::options::{
+    AscendLimit, CaseSensitivity, DirectoryMatch, FileType, MetadataFilter, MissingRoot, OnError,
+    PathNormalization, PatternLimits, RootSymlinks, WalkLimits,
+};
+pub use crate::parallel::IterParallel;
+pub use crate::pattern::Fuzzy;
+pub use crate::predicate::{And, Not, Or, PathMatcher};
+#[cfg(feature = "regex")]
+pub use crate::rewrite::Rewriter;
+pub use crate::roots::{IterMultiRoot, MultiRootMatcher};
+pub use crate::search::{ascend, RootMatch};
+pub use crate::source::{
+    SourceEntry, SourceFileType, SourceMetadata, SourceWalk, WalkDirSource, WalkSource,
+};
+#[cfg(feature = "async")]
+pub use crate::stream::MatchStream;
+pub use crate::tree::VirtualTree;
+#[cfg(feature = "camino")]
+pub use crate::utf8::IterUtf8;
 pub use crate::utils::{is_hidden_entry, is_hidden_path};
+pub use crate::wrappers::normalize_patterns;
 
 /// Asterisks `*` in a glob do not match path separators (e.g., `/` in unix).
 /// Only a double asterisk `**` match multiple folder levels.
 const REQUIRE_PATHSEP: bool = true;
 
+/// Maximum number of globs that the alternates of a glob are expanded into, e.g., to restrict
+/// the walk to the folders of each alternative. Globs with more alternatives are still matched,
+/// but without restricting the walk.
+const MAX_ALTERNATIVES: usize = 256;
+
+/// Maximum number of globs that are compiled for a single glob if
+/// [`PatternLimits::max_patterns`] is not configured.
+const MAX_PATTERNS: usize = 10_000;
+
 /// A builder for a matcher or globs.
 ///
 /// This builder can be configured to match case sensitive (default) or case insensitive.
 /// A single asterisk will not match path separators, e.g., `*/*.txt` does not match the file
 /// `path/to/file.txt`. Use `**` to match across directory boundaries.
 ///
-/// The lifetime `'a` refers to the lifetime of the glob string.
-#[derive(Debug)]
+/// The lifetime `'a` refers to the lifetime of the glob string. Use [`Builder::new_owned`] to
+/// create a builder (and thus a [`Matcher`]) that owns its glob and has a `'static` lifetime.
+#[derive(Clone, Debug)]
 pub struct Builder<'a> {
-    glob: &'a str,
-    case_sensitive: bool,
+    glob: borrow::Cow<'a, str>,
+    case_sensitivity: CaseSensitivity,
+    backslash_escape: Option<bool>,
+    backslash_separators: bool,
+    empty_alternates: bool,
+    expand_env: bool,
+    globstar_max_depth: Option<usize>,
+    fuzzy: Option<Fuzzy>,
+    directory_match: DirectoryMatch,
+    resolve: utils::ResolveOptions,
+    limits: PatternLimits,
+    walk: options::WalkOptions,
+    order: Option<options::WalkOrder>,
+    excludes: Vec<borrow::Cow<'a, str>>,
+    #[cfg(feature = "ignore")]
+    ignore_files: Vec<String>,
 }
 
 impl<'a> Builder<'a> {
@@ -195,9 +312,51 @@ impl<'a> Builder<'a> {
     ///
     /// The glob is not compiled until any of the `build` methods is called.
     pub fn new(glob: &'a str) -> Builder<'a> {
+        Builder::from_cow(borrow::Cow::Borrowed(glob))
+    }
+
+    /// Create a new builder that owns the given glob.
+    ///
+    /// All [`Matcher`]s, [`Glob`]s and [`GlobSet`]s that are built using this builder have the
+    /// `'static` lifetime, e.g., such that they can be stored in long-lived structures or moved
+    /// into threads.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let glob = String::from("test-files/c-simple/**/*.txt");
+    /// let matcher: globmatch::Matcher<'static, _> =
+    ///     globmatch::Builder::new_owned(glob).build(env!("CARGO_MANIFEST_DIR"))?;
+    ///
+    /// let paths = std::thread::spawn(move || matcher.into_iter().flatten().count())
+    ///     .join()
+    ///     .unwrap();
+    /// assert_eq!(6 + 2 + 1, paths);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn new_owned(glob: String) -> Builder<'static> {
+        Builder::from_cow(borrow::Cow::Owned(glob))
+    }
+
+    fn from_cow(glob: borrow::Cow<'a, str>) -> Builder<'a> {
         Builder {
             glob,
-            case_sensitive: true,
+            case_sensitivity: CaseSensitivity::Sensitive,
+            backslash_escape: None,
+            backslash_separators: cfg!(windows),
+            empty_alternates: false,
+            expand_env: false,
+            globstar_max_depth: None,
+            fuzzy: None,
+            directory_match: DirectoryMatch::Entry,
+            resolve: utils::ResolveOptions::default(),
+            limits: PatternLimits::default(),
+            walk: options::WalkOptions::default(),
+            order: None,
+            excludes: vec![],
+            #[cfg(feature = "ignore")]
+            ignore_files: vec![],
         }
     }
 
@@ -205,498 +364,5009 @@ impl<'a> Builder<'a> {
     ///
     /// The default setting is to match case **sensitive**.
     pub fn case_sensitive(&mut self, yes: bool) -> &mut Builder<'a> {
-        self.case_sensitive = yes;
+        self.case_sensitivity = match yes {
+            true => CaseSensitivity::Sensitive,
+            false => CaseSensitivity::Insensitive,
+        };
         self
     }
 
-    /// The actual facade for `globset::Glob`.
-    #[doc(hidden)]
-    fn glob_for(&self, glob: &str) -> Result<globset::Glob, String> {
-        globset::GlobBuilder::new(glob)
-            .literal_separator(REQUIRE_PATHSEP)
-            .case_insensitive(!self.case_sensitive)
-            .build()
-            .map_err(|err| {
-                format!(
-                    "'{}': {}",
-                    self.glob,
-                    utils::to_upper(err.kind().to_string())
-                )
-            })
+    /// Defines whether the glob matches case sensitive, see [`CaseSensitivity`].
+    ///
+    /// In contrast to [`Builder::case_sensitive`] this allows to use [`CaseSensitivity::Auto`],
+    /// which probes the file system of the root that is passed to [`Builder::build`], e.g., to
+    /// match case insensitive on macOS volumes that are case insensitive.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = env!("CARGO_MANIFEST_DIR");
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/a/a0/a*.txt")
+    ///     .case_sensitivity(globmatch::CaseSensitivity::Insensitive)
+    ///     .build(root)?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// assert_eq!(3, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn case_sensitivity(&mut self, mode: CaseSensitivity) -> &mut Builder<'a> {
+        self.case_sensitivity = mode;
+        self
     }
 
-    /// Builds a [`Matcher`] for the given [`Builder`] relative to `root`.
+    /// Toggle whether a backslash `\` escapes special characters, e.g., `\*` matches a literal `*`.
     ///
-    /// Resolves the relative path prefix for the `glob` that has been provided when creating the
-    /// builder for the given root directory, e.g.,
+    /// By default, as in `globset`, backslashes are escapes on all platforms except for Windows,
+    /// where a backslash is a path separator. Disabling this option on other platforms matches
+    /// backslashes literally.
+    pub fn backslash_escape(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.backslash_escape = Some(yes);
+        self
+    }
+
+    /// Toggle whether backslashes `\` in the glob are path separators, e.g., such that globs
+    /// copied from the Windows Explorer such as `test-files\a\**\*.txt` can be used.
     ///
-    /// For the root directory `/path/to/some/folder` and glob `../../*.txt`, this function will
-    /// move the relative path components to the root folder, resulting in only `*.txt` for the
-    /// glob, and `/path/to/some/folder/../../` for the root directory.
+    /// If enabled, all backslashes of the glob and of the [excluded](Builder::exclude) globs are
+    /// replaced by `/` when building the [`Matcher`], i.e., backslashes cannot be used as escapes
+    /// and [`Matcher::glob`] provides the glob with the replaced separators.
+    /// This is enabled by default on Windows, where backslashes are no escapes (see
+    /// [`Builder::backslash_escape`]), and disabled on all other platforms.
     ///
-    /// Notice that the relative path components will **not** be resolved. The caller of the
-    /// function can map and consolidate each path yielded by the iterator, if required.
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new(r"test-files\c-simple\a\**\*.txt")
+    ///     .backslash_separators(true)
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// assert_eq!(5, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn backslash_separators(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.backslash_separators = yes;
+        self
+    }
+
+    /// Toggle whether environment variables and a leading tilde `~` are expanded in the glob and
+    /// in the root, e.g., for globs such as `~/projects/**/*.rs` or `$WORKSPACE/src/**`.
     ///
-    /// # Errors
+    /// Variables are specified as `$NAME` or `${NAME}`, and `~` is replaced by the home directory
+    /// of the user (`HOME`, or `USERPROFILE` on Windows). If the expanded glob is an absolute path,
+    /// the root passed to [`Builder::build`] is ignored and the glob is resolved relative to the
+    /// root of the file system instead. The values of the variables are matched literally, e.g.,
+    /// an asterisk `*` within a value is no wildcard. This option is disabled by default.
     ///
-    /// Simple error messages will be provided in case of failures, e.g., for empty patterns or
-    /// patterns for which the compilation failed; as well as for invalid root directories.
-    pub fn build<P>(&self, root: P) -> Result<Matcher<'a, path::PathBuf>, String>
-    where
-        P: AsRef<path::Path>,
-    {
-        // notice that resolve_root does not return empty patterns
-        let (root, rest) = utils::resolve_root(root, self.glob).map_err(|err| {
-            format!(
-                "'Failed to resolve paths': {}",
-                utils::to_upper(err.to_string())
-            )
-        })?;
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let glob = "${CARGO_MANIFEST_DIR}/test-files/c-simple/b/*.txt";
+    /// let paths: Vec<_> = globmatch::Builder::new(glob)
+    ///     .expand_env(true)
+    ///     .build(".")?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// assert_eq!(1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// [`Builder::build`] provides an [`Error::InvalidPattern`] for variables in the glob that are
+    /// not set, and an [`Error::Resolve`] for variables in the root that are not set.
+    pub fn expand_env(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.expand_env = yes;
+        self
+    }
 
-        let matcher = self.glob_for(rest)?.compile_matcher();
-        Ok(Matcher {
-            glob: self.glob,
-            root,
-            rest,
-            matcher,
-        })
+    /// Toggle whether empty alternatives are matched, e.g., such that `*.{rs,}` matches the file
+    /// `main.rs` and the file `main.`.
+    ///
+    /// By default, as in `globset`, empty alternatives are ignored.
+    pub fn empty_alternates(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.empty_alternates = yes;
+        self
     }
 
-    // TODO: allow to build a matcher for absolute paths
-    // meaning, if self.glob is absolute, then simply don't resolve paths
-    // could be a property -> ignore_prefix_if_absolute
+    /// Limits the number of path components that are matched by a globstar `**`.
+    ///
+    /// By default a globstar matches any number of path components. With a limit of, e.g., `2`,
+    /// the glob `**/*.txt` only matches files that are at most two folders deep. This allows to
+    /// keep using `**` without scanning deeply nested trees, e.g., vendored dependencies.
+    ///
+    /// A trailing globstar (e.g., `src/**`) always matches at least one path component. This
+    /// option is applied by [`Builder::build`] and [`Builder::build_glob_set`].
+    pub fn globstar_max_depth(&mut self, depth: usize) -> &mut Builder<'a> {
+        self.globstar_max_depth = Some(depth);
+        self
+    }
 
-    /// Builds a [`Glob`].
+    /// Enables approximate matching of file names for [`Builder::build`].
     ///
-    /// This [`Glob`] that can be used for filtering paths provided by a [`Matcher`] (created
-    /// using the `build` function).
-    pub fn build_glob(&self) -> Result<Glob<'a>, String> {
-        if self.glob.is_empty() {
-            return Err("Empty glob".to_string());
-        }
+    /// In addition to the exact matches of the glob, the [`Matcher`] also yields paths with
+    /// file names that approximately match the last segment of the glob (see [`Fuzzy`]), e.g.,
+    /// `src/**/mian.rs` also matches `src/main.rs` using [`Fuzzy::Distance`] of `2`. The
+    /// directories of a path still need to be an exact match for the glob. Wildcards within the
+    /// last segment of the glob are ignored for approximate matches.
+    ///
+    /// This mode is intended for interactive use, e.g., for file pickers.
+    pub fn fuzzy(&mut self, mode: Fuzzy) -> &mut Builder<'a> {
+        self.fuzzy = Some(mode);
+        self
+    }
 
-        let matcher = self.glob_for(self.glob)?.compile_matcher();
-        Ok(Glob {
-            glob: self.glob,
-            matcher,
-        })
+    /// Defines what [`Builder::build`] matches for globs that resolve to a bare directory.
+    ///
+    /// For a glob such as `../test-files/c-simple`, where all path components exist, the
+    /// [`Matcher`] by default only yields the directory itself ([`DirectoryMatch::Entry`]).
+    /// Use [`DirectoryMatch::Contents`] to yield all paths within the directory instead, as if
+    /// the glob was specified as `../test-files/c-simple/**`.
+    pub fn directory_match(&mut self, mode: DirectoryMatch) -> &mut Builder<'a> {
+        self.directory_match = mode;
+        self
     }
 
-    /// Builds a combined [`GlobSet`].
+    /// Allows relative path components `..` after wildcards for [`Builder::build`].
     ///
-    /// A globset extends the provided `pattern` to `[pattern, **/pattern]`. This is useful, e.g.,
-    /// for blacklists, where only the file type is important.
+    /// By default, globs such as `build/*/../src/*.c` are rejected since the relative path
+    /// components cannot be moved into the root directory. If enabled, such components are
+    /// resolved lexically, i.e., each `..` removes the preceding component of the glob, even if
+    /// it is a wildcard: The glob `build/*/../src/*.c` is matched as `build/src/*.c`. Globs
+    /// that would escape the resolved root, e.g., `build/*/../../../*.c`, are still rejected.
     ///
-    /// Yes, it would be sufficient to use the pattern `**/pattern` in the first place. This is
-    /// a simple commodity function.
-    pub fn build_glob_set(&self) -> Result<GlobSet<'a>, String> {
-        if self.glob.is_empty() {
-            return Err("Empty glob".to_string());
-        }
+    /// This is useful for globs that are generated by other tools. Notice that the lexical
+    /// resolution does not check whether any path matches the removed wildcard.
+    pub fn resolve_parent_dirs(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.resolve.parent_dirs = yes;
+        self
+    }
 
-        let p = path::Path::new(self.glob);
-        if p.is_absolute() {
-            return Err(format!("{}' is an absolute path", self.glob));
-        }
+    /// Defines how [`Builder::build`] handles a root that is or contains a symbolic link.
+    ///
+    /// By default, the resolved root is used as provided ([`RootSymlinks::Keep`]): If the root
+    /// is a symbolic link, e.g., `link -> target`, all paths are yielded as `link/..`. With
+    /// [`RootSymlinks::Resolve`] the resolved root is canonicalized and all paths are yielded
+    /// relative to the target of the link, e.g., `/path/to/target/..`.
+    pub fn root_symlinks(&mut self, policy: RootSymlinks) -> &mut Builder<'a> {
+        self.resolve.root_symlinks = policy;
+        self
+    }
 
-        let glob_sub = "**/".to_string() + self.glob;
+    /// Defines how a root that does not exist is handled, e.g., to build [`Matcher`]s from a
+    /// configuration before the folders of a workspace have been created.
+    ///
+    /// By default, the `build` functions fail with [`Error::RootNotFound`] ([`MissingRoot::Fail`]).
+    /// Otherwise, a [`Matcher`] is built without resolving the root, i.e., the entire glob is
+    /// matched relative to the provided root. If the root does not exist while walking, the
+    /// iterators either yield no paths at all ([`MissingRoot::Empty`]) or a single
+    /// [`Error::RootNotFound`] ([`MissingRoot::Defer`]). Roots that exist are resolved as
+    /// usual.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let name = format!("globmatch-doc-missing-{}", std::process::id());
+    /// let root = std::env::temp_dir().join(name);
+    /// let matcher = globmatch::Builder::new("src/**/*.rs")
+    ///     .missing_root(globmatch::MissingRoot::Empty)
+    ///     .build(&root)?;
+    /// assert_eq!(0, matcher.into_iter().count());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn missing_root(&mut self, policy: MissingRoot) -> &mut Builder<'a> {
+        self.walk.missing_root = policy;
+        self
+    }
 
-        let matcher = globset::GlobSetBuilder::new()
-            .add(self.glob_for(self.glob)?)
-            .add(self.glob_for(&glob_sub)?)
-            .build()
-            .map_err(|err| {
-                format!(
-                    "'{}': {}",
-                    self.glob,
-                    utils::to_upper(err.kind().to_string())
-                )
-            })?;
+    /// Configures limits for the complexity of the glob, see [`PatternLimits`].
+    ///
+    /// All `build` functions fail with an error if any of the configured limits is exceeded.
+    /// The limits are checked before the glob is compiled (or expanded) where possible.
+    pub fn limits(&mut self, limits: PatternLimits) -> &mut Builder<'a> {
+        self.limits = limits;
+        self
+    }
 
-        Ok(GlobSet {
-            glob: self.glob,
-            matcher,
-        })
+    /// Checks the glob for likely mistakes, e.g., to report them to the user when loading a
+    /// configuration, see [`LintKind`] for all checks.
+    ///
+    /// In contrast to the errors of the `build` functions, the warnings concern globs that
+    /// compile but are unlikely to match what the user intended, e.g., `*/*.txt` instead of
+    /// `**/*.txt`. Each [`LintWarning`] provides a corrected glob if the intention is obvious.
+    /// The file system is not accessed and the exclusions (see [`Builder::exclude`]) are not
+    /// checked.
+    ///
+    /// ```
+    /// let warnings = globmatch::Builder::new("./src/**.rs").lint();
+    /// assert_eq!(2, warnings.len());
+    /// assert_eq!(globmatch::LintKind::CurrentDir, warnings[0].kind);
+    /// assert_eq!(
+    ///     "'**.rs' is not a separate path component and matches like '*', \
+    ///      did you mean './src/**/*.rs'?",
+    ///     warnings[1].to_string()
+    /// );
+    /// assert!(globmatch::Builder::new("src/**/*.rs").lint().is_empty());
+    /// ```
+    pub fn lint(&self) -> Vec<LintWarning> {
+        match self.backslash_separators {
+            true => lint::lint(&self.glob.replace('\\', "/"), false),
+            false => lint::lint(&self.glob, self.escapes()),
+        }
     }
-}
 
-/// Matcher type for transformation into an iterator.
-///
-/// This type exists such that [`Builder::build`] can return a result type (whereas `into_iter`
-/// cannot). Notice that `iter()` is not implemented due to the use of references.
-#[derive(Debug)]
-pub struct Matcher<'a, P>
-where
-    P: AsRef<path::Path>,
-{
-    glob: &'a str,
-    /// Original glob-pattern
-    root: P,
-    /// Root path of a resolved pattern
-    rest: &'a str,
-    /// Remaining pattern after root has been resolved
-    matcher: globset::GlobMatcher,
-}
+    /// Sets the number of threads that are used by [`Matcher::into_par_iter`].
+    ///
+    /// By default, the number of threads is determined by `std::thread::available_parallelism`.
+    pub fn threads(&mut self, n: usize) -> &mut Builder<'a> {
+        self.walk.threads = Some(n);
+        self
+    }
 
-impl<'a, P> IntoIterator for Matcher<'a, P>
-where
-    P: AsRef<path::Path>,
-{
-    type Item = Result<path::PathBuf, Error>;
-    type IntoIter = IterAll<P>;
+    /// Aborts the iteration after `n` file system entries have been walked.
+    ///
+    /// This is a safety valve, e.g., for services where a misconfigured glob rooted at `/` must
+    /// not scan the entire machine. All entries that are visited count towards the limit,
+    /// including directories and entries that do not match the glob. Once the limit is exceeded
+    /// the iterator yields a single [`Error::EntryLimit`] and ends.
+    pub fn max_entries(&mut self, n: usize) -> &mut Builder<'a> {
+        self.walk.max_entries = Some(n);
+        self
+    }
 
-    /// Transform the [`Matcher`] into a recursive directory iterator.
-    fn into_iter(self) -> Self::IntoIter {
-        let walk_root = path::PathBuf::from(self.root.as_ref());
-        IterAll::new(
-            self.root,
-            walkdir::WalkDir::new(walk_root).into_iter(),
-            self.matcher,
-        )
+    /// Configures limits for walking the file system, see [`WalkLimits`].
+    ///
+    /// Once any of the limits is exceeded, the iterator yields a single error that identifies
+    /// the limit, i.e., [`Error::EntryLimit`], [`Error::MatchLimit`] or [`Error::Timeout`], and
+    /// ends. Replaces the limit configured via [`Builder::max_entries`].
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .limit(globmatch::WalkLimits {
+    ///         max_matches: Some(2),
+    ///         timeout: Some(std::time::Duration::from_secs(5)),
+    ///         ..Default::default()
+    ///     })
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// assert_eq!(Some(&Err(globmatch::Error::MatchLimit(2))), paths.last());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn limit(&mut self, limits: WalkLimits) -> &mut Builder<'a> {
+        self.walk.max_entries = limits.max_entries;
+        self.walk.max_matches = limits.max_matches;
+        self.walk.timeout = limits.timeout;
+        self
     }
-}
 
-impl<'a, P> Matcher<'a, P>
-where
-    P: AsRef<path::Path>,
-{
-    /// Provides the original glob-pattern used to create this [`Matcher`].
+    /// Skips all paths that are excluded by `.gitignore` files, like `git` and `ripgrep`.
     ///
-    /// This is the unchanged glob, i.e., no relative path components have been resolved.
-    pub fn glob(&self) -> &str {
-        self.glob
+    /// Ignored folders are not walked at all, e.g., the build output of a project. The rules of
+    /// the global gitignore file, `.git/info/exclude` and all `.gitignore` files within the
+    /// repository are respected. Rules are only applied if the root is within a git repository.
+    ///
+    /// This option applies to the [`IterAll`], [`IterFilter`] and [`IterParallel`] iterators and
+    /// is disabled by default. Requires the `ignore` feature.
+    #[cfg(feature = "ignore")]
+    pub fn respect_gitignore(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.gitignore = yes;
+        self
     }
 
-    /// Provides the resolved root folder used by the [`Matcher`].
+    /// Skips all paths that are excluded by ignore files with the given name, e.g.,
+    /// `.prettierignore`, which are parsed with the semantics of `.gitignore` files.
     ///
-    /// This directory already contains the path components from the original glob. The main
-    /// intention of this function is to for debugging or logging (thus a String).
-    pub fn root(&self) -> String {
-        let path = path::PathBuf::from(self.root.as_ref());
-        String::from(path.to_str().unwrap())
+    /// Other than `.gitignore` files (see [`Builder::respect_gitignore`]), custom ignore files
+    /// are applied independent of any git repository, but only within the walked folders, i.e.,
+    /// the files of the parents of the root are not considered. The rules of the ignore file
+    /// that is closest to the entry take precedence; within the same folder, custom ignore files
+    /// take precedence over `.gitignore` files and files that have been added later take
+    /// precedence over files that have been added before.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let name = format!("globmatch-doc-ignore-{}", std::process::id());
+    /// let root = std::env::temp_dir().join(name);
+    /// # std::fs::create_dir_all(root.join("gen")).map_err(|err| err.to_string())?;
+    /// # std::fs::write(root.join("gen/out.txt"), "").map_err(|err| err.to_string())?;
+    /// # std::fs::write(root.join("main.txt"), "").map_err(|err| err.to_string())?;
+    /// std::fs::write(root.join(".mytoolignore"), "gen/\n").map_err(|err| err.to_string())?;
+    ///
+    /// let paths: Vec<_> = globmatch::Builder::new("**/*.txt")
+    ///     .add_ignore_file_name(".mytoolignore")
+    ///     .build(&root)?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// # std::fs::remove_dir_all(&root).map_err(|err| err.to_string())?;
+    ///
+    /// assert_eq!(vec![root.join("main.txt")], paths);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// This option applies to the [`IterAll`], [`IterFilter`] and [`IterParallel`] iterators.
+    /// Requires the `ignore` feature.
+    #[cfg(feature = "ignore")]
+    pub fn add_ignore_file_name<S>(&mut self, name: S) -> &mut Builder<'a>
+    where
+        S: Into<String>,
+    {
+        self.ignore_files.push(name.into());
+        self
     }
 
-    /// Provides the resolved glob used by the [`Matcher`].
+    /// Follow symbolic links while walking the file system.
     ///
-    /// All relative path components have been resolved for this glob. The glob is of type &str
-    /// since all globs are input parameters and specified as strings (and not paths).
-    pub fn rest(&self) -> &str {
-        self.rest
+    /// If enabled, symbolic links are resolved and the contents of linked folders are walked,
+    /// e.g., vendored folders that are linked into a project. The yielded paths are the paths of
+    /// the links, not of the resolved targets. Links that point to one of their ancestors are
+    /// reported using [`Error::SymlinkLoop`] instead of being walked endlessly.
+    ///
+    /// This option applies to the [`IterAll`], [`IterFilter`] and [`IterParallel`] iterators and
+    /// is disabled by default, i.e., symbolic links are yielded as entries but never walked.
+    pub fn follow_links(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.follow_links = yes;
+        self
     }
 
-    /// Checks whether the provided path is a match for the stored glob.
-    pub fn is_match(&self, p: P) -> bool {
-        self.matcher.is_match(p)
+    /// Never walks directories that are on a different file system than the resolved root.
+    ///
+    /// Mount points are still matched against the glob, but their contents are not walked,
+    /// e.g., such that backup or indexing tools do not wander into `/proc`, network mounts or
+    /// other devices. This option is disabled by default.
+    ///
+    /// This option applies to all iterators walking the file system. The parallel iterator
+    /// only supports it on Unix platforms, where the file system is identified by its device.
+    pub fn same_file_system(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.same_file_system = yes;
+        self
     }
-}
 
-/// Wrapper type for glob matching.
-///
-/// This type is created by [`Builder::build_glob`] for a single glob on which no transformations
-/// or path resolutions have been performed.
-#[derive(Debug)]
-pub struct Glob<'a> {
-    glob: &'a str,
-    /// Associated matcher.
-    pub matcher: globset::GlobMatcher,
-}
+    /// Yields the entries of each directory sorted by their file names.
+    ///
+    /// By default, the entries are yielded in the order provided by the file system, which
+    /// differs between platforms and file systems. Sorting the entries provides a deterministic,
+    /// lexicographic order, e.g., for tools that hash or diff lists of files. Directories are
+    /// still walked depth-first, i.e., the contents of a directory directly follow the directory.
+    ///
+    /// This option applies to the [`IterAll`] and [`IterFilter`] iterators, the parallel
+    /// iterator yields paths in a nondeterministic order. Replaces any previously configured
+    /// order, e.g., via [`Builder::sort_by`].
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .sort_by_file_name()
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert!(paths.ends_with(&[std::path::PathBuf::from(concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/test-files/c-simple/some_file.txt"
+    /// ))]));
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn sort_by_file_name(&mut self) -> &mut Builder<'a> {
+        self.order = Some(options::WalkOrder::FileName);
+        self
+    }
 
-impl<'a> Glob<'a> {
-    /// Provides the original glob-pattern used to create this [`Glob`].
-    pub fn glob(&self) -> &str {
-        self.glob
+    /// Yields the entries of each directory sorted by their file names in natural order.
+    ///
+    /// In contrast to [`Builder::sort_by_file_name`], runs of digits are compared by their
+    /// numeric value, e.g., `file2.txt` is yielded before `file10.txt`. All other characters are
+    /// compared lexicographically, i.e., the order is still case sensitive.
+    pub fn sort_by_natural_name(&mut self) -> &mut Builder<'a> {
+        self.order = Some(options::WalkOrder::NaturalName);
+        self
     }
 
-    /// Checks whether the provided path is a match for the stored glob.
-    pub fn is_match<P>(&self, p: P) -> bool
+    /// Yields the entries of each directory sorted using the given comparison.
+    ///
+    /// This is the equivalent of [`Builder::sort_by_file_name`] for a custom order, e.g., to
+    /// yield files before directories. The comparison is passed to `walkdir::WalkDir::sort_by`
+    /// and thus only compares entries within the same directory.
+    pub fn sort_by<F>(&mut self, compare: F) -> &mut Builder<'a>
     where
-        P: AsRef<path::Path>,
+        F: Fn(&walkdir::DirEntry, &walkdir::DirEntry) -> std::cmp::Ordering + Send + Sync + 'static,
     {
-        self.matcher.is_match(p)
+        self.order = Some(options::WalkOrder::Custom(std::sync::Arc::new(compare)));
+        self
     }
-}
 
-/// Comfort type for glob matching.
-///
-/// This type is created by [`Builder::build_glob_set`] (refer to the function documentation). The
-/// matcher stores two globs created from the original pattern as `[**/pattern, pattern]` for
-/// easy matching on multiple paths.
-#[derive(Debug)]
-pub struct GlobSet<'a> {
-    glob: &'a str,
-    /// Associated matcher.
-    pub matcher: globset::GlobSet,
-}
+    /// Only yields entries that are at least `depth` levels below the resolved root.
+    ///
+    /// The depth is relative to the root of the [`Matcher`], see [`Matcher::root_path`], i.e.,
+    /// after the leading path components of the glob have been moved to the root. The root itself
+    /// has the depth zero. This option applies to the [`IterAll`], [`IterFilter`] and
+    /// [`IterParallel`] iterators.
+    pub fn min_depth(&mut self, depth: usize) -> &mut Builder<'a> {
+        self.walk.min_depth = Some(depth);
+        self
+    }
 
-impl<'a> GlobSet<'a> {
-    /// Provides the original glob-pattern used to create this [`GlobSet`].
-    pub fn glob(&self) -> &str {
-        self.glob
+    /// Does not walk any entries that are more than `depth` levels below the resolved root.
+    ///
+    /// This avoids walking deeply nested trees for globs that can only match close to the root,
+    /// e.g., `src/*/Cargo.toml` in a large monorepo. The depth is relative to the root of the
+    /// [`Matcher`] as described for [`Builder::min_depth`]. This option applies to the
+    /// [`IterAll`], [`IterFilter`] and [`IterParallel`] iterators.
+    pub fn max_depth(&mut self, depth: usize) -> &mut Builder<'a> {
+        self.walk.max_depth = Some(depth);
+        self
     }
 
-    /// Checks whether the provided path is a match for any of the two stored globs.
-    pub fn is_match<P>(&self, p: P) -> bool
-    where
-        P: AsRef<path::Path>,
-    {
-        self.matcher.is_match(p)
+    /// Only yields entries of the given type, e.g., only regular files.
+    ///
+    /// The type is determined while walking the file system and thus does not require a call to
+    /// `metadata` for each matching path. Notice that symbolic links are neither files nor
+    /// directories, unless they are followed (see [`Builder::follow_links`]). All types are
+    /// yielded by default.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/a/**")
+    ///     .file_type(globmatch::FileType::Files)
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(4 + 1 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn file_type(&mut self, file_type: FileType) -> &mut Builder<'a> {
+        self.walk.file_type = file_type;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Only yields entries whose metadata satisfies the given filter, e.g., files larger than a
+    /// given size that have been modified after a given time, see [`MetadataFilter`].
+    ///
+    /// The metadata is only queried for entries that match the glob, symbolic links are not
+    /// followed unless configured via [`Builder::follow_links`]. The filter applies to all types
+    /// of entries, use [`Builder::file_type`] to only yield files. Errors while querying the
+    /// metadata are handled as configured via [`Builder::on_error`]. This option applies to the
+    /// [`IterAll`], [`IterFilter`] and [`IterParallel`] iterators.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let filter = globmatch::MetadataFilter {
+    ///     max_size: Some(1024),
+    ///     modified_before: Some(SystemTime::now() + Duration::from_secs(60)),
+    ///     ..Default::default()
+    /// };
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .filter_metadata(filter)
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(6 + 2 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn filter_metadata(&mut self, filter: MetadataFilter) -> &mut Builder<'a> {
+        self.walk.metadata = filter;
+        self
+    }
 
-    #[test]
-    fn test_path() {
-        let path = path::Path::new("");
-        assert!(!path.is_absolute());
+    /// Only yields the first path of each underlying file, e.g., if the tree contains hard links
+    /// or symbolic links that are followed (see [`Builder::follow_links`]).
+    ///
+    /// Files are identified by their device and inode. On platforms other than Unix, where these
+    /// identifiers are not available, the canonical paths of the files are compared instead,
+    /// i.e., hard links are not detected. Symbolic links that are not followed are files of
+    /// their own. Paths that cannot be accessed, e.g., broken symbolic links, are always
+    /// yielded. Use [`wrappers::PathOrder::DiscoveredUniqueFiles`] to remove duplicates across
+    /// multiple [`Matcher`]s. This option applies to the [`IterAll`], [`IterFilter`] and
+    /// [`IterParallel`] iterators and is disabled by default. Since the parallel iterator walks
+    /// the tree in a nondeterministic order, which of the paths of a file it yields is
+    /// nondeterministic as well.
+    pub fn dedup_files(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.dedup_files = yes;
+        self
     }
 
-    #[test]
-    #[cfg_attr(target_os = "windows", ignore)]
-    fn match_globset() {
-        // yes, it is on purpose that this is a simple list and not read from the test-files/c-simple
-        let files = vec![
-            "/some/path/test-files/c-simple/a",
-            "/some/path/test-files/c-simple/a/a0",
-            "/some/path/test-files/c-simple/a/a0/a0_0.txt",
-            "/some/path/test-files/c-simple/a/a0/a0_1.txt",
-            "/some/path/test-files/c-simple/a/a0/A0_3.txt",
-            "/some/path/test-files/c-simple/a/a0/a0_2.md",
-            "/some/path/test-files/c-simple/a/a1",
-            "/some/path/test-files/c-simple/a/a1/a1_0.txt",
-            "/some/path/test-files/c-simple/a/a2",
-            "/some/path/test-files/c-simple/a/a2/a2_0.txt",
+    /// Confines the matching to the provided root, e.g., for globs from untrusted configuration
+    /// files.
+    ///
+    /// The `build` functions reject globs that contain `..` components, or whose literal
+    /// components resolve to a path outside of the provided root, with [`Error::OutsideRoot`].
+    /// The check fails closed: a root or glob prefix that cannot be resolved is rejected as well.
+    /// While walking, symbolic links that point outside of the provided root, or whose target
+    /// cannot be resolved, are reported as [`Error::OutsideRoot`] according to
+    /// [`Builder::on_error`] and are neither yielded nor followed. Since their targets are
+    /// unknown, all symbolic links of a [`WalkSource`] are rejected. This applies to all
+    /// iterators of the [`Matcher`] and is disabled by default.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = env!("CARGO_MANIFEST_DIR");
+    /// let err = globmatch::Builder::new("../**/*.txt").jail(true).build(root).unwrap_err();
+    /// assert!(matches!(err, globmatch::Error::OutsideRoot(_)));
+    ///
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .jail(true)
+    ///     .build(root)?
+    ///     .into_iter()
+    ///     .collect::<Result<_, _>>()?;
+    /// assert_eq!(6 + 2 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn jail(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.jail = yes;
+        self
+    }
+
+    /// Yields paths relative to the resolved root instead of paths prefixed with the root.
+    ///
+    /// The paths are relative to [`Matcher::root_path`], i.e., they are the paths that have been
+    /// matched against the resolved glob, e.g., for writing file lists into archives or
+    /// manifests. If the root itself is a match, e.g., if the root is a file, its file name is
+    /// yielded. This option applies to the [`IterAll`], [`IterFilter`], [`IterEntries`] and
+    /// [`IterParallel`] iterators and is disabled by default.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/a/*/*.md")
+    ///     .relative_paths(true)
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![std::path::Path::new("a0/a0_2.md")], paths);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn relative_paths(&mut self, yes: bool) -> &mut Builder<'a> {
+        self.walk.relative_paths = yes;
+        self
+    }
+
+    /// Defines how the yielded paths are normalized, see [`PathNormalization`].
+    ///
+    /// By default, paths are yielded as walked, e.g., the glob `../b/*.txt` yields paths such as
+    /// `root/../b/b_0.txt`. Normalizing the paths while walking avoids additional calls to the
+    /// file system: Canonical paths are determined by joining the matched paths to the
+    /// canonicalized root, only symbolic links and paths within followed links are canonicalized
+    /// using the file system. Canonical paths are always absolute, i.e., they are not affected
+    /// by [`Builder::relative_paths`]. This option applies to the [`IterAll`], [`IterFilter`],
+    /// [`IterEntries`] and [`IterParallel`] iterators.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+    /// let paths: Vec<_> = globmatch::Builder::new("../b/*.txt")
+    ///     .path_normalization(globmatch::PathNormalization::Lexical)
+    ///     .build(&root)?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// let expected = format!("{}/test-files/c-simple/b/b_0.txt", env!("CARGO_MANIFEST_DIR"));
+    /// assert_eq!(vec![std::path::PathBuf::from(expected)], paths);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn path_normalization(&mut self, policy: PathNormalization) -> &mut Builder<'a> {
+        self.walk.normalization = policy;
+        self
+    }
+
+    /// Defines how errors are handled while walking the file system.
+    ///
+    /// By default, errors such as folders that cannot be read are yielded by the iterators and
+    /// the walk continues. Use [`OnError::Skip`] to skip such entries instead of dropping the
+    /// errors, e.g., using `flatten`, or [`OnError::Abort`] to stop at the first error.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths = globmatch::Builder::new("**/*.txt")
+    ///     .on_error(globmatch::OnError::Skip)
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// # let _ = paths;
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// This option applies to all iterators of the [`Matcher`].
+    pub fn on_error(&mut self, policy: OnError) -> &mut Builder<'a> {
+        self.walk.on_error = policy;
+        self
+    }
+
+    /// Excludes all paths that match the given glob, can be called multiple times.
+    ///
+    /// The exclusions are applied while iterating and thus replace hand-written `filter_entry`
+    /// predicates and post filters. As for the glob of the builder, the excluded glob is relative
+    /// to the root passed to [`Builder::build`] and uses the same case sensitivity. Excluded
+    /// folders are not walked, i.e., all of their contents are excluded as well.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .exclude("test-files/c-simple/.hidden")
+    ///     .exclude("**/a0_*")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(3 + 1 + 1, paths.len()); // A0_3.txt is not excluded, globs are case sensitive
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// This option applies to all iterators of the [`Matcher`]. Errors in the excluded globs are
+    /// reported by [`Builder::build`]. The globs are relative to the root as provided, also if
+    /// the root is canonicalized, see [`Builder::root_symlinks`].
+    pub fn exclude(&mut self, glob: impl Into<borrow::Cow<'a, str>>) -> &mut Builder<'a> {
+        self.excludes.push(glob.into());
+        self
+    }
+
+    /// Checks the length and the complexity of the glob against the configured limits.
+    fn check_limits(&self) -> Result<(), Error> {
+        let exceeds = |max: Option<usize>, count: &dyn Fn(&str) -> usize| {
+            max.filter(|max| count(&self.glob) > *max)
+        };
+        let alternatives =
+            |glob: &str| utils::count_braces(glob, self.empty_alternates, self.escapes());
+        let message = if let Some(max) = exceeds(self.limits.max_length, &str::len) {
+            format!("Pattern exceeds the maximum length of {max} bytes")
+        } else if let Some(max) = exceeds(self.limits.max_alternations, &alternatives) {
+            format!("Pattern exceeds the maximum of {max} alternatives")
+        } else if let Some(max) =
+            exceeds(self.limits.max_globstars, &utils::count_globstar_segments)
+        {
+            format!("Pattern exceeds the maximum of {max} globstars")
+        } else {
+            return Ok(());
+        };
+        Err(Error::pattern(&utils::shorten(&self.glob), message))
+    }
+
+    /// Checks the size of the compiled globs against the configured limits.
+    #[doc(hidden)]
+    fn check_compiled_size(&self, size: usize) -> Result<(), Error> {
+        match self.limits.max_compiled_size {
+            Some(max) if size > max => Err(Error::pattern(
+                &utils::shorten(&self.glob),
+                format!("Compiled pattern exceeds the maximum size of {max} bytes"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The actual facade for `globset::Glob`.
+    #[doc(hidden)]
+    fn glob_for(&self, glob: &str) -> Result<globset::Glob, Error> {
+        let mut builder = globset::GlobBuilder::new(glob);
+        builder
+            .literal_separator(REQUIRE_PATHSEP)
+            .case_insensitive(!self.case_sensitivity.is_sensitive(None))
+            .empty_alternates(self.empty_alternates);
+        if let Some(yes) = self.backslash_escape {
+            builder.backslash_escape(yes);
+        }
+        builder
+            .build()
+            .map_err(|err| Error::globset(&self.glob, err))
+    }
+
+    /// Creates the [`capture::CaptureGlob`] for the given glob, using the options of the builder.
+    ///
+    /// Numeric ranges are captured as alternates of their values, no values are captured if the
+    /// glob expands to too many globs, see [`PatternLimits::max_patterns`].
+    #[cfg(feature = "regex")]
+    fn capture_for(&self, glob: &str) -> capture::CaptureGlob {
+        if self.check_patterns(&[glob]).is_err() {
+            return capture::CaptureGlob::none();
+        }
+        let glob = utils::ranges_to_alternates(glob, self.escapes());
+        match self.flatten_braces(&glob) {
+            Ok(glob) => {
+                capture::CaptureGlob::new(&glob, self.globstar_max_depth, |g| self.glob_for(g))
+            }
+            Err(_) => capture::CaptureGlob::none(),
+        }
+    }
+
+    /// Creates a builder for the given glob with the options that are used to compile globs.
+    fn with_glob_options<'b>(&self, glob: borrow::Cow<'b, str>) -> Builder<'b> {
+        let mut builder = Builder::from_cow(glob);
+        builder.case_sensitivity = self.case_sensitivity;
+        builder.backslash_escape = self.backslash_escape;
+        builder.empty_alternates = self.empty_alternates;
+        builder.globstar_max_depth = self.globstar_max_depth;
+        builder.limits = self.limits;
+        builder
+    }
+
+    /// Checks whether backslashes escape special characters, see [`Builder::backslash_escape`].
+    fn escapes(&self) -> bool {
+        self.backslash_escape.unwrap_or(!cfg!(windows))
+    }
+
+    /// Compiles the glob of the builder as exclusion rule for the walk of `root`.
+    ///
+    /// Provides the directory that the compiled glob is relative to, i.e., the `root` for
+    /// relative globs.
+    #[doc(hidden)]
+    fn exclusion_for<'r>(
+        &self,
+        root: &'r path::Path,
+    ) -> Result<(&'r path::Path, pattern::PatternSet), Error> {
+        if self.glob.is_empty() {
+            return Err(Error::EmptyPattern);
+        }
+        self.check_limits()?;
+        let (base, rest) = match self.glob.strip_prefix('/') {
+            Some(rest) => (path::Path::new("/"), rest),
+            None => (root, self.glob.as_ref()),
+        };
+        Ok((base, self.glob_set_for(&[rest])?))
+    }
+
+    /// Expands the alternates of the glob, `None` if the glob expands to more than
+    /// [`MAX_ALTERNATIVES`] globs, see [`utils::expand_braces`].
+    fn expand_braces(&self, glob: &str) -> Option<Vec<String>> {
+        let (empty, escape) = (self.empty_alternates, self.escapes());
+        match utils::count_braces(glob, empty, escape) <= MAX_ALTERNATIVES {
+            true => Some(utils::expand_braces(glob, empty, escape)),
+            false => None,
+        }
+    }
+
+    /// Flattens the nested alternates of the glob, which are not supported by `globset`, see
+    /// [`utils::flatten_braces`].
+    fn flatten_braces(&self, glob: &str) -> Result<String, Error> {
+        let (empty, escape) = (self.empty_alternates, self.escapes());
+        utils::flatten_braces(glob, empty, escape, MAX_ALTERNATIVES).ok_or_else(|| {
+            Error::pattern(
+                &utils::shorten(&self.glob),
+                format!("Nested alternates expand to more than {MAX_ALTERNATIVES} alternatives"),
+            )
+        })
+    }
+
+    /// Checks that the given globs do not expand to more globs than allowed, see
+    /// [`PatternLimits::max_patterns`].
+    fn check_patterns(&self, globs: &[&str]) -> Result<(), Error> {
+        let max = self.limits.max_patterns.unwrap_or(MAX_PATTERNS);
+        let count = globs
+            .iter()
+            .map(|glob| {
+                let depth = self.globstar_max_depth;
+                utils::count_ranges(glob, self.escapes())
+                    .saturating_mul(depth.map_or(1, |depth| utils::count_globstar(glob, depth)))
+            })
+            .fold(0, usize::saturating_add);
+        if count > max {
+            return Err(Error::pattern(
+                &utils::shorten(&self.glob),
+                format!("Pattern expands to more than {max} globs"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Provides all expansions of the numeric ranges and, if limited, of the globstars of the
+    /// given globs, see [`Builder::glob_set_for`].
+    fn expand_globs(&self, globs: &[&str]) -> Vec<String> {
+        globs
+            .iter()
+            .flat_map(|glob| utils::expand_ranges(glob, self.escapes()))
+            .flat_map(|expanded| match self.globstar_max_depth {
+                None => vec![expanded],
+                Some(depth) => utils::expand_globstar(&expanded, depth),
+            })
+            .collect()
+    }
+
+    /// Provides the regular expressions of all expansions of the given globs, in the order in
+    /// which [`Builder::glob_set_for`] compiles them.
+    fn regexes_for(&self, globs: &[&str]) -> Result<Vec<String>, Error> {
+        self.expand_globs(globs)
+            .into_iter()
+            .map(|glob| {
+                let glob = self.flatten_braces(&glob)?;
+                Ok(match pattern::is_hybrid(&glob) {
+                    true => pattern::compile_hybrid(&glob, |g| self.glob_for(g))?
+                        .as_str()
+                        .to_string(),
+                    false => self.glob_for(&glob)?.regex().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Compiles a [`pattern::PatternSet`] containing all expansions of the given globs.
+    ///
+    /// Expands numeric ranges and, if configured, depth-bounded globstars. Alternates are kept
+    /// within the compiled globs and only expanded to restrict the walk. Globs that contain
+    /// regular expression segments are compiled separately.
+    #[doc(hidden)]
+    fn glob_set_for(&self, globs: &[&str]) -> Result<pattern::PatternSet, Error> {
+        self.check_patterns(globs)?;
+
+        let mut builder = globset::GlobSetBuilder::new();
+        let mut hybrids = vec![];
+        let mut prefixes = pattern::PrefixSet::default();
+        let mut size = 0;
+        for glob in self.expand_globs(globs) {
+            match self.expand_braces(&glob) {
+                Some(alternatives) => {
+                    for alternative in alternatives {
+                        prefixes.add(&alternative, self.escapes(), |g| self.glob_for(g))?;
+                    }
+                }
+                None => prefixes.add_unbounded(),
+            }
+            let glob = self.flatten_braces(&glob)?;
+            if pattern::is_hybrid(&glob) {
+                let re = pattern::compile_hybrid(&glob, |g| self.glob_for(g))?;
+                size += re.as_str().len();
+                hybrids.push(re);
+            } else {
+                let glob = self.glob_for(&glob)?;
+                size += glob.regex().len();
+                builder.add(glob);
+            }
+            self.check_compiled_size(size)?;
+        }
+        let source = (
+            self.with_glob_options(borrow::Cow::Owned(self.glob.to_string())),
+            globs.iter().map(ToString::to_string).collect(),
+        );
+        let globs = builder
+            .build()
+            .map_err(|err| Error::globset(&self.glob, err))?;
+        Ok(pattern::PatternSet {
+            globs,
+            hybrids,
+            source: Some(Box::new(source)),
+            fuzzy: vec![],
+            prefixes,
+            exclusions: None,
+            #[cfg(feature = "ignore")]
+            overrides: None,
+            combination: None,
+        })
+    }
+
+    /// Builds a [`Matcher`] for the given [`Builder`] relative to `root`.
+    ///
+    /// Resolves the relative path prefix for the `glob` that has been provided when creating the
+    /// builder for the given root directory, e.g.,
+    ///
+    /// For the root directory `/path/to/some/folder` and glob `../../*.txt`, this function will
+    /// move the relative path components to the root folder, resulting in only `*.txt` for the
+    /// glob, and `/path/to/some/folder/../../` for the root directory.
+    ///
+    /// Notice that the relative path components will **not** be resolved. The caller of the
+    /// function can map and consolidate each path yielded by the iterator, if required.
+    ///
+    /// All leading literal path components of the glob that exist are moved to the root as well,
+    /// e.g., for the glob `vendor/third_party/**/*.h` only the folder `vendor/third_party` is
+    /// walked. Components that contain wildcards, alternates or character classes always remain
+    /// part of the glob.
+    ///
+    /// If the resolved root is a file and not a directory, e.g., for the root `path/to/file.txt`
+    /// and the glob `*.txt`, the file name is matched against the glob as if the parent folder
+    /// was walked, i.e., the [`Matcher`] yields the file itself if its name matches the glob.
+    ///
+    /// If the glob does not contain a globstar `**`, the walk does not descend deeper than the
+    /// number of path components of the glob, e.g., `configs/*.yaml` never walks the contents of
+    /// the sub-folders of `configs`.
+    ///
+    /// Numeric ranges within the glob, e.g., `report_{2019..2024}/**/*.csv`, are expanded into
+    /// one glob per value. Ranges can be zero-padded (`{01..12}`) and descending (`{9..0}`).
+    /// Globs that expand to more than 10 000 globs are rejected, unless configured otherwise
+    /// via [`PatternLimits::max_patterns`].
+    ///
+    /// Alternates, e.g., `{src,include}/**/*.{h,c}`, can be nested and may contain path
+    /// separators, e.g., `{a/x,b/{y,z}}/*.txt`. Only the folders that are part of any
+    /// alternative are walked, i.e., for the glob `{a,b}/x/*.txt` the walk is restricted to the
+    /// folders `a/x` and `b/x`. For globs with more than 256 combinations of alternatives, the
+    /// walk is not restricted.
+    ///
+    /// A single path segment can be matched using a regular expression by specifying the segment
+    /// as `<re:regex>`, e.g., `src/<re:[a-z]+_test>/**`. The regular expression must match
+    /// the entire segment and never matches path separators. Notice that the regular expression
+    /// is matched on bytes, i.e., character classes such as `\w` only match ASCII characters.
+    /// Regular expression segments require the `regex` feature, otherwise such globs are
+    /// rejected with [`Error::InvalidPattern`].
+    ///
+    /// # Errors
+    ///
+    /// Provides an [`Error`] in case of failures, e.g., [`Error::InvalidPattern`] for patterns
+    /// for which the compilation failed or [`Error::RootNotFound`] for invalid root directories,
+    /// unless configured otherwise via [`Builder::missing_root`].
+    pub fn build<P>(&self, root: P) -> Result<Matcher<'a, path::PathBuf>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        self.build_in(root, &WalkDirSource)
+    }
+
+    /// Builds a [`Matcher`] relative to `root` within the tree of the given [`utils::Lookup`],
+    /// see [`Builder::build`].
+    #[doc(hidden)]
+    fn build_in<P>(
+        &self,
+        root: P,
+        lookup: &dyn utils::Lookup,
+    ) -> Result<Matcher<'a, path::PathBuf>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        if self.expand_env {
+            let var = |name: &str| lookup.var(name);
+            // the values are inserted literally into the glob
+            let escaped = |name: &str| {
+                lookup.var(name).map(|value| match self.escapes() {
+                    true => globset::escape(&value).replace('\\', "[\\]"),
+                    false => globset::escape(&value),
+                })
+            };
+            let missing = |name: String| format!("Environment variable '{name}' is not set");
+            let glob = utils::expand_env(&self.glob, escaped)
+                .map_err(|name| Error::pattern(&utils::shorten(&self.glob), missing(name)))?;
+            // roots that are not valid unicode cannot contain variables
+            let root = match root.as_ref().to_str() {
+                Some(root) => utils::expand_env(root, var)
+                    .map_err(|name| Error::resolve(missing(name)))?
+                    .into_owned()
+                    .into(),
+                None => path::PathBuf::from(root.as_ref()),
+            };
+
+            let mut builder = self.clone();
+            builder.expand_env = false;
+            return match utils::split_absolute(&glob) {
+                // expanded absolute globs are no longer relative to the jail
+                Some(_) if self.walk.jail => Err(Error::OutsideRoot(glob.as_ref().into())),
+                Some((anchor, rest)) => {
+                    builder.glob = borrow::Cow::Owned(rest);
+                    builder.build_in(anchor, lookup)
+                }
+                None => {
+                    builder.glob = borrow::Cow::Owned(glob.into_owned());
+                    builder.build_in(root, lookup)
+                }
+            };
+        }
+
+        // the case sensitivity is resolved once for the file system of the root
+        if self.case_sensitivity == CaseSensitivity::Auto {
+            let mut builder = self.clone();
+            builder.case_sensitive(lookup.is_case_sensitive(self.case_sensitivity, root.as_ref()));
+            return builder.build_in(root, lookup);
+        }
+
+        let has_backslash = |glob: &str| glob.contains('\\');
+        if self.backslash_separators
+            && (has_backslash(&self.glob) || self.excludes.iter().any(|g| has_backslash(g)))
+        {
+            let separators = |glob: &borrow::Cow<'a, str>| match has_backslash(glob) {
+                true => borrow::Cow::Owned(glob.replace('\\', "/")),
+                false => glob.clone(),
+            };
+            let mut builder = self.clone();
+            builder.glob = separators(&self.glob);
+            builder.excludes = self.excludes.iter().map(separators).collect();
+            return builder.build_in(root, lookup);
+        }
+
+        if self.walk.jail && utils::has_parent_dir(&self.glob) {
+            return Err(Error::OutsideRoot(self.glob.as_ref().into()));
+        }
+
+        let root_arg = root;
+        let (root, rest, rest_match) = match self.resolve(root_arg.as_ref(), lookup) {
+            Err(Error::RootNotFound { .. }) if self.walk.missing_root != MissingRoot::Fail => {
+                self.unresolved(root_arg.as_ref())?
+            }
+            resolved => resolved?,
+        };
+        let jail_root = match self.walk.jail {
+            true => Some(Builder::check_jail(root_arg.as_ref(), &root, lookup)?),
+            false => None,
+        };
+        debug!(glob = %self.glob, ?root, rest = %rest_match, "resolved root");
+
+        // the resolved root as it would be without resolving symbolic links
+        let provided = match self.resolve.root_symlinks {
+            RootSymlinks::Resolve => {
+                let mut options = self.resolve.clone();
+                options.root_symlinks = RootSymlinks::Keep;
+                utils::resolve_root_in(root_arg.as_ref(), &self.glob, &options, lookup)
+                    .map_or_else(|_| root.clone(), |(provided, _)| provided)
+            }
+            _ => root.clone(),
+        };
+        let prefix = winpath::strip_prefix(&provided, &winpath::normalize(root_arg.as_ref()))
+            .map(path::PathBuf::from)
+            .unwrap_or_default();
+
+        let mut matcher = self.glob_set_for(&[&rest_match])?;
+        if let Some(mode) = self.fuzzy {
+            let expanded = self.expand_braces(&rest_match).ok_or_else(|| {
+                Error::pattern(
+                    &utils::shorten(&self.glob),
+                    format!("Fuzzy glob expands to more than {MAX_ALTERNATIVES} globs"),
+                )
+            })?;
+            for glob in expanded
+                .iter()
+                .flat_map(|glob| utils::expand_ranges(glob, self.escapes()))
+            {
+                matcher.fuzzy.push(pattern::FuzzyGlob::new(
+                    &glob,
+                    mode,
+                    self.case_sensitivity.is_sensitive(None),
+                    |g| self.glob_set_for(&[g]),
+                )?);
+            }
+        }
+
+        // without globstars the number of components of the glob bounds the depth of the walk
+        let mut walk = self.walk;
+        let depth = self.expand_braces(&rest_match).and_then(|expanded| {
+            expanded
+                .iter()
+                .map(|glob| utils::max_depth(glob, self.globstar_max_depth, self.escapes()))
+                .try_fold(0, |max: usize, depth| Some(max.max(depth?)))
+        });
+        if let Some(depth) = depth {
+            walk.max_depth = Some(walk.max_depth.map_or(depth, |max| max.min(depth)));
+        }
+
+        if !self.excludes.is_empty() {
+            let mut rules = vec![];
+            for glob in &self.excludes {
+                let builder = self.with_glob_options(glob.clone());
+                let (base, rule) = builder.exclusion_for(root_arg.as_ref())?;
+                rules.push((base, rule, true));
+            }
+            // the walked paths are below the canonical root, whereas the exclusions are
+            // relative to the root as provided
+            let exclusions = pattern::Exclusions::new(rules).aliased(&root, &provided);
+            matcher.exclusions = Some(exclusions);
+        }
+
+        Ok(Matcher {
+            glob: self.glob.clone(),
+            root,
+            prefix,
+            rest,
+            matcher,
+            #[cfg(feature = "regex")]
+            captures: self.capture_for(&rest_match),
+            walk,
+            order: self.order.clone(),
+            observer: observer::Observer::default(),
+            cancel: None,
+            jail_root,
+            #[cfg(feature = "ignore")]
+            ignore_files: self.ignore_files.clone(),
+        })
+    }
+
+    /// Resolves the root directory for the glob, see [`Builder::build`].
+    ///
+    /// Provides the resolved root, the remaining part of the glob and the pattern that is
+    /// matched against the paths relative to the root.
+    #[doc(hidden)]
+    fn resolve<P>(
+        &self,
+        root: P,
+        lookup: &dyn utils::Lookup,
+    ) -> Result<(path::PathBuf, borrow::Cow<'a, str>, String), Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        self.check_limits()?;
+
+        // notice that resolve_root does not return empty patterns
+        let resolve_err = |err: std::io::Error| Error::from_resolve(root.as_ref(), &self.glob, err);
+        let (root, rest) = utils::resolve_root_in(root.as_ref(), &self.glob, &self.resolve, lookup)
+            .map_err(resolve_err)?;
+
+        // the remainder only contains relative path components if explicitly allowed
+        let rest_normalized;
+        let rest_match = match rest.split('/').any(|c| c == "..") {
+            true => {
+                rest_normalized = utils::normalize_parent_dirs(rest).map_err(resolve_err)?;
+                rest_normalized.as_str()
+            }
+            false => rest,
+        };
+
+        // resolve_root keeps the last path component in the remaining pattern if the glob
+        // resolves to an existing path, thus the remainder is an existing directory
+        let rest_match = match self.directory_match {
+            DirectoryMatch::Contents if lookup.is_dir(&root.join(rest_match)) => {
+                format!("{rest_match}/**")
+            }
+            _ => rest_match.to_string(),
+        };
+        // the remainder is a suffix of the glob, thus it can borrow from the original glob
+        let rest = match &self.glob {
+            borrow::Cow::Borrowed(glob) => borrow::Cow::Borrowed(&glob[glob.len() - rest.len()..]),
+            borrow::Cow::Owned(_) => borrow::Cow::Owned(rest.to_string()),
+        };
+        Ok((root, rest, rest_match))
+    }
+
+    /// Checks that the `resolved` root is contained within the provided `root`, see
+    /// [`Builder::jail`], and provides the canonical path of the provided `root`.
+    ///
+    /// The literal components of the glob that have been moved into the resolved root may be
+    /// symbolic links, thus the roots are compared lexically and canonically. Roots that do not
+    /// exist yet are compared by their closest existing ancestor. The check fails closed, i.e.,
+    /// paths that cannot be resolved are outside of the root.
+    fn check_jail(
+        root: &path::Path,
+        resolved: &path::Path,
+        lookup: &dyn utils::Lookup,
+    ) -> Result<path::PathBuf, Error> {
+        let outside = || Error::OutsideRoot(path::PathBuf::from(resolved));
+        let absolute = |p: &path::Path| {
+            path::absolute(p)
+                .map(|p| utils::normalize_path(&p))
+                .map_err(|_| outside())
+        };
+        let canonical = |p: &path::Path| {
+            let p = absolute(p)?;
+            p.ancestors()
+                .find_map(|ancestor| {
+                    let canonical = lookup.canonicalize(ancestor).ok()?;
+                    Some(canonical.join(p.strip_prefix(ancestor).ok()?))
+                })
+                .ok_or_else(outside)
+        };
+
+        let (root_abs, resolved_abs) = (absolute(root)?, absolute(resolved)?);
+        let root = canonical(&root_abs)?;
+        match resolved_abs.starts_with(&root_abs) && canonical(&resolved_abs)?.starts_with(&root) {
+            true => Ok(root),
+            false => Err(outside()),
+        }
+    }
+
+    /// Provides the root and the glob without resolving the root, see [`Builder::missing_root`].
+    fn unresolved(
+        &self,
+        root: &path::Path,
+    ) -> Result<(path::PathBuf, borrow::Cow<'a, str>, String), Error> {
+        if utils::is_absolute_pattern(&self.glob) {
+            return Err(Error::AbsolutePattern {
+                glob: self.glob.to_string(),
+                resolve: true,
+            });
+        }
+        let rest_match = match utils::has_parent_dir(&self.glob) {
+            true => utils::normalize_parent_dirs(&self.glob)
+                .map_err(|err| Error::from_resolve(root, &self.glob, err))?,
+            false => self.glob.to_string(),
+        };
+        Ok((winpath::normalize(root), self.glob.clone(), rest_match))
+    }
+
+    /// Matches the glob against the paths of a [`VirtualTree`] relative to `root`, without
+    /// accessing the file system.
+    ///
+    /// The `root` is a path within the tree, e.g., `.` for its top level, and the root of the
+    /// glob is resolved within the tree as described for [`Builder::build`], including relative
+    /// path prefixes such as `../`. The options of the builder are applied as for the iterator
+    /// of the [`Matcher`], except for options that require the file system, e.g.,
+    /// [`Builder::respect_gitignore`]. Canonical paths (see [`Builder::path_normalization`]) are
+    /// normalized lexically since the tree does not contain symbolic links. The paths are
+    /// provided in lexical order.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::build`], e.g., [`Error::RootNotFound`] if the `root` is not contained
+    /// within the tree.
+    pub fn match_virtual<P>(&self, tree: &VirtualTree, root: P) -> Result<Vec<path::PathBuf>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let matcher = self.build_in(root, &source::SourceLookup(tree))?;
+        Ok(tree.matches(&matcher))
+    }
+
+    /// Builds an iterator that walks the given [`WalkSource`] instead of the file system, e.g.,
+    /// an archive or a mock file system.
+    ///
+    /// The root of the glob is resolved within the source as described for [`Builder::build`],
+    /// refer to [`Matcher::into_source_iter`] for the options that are applied while walking.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::build`], e.g., [`Error::RootNotFound`] if the `root` does not exist
+    /// within the source, and [`Error::Unsupported`] for options that are not supported by a
+    /// source, see [`Matcher::into_source_iter`].
+    pub fn build_source<P, S>(
+        &self,
+        root: P,
+        source: S,
+    ) -> Result<IterAll<path::PathBuf, SourceWalk<S>>, Error>
+    where
+        P: AsRef<path::Path>,
+        S: WalkSource,
+    {
+        let matcher = self.build_in(root, &source::SourceLookup(&source))?;
+        matcher.into_source_iter(source)
+    }
+
+    /// Searches the glob within multiple `roots`, e.g., for resolver-style lookups such as
+    /// "find `plugins/*.so` within these three directories".
+    ///
+    /// The roots are searched in the given order, i.e., by priority. Each path is annotated
+    /// with the index of the root it has been found in, see [`RootMatch`]. Roots that do not
+    /// exist are skipped, as it is common for search paths (e.g., `PATH`) to contain such roots.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = env!("CARGO_MANIFEST_DIR");
+    /// let roots = [
+    ///     format!("{root}/test-files/c-simple/a"),
+    ///     format!("{root}/test-files/c-simple"),
+    /// ];
+    ///
+    /// let matches = globmatch::Builder::new("a1/*.txt").search(&roots)?;
+    /// assert_eq!(1, matches.len());
+    /// assert_eq!(0, matches[0].root);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::build`], and fails with the first error that is encountered while
+    /// walking the paths.
+    pub fn search<R>(&self, roots: &[R]) -> Result<Vec<RootMatch>, Error>
+    where
+        R: AsRef<path::Path>,
+    {
+        search::search(self, roots)
+    }
+
+    /// Searches the glob within multiple `roots`, where paths found in an earlier root shadow
+    /// the same relative paths in all later roots.
+    ///
+    /// This provides the effective set of paths for overlayed directories, e.g., for asset
+    /// directories where a mod or user directory overrides the default assets. The paths are
+    /// yielded in the same order as for [`Builder::search`], without the shadowed paths.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::search`].
+    pub fn search_overlay<R>(&self, roots: &[R]) -> Result<Vec<RootMatch>, Error>
+    where
+        R: AsRef<path::Path>,
+    {
+        search::overlay(self, roots)
+    }
+
+    /// Searches the glob within the directory `start` and all of its ancestors, e.g., to find
+    /// the nearest `Cargo.toml` or `.clang-format`.
+    ///
+    /// Provides all paths matching the glob within the nearest directory that contains at least
+    /// one match, or an empty list if no match has been found within the given `limit`. The
+    /// glob is matched against the entries of each directory, up to the number of path segments
+    /// of the glob, i.e., `.github/*.yml` matches the `.yml` files within the `.github` folder of
+    /// each directory but a globstar never matches more than a single segment.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let start = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+    ///
+    /// let paths = globmatch::ascend(start, "Cargo.toml")?;
+    /// assert_eq!(1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the glob is empty, cannot be compiled or contains relative path components, and
+    /// if the `start` directory does not exist.
+    pub fn ascend<P>(&self, start: P, limit: &AscendLimit) -> Result<Vec<path::PathBuf>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        search::ascend_with(self, start, limit)
+    }
+
+    /// Builds a [`Matcher`] relative to the nearest ancestor of `start` that contains a match
+    /// for the `marker` glob.
+    ///
+    /// This allows to express "match `src/**/*.rs` relative to the enclosing repository" in a
+    /// single call, e.g., by using `.git` as marker. The `marker` is searched as described for
+    /// [`Builder::ascend`] using the default settings of the [`Builder`], the glob of this builder
+    /// is then resolved relative to the ancestor as described for [`Builder::build`].
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let start = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+    ///
+    /// let paths: Vec<_> = globmatch::Builder::new("src/*.rs")
+    ///     .build_from_ancestor(start, "Cargo.toml")?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// assert!(!paths.is_empty());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if none of the ancestors contains a match for the `marker`, refer to
+    /// [`Builder::ascend`] and [`Builder::build`] for all other errors.
+    pub fn build_from_ancestor<P>(
+        &self,
+        start: P,
+        marker: &str,
+    ) -> Result<Matcher<'a, path::PathBuf>, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        search::build_from_ancestor(self, start, marker)
+    }
+
+    /// Builds a [`MultiRootMatcher`] that matches the glob relative to each of the given `roots`.
+    ///
+    /// A [`Matcher`] is built for each root as described for [`Builder::build`]. The roots are
+    /// walked one after the other, paths that are found within multiple roots, e.g., for nested
+    /// roots, are only yielded for the first root. Duplicates are detected using the absolute and
+    /// lexically normalized paths, use [`Builder::dedup_files`] to detect hard links as well.
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that occurs while building the [`Matcher`]s, refer to
+    /// [`Builder::build`].
+    pub fn build_many<I, P>(&self, roots: I) -> Result<MultiRootMatcher<'a>, Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<path::Path>,
+    {
+        let matchers = roots
+            .into_iter()
+            .map(|root| self.build(root))
+            .collect::<Result<_, _>>()?;
+        Ok(MultiRootMatcher::new(matchers))
+    }
+
+    // TODO: allow to build a matcher for absolute paths
+    // meaning, if self.glob is absolute, then simply don't resolve paths
+    // could be a property -> ignore_prefix_if_absolute
+
+    /// Builds a [`Glob`].
+    ///
+    /// This [`Glob`] that can be used for filtering paths provided by a [`Matcher`] (created
+    /// using the `build` function). Numeric ranges are **not** expanded since a [`Glob`] wraps
+    /// a single `globset::GlobMatcher`, use [`Builder::build_glob_set`] instead.
+    pub fn build_glob(&self) -> Result<Glob<'a>, Error> {
+        if self.glob.is_empty() {
+            return Err(Error::EmptyPattern);
+        }
+        self.check_limits()?;
+
+        let glob = self.glob_for(&self.glob)?;
+        self.check_compiled_size(glob.regex().len())?;
+        let matcher = glob.compile_matcher();
+        Ok(Glob {
+            glob: self.glob.clone(),
+            matcher,
+            #[cfg(feature = "regex")]
+            captures: self.capture_for(&self.glob),
+        })
+    }
+
+    /// Builds a [`Rewriter`] that maps paths matching the glob to output paths using the given
+    /// `template`.
+    ///
+    /// The glob is compiled as described for [`Builder::build_glob`], refer to [`Rewriter`] for
+    /// the placeholders that can be used within the template.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the glob cannot be compiled, or if the template contains invalid placeholders.
+    #[cfg(feature = "regex")]
+    pub fn build_rewriter(&self, template: &str) -> Result<Rewriter<'a>, Error> {
+        Rewriter::new(self.build_glob()?, template)
+    }
+
+    /// Builds a combined [`GlobSet`].
+    ///
+    /// A globset extends the provided `pattern` to `[pattern, **/pattern]`. This is useful, e.g.,
+    /// for blacklists, where only the file type is important.
+    ///
+    /// Yes, it would be sufficient to use the pattern `**/pattern` in the first place. This is
+    /// a simple commodity function. Numeric ranges and regular expression segments are supported
+    /// as described for [`Builder::build`].
+    pub fn build_glob_set(&self) -> Result<GlobSet<'a>, Error> {
+        if self.glob.is_empty() {
+            return Err(Error::EmptyPattern);
+        }
+        self.check_limits()?;
+
+        let p = path::Path::new(self.glob.as_ref());
+        if p.is_absolute() {
+            return Err(Error::AbsolutePattern {
+                glob: self.glob.to_string(),
+                resolve: false,
+            });
+        }
+
+        let glob_sub = "**/".to_string() + &self.glob;
+        self.glob_set_of(&[&self.glob, &glob_sub])
+    }
+
+    /// Builds a [`GlobSet`] that only matches the glob itself, i.e., without matching it in any
+    /// sub-folder. Other than [`Builder::build_glob_set`], absolute globs are allowed.
+    pub(crate) fn build_glob_set_anchored(&self) -> Result<GlobSet<'a>, Error> {
+        if self.glob.is_empty() {
+            return Err(Error::EmptyPattern);
+        }
+        self.check_limits()?;
+        self.glob_set_of(&[&self.glob])
+    }
+
+    fn glob_set_of(&self, globs: &[&str]) -> Result<GlobSet<'a>, Error> {
+        let pattern::PatternSet { globs, hybrids, .. } = self.glob_set_for(globs)?;
+
+        Ok(GlobSet {
+            glob: self.glob.clone(),
+            matcher: globs,
+            hybrids,
+            negated: false,
+            #[cfg(feature = "ignore")]
+            overrides: None,
+        })
+    }
+}
+
+/// Matcher type for transformation into an iterator.
+///
+/// This type exists such that [`Builder::build`] can return a result type (whereas `into_iter`
+/// cannot). Notice that `iter()` is not implemented due to the use of references.
+#[derive(Debug)]
+pub struct Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    glob: borrow::Cow<'a, str>,
+    /// Original glob-pattern
+    root: P,
+    /// Root path of a resolved pattern
+    prefix: path::PathBuf,
+    /// Components of the glob that have been moved into the root
+    rest: borrow::Cow<'a, str>,
+    /// Remaining pattern after root has been resolved
+    matcher: pattern::PatternSet,
+    #[cfg(feature = "regex")]
+    captures: capture::CaptureGlob,
+    walk: options::WalkOptions,
+    order: Option<options::WalkOrder>,
+    observer: observer::Observer,
+    cancel: Option<CancellationHandle>,
+    /// Canonical path of the provided root that walked symbolic links must not escape.
+    jail_root: Option<path::PathBuf>,
+    #[cfg(feature = "ignore")]
+    ignore_files: Vec<String>,
+}
+
+impl<'a, P> IntoIterator for Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    type Item = Result<path::PathBuf, Error>;
+    type IntoIter = IterAll<P>;
+
+    /// Transform the [`Matcher`] into a recursive directory iterator.
+    fn into_iter(self) -> Self::IntoIter {
+        let walk = WalkDirSource.walk(self.root.as_ref(), &self.walk, self.order);
+        let iter = IterAll::new(
+            self.root,
+            walk,
+            self.matcher,
+            self.walk,
+            self.observer,
+            self.cancel,
+        )
+        .with_jail_root(self.jail_root);
+        #[cfg(feature = "ignore")]
+        let iter = iter.with_ignore_files(self.ignore_files);
+        iter
+    }
+}
+
+impl<'a, P> Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Provides the original glob-pattern used to create this [`Matcher`].
+    ///
+    /// This is the unchanged glob, i.e., no relative path components have been resolved.
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+
+    /// Converts the [`Matcher`] into a [`Matcher`] that owns its glob-pattern.
+    ///
+    /// Refer to [`Builder::new_owned`] to build a `'static` [`Matcher`] in the first place.
+    pub fn into_owned(self) -> Matcher<'static, P> {
+        Matcher {
+            glob: borrow::Cow::Owned(self.glob.into_owned()),
+            root: self.root,
+            prefix: self.prefix,
+            rest: borrow::Cow::Owned(self.rest.into_owned()),
+            matcher: self.matcher,
+            #[cfg(feature = "regex")]
+            captures: self.captures,
+            walk: self.walk,
+            order: self.order,
+            observer: self.observer,
+            cancel: self.cancel,
+            jail_root: self.jail_root,
+            #[cfg(feature = "ignore")]
+            ignore_files: self.ignore_files,
+        }
+    }
+
+    /// Provides the resolved root folder used by the [`Matcher`].
+    ///
+    /// This directory already contains the path components from the original glob, e.g., the
+    /// root for the glob `src/**/*.rs` is the provided root joined with `src`.
+    pub fn root_path(&self) -> &path::Path {
+        self.root.as_ref()
+    }
+
+    /// Provides the resolved root folder used by the [`Matcher`] as a String.
+    ///
+    /// Roots that are not valid unicode are converted lossily, see [`Matcher::root_path`].
+    #[deprecated(since = "0.4.0", note = "use `Matcher::root_path` instead")]
+    pub fn root(&self) -> String {
+        self.root_path().to_string_lossy().into_owned()
+    }
+
+    /// Provides the resolved glob used by the [`Matcher`].
+    ///
+    /// All relative path components have been resolved for this glob. The glob is of type &str
+    /// since all globs are input parameters and specified as strings (and not paths).
+    pub fn rest(&self) -> &str {
+        &self.rest
+    }
+
+    /// Checks whether the provided path is a match for the stored glob.
+    pub fn is_match(&self, p: P) -> bool {
+        self.matcher.is_match(p)
+    }
+
+    /// Partitions the provided paths into the paths that are matched by the [`Matcher`] and the
+    /// remaining paths, without accessing the file system.
+    ///
+    /// This is useful for path lists that are provided externally, e.g., by `git ls-files`. The
+    /// paths can be paths below [`Matcher::root_path`], or paths relative to the root that was
+    /// passed to [`Builder::build`], i.e., relative paths are matched against the entire glob.
+    /// Absolute paths outside of the root are never matched. In contrast to
+    /// [`Matcher::is_match`], the globs excluded via [`Builder::exclude`] are applied. Options
+    /// that require the metadata of an entry, e.g., [`Builder::file_type`] or the depth limits of
+    /// the walk, are not applied. The paths are provided in the order of the input.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # fn example() -> Result<(), String> {
+    /// let matcher = globmatch::Builder::new("src/**/*.rs")
+    ///     .exclude("src/gen/**")
+    ///     .build(".")?;
+    ///
+    /// let paths = ["src/main.rs", "src/net/tcp.rs", "src/gen/ffi.rs", "build.rs"];
+    /// let (matched, unmatched) = matcher.match_all(paths);
+    /// assert_eq!(["src/main.rs", "src/net/tcp.rs"].map(PathBuf::from).to_vec(), matched);
+    /// assert_eq!(["src/gen/ffi.rs", "build.rs"].map(PathBuf::from).to_vec(), unmatched);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn match_all<I, Q>(&self, paths: I) -> (Vec<path::PathBuf>, Vec<path::PathBuf>)
+    where
+        I: IntoIterator<Item = Q>,
+        Q: AsRef<path::Path>,
+    {
+        let root = self.root.as_ref();
+        paths
+            .into_iter()
+            .map(|p| path::PathBuf::from(p.as_ref()))
+            .partition(|p| {
+                let relative = match winpath::strip_prefix(p, root) {
+                    Some(relative) => relative,
+                    // absolute paths outside of the root are never a match
+                    None if p.has_root() => return false,
+                    // relative paths are relative to the root that was provided to the builder
+                    None => match winpath::strip_prefix(p, &self.prefix) {
+                        Some(relative) => relative,
+                        None => return false,
+                    },
+                };
+                self.matcher.is_match(relative) && !self.matcher.is_excluded(&root.join(relative))
+            })
+    }
+
+    /// Provides a single regular expression that matches the same paths as
+    /// [`Matcher::is_match`], see [`Glob::to_regex`].
+    ///
+    /// The expression is created for the resolved glob (see [`Matcher::rest`]) and thus matches
+    /// paths relative to [`Matcher::root_path`]. If the glob is expanded into multiple globs,
+    /// e.g., for numeric ranges or [`Builder::globstar_max_depth`], the expressions of all globs
+    /// are combined as alternatives. Globs that are excluded via [`Builder::exclude`] are not
+    /// part of the expression.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+    /// let matcher = globmatch::Builder::new("a/**/a{0..1}_*.txt").build(&root)?;
+    ///
+    /// let re = regex::bytes::Regex::new(&matcher.to_regex().unwrap()).unwrap();
+    /// assert!(re.is_match(b"a0/a0_1.txt"));
+    /// assert!(!re.is_match(b"a2/a2_0.txt"));
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// Returns `None` for matchers that cannot be expressed by a single regular expression, i.e.,
+    /// matchers with fuzzy matching (see [`Builder::fuzzy`]) and combined matchers (see
+    /// [`Matcher::union`]).
+    pub fn to_regex(&self) -> Option<String> {
+        self.matcher.to_regex()
+    }
+
+    /// Provides the values captured by the wildcards of the resolved glob for the given path,
+    /// see [`Glob::captures`].
+    ///
+    /// The path can be a path yielded by the [`Matcher`] or a path relative to
+    /// [`Matcher::root_path`]. Since the leading path components of the glob have been moved to
+    /// the root, the values are the same as for the original glob. No values are captured for
+    /// combined matchers, see [`Matcher::union`]. Requires the `regex` feature.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+    /// let matcher = globmatch::Builder::new("a/*/a?_*.txt").build(&root)?;
+    ///
+    /// let captures = matcher.captures(format!("{root}/a/a1/a1_0.txt"));
+    /// assert_eq!(Some(vec!["a1".to_string(), "1".to_string(), "0".to_string()]), captures);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn captures<Q>(&self, p: Q) -> Option<Vec<String>>
+    where
+        Q: AsRef<path::Path>,
+    {
+        let p = p.as_ref();
+        let relative = winpath::strip_prefix(p, self.root.as_ref()).unwrap_or(p);
+        match relative.as_os_str().is_empty() {
+            // the root itself is a file, see `IterAll`
+            true => self
+                .captures
+                .captures(path::Path::new(p.file_name().unwrap_or_default())),
+            false => self.captures.captures(relative),
+        }
+    }
+
+    /// Combines two matchers into a single [`Matcher`] that yields all paths that are matched by
+    /// any of the two matchers, walking the file system only once.
+    ///
+    /// The root of the combined [`Matcher`] is the common ancestor of the roots of both
+    /// matchers, folders that cannot contain a match of any of the matchers are not walked. Each
+    /// matcher is applied to the paths relative to its own root, including its excluded globs
+    /// (see [`Builder::exclude`]) and depth limits. All other options for walking the file
+    /// system are taken from `self`, i.e., the corresponding options of `other` are ignored,
+    /// e.g., [`Builder::file_type`], [`Builder::follow_links`], the sort order of the walk, as
+    /// well as observers and cancellation handles (see [`Matcher::observe`] and
+    /// [`Matcher::cancel_on`]). The glob of the combined [`Matcher`] only describes the
+    /// combination, e.g., `(a/*.txt) | (b/*.txt)`, and cannot be compiled.
+    ///
+    /// Relative roots without a common path component, e.g., `x` and `y`, are combined within
+    /// the current directory `.`, i.e., the yielded paths start with `./`.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+    /// let a = globmatch::Builder::new("a/**/*.txt").build(&root)?;
+    /// let b = globmatch::Builder::new("b/*.txt").build(&root)?;
+    /// let not_a0 = globmatch::Builder::new("a/a0/**").build(&root)?;
+    ///
+    /// let paths: Vec<_> = a
+    ///     .union(b)?
+    ///     .difference(not_a0)?
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(2 + 1, paths.len()); // a1_0.txt, a2_0.txt and b_0.txt
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Resolve`] if the roots of the matchers do not have a common ancestor,
+    /// e.g., for an absolute and a relative root, or for the relative roots `../x` and `y`.
+    pub fn union<Q>(self, other: Matcher<'_, Q>) -> Result<Matcher<'static, path::PathBuf>, Error>
+    where
+        Q: AsRef<path::Path>,
+    {
+        self.combine(pattern::Combinator::Union, other)
+    }
+
+    /// Combines two matchers into a single [`Matcher`] that yields all paths that are matched by
+    /// both matchers, see [`Matcher::union`].
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Matcher::union`].
+    pub fn intersection<Q>(
+        self,
+        other: Matcher<'_, Q>,
+    ) -> Result<Matcher<'static, path::PathBuf>, Error>
+    where
+        Q: AsRef<path::Path>,
+    {
+        self.combine(pattern::Combinator::Intersection, other)
+    }
+
+    /// Combines two matchers into a single [`Matcher`] that yields all paths that are matched by
+    /// `self` but not by `other`, see [`Matcher::union`].
+    ///
+    /// Only the folders that may contain a match of `self` are walked.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Matcher::union`].
+    pub fn difference<Q>(
+        self,
+        other: Matcher<'_, Q>,
+    ) -> Result<Matcher<'static, path::PathBuf>, Error>
+    where
+        Q: AsRef<path::Path>,
+    {
+        self.combine(pattern::Combinator::Difference, other)
+    }
+
+    fn combine<Q>(
+        self,
+        op: pattern::Combinator,
+        other: Matcher<'_, Q>,
+    ) -> Result<Matcher<'static, path::PathBuf>, Error>
+    where
+        Q: AsRef<path::Path>,
+    {
+        let mut root_a = winpath::normalize(self.root.as_ref());
+        let mut root_b = winpath::normalize(other.root.as_ref());
+        let below_current = |root: &path::Path| {
+            matches!(root.components().next(), Some(path::Component::Normal(_)))
+        };
+        let mut common = match utils::common_ancestor(&root_a, &root_b) {
+            Some(common) => common,
+            // relative roots are walked from within the current directory
+            None if below_current(&root_a) && below_current(&root_b) => {
+                let current = path::PathBuf::from(".");
+                root_a = current.join(root_a);
+                root_b = current.join(root_b);
+                current
+            }
+            None => {
+                return Err(Error::resolve(format!(
+                    "The roots {} and {} do not have a common ancestor",
+                    root_a.to_string_lossy(),
+                    root_b.to_string_lossy()
+                )))
+            }
+        };
+        // the combined root must be a folder, the paths are matched relative to it
+        if common.is_file() {
+            common.pop();
+        }
+
+        let depth = |root: &path::Path, d: Option<usize>| {
+            d.map(|d| d + root.components().count() - common.components().count())
+        };
+        let (max_a, max_b) = (
+            depth(&root_a, self.walk.max_depth),
+            depth(&root_b, other.walk.max_depth),
+        );
+        let mut walk = self.walk;
+        // the depth limits of the operands are applied while matching
+        walk.min_depth = None;
+        walk.max_depth = match op {
+            pattern::Combinator::Union => max_a.zip(max_b).map(|(a, b)| a.max(b)),
+            pattern::Combinator::Intersection => max_a.into_iter().chain(max_b).min(),
+            pattern::Combinator::Difference => max_a,
+        };
+
+        let symbol = match op {
+            pattern::Combinator::Union => "|",
+            pattern::Combinator::Intersection => "&",
+            pattern::Combinator::Difference => "-",
+        };
+        let glob = format!("({}) {symbol} ({})", self.glob, other.glob);
+        let a = pattern::Operand {
+            root: root_a,
+            set: self.matcher,
+            min_depth: self.walk.min_depth,
+            max_depth: self.walk.max_depth,
+        };
+        let b = pattern::Operand {
+            root: root_b,
+            set: other.matcher,
+            min_depth: other.walk.min_depth,
+            max_depth: other.walk.max_depth,
+        };
+        Ok(Matcher {
+            glob: borrow::Cow::Owned(glob.clone()),
+            rest: borrow::Cow::Owned(glob),
+            matcher: pattern::PatternSet::combine(op, common.clone(), a, b),
+            // the combination cannot be translated, no values are captured
+            #[cfg(feature = "regex")]
+            captures: capture::CaptureGlob::none(),
+            root: common,
+            prefix: path::PathBuf::new(),
+            walk,
+            order: self.order,
+            observer: self.observer,
+            cancel: self.cancel,
+            // the operands may be confined to different roots, links are checked against the
+            // common root
+            jail_root: None,
+            #[cfg(feature = "ignore")]
+            ignore_files: self.ignore_files,
+        })
+    }
+
+    /// Restricts the [`Matcher`] to paths that are not excluded by the given
+    /// `ignore::overrides::Override`.
+    ///
+    /// The override is checked for all paths that match the glob, within the same iteration,
+    /// such that the semantics of both crates can be combined without matching each path twice.
+    /// Following the semantics of the `ignore` crate, a path is excluded if it is ignored by a
+    /// glob starting with `!`, or if the override contains whitelist globs and the path does not
+    /// match any of them. Notice that the override does not prevent walking directories.
+    ///
+    /// Requires the `ignore` feature.
+    #[cfg(feature = "ignore")]
+    pub fn with_overrides(mut self, overrides: ignore::overrides::Override) -> Matcher<'a, P> {
+        self.matcher.overrides = Some(overrides);
+        self
+    }
+
+    /// Explains why the given path is or is not matched by the [`Matcher`].
+    ///
+    /// The `path` is handled like the paths that are walked by the iterators, i.e., it must
+    /// contain the resolved root (see [`Matcher::root_path`]). The [`MatchExplanation`] provides
+    /// the relative path that is matched against the resolved glob and the path component at
+    /// which the match failed. Only the path is checked, e.g., the file type is not.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = env!("CARGO_MANIFEST_DIR");
+    /// let matcher = globmatch::Builder::new("test-files/c-simple/*/a0/*.txt").build(root)?;
+    ///
+    /// let explanation = matcher.explain(format!("{root}/test-files/c-simple/a/a1/a1_0.txt"));
+    /// assert!(!explanation.is_match);
+    /// assert_eq!(Some(1), explanation.failed_component);
+    /// assert_eq!(Some("a0"), explanation.failed_glob_component.as_deref());
+    /// assert_eq!(
+    ///     "'a/a1/a1_0.txt' does not match '*/a0/*.txt': 'a1' does not match 'a0'",
+    ///     explanation.to_string()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn explain<Q>(&self, path: Q) -> MatchExplanation
+    where
+        Q: AsRef<path::Path>,
+    {
+        explain::explain(self.root.as_ref(), &self.rest, &self.matcher, path.as_ref())
+    }
+
+    /// Provides up to `max` near-miss globs for a [`Matcher`] that did not yield any paths.
+    ///
+    /// The literal path segments of the resolved glob are checked against the directory
+    /// entries within the root: A segment that does not exist is replaced by existing entries
+    /// that only differ in case or by a single typo, e.g., for `test-fiels/**` this function
+    /// suggests `test-files/**`. If all literal segments exist, paths that would match the glob
+    /// case insensitive are suggested instead.
+    ///
+    /// The suggestions are globs in the same format as the original glob, i.e., relative to
+    /// the root that has been passed to [`Builder::build`]. Notice that this function walks the
+    /// file system and should only be used for diagnostics, e.g., to provide messages such as
+    /// "no matches for `test-fiels/**`; did you mean `test-files/**`?".
+    pub fn suggest(&self, max: usize) -> Vec<String> {
+        let prefix = &self.glob[..self.glob.len() - self.rest.len()];
+        suggest::suggest(self.root.as_ref(), &self.rest, max)
+            .into_iter()
+            .map(|rest| format!("{prefix}{rest}"))
+            .collect()
+    }
+
+    /// Transforms the [`Matcher`] into a parallel iterator, walking the file system using
+    /// multiple threads.
+    ///
+    /// This is useful for large trees, where walking the file system dominates the runtime. In
+    /// contrast to the iterator provided by `into_iter`, the paths are yielded in a
+    /// nondeterministic order. The number of threads can be configured via [`Builder::threads`].
+    ///
+    /// With the `ignore-walk` feature, the file system is walked using the parallel walker of the
+    /// `ignore` crate instead. Both walkers honor the same options as `into_iter`, except for
+    /// the order of the entries.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let mut paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_par_iter()
+    ///     .filter_entry(|p| !globmatch::is_hidden_path(p))
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// paths.sort();
+    /// assert_eq!(6 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn into_par_iter(self) -> IterParallel {
+        let iter = IterParallel::new(
+            path::PathBuf::from(self.root.as_ref()),
+            self.matcher,
+            self.walk,
+            self.observer,
+            self.cancel,
+        )
+        .with_jail_root(self.jail_root);
+        #[cfg(feature = "ignore")]
+        let iter = iter.with_ignore_files(self.ignore_files);
+        iter
+    }
+
+    /// Transforms the [`Matcher`] into a [`MatchStream`] for asynchronous services.
+    ///
+    /// The file system is walked by a blocking task of the `tokio` runtime, such that walking
+    /// large trees does not block the runtime. The stream implements `futures::Stream` and yields
+    /// the same items as the iterator provided by `into_iter`. The walk is cancelled once the
+    /// stream is dropped, or once the handle configured via [`Matcher::cancel_on`] is cancelled.
+    /// Requires the `async` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the context of a `tokio` runtime.
+    ///
+    /// ```
+    /// use futures_core::Stream;
+    ///
+    /// # fn example() -> Result<(), String> {
+    /// let runtime = tokio::runtime::Builder::new_current_thread()
+    ///     .build()
+    ///     .map_err(|err| err.to_string())?;
+    /// let paths = runtime.block_on(async {
+    ///     let mut stream = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///         .build(env!("CARGO_MANIFEST_DIR"))?
+    ///         .into_stream();
+    ///     let mut paths = vec![];
+    ///     // e.g., `stream.next().await` using `futures::StreamExt`
+    ///     while let Some(p) =
+    ///         std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await
+    ///     {
+    ///         paths.push(p?);
+    ///     }
+    ///     Ok::<_, globmatch::Error>(paths)
+    /// })?;
+    ///
+    /// assert_eq!(9, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn into_stream(self) -> MatchStream
+    where
+        P: Send + 'static,
+    {
+        MatchStream::new(self.into_iter())
+    }
+
+    /// Transforms the [`Matcher`] into an iterator that walks the given [`WalkSource`] instead of
+    /// the file system, see [`Builder::build_source`].
+    ///
+    /// All options of the [`Matcher`] are applied, except for the walk order (see
+    /// [`Builder::sort_by_file_name`]), since the entries are walked in the order provided by the
+    /// source, and symbolic links, which are never followed. Canonical paths (see
+    /// [`Builder::path_normalization`]) are normalized lexically.
+    ///
+    /// # Errors
+    ///
+    /// Provides [`Error::Unsupported`] for options that require the file system, i.e., ignore
+    /// files (see [`Builder::respect_gitignore`]) and duplicate files (see
+    /// [`Builder::dedup_files`]).
+    pub fn into_source_iter<S>(self, source: S) -> Result<IterAll<P, SourceWalk<S>>, Error>
+    where
+        S: WalkSource,
+    {
+        let mut options = self.walk;
+        #[cfg(feature = "ignore")]
+        if options.gitignore || !self.ignore_files.is_empty() {
+            return Err(Error::Unsupported(
+                "Ignore files are not supported for a WalkSource".to_string(),
+            ));
+        }
+        if options.dedup_files {
+            return Err(Error::Unsupported(
+                "Duplicate files cannot be detected within a WalkSource".to_string(),
+            ));
+        }
+        if options.normalization == PathNormalization::Canonical {
+            options.normalization = PathNormalization::Lexical;
+        }
+        let walk = SourceWalk::new(
+            source,
+            path::PathBuf::from(self.root.as_ref()),
+            options.min_depth,
+            options.max_depth,
+        );
+        Ok(IterAll::new(
+            self.root,
+            walk,
+            self.matcher,
+            options,
+            self.observer,
+            self.cancel,
+        ))
+    }
+
+    /// Transforms the [`Matcher`] into an iterator yielding the original `walkdir::DirEntry` for
+    /// each match, together with the path that has been matched against the glob.
+    ///
+    /// This allows to access, e.g., the depth of the entry or `path_is_symlink` without an
+    /// additional call to the file system.
+    pub fn raw_entries(self) -> IterRaw<P> {
+        IterRaw::new(self.into_iter())
+    }
+
+    /// Attaches the given [`WalkObserver`] to the [`Matcher`].
+    ///
+    /// The observer is notified about all matches, errors and skipped entries of the iterators
+    /// created from this [`Matcher`], e.g., to log paths that have been filtered out without
+    /// changing the iteration. Use an `Arc` to keep access to the observer. Only a single
+    /// observer can be attached, any previous observer is replaced.
+    pub fn observe<O>(mut self, observer: O) -> Matcher<'a, P>
+    where
+        O: WalkObserver + Send + Sync + 'static,
+    {
+        self.observer.set_observer(observer);
+        self
+    }
+
+    /// Invokes the given `callback` with the [`Progress`] of the walk every `every` walked
+    /// entries, and once the walk ends, i.e., also if the walk is aborted, e.g., by a
+    /// [`CancellationHandle`], or if the iterator is dropped early.
+    ///
+    /// This allows, e.g., CLI tools to render a progress bar while scanning very large trees.
+    /// The callback is invoked by the thread that walks the file system, which for the parallel
+    /// iterator may be any of its worker threads, and should therefore return quickly. Only a
+    /// single callback can be attached, any previous callback is replaced.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # fn example() -> Result<(), String> {
+    /// let last = Arc::new(Mutex::new(globmatch::Progress::default()));
+    /// let progress = Arc::clone(&last);
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .on_progress(10, move |p| *progress.lock().unwrap() = *p)
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(paths.len(), last.lock().unwrap().matches);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn on_progress<F>(mut self, every: usize, callback: F) -> Matcher<'a, P>
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.observer.set_progress(every, callback);
+        self
+    }
+
+    /// Aborts the iterators created from this [`Matcher`] once the given handle is cancelled.
+    ///
+    /// The handle is checked before each walked entry, such that a long walk, e.g., of a huge
+    /// or network-mounted tree, can be aborted from another thread. Once cancelled, the
+    /// iterator yields a single [`Error::Cancelled`] and ends. Only a single handle can be
+    /// attached, any previous handle is replaced.
+    pub fn cancel_on(mut self, handle: CancellationHandle) -> Matcher<'a, P> {
+        self.cancel = Some(handle);
+        self
+    }
+
+    /// Transforms the [`Matcher`] into an iterator yielding a [`MatchEntry`] for each match.
+    ///
+    /// Each entry provides the depth, the file type and the metadata of the matching path, such
+    /// that no additional call to `std::fs::metadata` is required. The metadata is only queried
+    /// for matching paths; failures are yielded as [`Error`]. The paths of the entries are the
+    /// paths yielded by [`Matcher::into_iter`], e.g., relative paths if enabled.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let size: u64 = globmatch::Builder::new("test-files/c-simple/a/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_entries()
+    ///     .flatten()
+    ///     .filter(|entry| entry.file_type.is_file())
+    ///     .map(|entry| entry.metadata.len())
+    ///     .sum();
+    /// # let _ = size;
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn into_entries(self) -> IterEntries<P> {
+        IterEntries::new(self.into_iter())
+    }
+
+    /// Collects all matching paths into the collection `C`, e.g., a `BTreeSet` or `HashSet`.
+    ///
+    /// The semantics for duplicate paths and the order of the paths are defined by the
+    /// collection, e.g., a `BTreeSet` provides sorted and unique paths.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// use std::collections::BTreeSet;
+    ///
+    /// let paths = globmatch::Builder::new("test-files/c-simple/a/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .collect_set::<BTreeSet<_>>()
+    ///     .map_err(|err| err.to_string())?;
+    ///
+    /// assert_eq!(5, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that is encountered while walking the paths.
+    pub fn collect_set<C>(self) -> Result<C, Error>
+    where
+        C: FromIterator<path::PathBuf>,
+    {
+        self.into_iter().collect()
+    }
+
+    /// Collects all matching paths, sorted and without duplicates.
+    ///
+    /// This is the order that is used by [`wrappers::match_paths`], but for a single [`Matcher`].
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that is encountered while walking the paths.
+    pub fn collect_sorted(self) -> Result<Vec<path::PathBuf>, Error> {
+        let mut paths: Vec<_> = self.collect_set()?;
+        paths.sort_unstable();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    /// Collects all matching paths in the order in which they have been discovered, only keeping
+    /// the first occurrence of each path.
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that is encountered while walking the paths.
+    pub fn collect_unique(self) -> Result<Vec<path::PathBuf>, Error> {
+        let mut paths: Vec<_> = self.collect_set()?;
+        let mut seen = std::collections::HashSet::new();
+        paths.retain(|p| seen.insert(p.clone()));
+        Ok(paths)
+    }
+
+    /// Collects all matching paths in a reproducible format, e.g., for tools that hash the list
+    /// of matched paths as part of a build fingerprint.
+    ///
+    /// The provided list is guaranteed to be byte-identical across platforms for the same tree:
+    ///
+    /// - Paths are relative to the root that has been passed to [`Builder::build`], i.e., they
+    ///   start with the path components of the glob as specified (without any `./`).
+    /// - Path components are separated by `/`.
+    /// - Paths are normalized to the Unicode Normalization Form C (NFC), since some file systems
+    ///   store names in decomposed form.
+    /// - Paths are sorted by their UTF-8 bytes and duplicates are removed.
+    ///
+    /// Requires the `reproducible` feature.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let paths = globmatch::Builder::new("./test-files/c-simple/a/*/*.md")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .collect_reproducible()
+    ///     .map_err(|err| err.to_string())?;
+    ///
+    /// assert_eq!(vec!["test-files/c-simple/a/a0/a0_2.md"], paths);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that is encountered while walking the paths. Paths that are
+    /// not valid UTF-8 are rejected with [`Error::NotUtf8`], and paths that are not located
+    /// within [`Matcher::root_path`] with [`Error::OutsideRoot`], e.g., canonical paths (see
+    /// [`Builder::path_normalization`]) for a root that is not canonical.
+    #[cfg(feature = "reproducible")]
+    pub fn collect_reproducible(self) -> Result<Vec<String>, Error> {
+        use unicode_normalization::UnicodeNormalization;
+
+        let prefix: Vec<_> = self.glob[..self.glob.len() - self.rest.len()]
+            .split('/')
+            .filter(|c| !c.is_empty() && *c != ".")
+            .map(String::from)
+            .collect();
+        let root = path::PathBuf::from(self.root.as_ref());
+        // canonical paths are always absolute, see `Builder::path_normalization`
+        let is_relative =
+            self.walk.relative_paths && self.walk.normalization != PathNormalization::Canonical;
+
+        let mut paths = vec![];
+        for p in self {
+            let p = p?;
+            let relative = match p.strip_prefix(&root) {
+                _ if is_relative => p.as_path(),
+                Ok(relative) if relative.as_os_str().is_empty() => {
+                    // the root itself is a match, e.g., if the root is a file
+                    path::Path::new(p.file_name().unwrap_or_default())
+                }
+                Ok(relative) => relative,
+                Err(_) => return Err(Error::OutsideRoot(p)),
+            };
+
+            let mut components = prefix.clone();
+            for c in relative.components() {
+                match c.as_os_str().to_str() {
+                    Some(c) => components.push(c.to_string()),
+                    None => return Err(Error::NotUtf8(p)),
+                }
+            }
+            paths.push(components.join("/").nfc().collect());
+        }
+
+        paths.sort_unstable();
+        paths.dedup();
+        Ok(paths)
+    }
+}
+
+/// Wrapper type for glob matching.
+///
+/// This type is created by [`Builder::build_glob`] for a single glob on which no transformations
+/// or path resolutions have been performed.
+#[derive(Debug)]
+pub struct Glob<'a> {
+    glob: borrow::Cow<'a, str>,
+    /// Associated matcher.
+    pub matcher: globset::GlobMatcher,
+    #[cfg(feature = "regex")]
+    captures: capture::CaptureGlob,
+}
+
+impl<'a> Glob<'a> {
+    /// Provides the original glob-pattern used to create this [`Glob`].
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+
+    /// Converts the [`Glob`] into a [`Glob`] that owns its glob-pattern.
+    pub fn into_owned(self) -> Glob<'static> {
+        Glob {
+            glob: borrow::Cow::Owned(self.glob.into_owned()),
+            matcher: self.matcher,
+            #[cfg(feature = "regex")]
+            captures: self.captures,
+        }
+    }
+
+    /// Checks whether the provided path is a match for the stored glob.
+    pub fn is_match<P>(&self, p: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        self.matcher.is_match(p)
+    }
+
+    /// Provides the regular expression that globset compiled for the glob.
+    ///
+    /// The expression matches the same paths as [`Glob::is_match`] using `/` as path separator
+    /// and can be passed to other regex engines, e.g., to filter paths within a database. Notice
+    /// that the expression uses the syntax of the [regex][regex] crate, e.g., `(?-u)` to disable
+    /// Unicode mode such that it must be compiled as `regex::bytes::Regex`, and may need to be
+    /// translated for other engines.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let glob = globmatch::Builder::new("*.txt").build_glob()?;
+    ///
+    /// let re = regex::bytes::Regex::new(glob.to_regex()).unwrap();
+    /// assert!(re.is_match(b"file.txt"));
+    /// assert!(!re.is_match(b"dir/file.txt"));
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// [regex]: https://docs.rs/regex
+    pub fn to_regex(&self) -> &str {
+        self.matcher.glob().regex()
+    }
+
+    /// Provides the values captured by the wildcards of the glob for the given path, or `None`
+    /// if the path is not a match or if any of the values is not valid UTF-8.
+    ///
+    /// A value is captured for each wildcard `*`, `**`, `?`, character class `[...]`,
+    /// alternate `{...}`, numeric range and regular expression segment, in the order of their
+    /// occurrence within the glob. Wildcards within alternates are not captured separately, and
+    /// each `*` within `**` that is not a separate path component is captured on its own. A
+    /// globstar `**/` that matches no path component captures an empty value. This is useful,
+    /// e.g., for tools that rename or copy the matched files. Requires the `regex` feature.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let glob = globmatch::Builder::new("src/**/*_test.rs").build_glob()?;
+    ///
+    /// let captures = glob.captures("src/net/tcp_test.rs");
+    /// assert_eq!(Some(vec!["net".to_string(), "tcp".to_string()]), captures);
+    /// assert_eq!(None, glob.captures("src/net/tcp.rs"));
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn captures<P>(&self, p: P) -> Option<Vec<String>>
+    where
+        P: AsRef<path::Path>,
+    {
+        self.captures.captures(p.as_ref())
+    }
+}
+
+/// Comfort type for glob matching.
+///
+/// This type is created by [`Builder::build_glob_set`] (refer to the function documentation). The
+/// matcher stores two globs created from the original pattern as `[**/pattern, pattern]` for
+/// easy matching on multiple paths.
+#[derive(Debug)]
+pub struct GlobSet<'a> {
+    glob: borrow::Cow<'a, str>,
+    /// Associated matcher.
+    ///
+    /// Globs containing regular expression segments `<re:...>` are not part of this matcher,
+    /// i.e., it never matches the paths that are only matched by such globs. Neither does it
+    /// apply the overrides of a [`GlobSet`] created via `GlobSet::from_override`. Use
+    /// [`GlobSet::is_match`] to match paths against the complete set.
+    pub matcher: globset::GlobSet,
+    hybrids: Vec<pattern::HybridRegex>,
+    negated: bool,
+    #[cfg(feature = "ignore")]
+    overrides: Option<ignore::overrides::Override>,
+}
+
+impl<'a> GlobSet<'a> {
+    /// Provides the original glob-pattern used to create this [`GlobSet`].
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+
+    /// Checks whether the [`GlobSet`] has been created for a negated glob `!glob`, see
+    /// [`wrappers::build_glob_set`].
+    ///
+    /// The functions [`GlobSet::is_match`] and [`PathMatcher::is_match`] ignore the negation,
+    /// i.e., a negated [`GlobSet`] matches all paths that are matched by the glob. Use
+    /// [`wrappers::is_match_ordered`] to apply negations.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Converts the [`GlobSet`] into a [`GlobSet`] that owns its glob-pattern.
+    pub fn into_owned(self) -> GlobSet<'static> {
+        GlobSet {
+            glob: borrow::Cow::Owned(self.glob.into_owned()),
+            matcher: self.matcher,
+            hybrids: self.hybrids,
+            negated: self.negated,
+            #[cfg(feature = "ignore")]
+            overrides: self.overrides,
+        }
+    }
+
+    /// Creates a [`GlobSet`] out of the given `ignore::overrides::Override`.
+    ///
+    /// A path is a match if it is whitelisted by the override, i.e., following the semantics of
+    /// the `ignore` crate, paths are matched relative to the root of the override and globs
+    /// starting with `!` exclude paths. The file system is queried to determine whether a path is
+    /// a directory, for globs that only match directories. The glob of the [`GlobSet`] is empty.
+    ///
+    /// Requires the `ignore` feature.
+    #[cfg(feature = "ignore")]
+    pub fn from_override(overrides: ignore::overrides::Override) -> GlobSet<'static> {
+        GlobSet {
+            glob: borrow::Cow::Borrowed(""),
+            matcher: globset::GlobSet::empty(),
+            hybrids: vec![],
+            negated: false,
+            overrides: Some(overrides),
+        }
+    }
+
+    /// Checks whether the provided path is a match for any of the two stored globs.
+    pub fn is_match<P>(&self, p: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        #[cfg(feature = "ignore")]
+        if let Some(overrides) = &self.overrides {
+            let p = p.as_ref();
+            if overrides.matched(p, p.is_dir()).is_whitelist() {
+                return true;
+            }
+        }
+
+        self.matcher.is_match(p.as_ref())
+            || self
+                .hybrids
+                .iter()
+                .any(|re| pattern::is_hybrid_match(re, p.as_ref()))
+    }
+
+    /// Partitions the provided paths into the paths that are a match for the [`GlobSet`] and the
+    /// remaining paths, see [`GlobSet::is_match`].
+    ///
+    /// Like [`Matcher::match_all`], this does not access the file system unless the set has been
+    /// created from overrides, which require the `ignore` feature. Negated sets (see
+    /// [`GlobSet::is_negated`]) are partitioned by their glob as well, i.e., the negation is not
+    /// applied. The paths are provided in the order of the input.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # fn example() -> Result<(), String> {
+    /// let set = globmatch::Builder::new("*.rs").build_glob_set()?;
+    ///
+    /// let (matched, unmatched) = set.match_all(["main.rs", "src/lib.rs", "README.md"]);
+    /// assert_eq!(["main.rs", "src/lib.rs"].map(PathBuf::from).to_vec(), matched);
+    /// assert_eq!(vec![PathBuf::from("README.md")], unmatched);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn match_all<I, P>(&self, paths: I) -> (Vec<path::PathBuf>, Vec<path::PathBuf>)
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<path::Path>,
+    {
+        paths
+            .into_iter()
+            .map(|p| path::PathBuf::from(p.as_ref()))
+            .partition(|p| self.is_match(p))
+    }
+}
+
+/// Parses a [`Glob`] using the default settings of the [`Builder`], e.g.,
+/// `"**/*.rs".parse::<Glob<'_>>()`.
+impl str::FromStr for Glob<'static> {
+    type Err = Error;
+
+    fn from_str(glob: &str) -> Result<Self, Self::Err> {
+        Builder::new(glob).build_glob().map(Glob::into_owned)
+    }
+}
+
+/// Equivalent to [`Builder::build_glob`] using the default settings of the [`Builder`].
+impl<'a> convert::TryFrom<&'a str> for Glob<'a> {
+    type Error = Error;
+
+    fn try_from(glob: &'a str) -> Result<Self, Self::Error> {
+        Builder::new(glob).build_glob()
+    }
+}
+
+/// Parses a [`GlobSet`] using the default settings of the [`Builder`], e.g.,
+/// `"*.rs".parse::<GlobSet<'_>>()`.
+impl str::FromStr for GlobSet<'static> {
+    type Err = Error;
+
+    fn from_str(glob: &str) -> Result<Self, Self::Err> {
+        Builder::new(glob).build_glob_set().map(GlobSet::into_owned)
+    }
+}
+
+/// Equivalent to [`Builder::build_glob_set`] using the default settings of the [`Builder`].
+impl<'a> convert::TryFrom<&'a str> for GlobSet<'a> {
+    type Error = Error;
+
+    fn try_from(glob: &'a str) -> Result<Self, Self::Error> {
+        Builder::new(glob).build_glob_set()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let path = path::Path::new("");
+        assert!(!path.is_absolute());
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "windows", ignore)]
+    fn match_globset() {
+        // yes, it is on purpose that this is a simple list and not read from the test-files/c-simple
+        let files = vec![
+            "/some/path/test-files/c-simple/a",
+            "/some/path/test-files/c-simple/a/a0",
+            "/some/path/test-files/c-simple/a/a0/a0_0.txt",
+            "/some/path/test-files/c-simple/a/a0/a0_1.txt",
+            "/some/path/test-files/c-simple/a/a0/A0_3.txt",
+            "/some/path/test-files/c-simple/a/a0/a0_2.md",
+            "/some/path/test-files/c-simple/a/a1",
+            "/some/path/test-files/c-simple/a/a1/a1_0.txt",
+            "/some/path/test-files/c-simple/a/a2",
+            "/some/path/test-files/c-simple/a/a2/a2_0.txt",
             "/some/path/test-files/c-simple/b/b_0.txt",
             "some_file.txt",
         ];
 
-        // function declaration within function. yay this starts to feel like python :D
-        fn match_glob<'a>(f: &'a str, m: &globset::GlobMatcher) -> Option<&'a str> {
-            match m.is_match(f) {
-                true => Some(f),
-                false => None,
-            }
+        // function declaration within function. yay this starts to feel like python :D
+        fn match_glob<'a>(f: &'a str, m: &globset::GlobMatcher) -> Option<&'a str> {
+            match m.is_match(f) {
+                true => Some(f),
+                false => None,
+            }
+        }
+
+        fn glob_for(
+            glob: &str,
+            case_sensitive: bool,
+        ) -> Result<globset::GlobMatcher, globset::Error> {
+            Ok(globset::GlobBuilder::new(glob)
+                .case_insensitive(!case_sensitive)
+                .backslash_escape(true)
+                .literal_separator(REQUIRE_PATHSEP)
+                .build()?
+                .compile_matcher())
+        }
+
+        fn test_for(glob: &str, len: usize, files: &[&str], case_sensitive: bool) {
+            let glob = glob_for(glob, case_sensitive).unwrap();
+            let matches = files
+                .iter()
+                .filter_map(|f| match_glob(f, &glob))
+                .collect::<Vec<_>>();
+            println!(
+                "matches for {}:\n'{}'",
+                glob.glob(),
+                matches
+                    .iter()
+                    .map(|f| f.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+            assert_eq!(len, matches.len());
+        }
+
+        test_for("/test-files/c-simple/**/*.txt", 0, &files, true);
+        test_for("test-files/c-simple/**/*.txt", 0, &files, true);
+        test_for("**/test-files/c-simple/**/*.txt", 6, &files, true);
+        test_for("**/test-files/c-simple/**/a*.txt", 4, &files, true);
+        test_for("**/test-files/c-simple/**/a*.txt", 5, &files, false);
+        test_for("**/test-files/c-simple/a/a*/a*.txt", 5, &files, false);
+        test_for("**/test-files/c-simple/a/a[01]/a*.txt", 4, &files, false);
+
+        // this is important, an empty pattern does not match anything
+        test_for("", 0, &files, false);
+
+        // notice that **/*.txt also matches zero recursive levels and thus also "some_file.txt"
+        test_for("**/*.txt", 7, &files, false);
+    }
+
+    #[test]
+    fn builder_build() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "**/*.txt";
+
+        let _builder = Builder::new(pattern).build(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn builder_err() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "a[";
+
+        match Builder::new(pattern).build(root) {
+            Ok(_) => Err("Expected pattern to fail".to_string()),
+            Err(_) => Ok(()),
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn match_absolute_pattern() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        match Builder::new("/test-files/c-simple/**/*.txt").build(root) {
+            Err(_) => Ok(()),
+            Ok(_) => Err("Expected failure".to_string()),
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn match_absolute_pattern() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        match Builder::new("C:/test-files/c-simple/**/*.txt").build(root) {
+            Err(_) => Ok(()),
+            Ok(_) => Err("Expected failure".to_string()),
+        }
+    }
+
+    /*
+    some helper functions for testing
+    */
+
+    fn log_paths<P>(paths: &[P])
+    where
+        P: AsRef<path::Path>,
+    {
+        println!(
+            "paths:\n{}",
+            paths
+                .iter()
+                .map(|p| format!("{}", p.as_ref().to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    fn log_paths_and_assert<P>(paths: &[P], expected_len: usize)
+    where
+        P: AsRef<path::Path>,
+    {
+        log_paths(paths);
+        assert_eq!(expected_len, paths.len());
+    }
+
+    #[test]
+    fn match_all() -> Result<(), String> {
+        // the following resolves to `<package-root>/test-files/c-simple/**/*.txt` and therefore
+        // successfully matches all files
+        let builder =
+            Builder::new("test-files/c-simple/**/*.txt").build(env!("CARGO_MANIFEST_DIR"))?;
+
+        let paths: Vec<_> = builder.into_iter().flatten().collect();
+        log_paths_and_assert(&paths, 6 + 2 + 1); // this also matches `some_file.txt`
+        Ok(())
+    }
+
+    #[test]
+    fn match_case() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/a/a?/a*.txt";
+
+        // default is case_sensitive(true)
+        let builder = Builder::new(pattern).build(root)?;
+        println!(
+            "working on root {} with glob {:?}",
+            builder.root_path().to_string_lossy(),
+            builder.rest()
+        );
+
+        let paths: Vec<_> = builder.into_iter().flatten().collect();
+        log_paths_and_assert(&paths, 4);
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn match_root_deprecated() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple/a/a1", env!("CARGO_MANIFEST_DIR"));
+
+        // the deprecated accessor provides the same root as its replacement
+        for pattern in ["*.txt", "a0/**/*.txt", "../a0/*.txt"] {
+            let builder = Builder::new(pattern).build(&root)?;
+            assert_eq!(builder.root_path().to_string_lossy(), builder.root());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_filter_entry() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/**/*.txt";
+
+        let builder = Builder::new(pattern).build(root)?;
+        let paths: Vec<_> = builder
+            .into_iter()
+            .filter_entry(|p| !is_hidden_entry(p))
+            .flatten()
+            .collect();
+
+        log_paths_and_assert(&paths, 6 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_filter() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/**/*.txt";
+
+        // this is slower than filter_entry since it matches all hidden paths
+        let builder = Builder::new(pattern).build(root)?;
+        let paths: Vec<_> = builder
+            .into_iter()
+            .flatten()
+            .filter(|p| !is_hidden_path(p))
+            .collect();
+
+        log_paths_and_assert(&paths, 6 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_with_glob() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/**/*.txt";
+
+        let glob = Builder::new("**/test-files/c-simple/a/a[0]/**").build_glob()?;
+        let paths: Vec<_> = Builder::new(pattern)
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .filter(|p| !is_hidden_path(p))
+            .filter(|p| glob.is_match(p))
+            .collect();
+
+        log_paths_and_assert(&paths, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn match_with_glob_all() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/**/*.*";
+
+        // build_glob creates a ["**/pattern", "pattern"] glob such that the user two separate
+        // patterns when scanning for files, e.g., using "*.txt" (which would need "**/*.txt"
+        // as well), but also when specifying paths within this glob.
+        let glob = Builder::new("*.txt").build_glob_set()?;
+        let paths: Vec<_> = Builder::new(pattern)
+            .build(root)?
+            .into_iter()
+            .filter_entry(|e| !is_hidden_entry(e))
+            .flatten()
+            .filter(|p| {
+                let is_match = glob.is_match(p);
+                println!("is match: {p:?} - {is_match}");
+                is_match
+            })
+            .collect();
+
+        log_paths_and_assert(&paths, 6 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_ranges() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let builder = Builder::new("test-files/c-simple/a/a{0..1}/*.txt").build(root)?;
+        assert_eq!("a{0..1}/*.txt", builder.rest());
+
+        let paths: Vec<_> = builder.into_iter().flatten().collect();
+        log_paths_and_assert(&paths, 3 + 1);
+
+        let glob = Builder::new("a{1..2}_*.txt").build_glob_set()?;
+        assert!(glob.is_match("test-files/c-simple/a/a1/a1_0.txt"));
+        assert!(!glob.is_match("test-files/c-simple/a/a0/a0_0.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn match_backslash_separators() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let matcher = Builder::new(r"test-files\c-simple\**\*.txt")
+            .backslash_separators(true)
+            .exclude(r"test-files\c-simple\a\a0")
+            .build(root)?;
+        assert_eq!("test-files/c-simple/**/*.txt", matcher.glob());
+        assert_eq!("**/*.txt", matcher.rest());
+
+        let paths: Vec<_> = matcher
+            .into_iter()
+            .filter_entry(|p| !is_hidden_entry(p))
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 4);
+
+        if !cfg!(windows) {
+            let paths: Vec<_> = Builder::new(r"test-files\c-simple\**\*.txt")
+                .build(root)?
+                .into_iter()
+                .flatten()
+                .collect();
+            log_paths_and_assert(&paths, 0);
+        }
+        Ok(())
+    }
+
+    /// [`utils::Lookup`] of the file system with the given environment variables.
+    struct Env(Vec<(&'static str, String)>);
+
+    impl utils::Lookup for Env {
+        fn exists(&self, p: &path::Path) -> bool {
+            WalkDirSource.exists(p)
+        }
+
+        fn is_dir(&self, p: &path::Path) -> bool {
+            WalkDirSource.is_dir(p)
+        }
+
+        fn canonicalize(&self, p: &path::Path) -> std::io::Result<path::PathBuf> {
+            WalkDirSource.canonicalize(p)
+        }
+
+        fn is_case_sensitive(&self, case: CaseSensitivity, root: &path::Path) -> bool {
+            WalkDirSource.is_case_sensitive(case, root)
+        }
+
+        fn var(&self, name: &str) -> Option<String> {
+            let value = self.0.iter().find(|(var, _)| *var == name);
+            value.map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn match_expand_env() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let env = Env(vec![
+            ("GLOBMATCH_TEST_ROOT", root.to_string()),
+            ("GLOBMATCH_TEST_NAME", "b_*".to_string()),
+        ]);
+
+        let matcher = Builder::new("$GLOBMATCH_TEST_ROOT/test-files/c-simple/b/*.txt")
+            .expand_env(true)
+            .build_in("does/not/matter", &env)?;
+        assert_eq!(
+            path::Path::new(root).join("test-files/c-simple/b"),
+            matcher.root
+        );
+        log_paths_and_assert(&matcher.into_iter().flatten().collect::<Vec<_>>(), 1);
+
+        let paths: Vec<_> = Builder::new("b/*.txt")
+            .expand_env(true)
+            .build_in("${GLOBMATCH_TEST_ROOT}/test-files/c-simple", &env)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+
+        // the values are matched literally
+        let paths: Vec<_> = Builder::new("b/${GLOBMATCH_TEST_NAME}.txt")
+            .expand_env(true)
+            .build_in(format!("{root}/test-files/c-simple"), &env)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 0);
+
+        let err = Builder::new("$GLOBMATCH_TEST_UNSET/*.txt")
+            .expand_env(true)
+            .build_in(root, &env)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidPattern { .. }));
+
+        // without the expansion the variable is matched literally
+        let paths: Vec<_> = Builder::new("$GLOBMATCH_TEST_ROOT/**/*.txt")
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn match_glob_options() -> Result<(), String> {
+        let glob = Builder::new("*.{md,}").build_glob_set()?;
+        assert!(glob.is_match("a.md"));
+        assert!(!glob.is_match("a."));
+
+        let glob = Builder::new("*.{md,}")
+            .empty_alternates(true)
+            .build_glob_set()?;
+        assert!(glob.is_match("a.md"));
+        assert!(glob.is_match("a."));
+
+        let glob = Builder::new(r"a\*").backslash_escape(true).build_glob()?;
+        assert!(glob.is_match("a*"));
+        assert!(!glob.is_match("ab"));
+
+        if !cfg!(windows) {
+            let glob = Builder::new(r"a\*").backslash_escape(false).build_glob()?;
+            assert!(glob.is_match(r"a\b"));
+            assert!(!glob.is_match("a*"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_case_auto() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let sensitive = CaseSensitivity::Auto.is_sensitive(Some(path::Path::new(&root)));
+
+        let paths: Vec<_> = Builder::new("a/a0/a*.txt")
+            .case_sensitivity(CaseSensitivity::Auto)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, if sensitive { 2 } else { 3 });
+        Ok(())
+    }
+
+    #[test]
+    fn match_braces() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let builder = Builder::new("test-files/c-simple/{a/{a1,a2},b}/*.txt").build(root)?;
+        assert_eq!("{a/{a1,a2},b}/*.txt", builder.rest());
+
+        let paths: Vec<_> = builder.into_iter().flatten().collect();
+        log_paths_and_assert(&paths, 3);
+
+        let paths: Vec<_> = Builder::new("{a,b}/**/*.{md,txt}")
+            .build(format!("{root}/test-files/c-simple"))?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 6 + 1);
+
+        // the alternates are kept within the compiled glob, i.e., they do not blow up
+        let glob = "{a,b}/".repeat(18) + "*.txt";
+        let start = std::time::Instant::now();
+        let matcher = Builder::new(&glob).build(format!("{root}/test-files/c-simple"))?;
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        assert!(matcher.is_match(path::PathBuf::from("a/b/".repeat(9) + "x.txt")));
+        assert!(!matcher.is_match(path::PathBuf::from("a/c/".repeat(9) + "x.txt")));
+        assert_eq!(0, matcher.into_iter().flatten().count());
+
+        let glob = Builder::new("{a,b{c,d}}/*.txt").build_glob_set()?;
+        assert!(glob.is_match("bd/x.txt"));
+        assert!(!glob.is_match("b/x.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn match_globstar_depth() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/**/*.txt";
+
+        let paths: Vec<_> = Builder::new(pattern)
+            .globstar_max_depth(0)
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1); // some_file.txt
+
+        let paths: Vec<_> = Builder::new(pattern)
+            .globstar_max_depth(1)
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1 + 1 + 2); // also b_0.txt and .hidden/*
+
+        let paths: Vec<_> = Builder::new(pattern)
+            .globstar_max_depth(2)
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 6 + 2 + 1);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn match_regex_segment() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/a/<re:a[02]>/*.txt")
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 3 + 1);
+
+        let glob = Builder::new(r"<re:a[0-9]_[0-9]+\.txt>").build_glob_set()?;
+        assert!(glob.is_match("test-files/c-simple/a/a1/a1_0.txt"));
+        assert!(!glob.is_match("test-files/c-simple/a/a0/A0_3.txt"));
+
+        assert!(Builder::new("a/<re:a[>/*.txt").build(root).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "regex"))]
+    fn match_regex_segment_disabled() {
+        let err = Builder::new("a/<re:a[0-9]>/*.txt")
+            .build_glob_set()
+            .unwrap_err();
+        assert_eq!(
+            Error::pattern(
+                "a/<re:a[0-9]>/*.txt",
+                "Regular expression segments require the `regex` feature"
+            ),
+            err
+        );
+    }
+
+    #[test]
+    fn match_fuzzy() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/some_fiel.txt")
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 0);
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/some_fiel.txt")
+            .fuzzy(Fuzzy::Distance(2))
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/a/**/a0md")
+            .fuzzy(Fuzzy::Subsequence)
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_suggest() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let matcher = Builder::new("test-files/c-simple/a/a9/*.txt").build(root)?;
+        assert_eq!(
+            vec![
+                "test-files/c-simple/a/a0/*.txt",
+                "test-files/c-simple/a/a1/*.txt",
+                "test-files/c-simple/a/a2/*.txt"
+            ],
+            matcher.suggest(5)
+        );
+
+        let root = format!("{root}/test-files/c-simple/a/a1");
+        let matcher = Builder::new("../a0/*.MD").build(root)?;
+        assert_eq!(vec!["../a0/a0_2.md"], matcher.suggest(5));
+        Ok(())
+    }
+
+    #[test]
+    fn match_file_root() -> Result<(), String> {
+        let root = format!(
+            "{}/test-files/c-simple/a/a0/a0_0.txt",
+            env!("CARGO_MANIFEST_DIR")
+        );
+
+        for (pattern, expected_len) in [("a0_0.txt", 1), ("*.txt", 1), ("*.md", 0), ("**", 1)] {
+            let builder = Builder::new(pattern).build(&root)?;
+            assert_eq!(path::Path::new(&root), builder.root_path());
+
+            let paths: Vec<_> = builder.into_iter().flatten().collect();
+            log_paths_and_assert(&paths, expected_len);
+            if expected_len > 0 {
+                assert_eq!(path::Path::new(&root), paths[0]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_root_vanished() -> Result<(), String> {
+        let root = utils::TempDir::new("vanished")?;
+        root.mkdir("sub")?;
+
+        let builder = Builder::new("sub/**/*.txt").build(&root)?;
+        std::fs::remove_dir_all(&root).map_err(|err| err.to_string())?;
+
+        let items: Vec<_> = builder.into_iter().collect();
+        assert_eq!(vec![Err(Error::RootVanished(root.join("sub")))], items);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn match_non_utf8() -> Result<(), String> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = utils::TempDir::new("non-utf8")?;
+        let dir = root.mkdir(std::ffi::OsStr::from_bytes(b"sub_\xff"))?;
+        let hidden = root.mkdir(std::ffi::OsStr::from_bytes(b".hidden_\xfe"))?;
+        for dir in [&dir, &hidden] {
+            root.write(dir.join("file.txt"), "")?;
+            root.write(dir.join(std::ffi::OsStr::from_bytes(b"\xff.txt")), "")?;
+        }
+        assert!(is_hidden_entry(&hidden));
+        assert!(is_hidden_path(hidden.join("file.txt")));
+
+        let matcher = Builder::new("sub_*/*.txt").build(&root)?;
+        assert_eq!(root.to_path_buf(), matcher.root_path());
+        let paths: Vec<_> = matcher
+            .into_iter()
+            .filter_entry(|p| !is_hidden_entry(p))
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 2);
+
+        #[cfg(feature = "regex")]
+        {
+            let paths: Vec<_> = Builder::new("**/<re:.*_[^a-z]+>/*.txt")
+                .build(&root)?
+                .into_iter()
+                .flatten()
+                .collect();
+            log_paths_and_assert(&paths, 4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_directory() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+
+        let paths: Vec<_> = Builder::new("../a")
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+
+        let paths: Vec<_> = Builder::new("../a")
+            .directory_match(DirectoryMatch::Contents)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 3 + 4 + 1 + 1); // a0..a2 and their files
+
+        // has no effect on files
+        let paths: Vec<_> = Builder::new("a0/a0_0.txt")
+            .directory_match(DirectoryMatch::Contents)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_parent_dirs() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let pattern = "test-files/c-simple/*/../a/a0/*.txt";
+
+        assert!(Builder::new(pattern).build(root).is_err());
+
+        let builder = Builder::new(pattern)
+            .resolve_parent_dirs(true)
+            .build(root)?;
+        assert_eq!("*/../a/a0/*.txt", builder.rest());
+
+        let paths: Vec<_> = builder.into_iter().flatten().collect();
+        log_paths_and_assert(&paths, 3);
+
+        assert!(Builder::new("test-files/c-simple/*/../../../*.txt")
+            .resolve_parent_dirs(true)
+            .build(root)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_root_symlink() -> Result<(), String> {
+        let target = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+        let target = path::PathBuf::from(target)
+            .canonicalize()
+            .map_err(|err| err.to_string())?;
+        let tmp = utils::TempDir::new("link")?;
+        let link = tmp.join("link");
+        std::os::unix::fs::symlink(&target, &link).map_err(|err| err.to_string())?;
+
+        let paths: Vec<_> = Builder::new("a0/*.txt")
+            .build(&link)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 3);
+        assert!(paths.iter().all(|p| p.starts_with(&link)));
+
+        let paths: Vec<_> = Builder::new("a0/*.txt")
+            .root_symlinks(RootSymlinks::Resolve)
+            .build(&link)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 3);
+        assert!(paths.iter().all(|p| p.starts_with(&target)));
+
+        // exclusions are relative to the provided root, also if it is canonicalized
+        for exclude in [
+            String::from("a0/a0_1.txt"),
+            format!("{}/a0/*_0.*", link.display()),
+        ] {
+            let paths: Vec<_> = Builder::new("a0/*.txt")
+                .root_symlinks(RootSymlinks::Resolve)
+                .exclude(exclude)
+                .build(&link)?
+                .into_iter()
+                .flatten()
+                .collect();
+            log_paths_and_assert(&paths, 2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_follow_links() -> Result<(), String> {
+        let target = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+        let root = utils::TempDir::new("follow")?;
+        root.mkdir("sub")?;
+        std::os::unix::fs::symlink(&target, root.join("vendor")).map_err(|err| err.to_string())?;
+        std::os::unix::fs::symlink(&root, root.join("sub/loop")).map_err(|err| err.to_string())?;
+
+        let walk = |follow: bool| -> Result<Vec<_>, String> {
+            Ok(Builder::new("**/*.txt")
+                .follow_links(follow)
+                .build(&root)?
+                .into_iter()
+                .collect())
+        };
+
+        let items = walk(false)?;
+        assert_eq!(0, items.len());
+
+        let items = walk(true)?;
+
+        let paths: Vec<_> = items.iter().flatten().collect();
+        log_paths_and_assert(&paths, 3 + 1 + 1);
+        assert!(paths.iter().all(|p| p.starts_with(root.join("vendor"))));
+
+        let loops: Vec<_> = items
+            .iter()
+            .filter_map(|item| item.as_ref().err())
+            .collect();
+        assert_eq!(
+            vec![&Error::SymlinkLoop {
+                ancestor: root.to_path_buf(),
+                child: root.join("sub/loop"),
+            }],
+            loops
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_path_normalization() -> Result<(), String> {
+        let target = format!("{}/test-files/c-simple/a/a0", env!("CARGO_MANIFEST_DIR"));
+        let root = utils::TempDir::new("normalize")?;
+        root.write("sub/file.txt", "")?;
+        std::os::unix::fs::symlink(&target, root.join("sub/link.txt"))
+            .map_err(|err| err.to_string())?;
+        std::os::unix::fs::symlink(root.join("missing"), root.join("sub/broken.txt"))
+            .map_err(|err| err.to_string())?;
+
+        let walk = |policy: PathNormalization, parallel: bool| -> Result<Vec<_>, String> {
+            let matcher = Builder::new("../sub/*.txt")
+                .path_normalization(policy)
+                .build(root.join("sub/."))?;
+            let mut items: Vec<_> = match parallel {
+                true => matcher.into_par_iter().collect(),
+                false => matcher.into_iter().collect(),
+            };
+            items.sort_by_key(|item| format!("{item:?}"));
+            Ok(items)
+        };
+
+        let keep = walk(PathNormalization::Keep, false)?;
+        let lexical = walk(PathNormalization::Lexical, false)?;
+        let canonical = walk(PathNormalization::Canonical, false)?;
+        let parallel = walk(PathNormalization::Canonical, true)?;
+        let canonical_root = root.canonicalize().map_err(|err| err.to_string())?;
+
+        assert_eq!(3, keep.len());
+        assert!(keep.contains(&Ok(root.join("sub/./../sub/file.txt"))));
+        assert!(lexical.contains(&Ok(root.join("sub/file.txt"))));
+        assert!(lexical.contains(&Ok(root.join("sub/broken.txt"))));
+
+        let paths: Vec<_> = canonical.iter().flatten().cloned().collect();
+        let expected = [
+            path::PathBuf::from(&target).canonicalize().unwrap(),
+            canonical_root.join("sub/file.txt"),
+        ];
+        assert_eq!(2, paths.len());
+        assert!(expected.iter().all(|p| paths.contains(p)));
+        // the broken link cannot be canonicalized
+        assert_eq!(1, canonical.iter().filter(|item| item.is_err()).count());
+        assert_eq!(canonical, parallel);
+
+        // paths that cannot be normalized are not counted as matches
+        let parallel: Vec<_> = Builder::new("*.txt")
+            .path_normalization(PathNormalization::Canonical)
+            .limit(WalkLimits {
+                max_matches: Some(2),
+                ..Default::default()
+            })
+            .build(root.join("sub"))?
+            .into_par_iter()
+            .collect();
+        assert_eq!(3, parallel.len());
+        assert!(!parallel.contains(&Err(Error::MatchLimit(2))));
+        Ok(())
+    }
+
+    #[test]
+    fn match_same_file_system() -> Result<(), String> {
+        // the root is on a single file system, all entries are walked
+        for parallel in [false, true] {
+            let matcher = Builder::new("test-files/c-simple/**/*.txt")
+                .same_file_system(true)
+                .build(env!("CARGO_MANIFEST_DIR"))?;
+            let paths: Vec<_> = match parallel {
+                true => matcher.into_par_iter().collect(),
+                false => matcher.into_iter().collect(),
+            };
+            assert_eq!(6 + 2 + 1, paths.len());
+            assert!(paths.iter().all(Result::is_ok));
+        }
+        Ok(())
+    }
+
+    /// Walks the real file system from `/`, which requires `/proc` to be a separate mount point.
+    #[test]
+    #[cfg(unix)]
+    #[ignore = "walks the entire file system and requires /proc to be mounted"]
+    fn match_same_file_system_mount() -> Result<(), String> {
+        use std::os::unix::fs::MetadataExt;
+
+        let walk = |same: bool, parallel: bool| -> Result<Vec<_>, String> {
+            let matcher = Builder::new("*/sys/kernel/hostname")
+                .same_file_system(same)
+                .build("/")?;
+            Ok(match parallel {
+                true => matcher.into_par_iter().flatten().collect(),
+                false => matcher.into_iter().flatten().collect(),
+            })
+        };
+        let device = |p: &str| std::fs::metadata(p).map(|meta| meta.dev()).ok();
+        assert!(device("/proc/sys/kernel/hostname").is_some());
+        assert_ne!(device("/proc"), device("/"), "/proc is not a mount point");
+        for parallel in [false, true] {
+            let hostname = path::PathBuf::from("/proc/sys/kernel/hostname");
+            assert!(walk(false, parallel)?.contains(&hostname));
+            assert!(!walk(true, parallel)?.contains(&hostname));
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_on_error() -> Result<(), String> {
+        let target = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+        let root = utils::TempDir::new("error")?;
+        root.mkdir("sub")?;
+        std::os::unix::fs::symlink(&target, root.join("vendor")).map_err(|err| err.to_string())?;
+        std::os::unix::fs::symlink(&root, root.join("sub/loop")).map_err(|err| err.to_string())?;
+
+        let walk = |policy: OnError| -> Result<Vec<_>, String> {
+            Ok(Builder::new("**/*.txt")
+                .follow_links(true)
+                .on_error(policy)
+                .build(&root)?
+                .into_iter()
+                .collect())
+        };
+        let yielded = walk(OnError::Yield)?;
+        let skipped = walk(OnError::Skip)?;
+        let aborted = walk(OnError::Abort)?;
+
+        assert_eq!(3 + 1 + 1 + 1, yielded.len());
+        assert_eq!(3 + 1 + 1, skipped.len());
+        assert!(skipped.iter().all(|item| item.is_ok()));
+
+        // the walk ends with the first error
+        assert!(aborted.last().is_some_and(|item| item.is_err()));
+        assert_eq!(1, aborted.iter().filter(|item| item.is_err()).count());
+        Ok(())
+    }
+
+    #[test]
+    fn match_normalized_root() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple/a", env!("CARGO_MANIFEST_DIR"));
+
+        let expected = Builder::new("a0/*.txt").build(&root)?;
+        let expected_root = expected.root_path().to_path_buf();
+        let expected_paths: Vec<_> = expected.into_iter().flatten().collect();
+
+        for root in [format!("{root}/"), format!("{root}/.")] {
+            let builder = Builder::new("a0/*.txt").build(root)?;
+            assert_eq!(expected_root, builder.root_path());
+
+            let paths: Vec<_> = builder.into_iter().flatten().collect();
+            assert_eq!(expected_paths, paths);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn build_limits() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let limits = PatternLimits {
+            max_length: Some(32),
+            max_patterns: Some(10),
+            max_compiled_size: Some(1024),
+            max_alternations: Some(2),
+            max_globstars: Some(2),
+        };
+
+        Builder::new("test-files/**/a{0..9}/*.txt")
+            .limits(limits)
+            .build(root)?;
+
+        let err = Builder::new("test-files/c-simple/a/a0/a0_0.txt")
+            .limits(limits)
+            .build(root)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            "'test-files/c-simple/a/a0/a0_0.txt': Pattern exceeds the maximum length of 32 bytes",
+            err
+        );
+
+        let err = Builder::new("a{0..10}/*.txt")
+            .limits(limits)
+            .build(root)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            "'a{0..10}/*.txt': Pattern expands to more than 10 globs",
+            err
+        );
+
+        let err = Builder::new("a{0..1}/**/*.txt")
+            .limits(limits)
+            .globstar_max_depth(5)
+            .build(root)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            "'a{0..1}/**/*.txt': Pattern expands to more than 10 globs",
+            err
+        );
+
+        // the pattern is so large that it would not even be possible to expand it
+        let err = Builder::new("{0..9223372036854775807}")
+            .limits(limits)
+            .build_glob_set()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Pattern expands to more than 10 globs"));
+
+        // without limits the number of expanded globs is still bounded
+        let err = Builder::new("{0..1000000000}/*.txt")
+            .build(root)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Pattern expands to more than 10000 globs"));
+        Builder::new("{0..1000}/*.txt").build(root)?;
+
+        let err = Builder::new("[a-z][a-z][a-z][a-z][a-z]")
+            .limits(PatternLimits {
+                max_compiled_size: Some(16),
+                ..Default::default()
+            })
+            .build_glob()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Compiled pattern exceeds the maximum size of 16 bytes"));
+
+        // ranges, escaped braces and braces within character classes are no alternates
+        let limits_3 = PatternLimits {
+            max_alternations: Some(3),
+            max_globstars: Some(2),
+            ..Default::default()
+        };
+        Builder::new("{a,b{c,d}}/[{,}]\\{x,y\\}/[]{,]/[!]{,]/{0..9}/**/**")
+            .limits(limits_3)
+            .backslash_escape(true)
+            .build_glob_set()?;
+        let err = Builder::new("{a,b{c,d}}/\\{x,y\\}")
+            .limits(limits_3)
+            .backslash_escape(false)
+            .build_glob_set()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Pattern exceeds the maximum of 3 alternatives"));
+
+        // the combinations of all alternates are limited, not the number of groups
+        for glob in ["{a,b}/{c,{d,e}}", "{a,b}/{c,d}"] {
+            let err = Builder::new(glob)
+                .limits(limits)
+                .build(root)
+                .unwrap_err()
+                .to_string();
+            assert_eq!(
+                format!("'{glob}': Pattern exceeds the maximum of 2 alternatives"),
+                err
+            );
+        }
+
+        let err = Builder::new("**/{a,**}/**")
+            .limits(limits)
+            .build_glob()
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            "'**/{a,**}/**': Pattern exceeds the maximum of 2 globstars",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_max_entries() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .max_entries(3)
+            .build(root)?
+            .into_iter()
+            .collect();
+        assert_eq!(Some(&Err(Error::EntryLimit(3))), paths.last());
+        assert!(paths.len() <= 3);
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .max_entries(3)
+            .build(root)?
+            .into_iter()
+            .filter_entry(|p| !is_hidden_path(p))
+            .collect();
+        assert_eq!(Some(&Err(Error::EntryLimit(3))), paths.last());
+
+        // only the root is accepted, the rejected entries count towards the limit as well
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .max_entries(3)
+            .build(root)?
+            .into_iter()
+            .filter_entry(|p| p.ends_with("c-simple"))
+            .collect();
+        assert_eq!(vec![Err(Error::EntryLimit(3))], paths);
+
+        // the limit is not exceeded, all entries are walked
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .max_entries(100)
+            .build(root)?
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.to_string())?;
+        log_paths_and_assert(&paths, 6 + 2 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_walk_limits() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let builder = |limits| {
+            let mut builder = Builder::new("test-files/c-simple/**/*.txt");
+            builder.limit(limits);
+            builder
+        };
+
+        let paths: Vec<_> = builder(WalkLimits {
+            max_matches: Some(3),
+            ..Default::default()
+        })
+        .build(root)?
+        .into_iter()
+        .collect();
+        assert_eq!(3 + 1, paths.len());
+        assert_eq!(Some(&Err(Error::MatchLimit(3))), paths.last());
+
+        let paths: Vec<_> = builder(WalkLimits {
+            max_matches: Some(3),
+            ..Default::default()
+        })
+        .build(root)?
+        .into_par_iter()
+        .collect();
+        assert_eq!(3 + 1, paths.len());
+        assert!(paths.contains(&Err(Error::MatchLimit(3))));
+
+        let paths: Vec<_> = builder(WalkLimits {
+            max_entries: Some(3),
+            ..Default::default()
+        })
+        .build(root)?
+        .into_iter()
+        .collect();
+        assert_eq!(Some(&Err(Error::EntryLimit(3))), paths.last());
+
+        // the limits are not exceeded, all entries are walked
+        let paths: Vec<_> = builder(WalkLimits {
+            max_entries: Some(100),
+            max_matches: Some(9),
+            timeout: Some(std::time::Duration::from_secs(60)),
+        })
+        .build(root)?
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|err| err.to_string())?;
+        log_paths_and_assert(&paths, 6 + 2 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn match_progress() -> Result<(), String> {
+        use std::sync::{Arc, Mutex};
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let builder = Builder::new("test-files/c-simple/**/*.txt");
+
+        let reports = Arc::new(Mutex::new(vec![]));
+        let progress = Arc::clone(&reports);
+        let paths: Vec<_> = builder
+            .build(root)?
+            .on_progress(5, move |p| progress.lock().unwrap().push(*p))
+            .into_iter()
+            .flatten()
+            .collect();
+        let reports = reports.lock().unwrap().clone();
+        let (last, cadence) = reports.split_last().unwrap();
+        assert!(cadence.iter().all(|p| p.entries % 5 == 0));
+        assert_eq!(last.entries / 5, cadence.len());
+        assert_eq!(paths.len(), last.matches);
+        assert!(last.dirs > 1 && last.dirs < last.entries);
+
+        // the parallel iterator walks the same entries
+        let parallel = Arc::new(Mutex::new(Progress::default()));
+        let progress = Arc::clone(&parallel);
+        let _ = builder
+            .build(root)?
+            .on_progress(usize::MAX, move |p| *progress.lock().unwrap() = *p)
+            .into_par_iter()
+            .count();
+        assert_eq!(*last, *parallel.lock().unwrap());
+
+        // the final progress is also reported if the walk is cancelled or dropped early
+        for parallel in [false, true] {
+            let handle = CancellationHandle::new();
+            let reports = Arc::new(Mutex::new(vec![]));
+            let progress = Arc::clone(&reports);
+            let matcher = builder
+                .build(root)?
+                .cancel_on(handle.clone())
+                .on_progress(usize::MAX, move |p| progress.lock().unwrap().push(*p));
+            let iter: Box<dyn Iterator<Item = _>> = match parallel {
+                true => Box::new(matcher.into_par_iter()),
+                false => Box::new(matcher.into_iter()),
+            };
+            handle.cancel();
+            assert_eq!(vec![Err(Error::Cancelled)], iter.collect::<Vec<_>>());
+            assert_eq!(1, reports.lock().unwrap().len());
+
+            let reports = Arc::new(Mutex::new(vec![]));
+            let progress = Arc::clone(&reports);
+            let matcher = builder
+                .build(root)?
+                .on_progress(usize::MAX, move |p| progress.lock().unwrap().push(*p));
+            let mut iter: Box<dyn Iterator<Item = _>> = match parallel {
+                true => Box::new(matcher.into_par_iter()),
+                false => Box::new(matcher.into_iter()),
+            };
+            assert!(iter.next().is_some_and(|p| p.is_ok()));
+            drop(iter);
+            let reports = reports.lock().unwrap();
+            assert_eq!(1, reports.len());
+            assert!(reports[0].matches >= 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_sorted() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .relative_paths(true)
+            .sort_by_file_name()
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        let expected = [
+            ".hidden/h_0.txt",
+            ".hidden/h_1.txt",
+            "a/a0/A0_3.txt",
+            "a/a0/a0_0.txt",
+            "a/a0/a0_1.txt",
+            "a/a1/a1_0.txt",
+            "a/a2/a2_0.txt",
+            "b/b_0.txt",
+            "some_file.txt",
+        ];
+        assert_eq!(expected.map(path::PathBuf::from).to_vec(), paths);
+
+        // files are yielded before the contents of the directories
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .relative_paths(true)
+            .sort_by(|a, b| {
+                let is_dir = |entry: &walkdir::DirEntry| entry.file_type().is_dir();
+                is_dir(a)
+                    .cmp(&is_dir(b))
+                    .then_with(|| b.file_name().cmp(a.file_name()))
+            })
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(Some(&path::PathBuf::from("some_file.txt")), paths.first());
+        assert_eq!(Some(&path::PathBuf::from(".hidden/h_0.txt")), paths.last());
+        Ok(())
+    }
+
+    #[test]
+    fn match_sorted_naturally() -> Result<(), String> {
+        let root = utils::TempDir::new("natural")?;
+        for p in ["take10/frame2.png", "take2/frame10.png", "take2/frame1.png"] {
+            root.write(p, "")?;
         }
+        let paths: Vec<_> = Builder::new("**/*.png")
+            .relative_paths(true)
+            .sort_by_natural_name()
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
 
-        fn glob_for(
-            glob: &str,
-            case_sensitive: bool,
-        ) -> Result<globset::GlobMatcher, globset::Error> {
-            Ok(globset::GlobBuilder::new(glob)
-                .case_insensitive(!case_sensitive)
-                .backslash_escape(true)
-                .literal_separator(REQUIRE_PATHSEP)
-                .build()?
-                .compile_matcher())
+        let expected = ["take2/frame1.png", "take2/frame10.png", "take10/frame2.png"];
+        assert_eq!(expected.map(path::PathBuf::from).to_vec(), paths);
+        Ok(())
+    }
+
+    #[test]
+    fn match_cancelled() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let handle = CancellationHandle::new();
+
+        let mut iter = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .cancel_on(handle.clone())
+            .into_iter()
+            .filter_entry(|p| !is_hidden_path(p));
+        assert!(iter.next().is_some_and(|p| p.is_ok()));
+        handle.clone().cancel();
+        assert!(handle.is_cancelled());
+        assert_eq!(Some(Err(Error::Cancelled)), iter.next());
+        assert_eq!(None, iter.next());
+
+        // the walk is cancelled while the predicate rejects all entries but the root, no further
+        // entries are walked once the first entry has been rejected
+        let cancel = CancellationHandle::new();
+        let mut calls = 0;
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .cancel_on(cancel.clone())
+            .into_iter()
+            .filter_entry(|p| {
+                calls += 1;
+                let is_root = p.ends_with("c-simple");
+                if !is_root {
+                    cancel.cancel();
+                }
+                is_root
+            })
+            .collect();
+        assert_eq!(vec![Err(Error::Cancelled)], paths);
+        assert_eq!(2, calls);
+
+        // a cancelled handle aborts the walk before the first entry
+        let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .cancel_on(handle)
+            .into_iter()
+            .collect();
+        assert_eq!(vec![Err(Error::Cancelled)], paths);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_globs() -> Result<(), String> {
+        let glob = "**/*.rs".parse::<Glob<'_>>()?;
+        assert_eq!("**/*.rs", glob.glob());
+        assert!(glob.is_match("src/lib.rs"));
+        assert!(!glob.is_match("src/lib.md"));
+
+        let glob = Glob::try_from("*.rs")?;
+        assert!(!glob.is_match("src/lib.rs"));
+
+        let glob_set = "*.rs".parse::<GlobSet<'_>>()?;
+        assert!(glob_set.is_match("src/lib.rs"));
+        #[cfg(feature = "regex")]
+        {
+            let glob_set = GlobSet::try_from(r"<re:li.\.rs>")?;
+            assert!(glob_set.is_match("src/lib.rs"));
         }
 
-        fn test_for(glob: &str, len: usize, files: &[&str], case_sensitive: bool) {
-            let glob = glob_for(glob, case_sensitive).unwrap();
-            let matches = files
-                .iter()
-                .filter_map(|f| match_glob(f, &glob))
-                .collect::<Vec<_>>();
-            println!(
-                "matches for {}:\n'{}'",
-                glob.glob(),
-                matches
+        assert!("".parse::<Glob<'_>>().is_err());
+        assert!("a[".parse::<Glob<'_>>().is_err());
+        assert!("/*.rs".parse::<GlobSet<'_>>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn match_collect() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let mut builder = Builder::new("test-files/c-simple/**/*.txt");
+
+        let discovered: Vec<_> = builder.build(root)?.into_iter().flatten().collect();
+        let sorted = builder.build(root)?.collect_sorted().unwrap();
+        let unique = builder.build(root)?.collect_unique().unwrap();
+        let set = builder
+            .build(root)?
+            .collect_set::<std::collections::BTreeSet<_>>()
+            .unwrap();
+
+        log_paths_and_assert(&sorted, 6 + 2 + 1);
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(discovered, unique);
+        assert_eq!(sorted, set.into_iter().collect::<Vec<_>>());
+
+        let err = builder
+            .max_entries(1)
+            .build(root)?
+            .collect_sorted()
+            .unwrap_err();
+        assert_eq!(Error::EntryLimit(1), err);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "ignore")]
+    fn match_overrides() -> Result<(), String> {
+        use ignore::overrides::OverrideBuilder;
+
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let overrides = OverrideBuilder::new(&root)
+            .add("!a/a0/**")
+            .and_then(|b| b.add("!.hidden/**"))
+            .and_then(|b| b.build())
+            .map_err(|err| err.to_string())?;
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .with_overrides(overrides.clone())
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1 + 1 + 1 + 1);
+
+        let glob_set = GlobSet::from_override(overrides);
+        assert!(!glob_set.is_match(format!("{root}/a/a0/a0_0.txt")));
+        assert!(!glob_set.is_match(format!("{root}/a/a1/a1_0.txt")));
+
+        let overrides = OverrideBuilder::new(&root)
+            .add("a/a1/*.txt")
+            .and_then(|b| b.build())
+            .map_err(|err| err.to_string())?;
+        let glob_set = GlobSet::from_override(overrides);
+        assert!(glob_set.is_match(format!("{root}/a/a1/a1_0.txt")));
+        assert!(!glob_set.is_match(format!("{root}/a/a0/a0_0.txt")));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "ignore")]
+    fn match_gitignore() -> Result<(), String> {
+        let root = utils::TempDir::new("gitignore")?;
+        let files = [
+            ".gitignore",
+            "keep.log",
+            "main.log",
+            "build/out.txt",
+            "src/a.txt",
+            "src/gen/b.txt",
+            "src/.gitignore",
+        ];
+        for file in files {
+            root.write(file, "")?;
+        }
+        root.write(".gitignore", "build/\n*.log\n!keep.log\n")?;
+        root.write("src/.gitignore", "gen\n")?;
+
+        let count = |gitignore: bool| -> Result<usize, String> {
+            Ok(Builder::new("**/*.{txt,log}")
+                .respect_gitignore(gitignore)
+                .build(&root)?
+                .into_iter()
+                .flatten()
+                .count())
+        };
+
+        // not a git repository, thus all rules are ignored
+        assert_eq!(5, count(true)?);
+
+        root.mkdir(".git")?;
+        assert_eq!(5, count(false)?);
+        let paths: Vec<_> = Builder::new("**/*.{txt,log}")
+            .respect_gitignore(true)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        log_paths_and_assert(&paths, 2);
+        assert!(paths.contains(&root.join("keep.log")));
+        assert!(paths.contains(&root.join("src/a.txt")));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "ignore")]
+    fn match_ignore_file_name() -> Result<(), String> {
+        let root = utils::TempDir::new("ignore")?;
+        let files = [
+            "a.txt",
+            "b.txt",
+            "c.log",
+            "gen/d.txt",
+            "src/e.txt",
+            "src/f.txt",
+        ];
+        for file in files {
+            root.write(file, "")?;
+        }
+        root.mkdir(".git")?;
+        root.write(".gitignore", "b.txt\n")?;
+        root.write(".toolignore", "gen/\n*.txt\n!b.txt\n")?;
+        root.write("src/.toolignore", "!e.txt\n")?;
+
+        let paths: Vec<_> = Builder::new("**/*.{txt,log}")
+            .respect_gitignore(true)
+            .add_ignore_file_name(".toolignore")
+            .add_ignore_file_name(".missingignore")
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        // the custom ignore file takes precedence over the `.gitignore` in the same folder
+        log_paths_and_assert(&paths, 3);
+        assert!(paths.contains(&root.join("b.txt")));
+        assert!(paths.contains(&root.join("c.log")));
+        assert!(paths.contains(&root.join("src/e.txt")));
+        Ok(())
+    }
+
+    #[test]
+    fn match_metadata() -> Result<(), String> {
+        let root = utils::TempDir::new("metadata")?;
+        for (file, len) in [("empty.txt", 0), ("small.txt", 10), ("large.txt", 1000)] {
+            root.write(file, vec![b'x'; len])?;
+        }
+
+        let paths = |filter: MetadataFilter| -> Result<(Vec<_>, Vec<_>), String> {
+            let mut builder = Builder::new("*.txt");
+            builder.filter_metadata(filter);
+            let mut sequential: Vec<_> = builder.build(&root)?.into_iter().flatten().collect();
+            let mut parallel: Vec<_> = builder.build(&root)?.into_par_iter().flatten().collect();
+            sequential.sort();
+            parallel.sort();
+            Ok((sequential, parallel))
+        };
+        let sized = paths(MetadataFilter {
+            min_size: Some(1),
+            max_size: Some(100),
+            ..Default::default()
+        });
+        let now = std::time::SystemTime::now();
+        let hour = std::time::Duration::from_secs(3600);
+        let recent = paths(MetadataFilter {
+            modified_after: Some(now - hour),
+            modified_before: Some(now + hour),
+            ..Default::default()
+        });
+        let future = paths(MetadataFilter {
+            modified_after: Some(now + hour),
+            ..Default::default()
+        });
+
+        let (sequential, parallel) = sized?;
+        assert_eq!(vec![root.join("small.txt")], sequential);
+        assert_eq!(sequential, parallel);
+        let (sequential, parallel) = recent?;
+        assert_eq!(3, sequential.len());
+        assert_eq!(sequential, parallel);
+        let (sequential, parallel) = future?;
+        assert!(sequential.is_empty() && parallel.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_dedup_files() -> Result<(), String> {
+        let root = utils::TempDir::new("dedup")?;
+        root.mkdir("sub")?;
+        root.write("a.txt", "a")?;
+        root.write("c.txt", "c")?;
+        std::fs::hard_link(root.join("a.txt"), root.join("sub/b.txt"))
+            .map_err(|err| err.to_string())?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("c.txt"), root.join("sub/d.txt"))
+            .map_err(|err| err.to_string())?;
+        let links = usize::from(cfg!(unix));
+
+        let count = |dedup: bool, follow: bool| -> Result<(usize, usize), String> {
+            let mut builder = Builder::new("**/*.txt");
+            builder.dedup_files(dedup).follow_links(follow);
+            let sequential = builder.build(&root)?.into_iter().flatten().count();
+            let parallel = builder.build(&root)?.into_par_iter().flatten().count();
+            Ok((sequential, parallel))
+        };
+        let all = count(false, false);
+        let unique = count(true, false);
+        let followed = count(true, true);
+        let (paths, _) = wrappers::match_paths_ordered(
+            wrappers::build_matchers(&["**/a.txt", "sub/*.txt", "*.txt"], &root)?,
+            None,
+            None,
+            wrappers::PathOrder::DiscoveredUniqueFiles,
+        );
+
+        assert_eq!((3 + links, 3 + links), all?);
+        assert_eq!((2 + links, 2 + links), unique?);
+        assert_eq!((2, 2), followed?);
+        // the link to `c.txt` is discovered first
+        let c = match cfg!(unix) {
+            true => root.join("sub/d.txt"),
+            false => root.join("c.txt"),
+        };
+        assert_eq!(vec![root.join("a.txt"), c], paths);
+        Ok(())
+    }
+
+    #[test]
+    fn match_missing_root() -> Result<(), String> {
+        let tmp = utils::TempDir::new("missing")?;
+        let root = tmp.join("root");
+        let build = |policy: MissingRoot| {
+            Builder::new("src/**/*.rs")
+                .missing_root(policy)
+                .build(&root)
+        };
+
+        let err = build(MissingRoot::Fail).unwrap_err();
+        assert!(matches!(&err, Error::RootNotFound { root: r, .. } if *r == root));
+        assert_eq!(0, build(MissingRoot::Empty)?.into_iter().count());
+        assert_eq!(0, build(MissingRoot::Empty)?.into_par_iter().count());
+        // the deferred error renders the same as the error of `build`
+        let expected = vec![Err(err)];
+        assert_eq!(
+            expected,
+            build(MissingRoot::Defer)?.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            expected,
+            build(MissingRoot::Defer)?
+                .into_par_iter()
+                .collect::<Vec<_>>()
+        );
+
+        // the matcher can be built before the root is created
+        let matcher = build(MissingRoot::Defer)?;
+        std::fs::create_dir_all(root.join("src/core")).map_err(|err| err.to_string())?;
+        std::fs::write(root.join("src/core/lib.rs"), "").map_err(|err| err.to_string())?;
+        std::fs::write(root.join("main.rs"), "").map_err(|err| err.to_string())?;
+        let paths = matcher.into_iter().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(vec![root.join("src/core/lib.rs")], paths);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn match_jail() -> Result<(), String> {
+        use std::os::unix::fs::symlink;
+
+        let tmp = utils::TempDir::new("jail")?;
+        let root = tmp.mkdir("jail")?;
+        tmp.write("jail/inner/a.txt", "")?;
+        tmp.write("outside/secret.txt", "")?;
+        symlink("inner/a.txt", root.join("ok.txt")).map_err(|err| err.to_string())?;
+        symlink("../outside/secret.txt", root.join("leak.txt")).map_err(|err| err.to_string())?;
+        symlink("../outside", root.join("escape")).map_err(|err| err.to_string())?;
+        tmp.write("jail/top.md", "")?;
+        symlink("../top.md", root.join("inner/up.md")).map_err(|err| err.to_string())?;
+
+        let count = |jail: bool, follow: bool| -> Result<[(usize, usize); 2], String> {
+            let mut builder = Builder::new("**/*.txt");
+            builder.jail(jail).follow_links(follow);
+            let split = |items: Vec<Result<path::PathBuf, Error>>| {
+                let errors = items
                     .iter()
-                    .map(|f| f.to_string())
-                    .collect::<Vec<_>>()
-                    .join("\n")
+                    .filter(|item| matches!(item, Err(Error::OutsideRoot(_))))
+                    .count();
+                (items.len() - errors, errors)
+            };
+            Ok([
+                split(builder.build(&root)?.into_iter().collect()),
+                split(builder.build(&root)?.into_par_iter().collect()),
+            ])
+        };
+        let free = count(false, false);
+        let jailed = count(true, false);
+        let followed = count(true, true);
+        let parent = Builder::new("../outside/*.txt").jail(true).build(&root);
+        let linked = Builder::new("escape/*.txt").jail(true).build(&root);
+        let mut provided = Builder::new("inner/*.md");
+        provided.jail(true);
+        let within: Vec<_> = provided.build(&root)?.into_iter().collect();
+        let within_par: Vec<_> = provided.build(&root)?.into_par_iter().collect();
+
+        assert_eq!([(3, 0), (3, 0)], free?);
+        assert_eq!([(2, 2), (2, 2)], jailed?);
+        assert_eq!((2, 2), followed?[0]);
+        assert!(matches!(parent, Err(Error::OutsideRoot(_))));
+        assert!(matches!(linked, Err(Error::OutsideRoot(_))));
+        assert!(matches!(within.as_slice(), [Ok(p)] if p.ends_with("inner/up.md")));
+        assert!(matches!(within_par.as_slice(), [Ok(p)] if p.ends_with("inner/up.md")));
+        Ok(())
+    }
+
+    /// [`utils::Lookup`] of the file system that fails to resolve any path.
+    struct Unresolved;
+
+    impl utils::Lookup for Unresolved {
+        fn exists(&self, p: &path::Path) -> bool {
+            WalkDirSource.exists(p)
+        }
+
+        fn is_dir(&self, p: &path::Path) -> bool {
+            WalkDirSource.is_dir(p)
+        }
+
+        fn canonicalize(&self, _p: &path::Path) -> std::io::Result<path::PathBuf> {
+            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+        }
+
+        fn is_case_sensitive(&self, case: CaseSensitivity, root: &path::Path) -> bool {
+            WalkDirSource.is_case_sensitive(case, root)
+        }
+    }
+
+    #[test]
+    fn check_jail_fails_closed() {
+        let root = path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let resolved = root.join("test-files");
+
+        let jailed = Builder::check_jail(root, &resolved, &WalkDirSource);
+        let unresolved = Builder::check_jail(root, &resolved, &Unresolved);
+        let empty = Builder::check_jail(path::Path::new(""), &resolved, &WalkDirSource);
+        let outside = Builder::check_jail(&resolved, root, &WalkDirSource);
+
+        assert_eq!(Some(root.canonicalize().unwrap()), jailed.ok());
+        assert!(matches!(unresolved, Err(Error::OutsideRoot(_))));
+        assert!(matches!(empty, Err(Error::OutsideRoot(_))));
+        assert!(matches!(outside, Err(Error::OutsideRoot(_))));
+    }
+
+    #[test]
+    fn match_relative_paths() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let paths: Vec<_> = Builder::new("test-files/c-simple/a/**/*.txt")
+            .relative_paths(true)
+            .build(root)?
+            .into_iter()
+            .filter_entry(|p| !p.ends_with("a1"))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(4, paths.len());
+        assert!(paths.contains(&path::PathBuf::from("a0/a0_0.txt")));
+        assert!(paths
+            .iter()
+            .all(|p| p.is_relative() && !p.starts_with("a1")));
+
+        let mut parallel: Vec<_> = Builder::new("test-files/c-simple/a/**/*.txt")
+            .relative_paths(true)
+            .build(root)?
+            .into_par_iter()
+            .collect::<Result<_, _>>()?;
+        parallel.sort();
+        let mut sequential: Vec<_> = Builder::new("test-files/c-simple/a/**/*.txt")
+            .relative_paths(true)
+            .build(root)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        sequential.sort();
+        assert_eq!(sequential, parallel);
+
+        let paths: Vec<_> = Builder::new("*.txt")
+            .relative_paths(true)
+            .build(format!("{root}/test-files/c-simple/some_file.txt"))?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        assert_eq!(vec![path::PathBuf::from("some_file.txt")], paths);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "reproducible")]
+    fn match_reproducible() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let paths = Builder::new("test-files/c-simple/**/[aA]*.txt")
+            .build(root)?
+            .collect_reproducible()
+            .map_err(|err| err.to_string())?;
+        assert_eq!(
+            vec![
+                "test-files/c-simple/a/a0/A0_3.txt",
+                "test-files/c-simple/a/a0/a0_0.txt",
+                "test-files/c-simple/a/a0/a0_1.txt",
+                "test-files/c-simple/a/a1/a1_0.txt",
+                "test-files/c-simple/a/a2/a2_0.txt",
+            ],
+            paths
+        );
+
+        let paths = Builder::new("../b/*.txt")
+            .build(format!("{root}/test-files/c-simple/a"))?
+            .collect_reproducible()
+            .map_err(|err| err.to_string())?;
+        assert_eq!(vec!["../b/b_0.txt"], paths);
+
+        let paths = Builder::new("*.txt")
+            .build(format!("{root}/test-files/c-simple/some_file.txt"))?
+            .collect_reproducible()
+            .map_err(|err| err.to_string())?;
+        assert_eq!(vec!["some_file.txt"], paths);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "reproducible")]
+    fn match_reproducible_errors() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple/a/../a", env!("CARGO_MANIFEST_DIR"));
+
+        let paths = Builder::new("a0/*.md")
+            .relative_paths(true)
+            .build(&root)?
+            .collect_reproducible()
+            .map_err(|err| err.to_string())?;
+        assert_eq!(vec!["a0/a0_2.md"], paths);
+
+        let err = Builder::new("a0/*.md")
+            .path_normalization(PathNormalization::Canonical)
+            .build(&root)?
+            .collect_reproducible()
+            .unwrap_err();
+        assert!(matches!(err, Error::OutsideRoot(_)));
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let tmp = utils::TempDir::new("reproducible")?;
+            let invalid = tmp.write(std::ffi::OsStr::from_bytes(b"\xff.txt"), "")?;
+            let err = Builder::new("*.txt")
+                .build(&tmp)?
+                .collect_reproducible()
+                .unwrap_err();
+            assert_eq!(Error::NotUtf8(invalid), err);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn match_raw_entries() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let entries: Vec<_> = Builder::new("a/**/*.txt")
+            .build(&root)?
+            .raw_entries()
+            .flatten()
+            .collect();
+        assert_eq!(5, entries.len());
+        for e in entries {
+            assert_eq!(2, e.entry.depth());
+            assert!(e.entry.file_type().is_file());
+            assert!(e.entry.path().ends_with(&e.relative));
+            assert!(
+                e.relative.starts_with("a0")
+                    || e.relative.starts_with("a1")
+                    || e.relative.starts_with("a2")
             );
-            assert_eq!(len, matches.len());
         }
 
-        test_for("/test-files/c-simple/**/*.txt", 0, &files, true);
-        test_for("test-files/c-simple/**/*.txt", 0, &files, true);
-        test_for("**/test-files/c-simple/**/*.txt", 6, &files, true);
-        test_for("**/test-files/c-simple/**/a*.txt", 4, &files, true);
-        test_for("**/test-files/c-simple/**/a*.txt", 5, &files, false);
-        test_for("**/test-files/c-simple/a/a*/a*.txt", 5, &files, false);
-        test_for("**/test-files/c-simple/a/a[01]/a*.txt", 4, &files, false);
+        let entries: Vec<_> = Builder::new("*.txt")
+            .build(format!("{root}/some_file.txt"))?
+            .raw_entries()
+            .flatten()
+            .collect();
+        assert_eq!(1, entries.len());
+        assert_eq!(path::Path::new("some_file.txt"), entries[0].relative);
+        Ok(())
+    }
 
-        // this is important, an empty pattern does not match anything
-        test_for("", 0, &files, false);
+    #[test]
+    fn match_entries() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
 
-        // notice that **/*.txt also matches zero recursive levels and thus also "some_file.txt"
-        test_for("**/*.txt", 7, &files, false);
+        let matcher = Builder::new("a/**").build(&root)?;
+        let base = matcher.root_path().components().count();
+
+        let entries: Vec<_> = matcher.into_entries().collect::<Result<_, _>>()?;
+        assert_eq!(1 + 3 + 4 + 1 + 1, entries.len());
+        for e in &entries {
+            assert_eq!(e.file_type.is_dir(), e.metadata.is_dir());
+            assert_eq!(base + e.depth, e.path.components().count());
+        }
+        let a0_1 = entries
+            .iter()
+            .find(|e| e.path.ends_with("a0_1.txt"))
+            .unwrap();
+        assert_eq!(2, a0_1.depth);
+        assert_eq!(
+            std::fs::metadata(&a0_1.path).unwrap().len(),
+            a0_1.metadata.len()
+        );
+
+        // the paths are the same as the paths of the path iterators
+        let mut builder = Builder::new("a/**/*.txt");
+        builder.relative_paths(true);
+        let paths: Vec<_> = builder.build(&root)?.into_iter().flatten().collect();
+        let entries: Vec<_> = builder
+            .build(&root)?
+            .into_entries()
+            .flatten()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(paths, entries);
+        assert!(entries.contains(&path::PathBuf::from("a0/a0_1.txt")));
+        Ok(())
     }
 
     #[test]
-    fn builder_build() -> Result<(), String> {
-        let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "**/*.txt";
+    fn match_depth() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
 
-        let _builder = Builder::new(pattern).build(root)?;
+        let walk = |min: usize, max: usize| -> Result<Vec<_>, String> {
+            Ok(Builder::new("**/*.txt")
+                .min_depth(min)
+                .max_depth(max)
+                .build(&root)?
+                .into_iter()
+                .flatten()
+                .collect())
+        };
+
+        log_paths_and_assert(&walk(0, 1)?, 1);
+        log_paths_and_assert(&walk(2, 2)?, 2 + 1);
+        log_paths_and_assert(&walk(3, 10)?, 3 + 1 + 1);
+
+        // the depth is relative to the resolved root
+        let paths: Vec<_> = Builder::new("a/**/*.txt")
+            .max_depth(1)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 0);
+
+        // the depth is bounded by the glob itself
+        let matcher = Builder::new("*/a?/*.txt").build(&root)?;
+        assert_eq!(Some(3), matcher.walk.max_depth);
+        log_paths_and_assert(
+            &matcher.into_iter().flatten().collect::<Vec<_>>(),
+            3 + 1 + 1,
+        );
+
+        let matcher = Builder::new("*/a?/*.txt").max_depth(2).build(&root)?;
+        assert_eq!(Some(2), matcher.walk.max_depth);
+
+        let matcher = Builder::new("**/*.txt").build(&root)?;
+        assert_eq!(None, matcher.walk.max_depth);
         Ok(())
     }
 
     #[test]
-    fn builder_err() -> Result<(), String> {
-        let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "a[";
+    fn match_file_type() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
 
-        match Builder::new(pattern).build(root) {
-            Ok(_) => Err("Expected pattern to fail".to_string()),
-            Err(_) => Ok(()),
+        let paths: Vec<_> = Builder::new("a/**")
+            .file_type(FileType::Dirs)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1 + 3); // `a/**` also matches `a`
+
+        let mut paths: Vec<_> = Builder::new("**/a*")
+            .file_type(FileType::Files)
+            .build(&root)?
+            .into_par_iter()
+            .flatten()
+            .collect();
+        paths.sort();
+        log_paths_and_assert(&paths, 3 + 1 + 1);
+
+        // the root itself is only matched by globstars, not by `*`
+        for (glob, expected) in [("*", 3), ("**", 1 + 3 + 3)] {
+            let matcher = || Builder::new(glob).file_type(FileType::Dirs).build(&root);
+            let paths: Vec<_> = matcher()?.into_iter().flatten().collect();
+            log_paths_and_assert(&paths, expected);
+            let paths: Vec<_> = matcher()?.into_par_iter().flatten().collect();
+            log_paths_and_assert(&paths, expected);
         }
+
+        let paths: Vec<_> = Builder::new("*.txt")
+            .file_type(FileType::Dirs)
+            .build(format!("{root}/some_file.txt"))?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 0);
+        Ok(())
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn match_absolute_pattern() -> Result<(), String> {
+    fn match_pruned() -> Result<(), String> {
         let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
-        match Builder::new("/test-files/c-simple/**/*.txt").build(root) {
-            Err(_) => Ok(()),
-            Ok(_) => Err("Expected failure".to_string()),
-        }
+
+        // the folders `a/a1` and `a/a2` are not walked, thus only 15 entries are visited
+        let paths: Vec<_> = Builder::new("*/a0/*.txt")
+            .max_entries(15)
+            .build(&root)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 3);
+
+        let mut paths: Vec<_> = Builder::new("*/a0/*.txt")
+            .build(&root)?
+            .into_par_iter()
+            .flatten()
+            .collect();
+        paths.sort();
+        log_paths_and_assert(&paths, 3);
+
+        // folders that match are yielded even if their contents are skipped
+        let paths: Vec<_> = Builder::new("a/a?")
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 3);
+        Ok(())
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn match_absolute_pattern() -> Result<(), String> {
+    fn match_exclude() -> Result<(), String> {
         let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
-        match Builder::new("C:/test-files/c-simple/**/*.txt").build(root) {
-            Err(_) => Ok(()),
-            Ok(_) => Err("Expected failure".to_string()),
-        }
-    }
 
-    /*
-    some helper functions for testing
-    */
+        // the excluded folder `a` is not walked, thus only 8 entries are visited
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .exclude("a")
+            .exclude("**/h_0.*")
+            .max_entries(8)
+            .build(&root)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 1 + 1 + 1);
 
-    fn log_paths<P>(paths: &[P])
-    where
-        P: AsRef<path::Path>,
-    {
-        println!(
-            "paths:\n{}",
-            paths
-                .iter()
-                .map(|p| format!("{}", p.as_ref().to_string_lossy()))
-                .collect::<Vec<_>>()
-                .join("\n")
-        );
-    }
+        let mut paths: Vec<_> = Builder::new("**/*.TXT")
+            .case_sensitive(false)
+            .exclude(".hidden")
+            .exclude("a/A0")
+            .build(&root)?
+            .into_par_iter()
+            .flatten()
+            .collect();
+        paths.sort();
+        log_paths_and_assert(&paths, 2 + 1 + 1);
 
-    fn log_paths_and_assert<P>(paths: &[P], expected_len: usize)
-    where
-        P: AsRef<path::Path>,
-    {
-        log_paths(paths);
-        assert_eq!(expected_len, paths.len());
+        let exclude = format!("{root}/a/**/*1*");
+        let paths: Vec<_> = Builder::new("a/**/*.txt")
+            .exclude(&exclude)
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 2 + 1); // also excludes the folder `a/a1`
+
+        assert!(Builder::new("**").exclude("").build(&root).is_err());
+        assert!(Builder::new("**").exclude("[a").build(&root).is_err());
+        Ok(())
     }
 
     #[test]
-    fn match_all() -> Result<(), String> {
-        // the following resolves to `<package-root>/test-files/c-simple/**/*.txt` and therefore
-        // successfully matches all files
-        let builder =
-            Builder::new("test-files/c-simple/**/*.txt").build(env!("CARGO_MANIFEST_DIR"))?;
+    fn match_all_paths() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let matcher = Builder::new("a/**/*.txt")
+            .exclude("a/a1/**")
+            .build(root.as_str())?;
+        let (matched, unmatched) = matcher.match_all([
+            format!("{root}/a/a0/a0_0.txt"),
+            "a/a2/a2_0.txt".to_string(),
+            format!("{root}/a/a1/a1_0.txt"),
+            format!("{root}/b/b_0.txt"),
+            "a/a0/a0_2.md".to_string(),
+            "a/a1/a1_0.txt".to_string(),
+            "a2/a2_0.txt".to_string(),
+        ]);
+        assert_eq!(
+            vec![
+                path::PathBuf::from(format!("{root}/a/a0/a0_0.txt")),
+                path::PathBuf::from("a/a2/a2_0.txt"),
+            ],
+            matched
+        );
+        assert_eq!(5, unmatched.len());
 
-        let paths: Vec<_> = builder.into_iter().flatten().collect();
-        log_paths_and_assert(&paths, 6 + 2 + 1); // this also matches `some_file.txt`
+        // the walk yields the same paths
+        let paths: Vec<_> = Builder::new("a/**/*.txt")
+            .exclude("a/a1/**")
+            .build(root.as_str())?
+            .into_iter()
+            .flatten()
+            .collect();
+        let (matched, unmatched) = matcher.match_all(&paths);
+        assert_eq!(paths, matched);
+        assert!(unmatched.is_empty());
+
+        let set = Builder::new("a?_*.txt").build_glob_set()?;
+        let (matched, unmatched) = set.match_all(["a/a0/a0_1.txt", "b/b_0.txt", "a1_0.txt"]);
+        assert_eq!(2, matched.len());
+        assert_eq!(vec![path::PathBuf::from("b/b_0.txt")], unmatched);
         Ok(())
     }
 
     #[test]
-    fn match_case() -> Result<(), String> {
+    fn match_to_regex() -> Result<(), String> {
         let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "test-files/c-simple/a/a?/a*.txt";
+        let paths = [
+            "test-files/c-simple/a/a0/a0_1.txt",
+            "test-files/c-simple/a/a0/a0_2.md",
+            "test-files/c-simple/a/a1/a1_0.txt",
+            "test-files/c-simple/b/b_0.txt",
+        ];
+        for (glob, n) in [
+            ("test-files/c-simple/**/a?_[01].txt", 2),
+            ("**/{a,b}*/*_{0..1}.*", 3),
+        ] {
+            let matcher = Builder::new(glob)
+                .globstar_max_depth(3)
+                .build(path::PathBuf::from(root))?;
+            let re = regex::bytes::Regex::new(&matcher.to_regex().unwrap()).unwrap();
+            let rest = format!("{}/", matcher.root_path().to_string_lossy());
+            let mut count = 0;
+            for p in paths {
+                let relative = format!("{root}/{p}").replacen(&rest, "", 1);
+                let is_match = re.is_match(relative.as_bytes());
+                assert_eq!(matcher.is_match(path::PathBuf::from(&relative)), is_match);
+                count += usize::from(is_match);
+            }
+            assert_eq!(n, count);
+        }
 
-        // default is case_sensitive(true)
-        let builder = Builder::new(pattern).build(root)?;
-        println!(
-            "working on root {} with glob {:?}",
-            builder.root(),
-            builder.rest()
-        );
+        let glob = Builder::new("a/*.txt").build_glob()?;
+        assert_eq!(glob.matcher.glob().regex(), glob.to_regex());
 
-        let paths: Vec<_> = builder.into_iter().flatten().collect();
-        log_paths_and_assert(&paths, 4);
+        let fuzzy = Builder::new("*.txt")
+            .fuzzy(Fuzzy::Subsequence)
+            .build(root)?;
+        assert_eq!(None, fuzzy.to_regex());
+        let combined = Builder::new("*.txt")
+            .build(root)?
+            .union(Builder::new("*.md").build(root)?)?;
+        assert_eq!(None, combined.to_regex());
         Ok(())
     }
 
     #[test]
-    fn match_filter_entry() -> Result<(), String> {
-        let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "test-files/c-simple/**/*.txt";
+    fn match_combinators() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let build = |glob: &'static str| Builder::new(glob).build(&root);
 
-        let builder = Builder::new(pattern).build(root)?;
-        let paths: Vec<_> = builder
+        // only the folders `a/a0` and `b` are walked, i.e., 13 of 17 entries are visited
+        let union = Builder::new("a/a0/*.txt")
+            .max_entries(13)
+            .build(&root)?
+            .union(build("b/*.txt")?)?;
+        assert_eq!(path::Path::new(&root), union.root_path());
+        assert_eq!("(a/a0/*.txt) | (b/*.txt)", union.glob());
+        let paths: Vec<_> = union.into_iter().collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 3 + 1);
+
+        let paths: Vec<_> = build("**/*.txt")?
+            .intersection(build("a/**")?)?
             .into_iter()
-            .filter_entry(|p| !is_hidden_entry(p))
-            .flatten()
-            .collect();
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 4 + 1);
+        assert!(paths.iter().all(|p| p.starts_with(format!("{root}/a"))));
 
-        log_paths_and_assert(&paths, 6 + 1);
+        let paths: Vec<_> = build("**/*.txt")?
+            .difference(build("a/a0/**")?)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 2 + 2 + 1 + 1);
+
+        // the exclusions of each matcher are applied to its own matches
+        let paths: Vec<_> = Builder::new("a/**/*.txt")
+            .exclude("a/a1")
+            .build(&root)?
+            .union(build("**/a1_*")?)?
+            .into_par_iter()
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 4 + 1);
+
+        let file = Builder::new("*.txt").build(format!("{root}/some_file.txt"))?;
+        let paths: Vec<_> = file
+            .union(build("b/*.txt")?)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 1 + 1);
+
+        let relative = Builder::new("*.txt").build("test-files")?;
+        assert!(matches!(
+            build("*.txt")?.union(relative),
+            Err(Error::Resolve { .. })
+        ));
+
+        // relative sibling roots are combined within the current directory
+        let union = Builder::new("a0/*.txt")
+            .build("test-files/c-simple/a")?
+            .union(Builder::new("lib.rs").build("src")?)?;
+        assert_eq!(path::Path::new("."), union.root_path());
+        let paths: Vec<_> = union.into_iter().collect::<Result<_, _>>()?;
+        log_paths_and_assert(&paths, 3 + 1);
+        assert!(paths.contains(&path::PathBuf::from("./src/lib.rs")));
         Ok(())
     }
 
     #[test]
-    fn match_filter() -> Result<(), String> {
-        let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "test-files/c-simple/**/*.txt";
+    fn match_observer() -> Result<(), String> {
+        use std::sync::{Arc, Mutex};
 
-        // this is slower than filter_entry since it matches all hidden paths
-        let builder = Builder::new(pattern).build(root)?;
-        let paths: Vec<_> = builder
+        #[derive(Default)]
+        struct Recorded {
+            skipped: Vec<(path::PathBuf, SkipReason)>,
+            errors: usize,
+            matches: usize,
+        }
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Recorded>);
+
+        impl WalkObserver for Recorder {
+            fn on_skip(&self, path: &path::Path, reason: SkipReason) {
+                let skipped = &mut self.0.lock().unwrap().skipped;
+                skipped.push((path.to_path_buf(), reason));
+            }
+            fn on_error(&self, _error: &Error) {
+                self.0.lock().unwrap().errors += 1;
+            }
+            fn on_match(&self, _path: &path::Path) {
+                self.0.lock().unwrap().matches += 1;
+            }
+        }
+
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let matcher = || {
+            Builder::new("**/*.txt")
+                .exclude("a/a0")
+                .exclude("**/b_*")
+                .build(&root)
+        };
+
+        let recorder = Arc::new(Recorder::default());
+        let paths: Vec<_> = matcher()?
+            .observe(Arc::clone(&recorder))
             .into_iter()
+            .filter_entry(|p| !is_hidden_path(p))
             .flatten()
-            .filter(|p| !is_hidden_path(p))
             .collect();
+        log_paths_and_assert(&paths, 1 + 1 + 1);
 
-        log_paths_and_assert(&paths, 6 + 1);
+        let mut skipped = recorder.0.lock().unwrap().skipped.clone();
+        skipped.sort_by(|a, b| a.0.cmp(&b.0));
+        let expected = [
+            (".hidden", SkipReason::Filtered),
+            ("a/a0", SkipReason::Excluded),
+            ("b/b_0.txt", SkipReason::Excluded),
+        ];
+        assert_eq!(expected.len(), skipped.len());
+        for ((p, reason), (expected, expected_reason)) in skipped.iter().zip(expected) {
+            assert_eq!(path::Path::new(&root).join(expected), *p);
+            assert_eq!(expected_reason, *reason);
+        }
+        assert_eq!((0, 3), {
+            let recorded = recorder.0.lock().unwrap();
+            (recorded.errors, recorded.matches)
+        });
+
+        let recorder = Arc::new(Recorder::default());
+        let paths: Vec<_> = matcher()?
+            .observe(Arc::clone(&recorder))
+            .into_par_iter()
+            .filter_entry(|p| !is_hidden_path(p))
+            .flatten()
+            .collect();
+        assert_eq!(3, paths.len());
+        let recorded = recorder.0.lock().unwrap();
+        assert_eq!(
+            (3, 0, 3),
+            (recorded.skipped.len(), recorded.errors, recorded.matches)
+        );
+        drop(recorded);
+
+        let recorder = Arc::new(Recorder::default());
+        let _ = Builder::new("**")
+            .max_entries(2)
+            .build(&root)?
+            .observe(Arc::clone(&recorder))
+            .into_iter()
+            .count();
+        assert_eq!(1, recorder.0.lock().unwrap().errors);
         Ok(())
     }
 
     #[test]
-    fn match_with_glob() -> Result<(), String> {
-        let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "test-files/c-simple/**/*.txt";
+    #[cfg(feature = "tracing")]
+    fn match_tracing() -> Result<(), String> {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
 
-        let glob = Builder::new("**/test-files/c-simple/a/a[0]/**").build_glob()?;
-        let paths: Vec<_> = Builder::new(pattern)
-            .build(root)?
-            .into_iter()
-            .flatten()
-            .filter(|p| !is_hidden_path(p))
-            .filter(|p| glob.is_match(p))
-            .collect();
+        /// Subscriber that records the messages of all events.
+        #[derive(Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
 
-        log_paths_and_assert(&paths, 3);
+        struct Message(Option<String>);
+
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{value:?}"));
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+                tracing::span::Id::from_u64(1)
+            }
+            fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+            fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {
+            }
+            fn event(&self, event: &tracing::Event<'_>) {
+                let mut message = Message(None);
+                event.record(&mut message);
+                self.0.lock().unwrap().extend(message.0);
+            }
+            fn enter(&self, _span: &tracing::span::Id) {}
+            fn exit(&self, _span: &tracing::span::Id) {}
+        }
+
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let recorder = Recorder::default();
+        let messages = Arc::clone(&recorder.0);
+        let paths = tracing::subscriber::with_default(recorder, || {
+            Builder::new("a/**/*.txt")
+                .exclude("a/a0")
+                .build(&root)?
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| err.to_string())
+        })?;
+        log_paths_and_assert(&paths, 2);
+
+        let messages = messages.lock().unwrap();
+        for expected in ["resolved root", "entering directory", "skipped entry"] {
+            assert!(
+                messages.iter().any(|m| m == expected),
+                "missing event '{expected}' in {messages:?}"
+            );
+        }
         Ok(())
     }
 
     #[test]
-    fn match_with_glob_all() -> Result<(), String> {
+    fn match_owned() -> Result<(), String> {
         let root = env!("CARGO_MANIFEST_DIR");
-        let pattern = "test-files/c-simple/**/*.*";
 
-        // build_glob creates a ["**/pattern", "pattern"] glob such that the user two separate
-        // patterns when scanning for files, e.g., using "*.txt" (which would need "**/*.txt"
-        // as well), but also when specifying paths within this glob.
-        let glob = Builder::new("*.txt").build_glob_set()?;
-        let paths: Vec<_> = Builder::new(pattern)
-            .build(root)?
-            .into_iter()
-            .filter_entry(|e| !is_hidden_entry(e))
-            .flatten()
-            .filter(|p| {
-                let is_match = glob.is_match(p);
-                println!("is match: {p:?} - {is_match}");
-                is_match
-            })
-            .collect();
+        let matcher = Builder::new_owned("test-files/c-simple/a/../**/*.txt".to_string())
+            .resolve_parent_dirs(true)
+            .build(root)?;
+        assert_eq!("test-files/c-simple/a/../**/*.txt", matcher.glob());
+        assert_eq!("**/*.txt", matcher.rest());
 
-        log_paths_and_assert(&paths, 6 + 1);
+        let glob = String::from("test-files/c-simple/b/*.txt");
+        let matcher = Builder::new(&glob).build(root)?.into_owned();
+        drop(glob);
+        assert_eq!("*.txt", matcher.rest());
+
+        let matchers: Vec<Matcher<'static, path::PathBuf>> = vec![matcher];
+        let paths: Vec<_> = std::thread::spawn(move || {
+            matchers
+                .into_iter()
+                .flat_map(|m| m.into_iter().flatten())
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+        log_paths_and_assert(&paths, 1);
         Ok(())
     }
 
+    #[test]
+    fn match_errors() {
+        use std::error::Error as _;
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let err = Builder::new("a[").build(root).unwrap_err();
+        assert!(matches!(err, Error::InvalidPattern { .. }));
+        assert!(err.source().is_some());
+        assert_eq!(
+            "'a[': Unclosed character class; missing ']'",
+            err.to_string()
+        );
+
+        let err = Builder::new("*.txt").build("does-not-exist").unwrap_err();
+        let expected = std::fs::metadata("does-not-exist").unwrap_err();
+        let message = utils::to_upper(expected.to_string());
+        assert_eq!(
+            Error::RootNotFound {
+                root: path::PathBuf::from("does-not-exist"),
+                message: message.clone(),
+                source: expected.into(),
+            },
+            err
+        );
+        assert_eq!(
+            format!("'Failed to resolve paths': {message}"),
+            err.to_string()
+        );
+        let source = err
+            .source()
+            .and_then(|err| err.downcast_ref::<std::io::Error>());
+        assert_eq!(
+            Some(std::io::ErrorKind::NotFound),
+            source.map(|err| err.kind())
+        );
+
+        let err = Builder::new("/*.txt").build(root).unwrap_err();
+        assert_eq!(
+            Error::AbsolutePattern {
+                glob: "/*.txt".to_string(),
+                resolve: true,
+            },
+            err
+        );
+        assert!(err.source().is_none());
+        assert_eq!(
+            "'Failed to resolve paths': '/*.txt' is an absolute path",
+            err.to_string()
+        );
+
+        let err = Builder::new("/*.txt").build_glob_set().unwrap_err();
+        assert_eq!("/*.txt' is an absolute path", err.to_string());
+
+        let err = Builder::new("").build_glob().unwrap_err();
+        assert_eq!(Error::EmptyPattern, err);
+        assert_eq!("Empty glob", String::from(err));
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let err = Error::from_io(path::Path::new("a/b"), 1, denied);
+        assert!(matches!(
+            &err,
+            Error::Walk {
+                source: Some(_),
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("Failed to walk path a/b: Missing permissions to read entry"));
+        let source = err
+            .source()
+            .and_then(|err| err.downcast_ref::<std::io::Error>());
+        assert_eq!(
+            Some(std::io::ErrorKind::PermissionDenied),
+            source.map(|err| err.kind())
+        );
+        assert_eq!(err.clone(), err);
+    }
+
     #[test]
     fn match_flavours() -> Result<(), String> {
         // TODO: implememnt tests for different relative pattern styles
@@ -722,4 +5392,60 @@ mod tests {
         log_paths_and_assert(&paths, 6 + 1);
         Ok(())
     }
+
+    #[test]
+    fn filter_entry_full() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry_full(|e| {
+                e.file_type().is_file() || e.depth() < 2 || e.path().ends_with("a0")
+            })
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 2 + 3 + 1 + 1); // skips `a/a1` and `a/a2`
+
+        // skips all (empty) files, folders are still walked
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry_full(|e| e.file_type().is_dir() || e.metadata().unwrap().len() > 0)
+            .flatten()
+            .collect();
+        let non_empty = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .filter(|p| std::fs::metadata(p).unwrap().len() > 0)
+            .count();
+        log_paths_and_assert(&paths, non_empty);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_entry_chained() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry(|p| !is_hidden_path(p))
+            .filter_entry_full(|e| e.depth() < 2 || !e.file_type().is_dir())
+            .filter_entry(|p| !p.ends_with("b"))
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1);
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry_full(|e| !e.path().ends_with("a"))
+            .filter_entry(|p| !is_hidden_path(p))
+            .flatten()
+            .collect();
+        log_paths_and_assert(&paths, 1 + 1);
+        Ok(())
+    }
 }
@@ -0,0 +1,381 @@
+use std::fmt;
+use std::ops;
+
+use crate::utils;
+
+/// Kind of a [`LintWarning`], see [`Builder::lint`](crate::Builder::lint).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum LintKind {
+    /// A single asterisk `*` as directory component, e.g., `*/*.txt`, only matches paths at
+    /// exactly one level of directories, whereas `**/*.txt` matches paths at any depth.
+    SingleLevelWildcard,
+    /// A globstar `**` that is not a complete path component, e.g., `**.txt`, matches like a
+    /// single asterisk `*` and thus never crosses directory boundaries.
+    PartialGlobstar,
+    /// A `.` component, e.g., `./src/*.rs`, is redundant and prevents any match if it is not
+    /// resolved as part of the root.
+    CurrentDir,
+    /// Consecutive separators, e.g., `src//*.rs`, create an empty path component that never
+    /// matches.
+    EmptyComponent,
+    /// A trailing separator, e.g., `src/*/`, never matches since the walked paths do not end
+    /// with a separator.
+    TrailingSeparator,
+    /// The glob starts with a Windows drive letter, e.g., `C:/src/*.rs`, whereas globs are
+    /// relative to the root that is passed to the `build` functions.
+    DriveLetter,
+    /// A backslash escapes an ordinary character, e.g., `src\main.rs`, which is likely intended
+    /// as path separator, see
+    /// [`Builder::backslash_separators`](crate::Builder::backslash_separators).
+    BackslashSeparator,
+    /// A character class that only contains path separators and starts or ends a path
+    /// component, e.g., `src/[/]*.rs`, can never match.
+    UnmatchableClass,
+    /// A regular expression segment that contains a path separator, e.g., `<re:a/b>/x`, is no
+    /// regular expression segment, since segments never span multiple path components.
+    SeparatorInRegex,
+}
+
+/// Warning for a likely mistake within a glob, see [`Builder::lint`](crate::Builder::lint).
+///
+/// The [`fmt::Display`] implementation provides a human readable message, e.g., to report the
+/// warning to the user that provided the glob.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LintWarning {
+    /// The kind of the mistake.
+    pub kind: LintKind,
+    /// The part of the glob that triggered the warning.
+    pub fragment: String,
+    /// Byte offset of the `fragment` within the glob.
+    pub offset: usize,
+    /// The corrected glob, `None` if the intention of the glob is not obvious.
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' ", self.fragment)?;
+        match self.kind {
+            LintKind::SingleLevelWildcard => write!(f, "only matches a single directory level"),
+            LintKind::PartialGlobstar => {
+                write!(f, "is not a separate path component and matches like '*'")
+            }
+            LintKind::CurrentDir => write!(f, "is redundant and may prevent any match"),
+            LintKind::EmptyComponent => write!(f, "contains an empty path component"),
+            LintKind::TrailingSeparator => write!(f, "at the end of the glob never matches"),
+            LintKind::DriveLetter => {
+                write!(f, "is a drive letter, globs must be relative to the root")
+            }
+            LintKind::BackslashSeparator => {
+                write!(
+                    f,
+                    "escapes a character instead of separating path components"
+                )
+            }
+            LintKind::UnmatchableClass => write!(f, "can never match a path component"),
+            LintKind::SeparatorInRegex => {
+                write!(f, "spans multiple path components and is matched literally")
+            }
+        }?;
+        match &self.suggestion {
+            Some(suggestion) => write!(f, ", did you mean '{suggestion}'?"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl LintWarning {
+    fn new(kind: LintKind, glob: &str, offset: usize, len: usize) -> LintWarning {
+        LintWarning {
+            kind,
+            fragment: String::from(&glob[offset..offset + len]),
+            offset,
+            suggestion: None,
+        }
+    }
+
+    /// Suggests the glob with the fragment replaced by `replacement`.
+    fn replace(mut self, glob: &str, replacement: &str) -> LintWarning {
+        let end = self.offset + self.fragment.len();
+        self.suggestion = Some(format!(
+            "{}{replacement}{}",
+            &glob[..self.offset],
+            &glob[end..]
+        ));
+        self
+    }
+}
+
+/// Checks the glob for likely mistakes, see [`LintKind`].
+///
+/// Backslashes are only checked if they are escapes, i.e., `backslash_escape` is set and
+/// backslashes have not been replaced by path separators. Regular expression segments `<re:...>`
+/// are not checked, except for path separators. Provides the warnings ordered by their offset
+/// within the glob.
+pub(crate) fn lint(glob: &str, backslash_escape: bool) -> Vec<LintWarning> {
+    let mut warnings = vec![];
+
+    // the regular expression segments are masked such that the offsets remain valid
+    let segments = regex_segments(glob);
+    let mut masked = glob.as_bytes().to_vec();
+    for segment in &segments {
+        if glob[segment.clone()].contains('/') {
+            warnings.push(LintWarning::new(
+                LintKind::SeparatorInRegex,
+                glob,
+                segment.start,
+                segment.len(),
+            ));
+        }
+        masked[segment.clone()].fill(b'x');
+    }
+    let masked = String::from_utf8(masked).expect("masking preserves UTF-8");
+
+    let bytes = masked.as_bytes();
+    if bytes.len() >= 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes.get(2), None | Some(b'/' | b'\\'))
+    {
+        warnings.push(LintWarning::new(LintKind::DriveLetter, glob, 0, 2));
+    }
+
+    if backslash_escape {
+        let mut i = 0;
+        while i + 1 < bytes.len() {
+            if bytes[i] == b'\\' {
+                if bytes[i + 1].is_ascii_alphanumeric() {
+                    warnings.push(
+                        LintWarning::new(LintKind::BackslashSeparator, glob, i, 2)
+                            .replace(glob, &format!("/{}", char::from(bytes[i + 1]))),
+                    );
+                }
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+
+    lint_components(glob, &masked, backslash_escape, &mut warnings);
+    lint_classes(glob, &masked, &mut warnings);
+
+    warnings.sort_by_key(|warning| warning.offset);
+    warnings
+}
+
+/// Finds the regular expression segments `<re:...>` of the glob.
+///
+/// Other than for matching, a segment ends at the first `>` that is followed by a path separator
+/// or the end of the glob, such that segments containing separators are found as well.
+fn regex_segments(glob: &str) -> Vec<ops::Range<usize>> {
+    let mut segments = vec![];
+    let mut start = 0;
+    while let Some(offset) = glob[start..].find("<re:") {
+        let open = start + offset;
+        start = open + 1;
+        if open > 0 && glob.as_bytes()[open - 1] != b'/' {
+            continue;
+        }
+        let close = glob[open..]
+            .match_indices('>')
+            .map(|(i, _)| open + i + 1)
+            .find(|&end| matches!(glob.as_bytes().get(end), None | Some(b'/')));
+        if let Some(end) = close {
+            segments.push(open..end);
+            start = end;
+        }
+    }
+    segments
+}
+
+/// Checks the path components of the glob, using the `masked` glob for the analysis.
+///
+/// Globs with alternates or character classes that contain a path separator cannot be split
+/// into path components and are not checked.
+fn lint_components(glob: &str, masked: &str, escape: bool, warnings: &mut Vec<LintWarning>) {
+    if utils::has_nested_separator(masked, escape) {
+        return;
+    }
+    let components: Vec<_> = masked.split('/').collect();
+    let last = components.len() - 1;
+    let mut offset = 0;
+    for (i, component) in components.iter().enumerate() {
+        let next = components.get(i + 1).copied();
+        match *component {
+            // a leading separator is an absolute path, which is rejected by `build`
+            "" if i == 0 => {}
+            "" if i == last => warnings.push(
+                LintWarning::new(LintKind::TrailingSeparator, glob, offset - 1, 1)
+                    .replace(glob, ""),
+            ),
+            "" => warnings.push(
+                LintWarning::new(LintKind::EmptyComponent, glob, offset - 1, 2).replace(glob, "/"),
+            ),
+            "." if last > 0 => {
+                let warning = match i == last {
+                    true => LintWarning::new(LintKind::CurrentDir, glob, offset - 1, 2),
+                    false => LintWarning::new(LintKind::CurrentDir, glob, offset, 2),
+                };
+                warnings.push(warning.replace(glob, ""));
+            }
+            "*" if i < last && next != Some("**") && next != Some("") => {
+                let previous = i.checked_sub(1).map(|i| components[i]);
+                if previous != Some("**") {
+                    warnings.push(
+                        LintWarning::new(LintKind::SingleLevelWildcard, glob, offset, 1)
+                            .replace(glob, "**"),
+                    );
+                }
+            }
+            _ => {
+                let partial = component
+                    .split(['{', ',', '}'])
+                    .any(|segment| segment != "**" && segment.contains("**"));
+                if partial {
+                    let warning =
+                        LintWarning::new(LintKind::PartialGlobstar, glob, offset, component.len());
+                    let replacement =
+                        match (component.strip_prefix("**"), component.strip_suffix("**")) {
+                            _ if component.contains(['{', ',', '}']) => None,
+                            (Some(rest), _) if !rest.contains('*') => Some(format!("**/*{rest}")),
+                            (_, Some(rest)) if !rest.contains('*') => Some(format!("{rest}*/**")),
+                            _ => None,
+                        };
+                    warnings.push(match replacement {
+                        Some(replacement) => warning.replace(glob, &replacement),
+                        None => warning,
+                    });
+                }
+            }
+        }
+        offset += component.len() + 1;
+    }
+}
+
+/// Checks the character classes of the glob, using the `masked` glob for the analysis.
+fn lint_classes(glob: &str, masked: &str, warnings: &mut Vec<LintWarning>) {
+    let bytes = masked.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => {
+                // a closing bracket directly after the opening (or negation) is a member
+                let start = match bytes.get(i + 1) {
+                    Some(b'!' | b'^') => i + 2,
+                    _ => i + 1,
+                };
+                let close = match masked.get(start + 1..).and_then(|rest| rest.find(']')) {
+                    Some(close) => start + 1 + close,
+                    None => return,
+                };
+                let members = &masked[start..close];
+                let at_boundary = i == 0
+                    || bytes[i - 1] == b'/'
+                    || matches!(bytes.get(close + 1), None | Some(b'/'));
+                if start == i + 1 && members.bytes().all(|b| b == b'/') && at_boundary {
+                    warnings.push(LintWarning::new(
+                        LintKind::UnmatchableClass,
+                        glob,
+                        i,
+                        close + 1 - i,
+                    ));
+                }
+                i = close;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, LintKind};
+
+    fn kinds(glob: &str) -> Vec<LintKind> {
+        lint(glob, true).into_iter().map(|w| w.kind).collect()
+    }
+
+    #[test]
+    fn lint_globs() {
+        for glob in [
+            "src/**/*.rs",
+            "*.txt",
+            "a/*/**/b",
+            "**/*/",
+            "[!/]/x",
+            "a\\*b",
+        ] {
+            let expected: &[LintKind] = match glob {
+                "**/*/" => &[LintKind::TrailingSeparator],
+                _ => &[],
+            };
+            assert_eq!(expected, kinds(glob), "{glob}");
+        }
+
+        assert_eq!(vec![LintKind::SingleLevelWildcard], kinds("*/*.txt"));
+        assert_eq!(vec![LintKind::PartialGlobstar], kinds("src/**.rs"));
+        assert_eq!(vec![LintKind::PartialGlobstar], kinds("src/{a**,b}/x"));
+        assert_eq!(
+            vec![LintKind::CurrentDir, LintKind::CurrentDir],
+            kinds("./src/./*.rs")
+        );
+        assert_eq!(
+            vec![LintKind::EmptyComponent, LintKind::TrailingSeparator],
+            kinds("src//*/")
+        );
+        assert_eq!(vec![LintKind::DriveLetter], kinds("C:/src/*.rs"));
+        assert_eq!(
+            vec![
+                LintKind::DriveLetter,
+                LintKind::BackslashSeparator,
+                LintKind::BackslashSeparator
+            ],
+            kinds("c:\\src\\main.rs")
+        );
+        assert!(lint("c:\\src\\main.rs", false).len() == 1);
+        assert_eq!(
+            vec![LintKind::UnmatchableClass, LintKind::UnmatchableClass],
+            kinds("[/]a/b[//]")
+        );
+        assert!(kinds("a[/]b").is_empty());
+
+        // regular expression segments are not checked as globs
+        for glob in [
+            r"src/<re:\d+\.\d+>/*.rs",
+            "<re:.**>/**/*.rs",
+            "<re:a[b>/./x",
+        ] {
+            let expected: &[LintKind] = match glob {
+                "<re:a[b>/./x" => &[LintKind::CurrentDir],
+                _ => &[],
+            };
+            assert_eq!(expected, kinds(glob), "{glob}");
+        }
+        assert_eq!(vec![LintKind::SeparatorInRegex], kinds("<re:a//b>/x"));
+        assert_eq!(vec![LintKind::SeparatorInRegex], kinds("a/<re:[/]>"));
+        assert_eq!(vec![LintKind::EmptyComponent], kinds("a<re:b/c>//x"));
+
+        let suggestions: Vec<_> = [
+            "*/*.txt",
+            "src/**.rs",
+            "lib**/x",
+            "./a",
+            "a/.",
+            "a//b",
+            "a/",
+        ]
+        .into_iter()
+        .flat_map(|glob| lint(glob, true))
+        .map(|warning| warning.suggestion.unwrap())
+        .collect();
+        assert_eq!(
+            vec!["**/*.txt", "src/**/*.rs", "lib*/**/x", "a", "a", "a/b", "a"],
+            suggestions
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::path;
+
+use crate::{utils, Builder, Error};
+
+/// Incremental matcher for interactive use, e.g., for file pickers.
+///
+/// In contrast to the [`Matcher`](crate::Matcher), which walks the file system whenever it is
+/// transformed into an iterator, the [`LiveMatcher`] caches all paths within the resolved root
+/// of the last query. As long as the literal prefix of the glob resolves to the same root,
+/// e.g., while the user edits `src/**/*.rs` character by character, the cached paths are
+/// matched against the new glob without walking the file system again.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let mut live = globmatch::LiveMatcher::new(env!("CARGO_MANIFEST_DIR"));
+///
+/// let paths = live.query("test-files/c-simple/**/*.t")?;
+/// assert_eq!(0, paths.len());
+///
+/// // the root remains the same, the cached paths are used
+/// let paths = live.query("test-files/c-simple/**/*.txt")?;
+/// assert_eq!(6 + 2 + 1, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LiveMatcher {
+    root: path::PathBuf,
+    case_sensitive: bool,
+    /// Resolved root of the cached walk.
+    walk_root: Option<path::PathBuf>,
+    /// Cached paths, relative to the `walk_root`.
+    entries: Vec<path::PathBuf>,
+    /// Errors of the cached walk.
+    errors: Vec<Error>,
+    /// Number of walks, only recorded for testing.
+    #[cfg(test)]
+    walks: usize,
+}
+
+impl LiveMatcher {
+    /// Creates a new [`LiveMatcher`] for globs relative to the given `root`.
+    ///
+    /// The file system is not walked until the first call to [`LiveMatcher::query`].
+    pub fn new<P>(root: P) -> LiveMatcher
+    where
+        P: AsRef<path::Path>,
+    {
+        LiveMatcher {
+            root: path::PathBuf::from(root.as_ref()),
+            case_sensitive: true,
+            walk_root: None,
+            entries: vec![],
+            errors: vec![],
+            #[cfg(test)]
+            walks: 0,
+        }
+    }
+
+    /// Toggle whether the globs match case sensitive or not.
+    ///
+    /// The default setting is to match case **sensitive**.
+    pub fn case_sensitive(&mut self, yes: bool) -> &mut LiveMatcher {
+        self.case_sensitive = yes;
+        self
+    }
+
+    /// Drops all cached paths such that the next query walks the file system again.
+    pub fn refresh(&mut self) -> &mut LiveMatcher {
+        self.walk_root = None;
+        self.entries.clear();
+        self.errors.clear();
+        self
+    }
+
+    /// Provides the errors that occurred while walking the cached paths, e.g., for folders that
+    /// cannot be read.
+    ///
+    /// Such errors do not fail a query, the paths within unreadable folders are simply missing.
+    /// The errors are replaced whenever the file system is walked again.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Provides all paths matching the given `glob`.
+    ///
+    /// The relative path components of the glob are resolved as for [`Builder::build`]. The
+    /// file system is only walked if the resolved root differs from the root of the previous
+    /// query. Trailing path separators are ignored such that partially typed globs, e.g.,
+    /// `src/`, can be queried.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Builder::build`], e.g., for empty globs or globs that cannot be compiled.
+    /// Errors that occur while walking the file system are provided by [`LiveMatcher::errors`].
+    pub fn query(&mut self, glob: &str) -> Result<Vec<path::PathBuf>, Error> {
+        let glob = glob.trim_end_matches('/');
+        let (root, rest) = utils::resolve_root(&self.root, glob)
+            .map_err(|err| Error::from_resolve(&self.root, glob, err))?;
+
+        let matcher = Builder::new(rest)
+            .case_sensitive(self.case_sensitive)
+            .glob_set_for(&[rest])?;
+
+        if self.walk_root.as_ref() != Some(&root) {
+            self.entries.clear();
+            self.errors.clear();
+            for entry in walkdir::WalkDir::new(&root) {
+                match entry {
+                    Ok(entry) => {
+                        if let Ok(p) = entry.path().strip_prefix(&root) {
+                            self.entries.push(path::PathBuf::from(p));
+                        }
+                    }
+                    Err(err) => self.errors.push(Error::from(err)),
+                }
+            }
+            self.walk_root = Some(root);
+            #[cfg(test)]
+            {
+                self.walks += 1;
+            }
+        }
+
+        // notice that the walk root has been set above
+        let root = self.walk_root.as_ref().unwrap();
+        Ok(self
+            .entries
+            .iter()
+            .filter(|p| matcher.is_match(p))
+            .map(|p| root.join(p))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_query() -> Result<(), String> {
+        let mut live = LiveMatcher::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(0, live.query("test-files/c-sim")?.len());
+        assert_eq!(1, live.walks);
+
+        // resolves to `test-files` as root and `c-simple` as remaining pattern
+        assert_eq!(1, live.query("test-files/c-simple")?.len());
+        assert_eq!(1, live.walks);
+
+        assert_eq!(1, live.query("test-files/c-simple/")?.len());
+        assert_eq!(1, live.walks);
+
+        // the root changes to `test-files/c-simple`
+        assert_eq!(0, live.query("test-files/c-simple/*.m")?.len());
+        assert_eq!(2, live.walks);
+
+        assert_eq!(1, live.query("test-files/c-simple/*.txt")?.len());
+        assert_eq!(6 + 2 + 1, live.query("test-files/c-simple/**/*.txt")?.len());
+        assert_eq!(5, live.query("test-files/c-simple/**/[aA]*.txt")?.len());
+        assert_eq!(2, live.walks);
+
+        live.case_sensitive(false);
+        assert!(live.query("test-files/c-simple/**/A*.txt[").is_err());
+        assert_eq!(5, live.query("test-files/c-simple/**/A*.txt")?.len());
+        assert_eq!(2, live.walks);
+
+        live.refresh();
+        assert_eq!(5, live.query("test-files/c-simple/**/A*.txt")?.len());
+        assert_eq!(3, live.walks);
+        assert!(live.errors().is_empty());
+
+        let err = LiveMatcher::new("does-not-exist")
+            .query("*.txt")
+            .unwrap_err();
+        assert!(matches!(err, Error::RootNotFound { .. }));
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! Manifests of the paths selected by globs, e.g., for lockfile-like build artifacts.
+//!
+//! A [`Manifest`] stores the paths relative to a root directory together with an optional
+//! digest for each path, e.g., as provided by `Matcher::hash_matches` (requires the `hash`
+//! feature). Build systems can embed manifests into their artifacts and compare them against the
+//! current state of the file system to detect drift.
+//!
+//! ```
+//! use globmatch::manifest::Manifest;
+//!
+//! # fn example() -> Result<(), String> {
+//! let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+//! let paths = globmatch::Builder::new("**/*.txt")
+//!     .build(&root)?
+//!     .collect_sorted()
+//!     .map_err(|err| err.to_string())?;
+//!
+//! let manifest = Manifest::from_paths(&root, &paths).map_err(|err| err.to_string())?;
+//! assert_eq!(".hidden/h_0.txt", manifest.entries()[0].path);
+//!
+//! let text = manifest.to_text();
+//! assert_eq!(manifest, Manifest::from_text(&text).map_err(|err| err.to_string())?);
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+//!
+//! # Format
+//!
+//! Manifests are stored as UTF-8 text and the format is guaranteed to remain stable for the
+//! version `v1` specified in the header:
+//!
+//! - The first line is the header `# globmatch manifest v1`.
+//! - Each following line contains the tab-separated fields `digest` and `path`, where the
+//!   digest is `-` if it has not been recorded.
+//! - Paths are relative to the root of the manifest and always use `/` as separator.
+//! - Backslashes, tabs and line breaks within a digest or path are escaped as `\\`, `\t`, `\n`
+//!   and `\r`, a digest that is `-` itself is stored as `\-`.
+//! - Lines are sorted by the (unescaped) path, comparing the UTF-8 bytes.
+//! - Each line, including the last one, is terminated by a single `\n`.
+
+use std::{collections, fs, io, path};
+
+use crate::snapshot::{escape, unescape, SnapshotDiff};
+
+const HEADER: &str = "# globmatch manifest v1";
+
+/// A single path within a [`Manifest`].
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct ManifestEntry {
+    /// Path relative to the root of the manifest, using `/` as separator.
+    pub path: String,
+    /// The digest of the file, if recorded.
+    pub digest: Option<String>,
+}
+
+/// Sorted list of paths relative to a root directory, refer to the [module documentation](self).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+/// Provides the path relative to `root` with `/` as separator.
+///
+/// Fails for paths that are not located within `root`, including the `root` itself, and for
+/// paths that are not valid UTF-8.
+fn relative_to(root: &path::Path, p: &path::Path) -> io::Result<String> {
+    let relative = match p.strip_prefix(root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Path {} is not within {}",
+                    p.to_string_lossy(),
+                    root.to_string_lossy()
+                ),
+            ))
+        }
+    };
+
+    let components = relative
+        .components()
+        .map(|c| {
+            c.as_os_str().to_str().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Path {} is not valid UTF-8", p.to_string_lossy()),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(components.join("/"))
+}
+
+impl Manifest {
+    /// Creates a manifest out of the given entries, sorting the entries by path.
+    ///
+    /// If multiple entries exist for the same path, only the last one is kept.
+    pub fn new(entries: Vec<ManifestEntry>) -> Manifest {
+        let entries: collections::BTreeMap<_, _> =
+            entries.into_iter().map(|e| (e.path.clone(), e)).collect();
+        Manifest {
+            entries: entries.into_values().collect(),
+        }
+    }
+
+    /// Creates a manifest for the given `paths` without any digests.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if any of the paths is not within the `root`
+    /// directory, and with [`io::ErrorKind::InvalidData`] if any path is not valid UTF-8.
+    pub fn from_paths<R, P>(root: R, paths: &[P]) -> io::Result<Manifest>
+    where
+        R: AsRef<path::Path>,
+        P: AsRef<path::Path>,
+    {
+        let entries = paths
+            .iter()
+            .map(|p| {
+                Ok(ManifestEntry {
+                    path: relative_to(root.as_ref(), p.as_ref())?,
+                    digest: None,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Manifest::new(entries))
+    }
+
+    /// Creates a manifest for the given map of paths and digests, e.g., as provided by
+    /// `Matcher::hash_matches`.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`Manifest::from_paths`].
+    pub fn from_digests<R>(
+        root: R,
+        digests: &collections::BTreeMap<path::PathBuf, String>,
+    ) -> io::Result<Manifest>
+    where
+        R: AsRef<path::Path>,
+    {
+        let entries = digests
+            .iter()
+            .map(|(p, digest)| {
+                Ok(ManifestEntry {
+                    path: relative_to(root.as_ref(), p)?,
+                    digest: Some(digest.clone()),
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Manifest::new(entries))
+    }
+
+    /// Provides all entries, sorted by path.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Reports all paths that have been added, removed or changed in the `current` manifest.
+    ///
+    /// An entry has changed if both manifests record a digest for the path, and the digests
+    /// differ. All paths within the [`SnapshotDiff`] are relative paths.
+    pub fn diff(&self, current: &Manifest) -> SnapshotDiff {
+        let old: collections::BTreeMap<_, _> =
+            self.entries.iter().map(|e| (&e.path, &e.digest)).collect();
+        let new: collections::BTreeMap<_, _> = current
+            .entries
+            .iter()
+            .map(|e| (&e.path, &e.digest))
+            .collect();
+
+        let mut diff = SnapshotDiff::default();
+        for (p, digest) in &new {
+            match old.get(p) {
+                None => diff.added.push(path::PathBuf::from(p)),
+                Some(Some(old)) if matches!(digest, Some(new) if new != old) => {
+                    diff.changed.push(path::PathBuf::from(p))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|p| !new.contains_key(*p))
+            .map(path::PathBuf::from)
+            .collect();
+        diff
+    }
+
+    /// Serializes the manifest in the format described in the [module documentation](self).
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        for entry in &self.entries {
+            let digest = match entry.digest.as_deref() {
+                None => "-".to_string(),
+                Some("-") => "\\-".to_string(),
+                Some(digest) => escape(digest),
+            };
+            text.push_str(&format!("{}\t{}\n", digest, escape(&entry.path)));
+        }
+        text
+    }
+
+    /// Parses a manifest in the format described in the [module documentation](self).
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the text is not a valid manifest.
+    pub fn from_text(text: &str) -> io::Result<Manifest> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid manifest in line {line}: {msg}"),
+            )
+        };
+
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(invalid(1, "Unsupported header")),
+        }
+
+        let mut entries = vec![];
+        for (i, line) in lines {
+            let Some((digest, p)) = line.split_once('\t') else {
+                return Err(invalid(i + 1, "Expected two fields"));
+            };
+            entries.push(ManifestEntry {
+                path: unescape(p).ok_or_else(|| invalid(i + 1, p))?,
+                digest: match digest {
+                    "-" => None,
+                    digest => Some(unescape(digest).ok_or_else(|| invalid(i + 1, digest))?),
+                },
+            });
+        }
+        Ok(Manifest::new(entries))
+    }
+}
+
+/// Writes the manifest to the given file.
+///
+/// # Errors
+///
+/// Fails if the file cannot be written.
+pub fn write_manifest<P>(path: P, manifest: &Manifest) -> io::Result<()>
+where
+    P: AsRef<path::Path>,
+{
+    fs::write(path, manifest.to_text())
+}
+
+/// Reads a manifest from the given file.
+///
+/// # Errors
+///
+/// Fails if the file cannot be read or if it does not contain a valid manifest.
+pub fn read_manifest<P>(path: P) -> io::Result<Manifest>
+where
+    P: AsRef<path::Path>,
+{
+    Manifest::from_text(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_text() -> io::Result<()> {
+        let root = path::Path::new("root");
+        let mut digests = collections::BTreeMap::new();
+        digests.insert(root.join("b").join("b.txt"), "02".to_string());
+        digests.insert(root.join("a\tb.txt"), "01".to_string());
+
+        let manifest = Manifest::from_digests(root, &digests)?;
+        assert_eq!(
+            "# globmatch manifest v1\n01\ta\\tb.txt\n02\tb/b.txt\n",
+            manifest.to_text()
+        );
+        assert_eq!(manifest, Manifest::from_text(&manifest.to_text())?);
+
+        let current = Manifest::new(vec![
+            ManifestEntry {
+                path: "b/b.txt".to_string(),
+                digest: Some("03".to_string()),
+            },
+            ManifestEntry {
+                path: "c.txt".to_string(),
+                digest: None,
+            },
+        ]);
+        let diff = manifest.diff(&current);
+        assert_eq!(vec![path::PathBuf::from("c.txt")], diff.added);
+        assert_eq!(vec![path::PathBuf::from("a\tb.txt")], diff.removed);
+        assert_eq!(vec![path::PathBuf::from("b/b.txt")], diff.changed);
+
+        assert!(Manifest::from_paths(root, &["other/a.txt"]).is_err());
+        assert!(Manifest::from_paths(root, &[root]).is_err());
+        assert!(Manifest::from_text("# globmatch manifest v2\n").is_err());
+        assert!(Manifest::from_text("# globmatch manifest v1\na.txt\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn manifest_escape_digest() -> io::Result<()> {
+        let manifest = Manifest::new(vec![
+            ManifestEntry {
+                path: "a.txt".to_string(),
+                digest: Some("-".to_string()),
+            },
+            ManifestEntry {
+                path: "a\tb".to_string(),
+                digest: Some("x\ty".to_string()),
+            },
+            ManifestEntry {
+                path: "b.txt".to_string(),
+                digest: None,
+            },
+        ]);
+        assert_eq!(
+            "# globmatch manifest v1\nx\\ty\ta\\tb\n\\-\ta.txt\n-\tb.txt\n",
+            manifest.to_text()
+        );
+        assert_eq!(manifest, Manifest::from_text(&manifest.to_text())?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn manifest_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = path::Path::new("root");
+        let a = root.join(std::ffi::OsStr::from_bytes(b"a\xff.txt"));
+        let b = root.join(std::ffi::OsStr::from_bytes(b"a\xfe.txt"));
+        let err = Manifest::from_paths(root, &[a, b]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,425 @@
+use std::path;
+
+use crate::error::Error;
+use crate::{pattern, utils, wrappers, Builder};
+
+/// Path yielded by the [`IterMulti`] iterator.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MultiMatch {
+    /// The matching path.
+    pub path: path::PathBuf,
+    /// Indices of all globs that match the path, in ascending order.
+    pub patterns: Vec<usize>,
+}
+
+/// Matcher for multiple globs that walks the file system only once.
+///
+/// In contrast to [`wrappers::build_matchers`], which creates one [`Matcher`](crate::Matcher)
+/// and thus one walk per glob, all globs are resolved relative to the common root of the globs
+/// and compiled into a single `globset::GlobSet`. Each match is attributed to the originating
+/// globs, see [`MultiMatch`]. As for [`wrappers::build_matchers`], each glob may start with an
+/// inline flag to override the case sensitivity (see the
+/// [module documentation](crate::wrappers#inline-flags)).
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let matcher = globmatch::MultiMatcher::new(
+///     &["test-files/c-simple/a/**/*.txt", "test-files/c-simple/**/a1/*"],
+///     env!("CARGO_MANIFEST_DIR"),
+/// )?;
+///
+/// let matches: Vec<_> = matcher.into_iter().flatten().collect();
+/// assert_eq!(5, matches.len());
+///
+/// let a1 = matches.iter().find(|m| m.path.ends_with("a1_0.txt")).unwrap();
+/// assert_eq!(vec![0, 1], a1.patterns);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiMatcher {
+    globs: Vec<String>,
+    /// Common root of all globs, i.e., the root of the walk.
+    root: path::PathBuf,
+    /// Directory that the compiled globs are relative to.
+    base: path::PathBuf,
+    set: globset::GlobSet,
+    /// Index of the originating glob for each glob in the `set`.
+    owners: Vec<usize>,
+    hybrids: Vec<(usize, pattern::HybridRegex)>,
+    /// Path components of all globs relative to the `base`, used to skip folders.
+    prefixes: pattern::PrefixSet,
+    /// Maximum depth of the walk, `None` if any glob contains a globstar.
+    max_depth: Option<usize>,
+}
+
+/// Provides the directory that the glob of a root is relative to.
+///
+/// If the root is a file, its name is matched as if the parent directory was walked.
+fn base_of(root: &path::Path) -> &path::Path {
+    match root.is_dir() {
+        true => root,
+        false => root.parent().unwrap_or(root),
+    }
+}
+
+impl MultiMatcher {
+    /// Creates a [`MultiMatcher`] for the list of `globs` relative to `root`.
+    ///
+    /// The walk starts at the common ancestor of the resolved roots of all globs. Only the folders
+    /// that may contain a match of any glob are walked, and the depth of the walk is bounded if
+    /// none of the globs contains a globstar `**`.
+    ///
+    /// # Errors
+    ///
+    /// Provides the first [`Error`] that occurs while resolving or compiling the globs, refer to
+    /// [`Builder::build`]. Fails with [`Error::Resolve`] if the resolved roots of the globs do
+    /// not have a common ancestor.
+    pub fn new<P>(globs: &[&str], root: P) -> Result<MultiMatcher, Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut resolved = globs
+            .iter()
+            .map(|glob| {
+                let (case_sensitive, pattern) = wrappers::split_case_flag(glob);
+                let mut builder = Builder::new(pattern);
+                builder.case_sensitive(case_sensitive.unwrap_or(!cfg!(windows)));
+                let (root, _, rest) = builder.resolve(root.as_ref(), &crate::WalkDirSource)?;
+                Ok((builder, utils::normalize_path(&root), rest))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let current = path::Path::new(".");
+        let below_current = |root: &path::Path| {
+            matches!(root.components().next(), Some(path::Component::Normal(_)))
+        };
+        let mut roots = resolved.iter().map(|(_, root, _)| root);
+        let mut walk_root = roots
+            .next()
+            .cloned()
+            .unwrap_or_else(|| path::PathBuf::from(root.as_ref()));
+        for root in roots {
+            walk_root = match utils::common_ancestor(&walk_root, root) {
+                Some(common) => common,
+                // relative roots are walked from within the current directory
+                None if (walk_root == current || below_current(&walk_root))
+                    && (root == current || below_current(root)) =>
+                {
+                    current.to_path_buf()
+                }
+                None => {
+                    return Err(Error::resolve(format!(
+                        "The roots {} and {} do not have a common ancestor",
+                        walk_root.to_string_lossy(),
+                        root.to_string_lossy()
+                    )))
+                }
+            };
+        }
+        if walk_root == current {
+            for (_, root, _) in &mut resolved {
+                if below_current(root) {
+                    *root = current.join(&root);
+                }
+            }
+        }
+        let base = path::PathBuf::from(base_of(&walk_root));
+
+        let mut builder = globset::GlobSetBuilder::new();
+        let mut owners = vec![];
+        let mut hybrids = vec![];
+        let mut prefixes = pattern::PrefixSet::default();
+        let mut max_depth = Some(0);
+        for (index, (glob, root, rest)) in resolved.iter().enumerate() {
+            // the compiled globs are relative to the common base and thus need the prefix
+            let prefix = base_of(root)
+                .strip_prefix(&base)
+                .map_err(|err| Error::resolve(format!("'{}': {}", glob.glob, err)))?
+                .components()
+                .map(|c| globset::escape(&c.as_os_str().to_string_lossy()))
+                .collect::<Vec<_>>();
+            let rest = match prefix.is_empty() {
+                true => rest.clone(),
+                false => format!("{}/{}", prefix.join("/"), rest),
+            };
+
+            glob.check_patterns(&[&rest])?;
+            for expanded in utils::expand_ranges(&rest, glob.escapes()) {
+                // without globstars the number of components of the glob bounds the depth
+                match glob.expand_braces(&expanded) {
+                    Some(alternatives) => {
+                        for alternative in alternatives {
+                            prefixes.add(&alternative, glob.escapes(), |g| glob.glob_for(g))?;
+                            let depth = utils::max_depth(&alternative, None, glob.escapes());
+                            max_depth = max_depth.zip(depth).map(|(max, d)| max.max(d));
+                        }
+                    }
+                    None => {
+                        prefixes.add_unbounded();
+                        max_depth = None;
+                    }
+                }
+                let expanded = glob.flatten_braces(&expanded)?;
+                if pattern::is_hybrid(&expanded) {
+                    let re = pattern::compile_hybrid(&expanded, |g| glob.glob_for(g))?;
+                    hybrids.push((index, re));
+                } else {
+                    builder.add(glob.glob_for(&expanded)?);
+                    owners.push(index);
+                }
+            }
+        }
+        let set = builder
+            .build()
+            .map_err(|err| Error::globset(&globs.join(", "), err))?;
+
+        Ok(MultiMatcher {
+            globs: globs.iter().map(|glob| glob.to_string()).collect(),
+            root: walk_root,
+            base,
+            set,
+            owners,
+            hybrids,
+            prefixes,
+            max_depth,
+        })
+    }
+
+    /// Provides the original globs used to create this [`MultiMatcher`].
+    pub fn globs(&self) -> &[String] {
+        &self.globs
+    }
+
+    /// Provides the common root of all globs, i.e., the root directory of the walk.
+    pub fn root(&self) -> &path::Path {
+        &self.root
+    }
+
+    /// Provides the indices of all globs matching the path relative to the common root.
+    fn matches(&self, p: &path::Path) -> Vec<usize> {
+        let mut patterns: Vec<_> = self
+            .set
+            .matches(p)
+            .into_iter()
+            .map(|i| self.owners[i])
+            .chain(
+                self.hybrids
+                    .iter()
+                    .filter(|(_, re)| pattern::is_hybrid_match(re, p))
+                    .map(|(index, _)| *index),
+            )
+            .collect();
+        patterns.sort_unstable();
+        patterns.dedup();
+        patterns
+    }
+}
+
+impl IntoIterator for MultiMatcher {
+    type Item = Result<MultiMatch, Error>;
+    type IntoIter = IterMulti;
+
+    /// Transform the [`MultiMatcher`] into a recursive directory iterator.
+    fn into_iter(self) -> Self::IntoIter {
+        let mut walk = walkdir::WalkDir::new(&self.root);
+        if let Some(depth) = self.max_depth {
+            walk = walk.max_depth(depth);
+        }
+        IterMulti {
+            iter: walk.into_iter(),
+            matcher: self,
+            filter: None,
+        }
+    }
+}
+
+/// Iterator created from a [`MultiMatcher`].
+pub struct IterMulti {
+    iter: walkdir::IntoIter,
+    matcher: MultiMatcher,
+    #[allow(clippy::type_complexity)]
+    filter: Option<Box<dyn FnMut(&path::Path) -> bool>>,
+}
+
+impl std::fmt::Debug for IterMulti {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IterMulti")
+            .field("iter", &self.iter)
+            .field("matcher", &self.matcher)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IterMulti {
+    /// Applies the given predicate to all walked paths, see [`IterAll::filter_entry`].
+    ///
+    /// Paths that do not satisfy the predicate are skipped, including all of their sub-paths.
+    ///
+    /// [`IterAll::filter_entry`]: crate::IterAll::filter_entry
+    pub fn filter_entry<F>(mut self, predicate: F) -> IterMulti
+    where
+        F: FnMut(&path::Path) -> bool + 'static,
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+}
+
+impl Iterator for IterMulti {
+    type Item = Result<MultiMatch, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.iter.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            if let Some(filter) = &mut self.filter {
+                if !filter(entry.path()) {
+                    if entry.file_type().is_dir() {
+                        self.iter.skip_current_dir();
+                    }
+                    continue;
+                }
+            }
+
+            let relative = match entry.path().strip_prefix(&self.matcher.base) {
+                Ok(relative) if !relative.as_os_str().is_empty() => relative,
+                _ => continue,
+            };
+            // the folder itself may still be a match, only its contents are skipped
+            if entry.depth() > 0
+                && entry.file_type().is_dir()
+                && !self.matcher.prefixes.may_contain_match(relative)
+            {
+                self.iter.skip_current_dir();
+            }
+            let patterns = self.matcher.matches(relative);
+            if !patterns.is_empty() {
+                return Some(Ok(MultiMatch {
+                    path: entry.into_path(),
+                    patterns,
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_attribution() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let globs = ["a/a0/*.txt", "**/*.md", "(?i)a/**/a0_*", "b/*.txt"];
+
+        let matcher = MultiMatcher::new(&globs, &root)?;
+        assert_eq!(path::Path::new(&root), matcher.root());
+
+        let mut matches: Vec<_> = matcher.into_iter().flatten().collect();
+        matches.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let matches: Vec<_> = matches
+            .iter()
+            .map(|m| {
+                let p = m.path.strip_prefix(&root).unwrap().to_path_buf();
+                (p, m.patterns.clone())
+            })
+            .collect();
+
+        let expected = [
+            ("a/a0/A0_3.txt", vec![0, 2]),
+            ("a/a0/a0_0.txt", vec![0, 2]),
+            ("a/a0/a0_1.txt", vec![0, 2]),
+            ("a/a0/a0_2.md", vec![1, 2]),
+            ("b/b_0.txt", vec![3]),
+        ];
+        assert_eq!(expected.len(), matches.len());
+        for ((path, patterns), (p, expected)) in matches.iter().zip(expected.iter()) {
+            assert_eq!(path::Path::new(p), path);
+            assert_eq!(expected, patterns);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn multi_filter() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        let count = MultiMatcher::new(&["**/*.txt", "**/h_*"], &root)?
+            .into_iter()
+            .filter_entry(|p| !crate::is_hidden_path(p))
+            .flatten()
+            .count();
+        assert_eq!(7, count);
+
+        assert!(MultiMatcher::new(&["a/**", "[a"], &root).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn multi_prune() -> Result<(), String> {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let globs = ["test-files/c-simple/a/a0/*.txt", "src/*.rs"];
+
+        let walked = Rc::new(RefCell::new(vec![]));
+        let record = Rc::clone(&walked);
+        let count = MultiMatcher::new(&globs, root)?
+            .into_iter()
+            .filter_entry(move |p| {
+                record.borrow_mut().push(p.to_path_buf());
+                true
+            })
+            .flatten()
+            .count();
+        assert!(count > 3);
+
+        // only the folders that may contain a match are walked, up to the depth of the globs
+        let walked = walked.borrow();
+        let skipped = [
+            "target",
+            "test-files/c-simple/b",
+            "test-files/c-simple/a/a1",
+        ];
+        for p in walked.iter() {
+            let relative = p.strip_prefix(root).unwrap();
+            let within = |s: &&str| relative.starts_with(s) && relative != path::Path::new(s);
+            assert!(!skipped.iter().any(within), "{p:?}");
+            assert!(relative.components().count() <= 5, "{p:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn multi_root() -> Result<(), String> {
+        let tmp = utils::TempDir::new("multi-root")?;
+        let root = tmp.mkdir("root")?;
+        tmp.write("root/a/a.txt", "")?;
+        tmp.write("probe/b.toml", "")?;
+
+        // the roots are normalized before determining the common root
+        let matcher = MultiMatcher::new(&["a/*.txt", "../probe/*.toml"], &root)?;
+        assert_eq!(tmp.to_path_buf(), matcher.root());
+        let mut matches: Vec<_> = matcher.into_iter().collect::<Result<_, _>>()?;
+        matches.sort_by(|a, b| a.path.cmp(&b.path));
+        let expected = [(tmp.join("probe/b.toml"), 1), (root.join("a/a.txt"), 0)];
+        assert_eq!(expected.len(), matches.len());
+        for (m, (path, index)) in matches.iter().zip(expected) {
+            assert_eq!(path, m.path);
+            assert_eq!(vec![index], m.patterns);
+        }
+
+        // relative roots without a common ancestor cannot be walked
+        let err = MultiMatcher::new(&["../../*.txt", "*.txt"], "test-files").unwrap_err();
+        assert!(matches!(err, Error::Resolve { .. }));
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::{path, sync};
+
+use crate::error::Error;
+
+/// Reason for skipping a walked entry, see [`WalkObserver::on_skip`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The entry does not satisfy the predicate of a `filter_entry` call.
+    Filtered,
+    /// The entry is excluded, see [`Builder::exclude`](crate::Builder::exclude).
+    Excluded,
+    /// The entry is ignored by a `.gitignore` file, see `Builder::respect_gitignore`.
+    Ignored,
+    /// The file of the entry has already been yielded, see
+    /// [`Builder::dedup_files`](crate::Builder::dedup_files).
+    Duplicate,
+}
+
+/// Observer that is notified about the progress of a walk, see [`Matcher::observe`].
+///
+/// All functions have an empty default implementation such that an observer only needs to
+/// implement the notifications that it is interested in. Since the observer is shared by all
+/// worker threads of the parallel iterator, it only has access to `&self` and must use interior
+/// mutability to record the notifications.
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Default)]
+/// struct Skipped(Mutex<Vec<std::path::PathBuf>>);
+///
+/// impl globmatch::WalkObserver for Skipped {
+///     fn on_skip(&self, path: &std::path::Path, _reason: globmatch::SkipReason) {
+///         self.0.lock().unwrap().push(path.to_path_buf());
+///     }
+/// }
+///
+/// # fn example() -> Result<(), String> {
+/// let skipped = Arc::new(Skipped::default());
+/// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+///     .build(env!("CARGO_MANIFEST_DIR"))?
+///     .observe(Arc::clone(&skipped))
+///     .into_iter()
+///     .filter_entry(|p| !globmatch::is_hidden_path(p))
+///     .flatten()
+///     .collect();
+///
+/// assert_eq!(6 + 1, paths.len());
+/// assert_eq!(1, skipped.0.lock().unwrap().len()); // the folder `.hidden`
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+///
+/// [`Matcher::observe`]: crate::Matcher::observe
+pub trait WalkObserver {
+    /// Called for each entry, and thus all of its contents, that is skipped for the `reason`.
+    fn on_skip(&self, _path: &path::Path, _reason: SkipReason) {}
+
+    /// Called for each error, also if the error is not yielded by the iterator, see
+    /// [`Builder::on_error`](crate::Builder::on_error).
+    fn on_error(&self, _error: &Error) {}
+
+    /// Called for each path that is yielded by the iterator.
+    fn on_match(&self, _path: &path::Path) {}
+}
+
+impl<O> WalkObserver for sync::Arc<O>
+where
+    O: WalkObserver + ?Sized,
+{
+    fn on_skip(&self, path: &path::Path, reason: SkipReason) {
+        self.as_ref().on_skip(path, reason);
+    }
+
+    fn on_error(&self, error: &Error) {
+        self.as_ref().on_error(error);
+    }
+
+    fn on_match(&self, path: &path::Path) {
+        self.as_ref().on_match(path);
+    }
+}
+
+/// Progress of a walk, reported to the callback configured via [`Matcher::on_progress`].
+///
+/// The numbers are counted since the start of the walk. For the parallel iterator the numbers
+/// are a snapshot of all worker threads, i.e., entries that are walked concurrently may not be
+/// reflected yet.
+///
+/// [`Matcher::on_progress`]: crate::Matcher::on_progress
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+    /// Number of file system entries that have been walked, including directories and entries
+    /// that do not match the glob.
+    pub entries: usize,
+    /// Number of directories that have been entered, i.e., whose contents are walked.
+    pub dirs: usize,
+    /// Number of matches that have been found.
+    pub matches: usize,
+}
+
+type ProgressCallback = dyn Fn(&Progress) + Send + Sync;
+
+/// Optional [`WalkObserver`] and progress callback that are passed from the [`crate::Matcher`]
+/// to its iterators.
+#[derive(Clone, Default)]
+pub(crate) struct Observer {
+    observer: Option<sync::Arc<dyn WalkObserver + Send + Sync>>,
+    /// Progress callback, invoked every given number of walked entries.
+    progress: Option<(usize, sync::Arc<ProgressCallback>)>,
+}
+
+impl std::fmt::Debug for Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observer")
+            .field("observer", &self.observer.is_some())
+            .field("progress", &self.progress.as_ref().map(|(every, _)| every))
+            .finish()
+    }
+}
+
+impl Observer {
+    pub(crate) fn set_observer<O>(&mut self, observer: O)
+    where
+        O: WalkObserver + Send + Sync + 'static,
+    {
+        self.observer = Some(sync::Arc::new(observer));
+    }
+
+    pub(crate) fn set_progress<F>(&mut self, every: usize, callback: F)
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some((every.max(1), sync::Arc::new(callback)));
+    }
+
+    pub(crate) fn on_skip(&self, path: &path::Path, reason: SkipReason) {
+        debug!(?path, ?reason, "skipped entry");
+        if let Some(observer) = &self.observer {
+            observer.on_skip(path, reason);
+        }
+    }
+
+    pub(crate) fn on_error(&self, error: &Error) {
+        debug!(%error, "failed to walk entry");
+        if let Some(observer) = &self.observer {
+            observer.on_error(error);
+        }
+    }
+
+    pub(crate) fn on_match(&self, path: &path::Path) {
+        trace!(?path, "matched entry");
+        if let Some(observer) = &self.observer {
+            observer.on_match(path);
+        }
+    }
+
+    /// Checks whether the progress must be reported after walking the given number of entries.
+    pub(crate) fn is_progress_due(&self, entries: usize) -> bool {
+        self.progress
+            .as_ref()
+            .is_some_and(|(every, _)| entries % every == 0)
+    }
+
+    /// Reports the progress, regardless of the configured cadence.
+    pub(crate) fn on_progress(&self, progress: Progress) {
+        if let Some((_, callback)) = &self.progress {
+            callback(&progress);
+        }
+    }
+}
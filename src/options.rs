@@ -0,0 +1,379 @@
+//! Option types used to configure the [`Builder`](crate::Builder).
+
+/// Defines what a glob matches if it resolves to a bare directory, see
+/// [`Builder::directory_match`](crate::Builder::directory_match).
+///
+/// A glob resolves to a bare directory if all of its path components exist, e.g., for the glob
+/// `../test-files/c-simple` where `test-files/c-simple` is a directory.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DirectoryMatch {
+    /// The glob only matches the directory itself (default).
+    #[default]
+    Entry,
+    /// The glob is expanded to `directory/**` and thus matches all paths within the directory,
+    /// but not the directory itself.
+    Contents,
+}
+
+/// Defines whether globs are matched case sensitive, see
+/// [`Builder::case_sensitivity`](crate::Builder::case_sensitivity).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CaseSensitivity {
+    /// Globs are matched case sensitive (default).
+    #[default]
+    Sensitive,
+    /// Globs are matched case insensitive.
+    Insensitive,
+    /// The case sensitivity is determined by probing the file system of the root directory,
+    /// e.g., globs are matched case insensitive on the default volumes of Windows and macOS, but
+    /// case sensitive on Linux.
+    ///
+    /// If the file system cannot be probed, e.g., for globs that are built without a root, the
+    /// case sensitivity defaults to the convention of the platform.
+    Auto,
+}
+
+impl CaseSensitivity {
+    /// Resolves the case sensitivity for the file system of the given directory.
+    pub(crate) fn is_sensitive(self, dir: Option<&std::path::Path>) -> bool {
+        match self {
+            CaseSensitivity::Sensitive => true,
+            CaseSensitivity::Insensitive => false,
+            CaseSensitivity::Auto => dir
+                .and_then(crate::utils::probe_case_sensitive)
+                .unwrap_or(!cfg!(any(windows, target_os = "macos"))),
+        }
+    }
+}
+
+/// Defines how a root that is or contains a symbolic link is handled, see
+/// [`Builder::root_symlinks`](crate::Builder::root_symlinks).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RootSymlinks {
+    /// The root is used as provided, all yielded paths are relative to the symbolic link
+    /// (default).
+    #[default]
+    Keep,
+    /// The resolved root is canonicalized, i.e., all symbolic links and relative path
+    /// components of the root are resolved. All yielded paths are relative to the target.
+    Resolve,
+}
+
+/// Defines how a root that does not exist is handled, see
+/// [`Builder::missing_root`](crate::Builder::missing_root).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MissingRoot {
+    /// The `build` functions fail with [`Error::RootNotFound`](crate::Error::RootNotFound)
+    /// (default).
+    #[default]
+    Fail,
+    /// The iterators do not yield any paths if the root does not exist while walking.
+    Empty,
+    /// The iterators yield a single [`Error::RootNotFound`](crate::Error::RootNotFound) if the
+    /// root does not exist while walking.
+    Defer,
+}
+
+/// Types of the entries that are yielded, see [`Builder::file_type`](crate::Builder::file_type).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FileType {
+    /// All matching entries are yielded (default).
+    #[default]
+    All,
+    /// Only regular files are yielded.
+    Files,
+    /// Only directories are yielded.
+    Dirs,
+}
+
+impl FileType {
+    /// Checks whether an entry of the given type is yielded.
+    pub(crate) fn accepts(self, file_type: crate::SourceFileType) -> bool {
+        match self {
+            FileType::All => true,
+            FileType::Files => file_type == crate::SourceFileType::File,
+            FileType::Dirs => file_type == crate::SourceFileType::Dir,
+        }
+    }
+}
+
+/// Defines how the yielded paths are normalized, see
+/// [`Builder::path_normalization`](crate::Builder::path_normalization).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PathNormalization {
+    /// Paths are yielded as walked, i.e., prefixed with the root as provided (default).
+    #[default]
+    Keep,
+    /// Paths are normalized lexically, i.e., `.` components are removed and `..` components are
+    /// resolved without accessing the file system. Notice that this may change the meaning of
+    /// paths that contain symbolic links followed by `..`.
+    Lexical,
+    /// Paths are canonicalized, i.e., they are absolute and all symbolic links and relative path
+    /// components are resolved. Paths that cannot be canonicalized, e.g., broken symbolic links,
+    /// are reported as errors.
+    Canonical,
+}
+
+/// Defines how errors are handled while walking the file system, see
+/// [`Builder::on_error`](crate::Builder::on_error).
+///
+/// This applies to errors of single entries, e.g., folders that cannot be read due to missing
+/// permissions, but not to the errors for exceeded [`WalkLimits`] or
+/// [`Error::Cancelled`](crate::Error::Cancelled).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OnError {
+    /// Errors are yielded by the iterator and the walk continues (default).
+    #[default]
+    Yield,
+    /// Errors are silently skipped and the walk continues.
+    Skip,
+    /// The first error is yielded by the iterator and the walk is aborted.
+    Abort,
+}
+
+/// Limits for the complexity of globs, see [`Builder::limits`](crate::Builder::limits).
+///
+/// These limits are intended for services that evaluate globs provided by users, to prevent
+/// that buggy or hostile globs blow up the time and memory needed to compile the globs. Apart
+/// from [`PatternLimits::max_patterns`], no limits are applied by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PatternLimits {
+    /// Maximum length of the glob in bytes.
+    pub max_length: Option<usize>,
+    /// Maximum number of globs that are compiled for a single glob, e.g., due to the expansion
+    /// of numeric ranges or depth-bounded globstars. If not set, at most 10 000 globs are
+    /// compiled for a single glob.
+    pub max_patterns: Option<usize>,
+    /// Maximum size of the compiled globs, measured as the total length in bytes of the regular
+    /// expressions that are generated for the globs.
+    pub max_compiled_size: Option<usize>,
+    /// Maximum number of combinations of the alternates `{a,b}` within the glob, e.g., the glob
+    /// `{a,b}/{c,d{e,f}}` has 6 combinations. Braces without a comma, e.g., numeric ranges, are
+    /// no alternates.
+    pub max_alternations: Option<usize>,
+    /// Maximum number of globstars `**` within the glob.
+    pub max_globstars: Option<usize>,
+}
+
+/// Limits for walking the file system, see [`Builder::limit`](crate::Builder::limit).
+///
+/// These limits are intended for services that evaluate globs provided by users, to prevent
+/// that a glob causes an unbounded traversal, e.g., a glob rooted at `/`. Once a limit is
+/// exceeded the iterator yields a single error identifying the limit and ends. No limits are
+/// applied by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WalkLimits {
+    /// Maximum number of entries that are walked, see [`Error::EntryLimit`].
+    ///
+    /// [`Error::EntryLimit`]: crate::Error::EntryLimit
+    pub max_entries: Option<usize>,
+    /// Maximum number of matches that are yielded, see [`Error::MatchLimit`].
+    ///
+    /// [`Error::MatchLimit`]: crate::Error::MatchLimit
+    pub max_matches: Option<usize>,
+    /// Maximum duration of the walk, measured from the first item that is requested from the
+    /// iterator, see [`Error::Timeout`].
+    ///
+    /// [`Error::Timeout`]: crate::Error::Timeout
+    pub timeout: Option<std::time::Duration>,
+}
+
+/// Filter for the metadata of the matching entries, see
+/// [`Builder::filter_metadata`](crate::Builder::filter_metadata).
+///
+/// All configured bounds must be satisfied for an entry to be yielded, the size bounds are
+/// inclusive and the bounds of the modification time are exclusive. Entries whose modification
+/// time is not available on the platform are not yielded if a bound for the modification time is
+/// configured. No bounds are configured by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MetadataFilter {
+    /// Minimum size of the entry in bytes.
+    pub min_size: Option<u64>,
+    /// Maximum size of the entry in bytes.
+    pub max_size: Option<u64>,
+    /// Only entries that have been modified after this time are yielded.
+    pub modified_after: Option<std::time::SystemTime>,
+    /// Only entries that have been modified before this time are yielded.
+    pub modified_before: Option<std::time::SystemTime>,
+}
+
+impl MetadataFilter {
+    /// Checks whether any bound is configured, i.e., whether the metadata must be queried.
+    pub(crate) fn is_active(&self) -> bool {
+        *self != MetadataFilter::default()
+    }
+
+    /// Checks whether an entry with the given metadata satisfies all bounds of the filter.
+    ///
+    /// This allows to apply the same filter to paths that are not provided by a [`Matcher`],
+    /// e.g., the paths provided by the functions in [`wrappers`](crate::wrappers).
+    ///
+    /// [`Matcher`]: crate::Matcher
+    pub fn is_match(&self, metadata: &std::fs::Metadata) -> bool {
+        self.is_match_with(metadata.len(), || metadata.modified().ok())
+    }
+
+    /// Checks whether an entry of a [`WalkSource`](crate::WalkSource) satisfies all bounds.
+    pub(crate) fn is_source_match(&self, metadata: &crate::SourceMetadata) -> bool {
+        self.is_match_with(metadata.len, || metadata.modified)
+    }
+
+    /// Checks the bounds for the given size, the modification time is only queried if required.
+    fn is_match_with<F>(&self, len: u64, modified: F) -> bool
+    where
+        F: FnOnce() -> Option<std::time::SystemTime>,
+    {
+        if self.min_size.is_some_and(|min| len < min) || self.max_size.is_some_and(|max| len > max)
+        {
+            return false;
+        }
+        if self.modified_after.is_none() && self.modified_before.is_none() {
+            return true;
+        }
+        match modified() {
+            Some(modified) => {
+                self.modified_after.is_none_or(|after| modified > after)
+                    && self.modified_before.is_none_or(|before| modified < before)
+            }
+            None => false,
+        }
+    }
+}
+
+type Compare = dyn Fn(&walkdir::DirEntry, &walkdir::DirEntry) -> std::cmp::Ordering + Send + Sync;
+
+/// Order of the entries within each walked directory, see
+/// [`Builder::sort_by_file_name`](crate::Builder::sort_by_file_name),
+/// [`Builder::sort_by_natural_name`](crate::Builder::sort_by_natural_name) and
+/// [`Builder::sort_by`](crate::Builder::sort_by).
+#[derive(Clone)]
+pub(crate) enum WalkOrder {
+    /// Entries are sorted by their file names.
+    FileName,
+    /// Entries are sorted by their file names in natural order.
+    NaturalName,
+    /// Entries are sorted using a custom comparison.
+    Custom(std::sync::Arc<Compare>),
+}
+
+impl std::fmt::Debug for WalkOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalkOrder::FileName => write!(f, "FileName"),
+            WalkOrder::NaturalName => write!(f, "NaturalName"),
+            WalkOrder::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl WalkOrder {
+    /// Applies the order to the given walk.
+    pub(crate) fn apply(self, walk: walkdir::WalkDir) -> walkdir::WalkDir {
+        match self {
+            WalkOrder::FileName => walk.sort_by_file_name(),
+            WalkOrder::NaturalName => {
+                walk.sort_by(|a, b| crate::utils::natural_cmp(a.file_name(), b.file_name()))
+            }
+            WalkOrder::Custom(compare) => walk.sort_by(move |a, b| compare(a, b)),
+        }
+    }
+}
+
+/// Options that are applied while walking the file system, passed from the
+/// [`Builder`](crate::Builder) to the iterators of the [`Matcher`](crate::Matcher).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct WalkOptions {
+    /// Maximum number of entries that are walked before the iteration is aborted.
+    pub(crate) max_entries: Option<usize>,
+    /// Maximum number of matches that are yielded before the iteration is aborted.
+    pub(crate) max_matches: Option<usize>,
+    /// Maximum duration of the walk before the iteration is aborted.
+    pub(crate) timeout: Option<std::time::Duration>,
+    /// Number of threads used by the parallel iterator, defaults to the available parallelism.
+    pub(crate) threads: Option<usize>,
+    /// Skip all entries that are ignored by `.gitignore` files.
+    pub(crate) gitignore: bool,
+    /// Follow symbolic links while walking.
+    pub(crate) follow_links: bool,
+    /// Do not walk directories on other file systems than the root.
+    pub(crate) same_file_system: bool,
+    /// Minimum depth of the walked entries, relative to the resolved root.
+    pub(crate) min_depth: Option<usize>,
+    /// Maximum depth of the walked entries, relative to the resolved root.
+    pub(crate) max_depth: Option<usize>,
+    /// Types of the entries that are yielded.
+    pub(crate) file_type: FileType,
+    /// Bounds for the metadata of the entries that are yielded.
+    pub(crate) metadata: MetadataFilter,
+    /// Only yield the first path of each underlying file.
+    pub(crate) dedup_files: bool,
+    /// Handling of a root that does not exist.
+    pub(crate) missing_root: MissingRoot,
+    /// Reject all paths that escape the resolved root.
+    pub(crate) jail: bool,
+    /// Yield paths relative to the resolved root.
+    pub(crate) relative_paths: bool,
+    /// Normalization of the yielded paths.
+    pub(crate) normalization: PathNormalization,
+    /// Handling of errors while walking.
+    pub(crate) on_error: OnError,
+}
+
+impl WalkOptions {
+    /// Applies the policy for missing roots to an error of the walk, provides the error if it
+    /// must be handled, or `None` if the walk should end without an error.
+    pub(crate) fn missing_root_error(&self, err: crate::Error) -> Option<crate::Error> {
+        match (self.missing_root, err) {
+            (MissingRoot::Empty, crate::Error::RootVanished(_)) => None,
+            (MissingRoot::Defer, crate::Error::RootVanished(root)) => {
+                // report the message of the operating system, as for the `build` functions
+                let err = std::fs::metadata(&root)
+                    .err()
+                    .unwrap_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound));
+                Some(crate::Error::root_not_found(&root, err))
+            }
+            (_, err) => Some(err),
+        }
+    }
+
+    /// Provides the path that is yielded for the walked path `p`, which is `relative` to the
+    /// resolved root.
+    ///
+    /// Canonical paths are joined to the `canonical_root` if provided, which is only possible if
+    /// the root is a directory and no symbolic links have been walked. Otherwise the path is
+    /// canonicalized using the file system.
+    pub(crate) fn yielded_path(
+        &self,
+        p: &std::path::Path,
+        relative: &std::path::Path,
+        canonical_root: Option<&std::path::Path>,
+    ) -> std::io::Result<std::path::PathBuf> {
+        let yielded = match self.relative_paths {
+            true => relative,
+            false => p,
+        };
+        match self.normalization {
+            PathNormalization::Keep => Ok(std::path::PathBuf::from(yielded)),
+            PathNormalization::Lexical => Ok(crate::utils::normalize_path(yielded)),
+            PathNormalization::Canonical => match canonical_root {
+                Some(root) if relative.as_os_str().is_empty() => Ok(root.to_path_buf()),
+                Some(root) => Ok(root.join(relative)),
+                None => std::fs::canonicalize(p),
+            },
+        }
+    }
+}
+
+/// Limits for the upward search of [`Builder::ascend`](crate::Builder::ascend).
+///
+/// By default, all ancestors of the start directory are searched up to the root of the file
+/// system.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AscendLimit {
+    /// Maximum number of ancestors that are searched, e.g., `0` only searches the start
+    /// directory itself.
+    pub max_depth: Option<usize>,
+    /// Directory at which the search stops, e.g., the home directory of the user. The boundary
+    /// itself is still searched.
+    pub boundary: Option<std::path::PathBuf>,
+}
@@ -0,0 +1,941 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{atomic, mpsc, Arc, Condvar, Mutex};
+use std::{fs, path, thread, time};
+
+use crate::cancel::CancellationHandle;
+use crate::error::Error;
+#[cfg(all(feature = "ignore", not(feature = "ignore-walk")))]
+use crate::gitignore::GitignoreStack;
+use crate::observer::{Observer, Progress, SkipReason};
+use crate::options::{OnError, PathNormalization, WalkOptions};
+use crate::{pattern, utils};
+
+type Predicate = dyn Fn(&path::Path) -> bool + Send + Sync;
+type Match = Result<path::PathBuf, Error>;
+
+/// Directory that still needs to be walked.
+#[derive(Debug)]
+struct Dir {
+    path: path::PathBuf,
+    /// Depth of the directory relative to the root.
+    #[cfg(not(feature = "ignore-walk"))]
+    depth: usize,
+    /// Ignore rules that apply to the entries of the directory.
+    #[cfg(all(feature = "ignore", not(feature = "ignore-walk")))]
+    ignore: Option<crate::gitignore::GitignoreStack>,
+}
+
+/// Queue of directories that still need to be walked.
+#[derive(Debug, Default)]
+struct Queue {
+    dirs: VecDeque<Dir>,
+    /// Number of directories that are currently walked by the workers.
+    #[cfg(not(feature = "ignore-walk"))]
+    active: usize,
+}
+
+/// State shared by all worker threads.
+struct Shared {
+    root: path::PathBuf,
+    matcher: pattern::PatternSet,
+    options: WalkOptions,
+    observer: Observer,
+    /// Handle that aborts the iteration once cancelled.
+    handle: Option<CancellationHandle>,
+    predicate: Option<Box<Predicate>>,
+    /// Device of the root, only set if the walk must not leave its file system.
+    device: Option<u64>,
+    /// Canonicalized root, only set if the paths are canonicalized.
+    canonical_root: Option<path::PathBuf>,
+    /// Canonicalized root that walked paths must not escape, only set if the walk is jailed.
+    jail_root: Option<path::PathBuf>,
+    /// Files that have been yielded so far, only recorded if duplicates are skipped.
+    seen: Mutex<HashSet<utils::FileId>>,
+    /// Names of custom ignore files, see `Builder::add_ignore_file_name`.
+    #[cfg(feature = "ignore")]
+    ignore_files: Vec<String>,
+    queue: Mutex<Queue>,
+    available: Condvar,
+    entries: atomic::AtomicUsize,
+    dirs: atomic::AtomicUsize,
+    matches: atomic::AtomicUsize,
+    /// Start of the walk, i.e., the time at which the worker threads have been started.
+    started: time::Instant,
+    cancelled: atomic::AtomicBool,
+}
+
+impl Shared {
+    fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::SeqCst);
+        self.wake_all();
+    }
+
+    /// Wakes all waiting workers, e.g., after the iteration has been cancelled.
+    fn wake_all(&self) {
+        // the lock is required such that a worker cannot miss the notification between checking
+        // the cancellation flag and waiting for the condition variable
+        let _queue = self.queue.lock().unwrap();
+        self.available.notify_all();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Counts the walked entry, returns `false` if the iteration must be aborted.
+    fn count_entry(&self, tx: &mpsc::Sender<Match>) -> bool {
+        let count = self.entries.fetch_add(1, atomic::Ordering::SeqCst) + 1;
+        if self
+            .handle
+            .as_ref()
+            .is_some_and(CancellationHandle::is_cancelled)
+        {
+            return self.abort(Error::Cancelled, tx);
+        }
+        if let Some(timeout) = self.options.timeout {
+            if self.started.elapsed() > timeout {
+                return self.abort(Error::Timeout(timeout), tx);
+            }
+        }
+        match self.options.max_entries {
+            Some(max) if count > max => self.abort(Error::EntryLimit(max), tx),
+            _ => {
+                if self.observer.is_progress_due(count) {
+                    self.observer.on_progress(self.progress());
+                }
+                !self.is_cancelled()
+            }
+        }
+    }
+
+    /// Provides a snapshot of the progress of all workers.
+    fn progress(&self) -> Progress {
+        Progress {
+            entries: self.entries.load(atomic::Ordering::SeqCst),
+            dirs: self.dirs.load(atomic::Ordering::SeqCst),
+            matches: self.matches.load(atomic::Ordering::SeqCst),
+        }
+    }
+
+    /// Counts the match, returns `false` if the iteration must be aborted.
+    fn count_match(&self, tx: &mpsc::Sender<Match>) -> bool {
+        let count = self.matches.fetch_add(1, atomic::Ordering::SeqCst) + 1;
+        match self.options.max_matches {
+            Some(max) if count > max => self.abort(Error::MatchLimit(max), tx),
+            _ => true,
+        }
+    }
+
+    /// Aborts the iteration with the given error, always returns `false`.
+    fn abort(&self, err: Error, tx: &mpsc::Sender<Match>) -> bool {
+        // only the first thread aborting the iteration reports the error
+        if !self.cancelled.swap(true, atomic::Ordering::SeqCst) {
+            self.observer.on_error(&err);
+            let _ = tx.send(Err(err));
+        }
+        self.wake_all();
+        false
+    }
+
+    /// Reports an error of a single entry according to the configured error policy.
+    fn report(&self, err: Error, tx: &mpsc::Sender<Match>) {
+        self.observer.on_error(&err);
+        match self.options.on_error {
+            OnError::Yield => {
+                let _ = tx.send(Err(err));
+            }
+            OnError::Skip => {}
+            OnError::Abort => {
+                // only the first error is reported, all workers stop
+                if !self.cancelled.swap(true, atomic::Ordering::SeqCst) {
+                    let _ = tx.send(Err(err));
+                }
+                self.wake_all();
+            }
+        }
+    }
+
+    /// Matches the given entry and sends it if it is a match.
+    fn match_entry(&self, p: &path::Path, file_type: fs::FileType, tx: &mpsc::Sender<Match>) {
+        let relative = match p.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) => return,
+        };
+        // the relative path of the root is empty, which is only matched by globstars
+        if relative.as_os_str().is_empty() && !self.matcher.may_match_root() {
+            return;
+        }
+        trace!(
+            path = ?p,
+            ?relative,
+            matched = self.matcher.is_match(relative),
+            "matching entry"
+        );
+        if !self.matcher.is_match(relative) || !self.options.file_type.accepts(file_type.into()) {
+            return;
+        }
+        if self.matcher.is_excluded(p) {
+            self.observer.on_skip(p, SkipReason::Excluded);
+            return;
+        }
+        if self.matcher.is_allowed(p, file_type.is_dir())
+            && self.is_metadata_match(p, relative, tx)
+            && self.is_first_occurrence(p)
+        {
+            self.send_match(p, relative, file_type, tx);
+        }
+    }
+
+    /// Checks whether the entry is contained within the jail root, see `jail`, and reports
+    /// symbolic links that escape the root.
+    fn is_jailed(&self, p: &path::Path, is_symlink: bool, tx: &mpsc::Sender<Match>) -> bool {
+        if !self.options.jail || !is_symlink {
+            return true;
+        }
+        let jailed = self
+            .jail_root
+            .as_deref()
+            .is_some_and(|root| utils::is_within(p, root));
+        if !jailed {
+            self.report(Error::OutsideRoot(path::PathBuf::from(p)), tx);
+        }
+        jailed
+    }
+
+    /// Checks whether the file of the path `p` has not been yielded yet, see `dedup_files`.
+    fn is_first_occurrence(&self, p: &path::Path) -> bool {
+        if !self.options.dedup_files {
+            return true;
+        }
+        let first = match utils::file_id(p, self.options.follow_links) {
+            Some(id) => self.seen.lock().unwrap().insert(id),
+            None => true,
+        };
+        if !first {
+            self.observer.on_skip(p, SkipReason::Duplicate);
+        }
+        first
+    }
+
+    /// Checks whether the metadata of the path `p`, which is `relative` to the root, satisfies
+    /// the configured filter.
+    fn is_metadata_match(
+        &self,
+        p: &path::Path,
+        relative: &path::Path,
+        tx: &mpsc::Sender<Match>,
+    ) -> bool {
+        if !self.options.metadata.is_active() {
+            return true;
+        }
+        // symbolic links are not followed, consistent with `walkdir`
+        match fs::symlink_metadata(p) {
+            Ok(meta) => self.options.metadata.is_match(&meta),
+            Err(err) => {
+                let depth = relative.components().count();
+                self.report(Error::from_io(p, depth, err), tx);
+                false
+            }
+        }
+    }
+
+    /// Sends the matching path `p`, which is `relative` to the root.
+    ///
+    /// Matches are only counted once the yielded path has been determined, i.e., paths that
+    /// cannot be normalized do not count towards the configured maximum.
+    fn send_match(
+        &self,
+        p: &path::Path,
+        relative: &path::Path,
+        file_type: fs::FileType,
+        tx: &mpsc::Sender<Match>,
+    ) {
+        // paths of symbolic links cannot be canonicalized by joining them to the root
+        let canonical_root = match file_type.is_symlink() {
+            true => None,
+            false => self.canonical_root.as_deref(),
+        };
+        let yielded = match self.options.yielded_path(p, relative, canonical_root) {
+            Ok(yielded) => yielded,
+            Err(err) => {
+                let depth = relative.components().count();
+                self.report(Error::from_io(p, depth, err), tx);
+                return;
+            }
+        };
+        if self.count_match(tx) {
+            self.observer.on_match(p);
+            if tx.send(Ok(yielded)).is_err() {
+                // the receiver has been dropped
+                self.cancel();
+            }
+        }
+    }
+
+    /// Provides the root directory of the walk, including the ignore rules of the root itself.
+    fn root_dir(&self) -> Dir {
+        #[cfg(all(feature = "ignore", not(feature = "ignore-walk")))]
+        let ignore = match self.options.gitignore || !self.ignore_files.is_empty() {
+            true => {
+                let names = self.ignore_files.clone();
+                let stack = GitignoreStack::new(&self.root, self.options.gitignore, names);
+                stack.map(|mut stack| {
+                    // the root is never ignored, but provides the rules of its ignore files
+                    stack.is_ignored(&self.root, &self.root, 0, true);
+                    stack
+                })
+            }
+            false => None,
+        };
+        Dir {
+            path: self.root.clone(),
+            #[cfg(not(feature = "ignore-walk"))]
+            depth: 0,
+            #[cfg(all(feature = "ignore", not(feature = "ignore-walk")))]
+            ignore,
+        }
+    }
+
+    /// Walks the entries of a single directory, queueing all sub-directories.
+    #[cfg(not(feature = "ignore-walk"))]
+    fn walk_dir(&self, dir: Dir, tx: &mpsc::Sender<Match>) {
+        let depth = dir.depth + 1;
+        #[cfg(feature = "ignore")]
+        let mut ignore = dir.ignore;
+        trace!(path = ?dir.path, depth = dir.depth, "entering directory");
+        self.dirs.fetch_add(1, atomic::Ordering::SeqCst);
+        let entries = match fs::read_dir(&dir.path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.report(Error::from_io(&dir.path, dir.depth, err), tx);
+                return;
+            }
+        };
+
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    // the path of an entry that cannot be read is unknown
+                    let dir = dir.path.to_string_lossy();
+                    let message = format!("Failed to read an entry of {dir}: {err}");
+                    self.report(Error::walk_io(message, err), tx);
+                    continue;
+                }
+            };
+            if !self.count_entry(tx) {
+                return;
+            }
+
+            let p = entry.path();
+            if let Some(predicate) = &self.predicate {
+                if !predicate(&p) {
+                    self.observer.on_skip(&p, SkipReason::Filtered);
+                    continue;
+                }
+            }
+
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    self.report(Error::from_io(&p, depth, err), tx);
+                    continue;
+                }
+            };
+            // symbolic links are only resolved if followed, consistent with `walkdir`
+            let is_symlink = file_type.is_symlink();
+            let file_type = match is_symlink && self.options.follow_links {
+                true => match fs::metadata(&p) {
+                    Ok(meta) => meta.file_type(),
+                    Err(err) => {
+                        self.report(Error::from_io(&p, depth, err), tx);
+                        continue;
+                    }
+                },
+                false => file_type,
+            };
+            let is_dir = file_type.is_dir();
+            #[cfg(feature = "ignore")]
+            if let Some(stack) = &mut ignore {
+                if stack.is_ignored(&self.root, &p, depth, is_dir) {
+                    self.observer.on_skip(&p, SkipReason::Ignored);
+                    continue;
+                }
+            }
+            if !self.is_jailed(&p, is_symlink, tx) {
+                continue;
+            }
+            if is_dir && is_symlink && self.is_loop(&dir.path, &p, tx) {
+                continue;
+            }
+            if is_dir && self.matcher.is_pruned(&p) {
+                self.observer.on_skip(&p, SkipReason::Excluded);
+                continue;
+            }
+            if depth >= self.options.min_depth.unwrap_or(0) {
+                self.match_entry(&p, file_type, tx);
+            }
+
+            let may_contain_match = |p: &path::Path| match p.strip_prefix(&self.root) {
+                Ok(relative) => self.matcher.may_contain_match(relative),
+                Err(_) => false,
+            };
+            if is_dir
+                && self.options.max_depth.is_none_or(|max| depth < max)
+                && may_contain_match(&p)
+                && self.is_same_device(&p)
+            {
+                let sub = Dir {
+                    path: p,
+                    depth,
+                    #[cfg(feature = "ignore")]
+                    ignore: ignore.clone(),
+                };
+                self.queue.lock().unwrap().dirs.push_back(sub);
+                self.available.notify_one();
+            }
+        }
+    }
+
+    /// Checks whether the followed symbolic link `p` within the directory `dir` points to one of
+    /// the walked ancestors, and reports the loop.
+    #[cfg(not(feature = "ignore-walk"))]
+    fn is_loop(&self, dir: &path::Path, p: &path::Path, tx: &mpsc::Sender<Match>) -> bool {
+        let target = match utils::file_id(p, true) {
+            Some(target) => target,
+            None => return false,
+        };
+        let ancestor = dir
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(&self.root))
+            .find(|ancestor| utils::file_id(ancestor, true).as_ref() == Some(&target));
+        match ancestor {
+            Some(ancestor) => {
+                let err = Error::SymlinkLoop {
+                    ancestor: path::PathBuf::from(ancestor),
+                    child: path::PathBuf::from(p),
+                };
+                self.report(err, tx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks whether the directory is on the file system of the root, see `same_file_system`.
+    #[cfg(not(feature = "ignore-walk"))]
+    fn is_same_device(&self, p: &path::Path) -> bool {
+        match self.device {
+            // the metadata of followed symbolic links is the metadata of their target
+            Some(root) => fs::metadata(p).map_or(true, |meta| device(&meta) == Some(root)),
+            None => true,
+        }
+    }
+
+    /// Walks the queued root using the parallel walker of the `ignore` crate, which manages its
+    /// own worker threads and applies the ignore files.
+    #[cfg(feature = "ignore-walk")]
+    fn walk_ignore(&self, threads: usize, tx: mpsc::Sender<Match>) {
+        use ignore::WalkState;
+
+        // the root has only been queued if it is a directory
+        let root = match self.queue.lock().unwrap().dirs.pop_front() {
+            Some(root) => root.path,
+            None => return,
+        };
+        let mut builder = ignore::WalkBuilder::new(&root);
+        builder
+            .standard_filters(false)
+            .parents(self.options.gitignore)
+            .git_ignore(self.options.gitignore)
+            .git_global(self.options.gitignore)
+            .git_exclude(self.options.gitignore)
+            .same_file_system(self.options.same_file_system)
+            .follow_links(self.options.follow_links)
+            .max_depth(self.options.max_depth)
+            .threads(threads);
+        for name in &self.ignore_files {
+            builder.add_custom_ignore_filename(name);
+        }
+
+        builder.build_parallel().run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        self.report(ignore_error(err), &tx);
+                        return match self.is_cancelled() {
+                            true => WalkState::Quit,
+                            false => WalkState::Continue,
+                        };
+                    }
+                };
+                // the root itself has already been matched
+                if entry.depth() == 0 {
+                    trace!(path = ?entry.path(), depth = 0, "entering directory");
+                    self.dirs.fetch_add(1, atomic::Ordering::SeqCst);
+                    return WalkState::Continue;
+                }
+                if !self.count_entry(&tx) {
+                    return WalkState::Quit;
+                }
+
+                let p = entry.path();
+                if let Some(predicate) = &self.predicate {
+                    if !predicate(p) {
+                        self.observer.on_skip(p, SkipReason::Filtered);
+                        return WalkState::Skip;
+                    }
+                }
+                let file_type = match entry.file_type() {
+                    Some(file_type) => file_type,
+                    None => return WalkState::Continue,
+                };
+                // the file type of a followed symbolic link is the type of its target
+                if !self.is_jailed(p, entry.path_is_symlink(), &tx) {
+                    return WalkState::Skip;
+                }
+                let is_dir = file_type.is_dir();
+                if is_dir && self.matcher.is_pruned(p) {
+                    self.observer.on_skip(p, SkipReason::Excluded);
+                    return WalkState::Skip;
+                }
+                if entry.depth() >= self.options.min_depth.unwrap_or(0) {
+                    self.match_entry(p, file_type, &tx);
+                }
+
+                if !is_dir {
+                    return WalkState::Continue;
+                }
+                match p.strip_prefix(&self.root) {
+                    Ok(relative) if self.matcher.may_contain_match(relative) => {
+                        trace!(path = ?p, depth = entry.depth(), "entering directory");
+                        self.dirs.fetch_add(1, atomic::Ordering::SeqCst);
+                        WalkState::Continue
+                    }
+                    _ => WalkState::Skip,
+                }
+            })
+        });
+    }
+
+    /// Loop of a single worker thread.
+    #[cfg(not(feature = "ignore-walk"))]
+    fn work(&self, tx: mpsc::Sender<Match>) {
+        loop {
+            let dir = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    if self.is_cancelled() {
+                        return;
+                    }
+                    if let Some(dir) = queue.dirs.pop_front() {
+                        queue.active += 1;
+                        break dir;
+                    }
+                    if queue.active == 0 {
+                        // no directories left and no worker that could provide more
+                        self.available.notify_all();
+                        return;
+                    }
+                    queue = self.available.wait(queue).unwrap();
+                }
+            };
+
+            self.walk_dir(dir, &tx);
+
+            let mut queue = self.queue.lock().unwrap();
+            queue.active -= 1;
+            if queue.active == 0 && queue.dirs.is_empty() {
+                self.available.notify_all();
+            }
+        }
+    }
+}
+
+/// Converts an error of the parallel walker of the `ignore` crate.
+#[cfg(feature = "ignore-walk")]
+fn ignore_error(err: ignore::Error) -> Error {
+    match err {
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. }
+            if matches!(*err, ignore::Error::Loop { .. }) =>
+        {
+            ignore_error(*err)
+        }
+        ignore::Error::Loop { ancestor, child } => Error::SymlinkLoop { ancestor, child },
+        err => Error::walk(err.to_string()),
+    }
+}
+
+/// Provides the device of the file system containing the entry.
+#[cfg(unix)]
+fn device(meta: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.dev())
+}
+
+/// Provides the device of the file system containing the entry, only supported on Unix.
+#[cfg(not(unix))]
+fn device(_meta: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Parallel iterator created via [`Matcher::into_par_iter`](crate::Matcher::into_par_iter).
+///
+/// The file system is walked by multiple worker threads, which are started when the first item
+/// is requested. Paths are therefore yielded in a nondeterministic order. All worker threads are
+/// stopped when the iterator is dropped.
+///
+/// With the `ignore-walk` feature, the worker threads are provided by the parallel walker of the
+/// `ignore` crate instead.
+pub struct IterParallel {
+    shared: Option<Shared>,
+    running: Option<(Arc<Shared>, mpsc::Receiver<Match>)>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for IterParallel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IterParallel")
+            .field("running", &self.running.is_some())
+            .field("threads", &self.handles.len())
+            .finish()
+    }
+}
+
+impl IterParallel {
+    pub(crate) fn new(
+        root: path::PathBuf,
+        matcher: pattern::PatternSet,
+        options: WalkOptions,
+        observer: Observer,
+        handle: Option<CancellationHandle>,
+    ) -> IterParallel {
+        IterParallel {
+            shared: Some(Shared {
+                root,
+                matcher,
+                options,
+                observer,
+                handle,
+                predicate: None,
+                device: None,
+                canonical_root: None,
+                jail_root: None,
+                seen: Mutex::new(HashSet::new()),
+                #[cfg(feature = "ignore")]
+                ignore_files: vec![],
+                queue: Mutex::new(Queue::default()),
+                available: Condvar::new(),
+                entries: atomic::AtomicUsize::new(0),
+                dirs: atomic::AtomicUsize::new(0),
+                matches: atomic::AtomicUsize::new(0),
+                started: time::Instant::now(),
+                cancelled: atomic::AtomicBool::new(false),
+            }),
+            running: None,
+            handles: vec![],
+        }
+    }
+
+    /// Skips all paths, and their sub-paths, that do not satisfy the given `predicate`.
+    ///
+    /// This is the equivalent of [`IterAll::filter_entry`](crate::IterAll::filter_entry), but
+    /// the predicate is shared by all worker threads. The predicate must be set before the first
+    /// item is requested, otherwise it has no effect.
+    pub fn filter_entry<F>(mut self, predicate: F) -> IterParallel
+    where
+        F: Fn(&path::Path) -> bool + Send + Sync + 'static,
+    {
+        if let Some(shared) = &mut self.shared {
+            shared.predicate = Some(Box::new(predicate));
+        }
+        self
+    }
+
+    /// Checks symbolic links against the given canonical root instead of the resolved root, see
+    /// [`Builder::jail`](crate::Builder::jail).
+    pub(crate) fn with_jail_root(mut self, root: Option<path::PathBuf>) -> IterParallel {
+        if let Some(shared) = &mut self.shared {
+            shared.jail_root = root;
+        }
+        self
+    }
+
+    /// Applies the ignore files with the given names, see
+    /// [`Builder::add_ignore_file_name`](crate::Builder::add_ignore_file_name).
+    #[cfg(feature = "ignore")]
+    pub(crate) fn with_ignore_files(mut self, names: Vec<String>) -> IterParallel {
+        if let Some(shared) = &mut self.shared {
+            shared.ignore_files = names;
+        }
+        self
+    }
+
+    /// Starts the worker threads, walking the root itself in the calling thread.
+    fn start(&mut self, mut shared: Shared) {
+        let (tx, rx) = mpsc::channel();
+        let root = shared.root.clone();
+        shared.started = time::Instant::now();
+
+        if shared.options.same_file_system {
+            shared.device = fs::metadata(&root).ok().and_then(|meta| device(&meta));
+        }
+        // paths of followed symbolic links cannot be canonicalized by joining them to the root
+        if shared.options.normalization == PathNormalization::Canonical
+            && !shared.options.follow_links
+        {
+            shared.canonical_root = fs::canonicalize(&root).ok().filter(|root| root.is_dir());
+        }
+        if shared.options.jail && shared.jail_root.is_none() {
+            shared.jail_root = fs::canonicalize(&root).ok();
+        }
+
+        let metadata = match shared.options.follow_links {
+            true => fs::metadata(&root),
+            false => fs::symlink_metadata(&root),
+        };
+        let is_root_match = shared.options.min_depth.unwrap_or(0) == 0;
+        match metadata {
+            Err(err) => {
+                let err = Error::from_io(&root, 0, err);
+                if let Some(err) = shared.options.missing_root_error(err) {
+                    shared.report(err, &tx);
+                }
+            }
+            Ok(meta) if shared.count_entry(&tx) => match meta.is_dir() {
+                true => {
+                    if is_root_match {
+                        shared.match_entry(&root, meta.file_type(), &tx);
+                    }
+                    if shared.options.max_depth != Some(0) {
+                        let dir = shared.root_dir();
+                        shared.queue.lock().unwrap().dirs.push_back(dir);
+                    }
+                }
+                false => {
+                    // the root itself is a file, match on its name as if walking its parent
+                    if let Some(name) = root.file_name().filter(|_| is_root_match) {
+                        if shared.matcher.is_match(name)
+                            && shared.options.file_type.accepts(meta.file_type().into())
+                            && shared.matcher.is_allowed(&root, false)
+                            && shared.is_metadata_match(&root, path::Path::new(""), &tx)
+                            && shared.is_first_occurrence(&root)
+                        {
+                            shared.send_match(&root, path::Path::new(name), meta.file_type(), &tx);
+                        }
+                    }
+                }
+            },
+            Ok(_) => {}
+        }
+
+        let threads = shared
+            .options
+            .threads
+            .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+            .max(1);
+        let shared = Arc::new(shared);
+        #[cfg(not(feature = "ignore-walk"))]
+        for _ in 0..threads {
+            let shared = Arc::clone(&shared);
+            let tx = tx.clone();
+            self.handles.push(thread::spawn(move || shared.work(tx)));
+        }
+        #[cfg(feature = "ignore-walk")]
+        {
+            let shared = Arc::clone(&shared);
+            self.handles
+                .push(thread::spawn(move || shared.walk_ignore(threads, tx)));
+        }
+        self.running = Some((shared, rx));
+    }
+}
+
+impl Iterator for IterParallel {
+    type Item = Result<path::PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(shared) = self.shared.take() {
+            self.start(shared);
+        }
+        // the receiver yields `None` once all worker threads have terminated
+        let next = self.running.as_ref()?.1.recv().ok();
+        if next.is_none() {
+            // the final progress is reported once the walk ends
+            if let Some((shared, _)) = self.running.take() {
+                shared.observer.on_progress(shared.progress());
+            }
+        }
+        next
+    }
+}
+
+impl Drop for IterParallel {
+    fn drop(&mut self) {
+        if let Some((shared, _)) = &self.running {
+            shared.cancel();
+        }
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+        // the walk has been stopped before the receiver yielded `None`
+        if let Some((shared, _)) = self.running.take() {
+            shared.observer.on_progress(shared.progress());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{is_hidden_path, Builder, CancellationHandle, Error};
+
+    #[test]
+    fn parallel() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let builder = Builder::new("test-files/c-simple/**/*.txt");
+
+        let mut sequential: Vec<_> = builder.build(root)?.into_iter().flatten().collect();
+        let mut parallel: Vec<_> = builder.build(root)?.into_par_iter().flatten().collect();
+        sequential.sort();
+        parallel.sort();
+        assert_eq!(6 + 2 + 1, parallel.len());
+        assert_eq!(sequential, parallel);
+
+        let parallel: Vec<_> = builder
+            .build(root)?
+            .into_par_iter()
+            .filter_entry(|p| !is_hidden_path(p))
+            .flatten()
+            .collect();
+        assert_eq!(6 + 1, parallel.len());
+
+        let parallel: Vec<_> = Builder::new("*.txt")
+            .threads(1)
+            .build(format!("{root}/test-files/c-simple/some_file.txt"))?
+            .into_par_iter()
+            .collect();
+        assert_eq!(1, parallel.len());
+
+        let parallel: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .max_entries(3)
+            .build(root)?
+            .into_par_iter()
+            .collect();
+        assert!(parallel.contains(&Err(Error::EntryLimit(3))));
+
+        let handle = CancellationHandle::new();
+        handle.cancel();
+        let parallel: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .cancel_on(handle)
+            .into_par_iter()
+            .collect();
+        assert_eq!(vec![Err(Error::Cancelled)], parallel);
+
+        // dropping the iterator early stops all workers
+        let mut iter = Builder::new("**").threads(4).build(root)?.into_par_iter();
+        assert!(iter.next().is_some());
+        drop(iter);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "ignore")]
+    fn parallel_ignore_files() -> Result<(), String> {
+        let root = crate::utils::TempDir::new("par-ignore")?;
+        for file in ["a.txt", "b.txt", "build/c.txt", "src/d.txt", "src/e.txt"] {
+            root.write(file, "")?;
+        }
+        root.mkdir(".git")?;
+        root.write(".gitignore", "build/\n")?;
+        root.write("src/.toolignore", "e.txt\n")?;
+
+        let paths = |gitignore: bool| -> Result<Vec<_>, String> {
+            let mut paths: Vec<_> = Builder::new("**/*.txt")
+                .respect_gitignore(gitignore)
+                .add_ignore_file_name(".toolignore")
+                .build(&root)?
+                .into_par_iter()
+                .collect::<Result<_, _>>()?;
+            paths.sort();
+            Ok(paths)
+        };
+        let all = paths(false);
+        let ignored = paths(true);
+
+        let expected = |files: &[&str]| files.iter().map(|f| root.join(f)).collect::<Vec<_>>();
+        assert_eq!(
+            expected(&["a.txt", "b.txt", "build/c.txt", "src/d.txt"]),
+            all?
+        );
+        assert_eq!(expected(&["a.txt", "b.txt", "src/d.txt"]), ignored?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parallel_walk_options() -> Result<(), String> {
+        let root = crate::utils::TempDir::new("par-options")?;
+        for file in ["a.txt", "src/b.txt", "src/sub/c.txt", "target/d.txt"] {
+            root.write(file, "")?;
+        }
+        std::os::unix::fs::symlink(root.join("target"), root.join("src/link"))
+            .map_err(|err| err.to_string())?;
+
+        let paths = |follow: bool, min: usize, max: usize| -> Result<[Vec<_>; 2], String> {
+            let mut builder = Builder::new("src/**/*.txt");
+            builder.follow_links(follow).min_depth(min).max_depth(max);
+            let mut sequential: Vec<_> = builder
+                .build(&root)?
+                .into_iter()
+                .collect::<Result<_, _>>()?;
+            let mut parallel: Vec<_> = builder
+                .build(&root)?
+                .into_par_iter()
+                .collect::<Result<_, _>>()?;
+            sequential.sort();
+            parallel.sort();
+            Ok([sequential, parallel])
+        };
+        let expected = |files: &[&str]| files.iter().map(|f| root.join(f)).collect::<Vec<_>>();
+
+        let [sequential, parallel] = paths(false, 0, 9)?;
+        assert_eq!(expected(&["src/b.txt", "src/sub/c.txt"]), parallel);
+        assert_eq!(sequential, parallel);
+
+        let [sequential, parallel] = paths(true, 0, 9)?;
+        let followed = ["src/b.txt", "src/link/d.txt", "src/sub/c.txt"];
+        assert_eq!(expected(&followed), parallel);
+        assert_eq!(sequential, parallel);
+
+        let [sequential, parallel] = paths(true, 2, 9)?;
+        assert_eq!(expected(&["src/link/d.txt", "src/sub/c.txt"]), parallel);
+        assert_eq!(sequential, parallel);
+
+        let [sequential, parallel] = paths(true, 0, 1)?;
+        assert_eq!(expected(&["src/b.txt"]), parallel);
+        assert_eq!(sequential, parallel);
+
+        // links to an ancestor are reported instead of being walked
+        std::os::unix::fs::symlink(root.join("src"), root.join("src/sub/back"))
+            .map_err(|err| err.to_string())?;
+        let loops = |items: Vec<Result<_, Error>>| {
+            let is_loop = |item: &Result<_, _>| matches!(item, Err(Error::SymlinkLoop { .. }));
+            (
+                items.len(),
+                items.iter().filter(|item| is_loop(item)).count(),
+            )
+        };
+        let mut builder = Builder::new("src/**/*.txt");
+        builder.follow_links(true);
+        assert_eq!((4, 1), loops(builder.build(&root)?.into_iter().collect()));
+        assert_eq!(
+            (4, 1),
+            loops(builder.build(&root)?.into_par_iter().collect())
+        );
+        Ok(())
+    }
+}
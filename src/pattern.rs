@@ -0,0 +1,735 @@
+use std::borrow::Cow;
+use std::path;
+
+use crate::error::Error;
+use crate::{utils, winpath};
+
+/// Prefix of a path segment that is matched by a regular expression instead of a glob.
+const SEGMENT_PREFIX: &str = "<re:";
+/// Suffix of a path segment that is matched by a regular expression instead of a glob.
+const SEGMENT_SUFFIX: &str = ">";
+
+/// Checks if the provided path segment is a regular expression segment `<re:...>`.
+pub(crate) fn regex_segment(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix(SEGMENT_PREFIX)
+        .and_then(|s| s.strip_suffix(SEGMENT_SUFFIX))
+}
+
+/// Placeholder used in place of a regular expression segment when compiling the glob.
+///
+/// The placeholder only consists of alphanumeric characters such that it is not escaped by
+/// `globset` and can be found within the generated regular expression.
+#[cfg(feature = "regex")]
+pub(crate) fn placeholder(index: usize) -> String {
+    format!("GLOBMATCHSEGMENT{index}END")
+}
+
+/// Checks if the glob contains at least one regular expression segment `<re:...>`.
+pub(crate) fn is_hybrid(glob: &str) -> bool {
+    glob.split('/').any(|s| regex_segment(s).is_some())
+}
+
+/// Regular expression of a glob containing regular expression segments, see [`compile_hybrid`].
+#[cfg(feature = "regex")]
+pub(crate) type HybridRegex = regex::bytes::Regex;
+
+/// Regular expression of a glob containing regular expression segments, which cannot be
+/// compiled without the `regex` feature.
+#[cfg(not(feature = "regex"))]
+#[derive(Clone, Debug)]
+pub(crate) enum HybridRegex {}
+
+#[cfg(not(feature = "regex"))]
+impl HybridRegex {
+    pub(crate) fn as_str(&self) -> &str {
+        match *self {}
+    }
+}
+
+/// Compiles a glob containing regular expression segments into a single regular expression.
+///
+/// All segments of the form `<re:...>` are replaced by a placeholder before the glob is compiled
+/// by the provided `compile` function. The placeholders in the resulting regular expression
+/// are then replaced by a named group for each regular expression segment.
+#[cfg(feature = "regex")]
+pub(crate) fn compile_hybrid<F>(glob: &str, compile: F) -> Result<HybridRegex, Error>
+where
+    F: FnOnce(&str) -> Result<globset::Glob, Error>,
+{
+    let (mut re, segments) = hybrid_regex(glob, compile)?;
+    for (i, segment) in segments.iter().enumerate() {
+        // compile each segment on its own to provide meaningful error messages
+        regex::bytes::Regex::new(segment).map_err(|err| {
+            Error::pattern(
+                glob,
+                format!("Invalid regular expression '{segment}': {err}"),
+            )
+        })?;
+        re = re.replace(&placeholder(i), &format!("(?P<globmatch{i}>{segment})"));
+    }
+
+    regex::bytes::Regex::new(&re).map_err(|err| Error::pattern(glob, err.to_string()))
+}
+
+/// Rejects globs containing regular expression segments, which require the `regex` feature.
+#[cfg(not(feature = "regex"))]
+pub(crate) fn compile_hybrid<F>(glob: &str, _compile: F) -> Result<HybridRegex, Error>
+where
+    F: FnOnce(&str) -> Result<globset::Glob, Error>,
+{
+    Err(Error::pattern(
+        glob,
+        "Regular expression segments require the `regex` feature",
+    ))
+}
+
+/// Provides the regular expression of `globset` for a glob containing regular expression
+/// segments, where each segment is replaced by its [`placeholder`], and the list of segments.
+#[cfg(feature = "regex")]
+pub(crate) fn hybrid_regex<F>(glob: &str, compile: F) -> Result<(String, Vec<&str>), Error>
+where
+    F: FnOnce(&str) -> Result<globset::Glob, Error>,
+{
+    let mut segments = vec![];
+    let replaced = glob
+        .split('/')
+        .map(|s| match regex_segment(s) {
+            None => Cow::from(s),
+            Some(re) => {
+                segments.push(re);
+                Cow::from(placeholder(segments.len() - 1))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let re = compile(&replaced)?.regex().to_string();
+    Ok((re, segments))
+}
+
+/// Provides the bytes of a path as used for matching, with `/` as path separator.
+#[cfg(feature = "regex")]
+pub(crate) fn path_bytes(p: &path::Path) -> Cow<'_, [u8]> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Cow::from(p.as_os_str().as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        Cow::from(p.to_string_lossy().replace('\\', "/").into_bytes())
+    }
+}
+
+/// Checks if the provided path is a match for the regular expression of a hybrid glob.
+///
+/// Each regular expression segment must match exactly one path segment, i.e., the captured
+/// value must not contain any path separators.
+#[cfg(feature = "regex")]
+pub(crate) fn is_hybrid_match(re: &HybridRegex, p: &path::Path) -> bool {
+    let bytes = path_bytes(p);
+    match re.captures(&bytes) {
+        None => false,
+        Some(captures) => re
+            .capture_names()
+            .flatten()
+            .filter_map(|name| captures.name(name))
+            .all(|c| !c.as_bytes().contains(&b'/')),
+    }
+}
+
+/// Checks if the provided path is a match for the regular expression of a hybrid glob, which
+/// cannot be compiled without the `regex` feature.
+#[cfg(not(feature = "regex"))]
+pub(crate) fn is_hybrid_match(re: &HybridRegex, _p: &path::Path) -> bool {
+    match *re {}
+}
+
+/// Approximate matching mode for the file name of a glob, see [`crate::Builder::fuzzy`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fuzzy {
+    /// The file name matches if it is within the given edit (Levenshtein) distance of the file
+    /// name specified in the glob.
+    Distance(usize),
+    /// The file name matches if it contains all characters of the file name specified in the
+    /// glob in the same order, like in the typical fuzzy finder.
+    Subsequence,
+}
+
+/// Computes the edit (Levenshtein) distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(prev + cost);
+            prev = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// Checks if all characters of `query` appear in the same order in `name`.
+fn is_subsequence(query: &str, name: &str) -> bool {
+    let mut name = name.chars();
+    query.chars().all(|q| name.any(|c| c == q))
+}
+
+/// Glob with an approximately matching file name.
+///
+/// The directory part of the glob is matched exactly, the file name (last segment) of the glob
+/// is used as query for the configured [`Fuzzy`] mode.
+#[derive(Clone, Debug)]
+pub(crate) struct FuzzyGlob {
+    /// Matches the directory part of the glob followed by any file name.
+    parent: Box<PatternSet>,
+    query: String,
+    mode: Fuzzy,
+    case_sensitive: bool,
+}
+
+impl FuzzyGlob {
+    /// Creates a fuzzy glob. The `compile` function is used to compile the directory part, such
+    /// that it is matched like any other glob, e.g., including regular expression segments.
+    pub(crate) fn new<F>(
+        glob: &str,
+        mode: Fuzzy,
+        case_sensitive: bool,
+        compile: F,
+    ) -> Result<FuzzyGlob, Error>
+    where
+        F: FnOnce(&str) -> Result<PatternSet, Error>,
+    {
+        let (parent, name) = match glob.rsplit_once('/') {
+            Some((parent, name)) => (format!("{parent}/*"), name),
+            None => ("*".to_string(), glob),
+        };
+        let query: String = name
+            .chars()
+            .filter(|c| !matches!(c, '*' | '?' | '[' | ']' | '{' | '}'))
+            .collect();
+        let query = match case_sensitive {
+            true => query,
+            false => query.to_lowercase(),
+        };
+
+        Ok(FuzzyGlob {
+            parent: Box::new(compile(&parent)?),
+            query,
+            mode,
+            case_sensitive,
+        })
+    }
+
+    /// Checks whether the provided path is an approximate match.
+    pub(crate) fn is_match(&self, p: &path::Path) -> bool {
+        let name = match p.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return false,
+        };
+        let name = match self.case_sensitive {
+            true => name,
+            false => Cow::from(name.to_lowercase()),
+        };
+
+        self.parent.is_match(p)
+            && match self.mode {
+                Fuzzy::Distance(max) => edit_distance(&self.query, &name) <= max,
+                Fuzzy::Subsequence => is_subsequence(&self.query, &name),
+            }
+    }
+}
+
+/// Single path component of a glob within a [`PrefixSet`].
+#[derive(Clone, Debug)]
+enum Prefix {
+    /// A path component matching the glob.
+    Component(globset::GlobMatcher),
+    /// Any path component, used for regular expression segments.
+    Any,
+    /// A globstar `**`, i.e., any number of path components.
+    Globstar,
+}
+
+/// Path components of a set of globs, used to skip directories that cannot contain a match.
+///
+/// E.g., for the glob `src/*/tests/*.rs` there is no need to walk the folder `src/a/b` since no
+/// path within this folder can be matched by the glob.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PrefixSet {
+    globs: Vec<Vec<Prefix>>,
+    /// Set if any glob cannot be split into path components, which disables pruning.
+    unbounded: bool,
+}
+
+impl PrefixSet {
+    /// Adds a glob to the set. The `compile` function is used to compile each path component,
+    /// `escape` specifies whether backslashes escape special characters.
+    pub(crate) fn add<F>(&mut self, glob: &str, escape: bool, compile: F) -> Result<(), Error>
+    where
+        F: Fn(&str) -> Result<globset::Glob, Error>,
+    {
+        if utils::has_nested_separator(glob, escape) {
+            self.unbounded = true;
+            return Ok(());
+        }
+
+        let mut components = vec![];
+        for c in glob.split('/') {
+            components.push(match c {
+                "**" => Prefix::Globstar,
+                _ if regex_segment(c).is_some() => Prefix::Any,
+                _ => Prefix::Component(compile(c)?.compile_matcher()),
+            });
+        }
+        self.globs.push(components);
+        Ok(())
+    }
+
+    /// Disables pruning, e.g., for globs that expand to too many alternatives.
+    pub(crate) fn add_unbounded(&mut self) {
+        self.unbounded = true;
+    }
+
+    /// Checks whether any glob may match the root itself, i.e., the empty relative path.
+    ///
+    /// Only globs that consist of globstars match zero path components, whereas `globset`
+    /// also matches the empty path for, e.g., `*`.
+    pub(crate) fn may_match_root(&self) -> bool {
+        self.unbounded
+            || self
+                .globs
+                .iter()
+                .any(|glob| glob.iter().all(|c| matches!(c, Prefix::Globstar)))
+    }
+
+    /// Checks whether the folder `dir`, relative to the root, can contain any match.
+    pub(crate) fn may_contain_match(&self, dir: &path::Path) -> bool {
+        if self.unbounded {
+            return true;
+        }
+
+        let components: Vec<_> = dir.components().collect();
+        self.globs.iter().any(|glob| {
+            for (i, c) in components.iter().enumerate() {
+                match glob.get(i) {
+                    None => return false,
+                    Some(Prefix::Globstar) => return true,
+                    Some(Prefix::Any) => {}
+                    Some(Prefix::Component(m)) if m.is_match(c) => {}
+                    Some(Prefix::Component(_)) => return false,
+                }
+            }
+            // the entries within the folder have one more component
+            glob.len() > components.len()
+        })
+    }
+}
+
+/// Ordered list of globs that exclude or re-include matching paths.
+///
+/// The last glob that matches a path decides whether the path is excluded, like for the
+/// negations within `.gitignore` files.
+#[derive(Clone, Debug)]
+pub(crate) struct Exclusions {
+    /// Globs in their original order with the directory that they are relative to, `true` for
+    /// globs that exclude a path.
+    rules: Vec<(path::PathBuf, PatternSet, bool)>,
+    /// Whether the contents of excluded folders are excluded as well, which is the case if no
+    /// rule can include a path again.
+    prune: bool,
+    /// Walked root and the path that it is matched as, e.g., for canonicalized roots.
+    alias: Option<(path::PathBuf, path::PathBuf)>,
+}
+
+impl Exclusions {
+    pub(crate) fn new(rules: Vec<(&path::Path, PatternSet, bool)>) -> Exclusions {
+        let prune = rules.iter().all(|(_, _, exclude)| *exclude);
+        let rules = rules
+            .into_iter()
+            // consistent with the resolved root, see `utils::resolve_root`
+            .map(|(base, rule, exclude)| (winpath::normalize(base), rule, exclude))
+            .collect();
+        Exclusions {
+            rules,
+            prune,
+            alias: None,
+        }
+    }
+
+    /// Matches the paths below `root` as if they were below `alias`, e.g., if the walked root
+    /// is the canonical path of the root that the rules are relative to.
+    pub(crate) fn aliased(mut self, root: &path::Path, alias: &path::Path) -> Exclusions {
+        if root != alias {
+            self.alias = Some((winpath::normalize(root), winpath::normalize(alias)));
+        }
+        self
+    }
+
+    /// Checks whether the given `path` of a walked entry is excluded.
+    pub(crate) fn is_excluded(&self, path: &path::Path) -> bool {
+        let aliased = self.alias.as_ref().and_then(|(root, alias)| {
+            winpath::strip_prefix(path, root).map(|relative| alias.join(relative))
+        });
+        let path = aliased.as_deref().unwrap_or(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(base, rule, _)| match winpath::strip_prefix(path, base) {
+                Some(relative) => rule.is_match(relative),
+                None => false,
+            })
+            .is_some_and(|(_, _, exclude)| *exclude)
+    }
+}
+
+/// Set operation that combines two [`PatternSet`]s, see [`Combination`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Combinator {
+    /// A path must be matched by any of the two sets.
+    Union,
+    /// A path must be matched by both sets.
+    Intersection,
+    /// A path must be matched by the first but not by the second set.
+    Difference,
+}
+
+/// Operand of a [`Combination`], i.e., the [`PatternSet`] of a matcher with its root and depth
+/// limits.
+#[derive(Clone, Debug)]
+pub(crate) struct Operand {
+    pub(crate) root: path::PathBuf,
+    pub(crate) set: PatternSet,
+    pub(crate) min_depth: Option<usize>,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl Operand {
+    /// Checks whether the `full` path is a match for the operand.
+    fn is_match(&self, full: &path::Path) -> bool {
+        let Some(relative) = winpath::strip_prefix(full, &self.root) else {
+            return false;
+        };
+        let depth = relative.components().count();
+        if self.min_depth.is_some_and(|min| depth < min)
+            || self.max_depth.is_some_and(|max| depth > max)
+        {
+            return false;
+        }
+        // if the root of the operand is a file, its name is matched, see `IterAll`
+        let relative = match relative.as_os_str().is_empty() && full.is_file() {
+            true => path::Path::new(full.file_name().unwrap_or_default()),
+            false => relative,
+        };
+        // the file system is only queried if the operand has any overrides
+        self.set.is_match(relative)
+            && !self.set.is_excluded(full)
+            && (!self.set.has_overrides() || self.set.is_allowed(full, full.is_dir()))
+    }
+
+    /// Checks whether the `full` path of a folder can contain any match of the operand.
+    fn may_contain_match(&self, full: &path::Path) -> bool {
+        match winpath::strip_prefix(full, &self.root) {
+            Some(relative) => {
+                // the entries within the folder have one more component
+                let depth = relative.components().count() + 1;
+                self.max_depth.is_none_or(|max| depth <= max)
+                    && !self.set.is_pruned(full)
+                    && self.set.may_contain_match(relative)
+            }
+            // the folder must be walked if it is an ancestor of the root of the operand
+            None => winpath::strip_prefix(&self.root, full).is_some(),
+        }
+    }
+}
+
+/// Combination of the [`PatternSet`]s of two matchers with different roots.
+///
+/// The combination is matched relative to the common ancestor `root` of both matchers. Each
+/// operand is matched against the path relative to its own root, including its exclusions,
+/// overrides and depth limits.
+#[derive(Clone, Debug)]
+pub(crate) struct Combination {
+    pub(crate) op: Combinator,
+    pub(crate) root: path::PathBuf,
+    pub(crate) operands: [Operand; 2],
+}
+
+impl Combination {
+    /// Provides the path that is relative to the root of the combination as a full path.
+    fn full_path(&self, p: &path::Path) -> path::PathBuf {
+        match p.as_os_str().is_empty() {
+            true => self.root.clone(),
+            false => self.root.join(p),
+        }
+    }
+
+    /// Checks whether the path, relative to the root of the combination, is a match.
+    fn is_match(&self, p: &path::Path) -> bool {
+        let full = self.full_path(p);
+        let [a, b] = &self.operands;
+        match self.op {
+            Combinator::Union => a.is_match(&full) || b.is_match(&full),
+            Combinator::Intersection => a.is_match(&full) && b.is_match(&full),
+            Combinator::Difference => a.is_match(&full) && !b.is_match(&full),
+        }
+    }
+
+    /// Checks whether the folder `dir`, relative to the root of the combination, can contain any
+    /// match.
+    fn may_contain_match(&self, dir: &path::Path) -> bool {
+        let full = self.full_path(dir);
+        let [a, b] = &self.operands;
+        match self.op {
+            Combinator::Union => a.may_contain_match(&full) || b.may_contain_match(&full),
+            Combinator::Intersection => a.may_contain_match(&full) && b.may_contain_match(&full),
+            // paths that are only matched by the second operand are never a match
+            Combinator::Difference => a.may_contain_match(&full),
+        }
+    }
+}
+
+/// Compiled set of globs used by the [`crate::Matcher`].
+///
+/// Plain globs are compiled into a `globset::GlobSet`, globs containing regular expression
+/// segments are compiled into separate regular expressions. Fuzzy globs are only used if
+/// an approximate matching mode has been configured.
+#[derive(Clone, Debug)]
+pub(crate) struct PatternSet {
+    pub(crate) globs: globset::GlobSet,
+    pub(crate) hybrids: Vec<HybridRegex>,
+    /// Globs of the set and the builder that compiled them, used to provide the regular
+    /// expression on demand, see [`PatternSet::to_regex`].
+    pub(crate) source: Option<Box<(crate::Builder<'static>, Vec<String>)>>,
+    pub(crate) fuzzy: Vec<FuzzyGlob>,
+    /// Path components of the globs, used to skip folders that cannot contain any match.
+    pub(crate) prefixes: PrefixSet,
+    /// Globs that exclude matching paths.
+    pub(crate) exclusions: Option<Exclusions>,
+    /// Overrides that are additionally applied to all walked entries.
+    #[cfg(feature = "ignore")]
+    pub(crate) overrides: Option<ignore::overrides::Override>,
+    /// Combination of two sets, which replaces the globs of this set.
+    pub(crate) combination: Option<Box<Combination>>,
+}
+
+impl PatternSet {
+    /// Creates a set that combines the sets of two matchers, relative to the given root.
+    pub(crate) fn combine(
+        op: Combinator,
+        root: path::PathBuf,
+        a: Operand,
+        b: Operand,
+    ) -> PatternSet {
+        PatternSet {
+            globs: globset::GlobSet::empty(),
+            hybrids: vec![],
+            source: None,
+            fuzzy: vec![],
+            prefixes: PrefixSet::default(),
+            exclusions: None,
+            #[cfg(feature = "ignore")]
+            overrides: None,
+            combination: Some(Box::new(Combination {
+                op,
+                root,
+                operands: [a, b],
+            })),
+        }
+    }
+
+    /// Checks whether the provided path is a match for any of the globs in the set.
+    pub(crate) fn is_match<P>(&self, p: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        if let Some(combination) = &self.combination {
+            return combination.is_match(p.as_ref());
+        }
+        self.globs.is_match(p.as_ref())
+            || self
+                .hybrids
+                .iter()
+                .any(|re| is_hybrid_match(re, p.as_ref()))
+            || self.fuzzy.iter().any(|f| f.is_match(p.as_ref()))
+    }
+
+    /// Provides a single regular expression that matches the same paths as
+    /// [`PatternSet::is_match`], `None` for combined sets and sets with fuzzy globs.
+    pub(crate) fn to_regex(&self) -> Option<String> {
+        if self.combination.is_some() || !self.fuzzy.is_empty() {
+            return None;
+        }
+        let (builder, globs) = self.source.as_deref()?;
+        let globs: Vec<_> = globs.iter().map(String::as_str).collect();
+        match builder.regexes_for(&globs).ok()?.as_slice() {
+            [] => Some("[^\\s\\S]".to_string()),
+            [re] => Some(re.clone()),
+            regexes => Some(
+                regexes
+                    .iter()
+                    .map(|re| format!("(?:{re})"))
+                    .collect::<Vec<_>>()
+                    .join("|"),
+            ),
+        }
+    }
+
+    /// Checks whether the root itself may be a match, see [`PrefixSet::may_match_root`].
+    pub(crate) fn may_match_root(&self) -> bool {
+        // the operands of combinations are relative to different roots
+        self.combination.is_some() || self.prefixes.may_match_root()
+    }
+
+    /// Checks whether the folder `dir`, relative to the root, can contain any match.
+    pub(crate) fn may_contain_match(&self, dir: &path::Path) -> bool {
+        match &self.combination {
+            Some(combination) => combination.may_contain_match(dir),
+            None => self.prefixes.may_contain_match(dir),
+        }
+    }
+
+    /// Checks whether the given `path` of a walked entry is excluded, see [`Exclusions`].
+    ///
+    /// In contrast to [`PatternSet::is_match`] the `path` includes the root.
+    pub(crate) fn is_excluded(&self, path: &path::Path) -> bool {
+        self.exclusions
+            .as_ref()
+            .is_some_and(|exclusions| exclusions.is_excluded(path))
+    }
+
+    /// Checks whether the folder `dir` of a walked entry is excluded including its contents.
+    pub(crate) fn is_pruned(&self, dir: &path::Path) -> bool {
+        self.exclusions
+            .as_ref()
+            .is_some_and(|exclusions| exclusions.prune && exclusions.is_excluded(dir))
+    }
+
+    /// Checks whether the given `path` of a walked entry is not excluded by any overrides.
+    ///
+    /// In contrast to [`PatternSet::is_match`] the `path` includes the root.
+    #[cfg(feature = "ignore")]
+    pub(crate) fn is_allowed(&self, path: &path::Path, is_dir: bool) -> bool {
+        match &self.overrides {
+            Some(overrides) => !overrides.matched(path, is_dir).is_ignore(),
+            None => true,
+        }
+    }
+
+    /// Checks whether the given `path` of a walked entry is not excluded by any overrides.
+    #[cfg(not(feature = "ignore"))]
+    pub(crate) fn is_allowed(&self, _path: &path::Path, _is_dir: bool) -> bool {
+        true
+    }
+
+    /// Checks whether any overrides are applied, see [`PatternSet::is_allowed`].
+    fn has_overrides(&self) -> bool {
+        #[cfg(feature = "ignore")]
+        return self.overrides.is_some();
+        #[cfg(not(feature = "ignore"))]
+        return false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(glob: &str) -> Result<globset::Glob, Error> {
+        globset::GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| Error::globset(glob, err))
+    }
+
+    fn compile_set(glob: &str) -> Result<PatternSet, Error> {
+        crate::Builder::new(glob).glob_set_for(&[glob])
+    }
+
+    #[test]
+    fn prefixes() -> Result<(), String> {
+        let mut prefixes = PrefixSet::default();
+        prefixes.add("src/*/tests/*.rs", true, compile)?;
+        prefixes.add("lib/<re:v[0-9]+>/**/*.rs", true, compile)?;
+
+        let may_contain = |p: &str| prefixes.may_contain_match(path::Path::new(p));
+        assert!(may_contain("src"));
+        assert!(may_contain("src/a"));
+        assert!(may_contain("src/a/tests"));
+        assert!(!may_contain("src/a/tests/b"));
+        assert!(!may_contain("src/a/b"));
+        assert!(!may_contain("doc"));
+        assert!(may_contain("lib/v1/a/b/c"));
+        // regular expression segments are not checked
+        assert!(may_contain("lib/v1.0"));
+
+        prefixes.add("{doc,src/a}/*.md", true, compile)?;
+        assert!(prefixes.may_contain_match(path::Path::new("doc")));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn hybrid() -> Result<(), String> {
+        assert!(is_hybrid("src/<re:[a-z]+_test>/**"));
+        assert!(!is_hybrid("src/<re:[a-z]+_test/**"));
+
+        let re = compile_hybrid("src/<re:[a-z]+_test>/**", compile)?;
+        assert!(is_hybrid_match(&re, path::Path::new("src/net_test/a.rs")));
+        assert!(!is_hybrid_match(&re, path::Path::new("src/net_test")));
+        assert!(!is_hybrid_match(&re, path::Path::new("src/net0_test/a.rs")));
+
+        // the regular expression must not match across path separators
+        let re = compile_hybrid("src/<re:.*>/*.rs", compile)?;
+        assert!(is_hybrid_match(&re, path::Path::new("src/a/b.rs")));
+        assert!(!is_hybrid_match(&re, path::Path::new("src/a/b/c.rs")));
+
+        assert!(compile_hybrid("src/<re:[a-z>/*.rs", compile).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fuzzy() -> Result<(), String> {
+        assert_eq!(0, edit_distance("abc", "abc"));
+        assert_eq!(1, edit_distance("abc", "abd"));
+        assert_eq!(3, edit_distance("", "abc"));
+        assert_eq!(2, edit_distance("files", "fiels"));
+        assert!(is_subsequence("a0t", "a0_0.txt"));
+        assert!(!is_subsequence("t0a", "a0_0.txt"));
+
+        let glob = FuzzyGlob::new("a/*/a0_9.txt", Fuzzy::Distance(1), true, compile_set)?;
+        assert!(glob.is_match(path::Path::new("a/a0/a0_0.txt")));
+        assert!(!glob.is_match(path::Path::new("a/a0/A0_3.txt")));
+        assert!(!glob.is_match(path::Path::new("b/a0/a0_0.txt")));
+
+        let glob = FuzzyGlob::new("**/A0txt", Fuzzy::Subsequence, false, compile_set)?;
+        assert!(glob.is_match(path::Path::new("a/a0/a0_0.txt")));
+        assert!(glob.is_match(path::Path::new("a0_0.txt")));
+
+        #[cfg(feature = "regex")]
+        {
+            let glob = FuzzyGlob::new(
+                "<re:a[0-9]>/a0_9.txt",
+                Fuzzy::Distance(1),
+                true,
+                compile_set,
+            )?;
+            assert!(glob.is_match(path::Path::new("a0/a0_0.txt")));
+            assert!(!glob.is_match(path::Path::new("b0/a0_0.txt")));
+        }
+
+        let glob = FuzzyGlob::new("**/a0txt", Fuzzy::Subsequence, true, |g| {
+            crate::Builder::new(g)
+                .globstar_max_depth(1)
+                .glob_set_for(&[g])
+        })?;
+        assert!(glob.is_match(path::Path::new("a0/a0_0.txt")));
+        assert!(!glob.is_match(path::Path::new("a/a0/a0_0.txt")));
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::path;
+
+use crate::{Glob, GlobSet, Matcher};
+
+/// Common interface for all types that match paths.
+///
+/// This trait is implemented by [`Matcher`], [`Glob`] and [`GlobSet`], as well as for all
+/// closures `Fn(&Path) -> bool`. This allows to accept `impl PathMatcher` in downstream code,
+/// and to test such code using simple closures.
+///
+/// ```
+/// use globmatch::PathMatcher;
+/// use std::path::Path;
+///
+/// fn count_matches(m: &impl PathMatcher, paths: &[&str]) -> usize {
+///     paths.iter().filter(|p| m.is_match(Path::new(p))).count()
+/// }
+///
+/// # fn example() -> Result<(), String> {
+/// let paths = ["src/lib.rs", "src/main.rs", "readme.md"];
+/// let glob = globmatch::Builder::new("*.rs").build_glob_set()?;
+///
+/// assert_eq!(2, count_matches(&glob, &paths));
+/// assert_eq!(1, count_matches(&|p: &Path| p.ends_with("readme.md"), &paths));
+///
+/// // matchers can be combined, e.g., to compose include and exclude logic
+/// let main = globmatch::Builder::new("main.rs").build_glob_set()?;
+/// let src = |p: &Path| p.starts_with("src");
+/// assert_eq!(1, count_matches(&glob.and(src).and(main.not()), &paths));
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub trait PathMatcher {
+    /// Checks whether the provided path is a match.
+    fn is_match(&self, path: &path::Path) -> bool;
+
+    /// Combines two matchers such that a path must match both of them.
+    fn and<M>(self, other: M) -> And<Self, M>
+    where
+        Self: Sized,
+        M: PathMatcher,
+    {
+        And(self, other)
+    }
+
+    /// Combines two matchers such that a path must match any of them.
+    fn or<M>(self, other: M) -> Or<Self, M>
+    where
+        Self: Sized,
+        M: PathMatcher,
+    {
+        Or(self, other)
+    }
+
+    /// Inverts the matcher such that a path matches if it is not matched by this matcher.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+
+    /// Transforms the matcher into a closure, e.g., to pass combined matchers as predicate to
+    /// [`IterAll::filter_entry`](crate::IterAll::filter_entry).
+    ///
+    /// ```
+    /// use globmatch::PathMatcher;
+    ///
+    /// # fn example() -> Result<(), String> {
+    /// let hidden = |p: &std::path::Path| globmatch::is_hidden_path(p);
+    /// let target = globmatch::Builder::new("**/target").build_glob()?;
+    ///
+    /// let paths: Vec<_> = globmatch::Builder::new("test-files/c-simple/**/*.txt")
+    ///     .build(env!("CARGO_MANIFEST_DIR"))?
+    ///     .into_iter()
+    ///     .filter_entry(hidden.or(target).not().into_fn())
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(6 + 1, paths.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    fn into_fn(self) -> impl Fn(&path::Path) -> bool
+    where
+        Self: Sized,
+    {
+        move |path: &path::Path| self.is_match(path)
+    }
+}
+
+/// Matches a path if both matchers match, created via [`PathMatcher::and`].
+#[derive(Clone, Debug)]
+pub struct And<A, B>(A, B);
+
+impl<A, B> PathMatcher for And<A, B>
+where
+    A: PathMatcher,
+    B: PathMatcher,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self.0.is_match(path) && self.1.is_match(path)
+    }
+}
+
+/// Matches a path if any of the two matchers match, created via [`PathMatcher::or`].
+#[derive(Clone, Debug)]
+pub struct Or<A, B>(A, B);
+
+impl<A, B> PathMatcher for Or<A, B>
+where
+    A: PathMatcher,
+    B: PathMatcher,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self.0.is_match(path) || self.1.is_match(path)
+    }
+}
+
+/// Matches a path if the matcher does not match, created via [`PathMatcher::not`].
+#[derive(Clone, Debug)]
+pub struct Not<A>(A);
+
+impl<A> PathMatcher for Not<A>
+where
+    A: PathMatcher,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        !self.0.is_match(path)
+    }
+}
+
+/// A list of matchers matches a path if any of the matchers match, i.e., an empty list never
+/// matches any path.
+impl<M> PathMatcher for [M]
+where
+    M: PathMatcher,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self.iter().any(|m| m.is_match(path))
+    }
+}
+
+/// Refer to the implementation for slices.
+impl<M> PathMatcher for Vec<M>
+where
+    M: PathMatcher,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self.as_slice().is_match(path)
+    }
+}
+
+impl<F> PathMatcher for F
+where
+    F: Fn(&path::Path) -> bool,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self(path)
+    }
+}
+
+/// The path is matched against the resolved glob, i.e., it must be relative to the
+/// [`Matcher::root_path`].
+impl<'a, P> PathMatcher for Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    fn is_match(&self, path: &path::Path) -> bool {
+        self.matcher.is_match(path)
+    }
+}
+
+impl<'a> PathMatcher for Glob<'a> {
+    fn is_match(&self, path: &path::Path) -> bool {
+        Glob::is_match(self, path)
+    }
+}
+
+impl<'a> PathMatcher for GlobSet<'a> {
+    fn is_match(&self, path: &path::Path) -> bool {
+        GlobSet::is_match(self, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+
+    fn is_match(m: &impl PathMatcher, p: &str) -> bool {
+        m.is_match(path::Path::new(p))
+    }
+
+    #[test]
+    fn path_matcher() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+
+        // the matcher resolves `a` as root, the remaining glob is `**/*.txt`
+        let matcher = Builder::new("a/**/*.txt").build(&root)?;
+        assert!(is_match(&matcher, "a0/a0_0.txt"));
+        assert!(!is_match(&matcher, "a0/a0_2.md"));
+
+        let glob = Builder::new("*.txt").build_glob()?;
+        assert!(is_match(&glob, "b_0.txt"));
+        assert!(!is_match(&glob, "b/b_0.txt"));
+
+        let glob_set = Builder::new("*.txt").build_glob_set()?;
+        assert!(is_match(&glob_set, "b/b_0.txt"));
+
+        let closure = |p: &path::Path| p.extension().is_some_and(|e| e == "md");
+        assert!(is_match(&closure, "a/a0/a0_2.md"));
+        assert!(!is_match(&closure, "b/b_0.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn path_matcher_combinators() -> Result<(), String> {
+        let txt = Builder::new("*.txt").build_glob_set()?;
+        let hidden = Builder::new(".*/**").build_glob()?;
+        let a0 = |p: &path::Path| p.starts_with("a/a0");
+
+        let m = txt.and(hidden.not()).and(a0.not());
+        assert!(is_match(&m, "b/b_0.txt"));
+        assert!(!is_match(&m, ".hidden/h_0.txt"));
+        assert!(!is_match(&m, "a/a0/a0_0.txt"));
+        assert!(!is_match(&m, "a/a1/a1_0.md"));
+
+        let md = Builder::new("*.md").build_glob_set()?;
+        let m = m.or(md);
+        assert!(is_match(&m, "a/a1/a1_0.md"));
+
+        let set = vec![
+            Builder::new("*.md").build_glob_set()?,
+            Builder::new("*.txt").build_glob_set()?,
+        ];
+        assert!(is_match(&set, "a/a1/a1_0.md"));
+        assert!(!is_match(&set, "a/a1/a1_0.rs"));
+        assert!(!is_match(&Vec::<GlobSet<'_>>::new(), "a/a1/a1_0.md"));
+
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let skipped = || {
+            let a = |p: &path::Path| p.ends_with("a");
+            Builder::new("**/b").build_glob().map(|b| a.or(b))
+        };
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry(skipped()?.not().into_fn())
+            .collect::<Result<_, _>>()?;
+        assert_eq!(2 + 1, paths.len());
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .threads(2)
+            .build(&root)?
+            .into_par_iter()
+            .filter_entry(skipped()?.not().into_fn())
+            .collect::<Result<_, _>>()?;
+        assert_eq!(2 + 1, paths.len());
+        Ok(())
+    }
+}
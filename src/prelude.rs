@@ -0,0 +1,31 @@
+//! Convenience re-exports of the most commonly used types and functions.
+//!
+//! ```
+//! use globmatch::prelude::*;
+//!
+//! # fn example() -> Result<(), String> {
+//! let paths: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+//!     .build(env!("CARGO_MANIFEST_DIR"))?
+//!     .into_iter()
+//!     .filter_entry(|p| !is_hidden_path(p))
+//!     .flatten()
+//!     .collect();
+//!
+//! assert_eq!(6 + 1, paths.len());
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+
+pub use crate::wrappers::{match_paths, MatchReport, PathOrder};
+#[cfg(feature = "regex")]
+pub use crate::Rewriter;
+pub use crate::{
+    ascend, is_hidden_entry, is_hidden_path, AscendLimit, Builder, CancellationHandle,
+    CaseSensitivity, DirectoryMatch, EntryInfo, Error, FileType, Fuzzy, GitignoreSet, Glob,
+    GlobSet, HiddenPolicy, IterAll, IterEntries, IterFilter, IterMulti, IterMultiRoot,
+    IterParallel, IterRaw, LintKind, LintWarning, LiveMatcher, MatchEntry, MatchExplanation,
+    Matcher, MetadataFilter, MissingRoot, MultiMatch, MultiMatcher, MultiRootMatcher, OnError,
+    PathMatcher, PathNormalization, PatternLimits, Progress, RootMatch, RootSymlinks, SkipReason,
+    VirtualTree, WalkLimits, WalkObserver, WalkSource,
+};
@@ -0,0 +1,110 @@
+//! Curated filter presets for commonly skipped files and folders.
+//!
+//! Each preset is available as a list of globs, e.g., to combine it with custom globs, and as a
+//! list of [`GlobSet`]s that can be passed as `filter_entry` or `filter_post` to the functions
+//! in [`wrappers`](crate::wrappers). Since a [`GlobSet`] matches its glob in any folder, the
+//! contents of matching folders are skipped as well when used as `filter_entry`.
+//!
+//! ```
+//! # fn example() -> Result<(), String> {
+//! use globmatch::{presets, wrappers};
+//!
+//! let root = env!("CARGO_MANIFEST_DIR");
+//! let candidates = wrappers::build_matchers(&["**/*.rs"], &root)?;
+//!
+//! let mut filter_entry = presets::vcs();
+//! filter_entry.extend(presets::build_artifacts());
+//! filter_entry.extend(presets::os_junk());
+//!
+//! let (paths, _) = wrappers::match_paths(candidates, Some(filter_entry), None);
+//! assert!(paths.iter().all(|p| !p.starts_with(format!("{root}/target"))));
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+
+use crate::{Builder, GlobSet};
+
+/// Folders of version control systems, see [`vcs`].
+pub const VCS: &[&str] = &[".git", ".hg", ".svn", ".bzr", "_darcs", "CVS"];
+
+/// Build outputs and dependency folders of common toolchains, see [`build_artifacts`].
+pub const BUILD_ARTIFACTS: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "__pycache__",
+    ".tox",
+    ".venv",
+];
+
+/// Metadata files created by operating systems and their file managers, see [`os_junk`].
+pub const OS_JUNK: &[&str] = &[
+    ".DS_Store",
+    "._*",
+    ".Spotlight-V100",
+    ".Trashes",
+    "Thumbs.db",
+    "ehthumbs.db",
+    "desktop.ini",
+    "$RECYCLE.BIN",
+];
+
+/// Provides the filters for the folders of version control systems, e.g., `.git`, `.hg` and
+/// `.svn`, matched case sensitive.
+pub fn vcs() -> Vec<GlobSet<'static>> {
+    build(VCS, true)
+}
+
+/// Provides the filters for build outputs and dependency folders, e.g., `target`,
+/// `node_modules` and `dist`, matched case sensitive.
+pub fn build_artifacts() -> Vec<GlobSet<'static>> {
+    build(BUILD_ARTIFACTS, true)
+}
+
+/// Provides the filters for metadata files created by operating systems, e.g., `.DS_Store` and
+/// `Thumbs.db`. These files are created on case insensitive file systems and are thus matched
+/// case insensitive.
+pub fn os_junk() -> Vec<GlobSet<'static>> {
+    build(OS_JUNK, false)
+}
+
+fn build(globs: &[&'static str], case_sensitive: bool) -> Vec<GlobSet<'static>> {
+    globs
+        .iter()
+        .map(|glob| {
+            Builder::new(glob)
+                .case_sensitive(case_sensitive)
+                .build_glob_set()
+                .expect("presets must be valid globs")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrappers::is_match_ordered;
+
+    #[test]
+    fn presets() {
+        let vcs = vcs();
+        assert_eq!(VCS.len(), vcs.len());
+        assert!(is_match_ordered(&vcs, "/home/user/project/.git"));
+        assert!(is_match_ordered(&vcs, ".svn"));
+        assert!(!is_match_ordered(&vcs, "/home/user/project/.github"));
+
+        let artifacts = build_artifacts();
+        assert!(is_match_ordered(&artifacts, "crate/target"));
+        assert!(is_match_ordered(&artifacts, "web/node_modules"));
+        assert!(!is_match_ordered(&artifacts, "crate/Target"));
+
+        let junk = os_junk();
+        assert!(is_match_ordered(&junk, "photos/Thumbs.db"));
+        assert!(is_match_ordered(&junk, "photos/thumbs.db"));
+        assert!(is_match_ordered(&junk, "music/._track.mp3"));
+        assert!(is_match_ordered(&junk, "D:/$RECYCLE.BIN"));
+        assert!(!is_match_ordered(&junk, "photos/holiday.jpg"));
+    }
+}
@@ -0,0 +1,224 @@
+use std::{ffi, fmt, path};
+
+use crate::error::Error;
+use crate::Glob;
+
+/// Part of a parsed template, see [`Rewriter`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Part {
+    Literal(String),
+    /// The entire matched path for `{0}`, the value of the `n`-th wildcard for `{n}`.
+    Capture(usize),
+}
+
+/// Maps matching paths to output paths using a template, created via
+/// [`Builder::build_rewriter`](crate::Builder::build_rewriter).
+///
+/// The template refers to the values captured by the wildcards of the glob (see
+/// [`Glob::captures`]) using placeholders, where `{1}` is replaced by the value of the first
+/// wildcard, `{2}` by the value of the second wildcard and so on. The placeholder `{0}` is
+/// replaced by the entire matched path. Literal braces are written as `{{` and `}}`.
+///
+/// Other than [`Glob::captures`], the values are not required to be valid UTF-8, i.e., paths
+/// that are not valid UTF-8 are rewritten as well.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let rewriter = globmatch::Builder::new("src/**/*_test.rs").build_rewriter("dst/{1}/{2}.bak")?;
+///
+/// assert_eq!(
+///     Some(std::path::PathBuf::from("dst/net/tcp.bak")),
+///     rewriter.rewrite("src/net/tcp_test.rs")
+/// );
+/// assert_eq!(None, rewriter.rewrite("src/net/tcp.rs"));
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Rewriter<'a> {
+    glob: Glob<'a>,
+    template: String,
+    parts: Vec<Part>,
+}
+
+impl<'a> Rewriter<'a> {
+    /// Creates a new [`Rewriter`] for the compiled glob.
+    ///
+    /// Fails with [`Error::InvalidTemplate`] if the template contains invalid placeholders, or
+    /// placeholders that refer to wildcards that do not exist within the glob.
+    pub(crate) fn new(glob: Glob<'a>, template: &str) -> Result<Rewriter<'a>, Error> {
+        let parts = parse(template)?;
+        let captures = glob.captures.len().ok_or_else(|| {
+            Error::pattern(glob.glob(), "Values of the wildcards cannot be captured")
+        })?;
+        let max = parts.iter().fold(0, |max, part| match part {
+            Part::Capture(n) => max.max(*n),
+            Part::Literal(_) => max,
+        });
+        if max > captures {
+            return Err(Error::template(
+                template,
+                format!("Placeholder {{{max}}} exceeds the {captures} wildcards of the glob"),
+            ));
+        }
+        Ok(Rewriter {
+            glob,
+            template: template.to_string(),
+            parts,
+        })
+    }
+
+    /// Provides the glob that paths are matched against.
+    pub fn glob(&self) -> &Glob<'a> {
+        &self.glob
+    }
+
+    /// Provides the template used to create the output paths.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Provides the output path for the given path, `None` if the path is not a match.
+    pub fn rewrite<P>(&self, p: P) -> Option<path::PathBuf>
+    where
+        P: AsRef<path::Path>,
+    {
+        let p = p.as_ref();
+        let captures = self.glob.captures.captures_os(p)?;
+        let mut rewritten = ffi::OsString::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(s) => rewritten.push(s),
+                Part::Capture(0) => rewritten.push(p),
+                Part::Capture(n) => rewritten.push(&captures[n - 1]),
+            }
+        }
+        Some(path::PathBuf::from(rewritten))
+    }
+}
+
+impl fmt::Display for Rewriter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.glob.glob(), self.template)
+    }
+}
+
+/// Parses the template into literals and placeholders.
+fn parse(template: &str) -> Result<Vec<Part>, Error> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => index.push(c),
+                        None => return Err(Error::template(template, "Unclosed placeholder")),
+                    }
+                }
+                let n = index.parse().map_err(|_| {
+                    Error::template(template, format!("Invalid placeholder '{{{index}}}'"))
+                })?;
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(Part::Capture(n));
+            }
+            '}' => return Err(Error::template(template, "Unmatched '}'")),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+
+    #[test]
+    fn templates() {
+        assert_eq!(
+            Ok(vec![
+                Part::Literal("dst/{".to_string()),
+                Part::Capture(1),
+                Part::Literal("}/".to_string()),
+                Part::Capture(0),
+            ]),
+            parse("dst/{{{1}}}/{0}")
+        );
+        assert!(parse("dst/{1").is_err());
+        assert!(parse("dst/{a}").is_err());
+        assert!(parse("dst/}").is_err());
+    }
+
+    #[test]
+    fn rewrite() -> Result<(), String> {
+        let rewriter = Builder::new("**/{a,b}?_*.txt").build_rewriter("out/{2}{3}/{1}.{4}")?;
+        assert_eq!(
+            Some(path::PathBuf::from("out/a1/c.0")),
+            rewriter.rewrite("c/a1_0.txt")
+        );
+        assert_eq!(
+            Some(path::PathBuf::from("out/b2/.x")),
+            rewriter.rewrite("b2_x.txt")
+        );
+        assert_eq!(None, rewriter.rewrite("c/c1_0.txt"));
+        assert_eq!(
+            "**/{a,b}?_*.txt -> out/{2}{3}/{1}.{4}",
+            rewriter.to_string()
+        );
+
+        let rewriter = Builder::new("*.txt").build_rewriter("{0}.bak")?;
+        assert_eq!(
+            Some(path::PathBuf::from("a.txt.bak")),
+            rewriter.rewrite("a.txt")
+        );
+
+        assert!(matches!(
+            Builder::new("*.txt").build_rewriter("{2}"),
+            Err(Error::InvalidTemplate { .. })
+        ));
+        assert!(matches!(
+            Builder::new("[a").build_rewriter("{1}"),
+            Err(Error::InvalidPattern { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rewrite_non_utf8() -> Result<(), String> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let p = path::Path::new(ffi::OsStr::from_bytes(b"src/\xff.txt"));
+        let rewriter = Builder::new("src/*.txt").build_rewriter("{0}.bak")?;
+        assert_eq!(
+            Some(path::PathBuf::from(ffi::OsStr::from_bytes(
+                b"src/\xff.txt.bak"
+            ))),
+            rewriter.rewrite(p)
+        );
+        let rewriter = Builder::new("src/*.txt").build_rewriter("dst/{1}")?;
+        assert_eq!(
+            Some(path::PathBuf::from(ffi::OsStr::from_bytes(b"dst/\xff"))),
+            rewriter.rewrite(p)
+        );
+        Ok(())
+    }
+}
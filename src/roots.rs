@@ -0,0 +1,143 @@
+use std::{collections, path};
+
+use crate::error::Error;
+use crate::{utils, IterAll, Matcher};
+
+/// Matcher for a single glob relative to multiple roots, see
+/// [`Builder::build_many`](crate::Builder::build_many).
+///
+/// Each root is walked by its own [`Matcher`], in the order in which the roots have been
+/// provided. Paths that are found within overlapping roots, e.g., for the roots `src` and
+/// `src/core`, are only yielded once.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = env!("CARGO_MANIFEST_DIR");
+/// let matcher = globmatch::Builder::new("**/*.txt").build_many([
+///     format!("{root}/test-files/c-simple/a"),
+///     format!("{root}/test-files/c-simple/a/a0"),
+///     format!("{root}/test-files/c-simple/b"),
+/// ])?;
+///
+/// assert_eq!(3, matcher.matchers().len());
+/// let paths: Vec<_> = matcher.into_iter().flatten().collect();
+/// assert_eq!(4 + 1 + 1, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MultiRootMatcher<'a> {
+    matchers: Vec<Matcher<'a, path::PathBuf>>,
+}
+
+impl<'a> MultiRootMatcher<'a> {
+    pub(crate) fn new(matchers: Vec<Matcher<'a, path::PathBuf>>) -> MultiRootMatcher<'a> {
+        MultiRootMatcher { matchers }
+    }
+
+    /// Provides the [`Matcher`]s for all roots, in the order in which the roots are walked.
+    pub fn matchers(&self) -> &[Matcher<'a, path::PathBuf>] {
+        &self.matchers
+    }
+
+    /// Converts the [`MultiRootMatcher`] into the [`Matcher`]s for all roots, e.g., to pass them
+    /// to the functions in [`wrappers`](crate::wrappers).
+    pub fn into_matchers(self) -> Vec<Matcher<'a, path::PathBuf>> {
+        self.matchers
+    }
+}
+
+impl IntoIterator for MultiRootMatcher<'_> {
+    type Item = Result<path::PathBuf, Error>;
+    type IntoIter = IterMultiRoot;
+
+    /// Transform the [`MultiRootMatcher`] into an iterator walking all roots.
+    fn into_iter(self) -> Self::IntoIter {
+        IterMultiRoot {
+            iters: self.matchers.into_iter().map(Matcher::into_iter).collect(),
+            seen: collections::HashSet::new(),
+        }
+    }
+}
+
+/// Iterator created from a [`MultiRootMatcher`].
+///
+/// Yields the matches of all roots in the order in which the roots have been provided. Errors are
+/// yielded as for [`IterAll`], whereas paths that have already been yielded for a previous root
+/// are skipped.
+#[derive(Debug)]
+pub struct IterMultiRoot {
+    iters: collections::VecDeque<IterAll<path::PathBuf>>,
+    /// Absolute and lexically normalized walked paths of all matches that have been yielded.
+    seen: collections::HashSet<path::PathBuf>,
+}
+
+impl Iterator for IterMultiRoot {
+    type Item = Result<path::PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = match self.iters.front_mut()?.next_walked() {
+                Some(next) => next,
+                None => {
+                    self.iters.pop_front();
+                    continue;
+                }
+            };
+            match next {
+                Ok((walked, yielded)) => {
+                    // the yielded path may be relative, duplicates are detected via the walked path
+                    let walked = path::absolute(&walked).unwrap_or(walked);
+                    if self.seen.insert(utils::normalize_path(&walked)) {
+                        return Some(Ok(yielded));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Builder;
+
+    #[test]
+    fn match_many_roots() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let roots = [
+            format!("{root}/test-files/c-simple"),
+            format!("{root}/test-files/c-simple/a/../a"),
+            format!("{root}/test-files/c-simple/b/b_0.txt"),
+        ];
+
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build_many(&roots)?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        assert_eq!(6 + 2 + 1, paths.len());
+
+        // relative paths of different roots are not mistaken for duplicates
+        let paths: Vec<std::path::PathBuf> = Builder::new("*.txt")
+            .relative_paths(true)
+            .build_many([
+                format!("{root}/test-files/c-simple/a/a1"),
+                format!("{root}/test-files/c-simple/a/a2"),
+                format!("{root}/test-files/c-simple/a/a1"),
+            ])?
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        assert_eq!(
+            vec![
+                std::path::Path::new("a1_0.txt"),
+                std::path::Path::new("a2_0.txt")
+            ],
+            paths
+        );
+
+        let missing = Builder::new("*.txt").build_many([format!("{root}/missing")]);
+        assert!(missing.is_err());
+        Ok(())
+    }
+}
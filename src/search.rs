@@ -0,0 +1,293 @@
+use std::{collections, fs, path};
+
+use crate::{AscendLimit, Builder, Error, Matcher};
+
+/// Path found by a search across multiple roots, see [`Builder::search`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RootMatch {
+    /// Index of the root within the list of roots that has been searched.
+    pub root: usize,
+    /// The matching path, including the root.
+    pub path: path::PathBuf,
+    /// The matching path relative to its root.
+    pub relative: path::PathBuf,
+}
+
+/// Implementation of [`Builder::search`].
+pub(crate) fn search<R>(builder: &Builder<'_>, roots: &[R]) -> Result<Vec<RootMatch>, Error>
+where
+    R: AsRef<path::Path>,
+{
+    let mut matches = vec![];
+    for (index, root) in roots.iter().enumerate() {
+        let root = root.as_ref();
+        // roots are typically provided by the environment, e.g., via a `PATH` variable, and
+        // it is not an error if any of these roots does not exist
+        if !root.exists() {
+            continue;
+        }
+
+        let matcher = builder.build(root)?;
+        let resolved = matcher.root_path().to_path_buf();
+        for entry in matcher {
+            let path = entry?;
+            let relative = path
+                .strip_prefix(root)
+                .or_else(|_| path.strip_prefix(&resolved))
+                .map(path::PathBuf::from)
+                .unwrap_or_else(|_| path.clone());
+            matches.push(RootMatch {
+                root: index,
+                path,
+                relative,
+            });
+        }
+    }
+    Ok(matches)
+}
+
+/// Implementation of [`Builder::search_overlay`].
+pub(crate) fn overlay<R>(builder: &Builder<'_>, roots: &[R]) -> Result<Vec<RootMatch>, Error>
+where
+    R: AsRef<path::Path>,
+{
+    let mut seen = collections::HashSet::new();
+    let mut matches = search(builder, roots)?;
+    // the matches are provided in the order of the roots, thus the first occurrence shadows
+    // all occurrences in later roots
+    matches.retain(|m| seen.insert(m.relative.clone()));
+    Ok(matches)
+}
+
+/// Searches the glob within the directory `start` and all of its ancestors using the default
+/// settings of the [`Builder`], see [`Builder::ascend`].
+///
+/// # Errors
+///
+/// Refer to [`Builder::ascend`].
+pub fn ascend<P>(start: P, glob: &str) -> Result<Vec<path::PathBuf>, Error>
+where
+    P: AsRef<path::Path>,
+{
+    Builder::new(glob).ascend(start, &AscendLimit::default())
+}
+
+/// Implementation of [`Builder::ascend`].
+pub(crate) fn ascend_with<P>(
+    builder: &Builder<'_>,
+    start: P,
+    limit: &AscendLimit,
+) -> Result<Vec<path::PathBuf>, Error>
+where
+    P: AsRef<path::Path>,
+{
+    Ok(nearest_ancestor(builder, start, limit)?
+        .map(|(_, paths)| paths)
+        .unwrap_or_default())
+}
+
+/// Provides the nearest ancestor of `start` that contains at least one match for the glob of the
+/// `builder`, together with all matches within this ancestor.
+fn nearest_ancestor<P>(
+    builder: &Builder<'_>,
+    start: P,
+    limit: &AscendLimit,
+) -> Result<Option<(path::PathBuf, Vec<path::PathBuf>)>, Error>
+where
+    P: AsRef<path::Path>,
+{
+    let glob: &str = &builder.glob;
+    if glob.is_empty() {
+        return Err(Error::EmptyPattern);
+    }
+    if path::Path::new(glob).is_absolute() || glob.split('/').any(|c| c == "." || c == "..") {
+        return Err(Error::pattern(
+            glob,
+            "Relative path components and absolute paths are not supported",
+        ));
+    }
+    builder.check_limits()?;
+    let matcher = builder.glob_set_for(&[glob])?;
+
+    let start = fs::canonicalize(start.as_ref())
+        .map_err(|err| Error::from_resolve(start.as_ref(), glob, err))?;
+    let boundary = limit
+        .boundary
+        .as_ref()
+        .and_then(|b| fs::canonicalize(b).ok());
+
+    // the number of entries within each ancestor is unbounded, thus only the entries up to the
+    // depth of the glob are matched
+    let depth = glob.split('/').count();
+    for (i, dir) in start.ancestors().enumerate() {
+        if limit.max_depth.is_some_and(|max| i > max) {
+            break;
+        }
+
+        let mut paths: Vec<_> = walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .max_depth(depth)
+            .into_iter()
+            .flatten()
+            .filter(|e| match e.path().strip_prefix(dir) {
+                Ok(p) => matcher.is_match(p),
+                Err(_) => false,
+            })
+            .map(|e| e.into_path())
+            .collect();
+
+        if !paths.is_empty() {
+            paths.sort_unstable();
+            return Ok(Some((path::PathBuf::from(dir), paths)));
+        }
+        if boundary.as_deref() == Some(dir) {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+/// Implementation of [`Builder::build_from_ancestor`].
+pub(crate) fn build_from_ancestor<'a, P>(
+    builder: &Builder<'a>,
+    start: P,
+    marker: &str,
+) -> Result<Matcher<'a, path::PathBuf>, Error>
+where
+    P: AsRef<path::Path>,
+{
+    let marker_builder = Builder::new(marker);
+    match nearest_ancestor(&marker_builder, start.as_ref(), &AscendLimit::default())? {
+        Some((dir, _)) => builder.build(dir),
+        None => Err(Error::pattern(
+            marker,
+            format!(
+                "No ancestor of {} contains a match",
+                start.as_ref().to_string_lossy()
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_roots() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let roots = [
+            format!("{root}/a/a1"),
+            format!("{root}/does-not-exist"),
+            format!("{root}/a/a2"),
+            format!("{root}/b"),
+        ];
+
+        let matches = search(&Builder::new("*_0.txt"), &roots)?;
+        assert_eq!(
+            vec![
+                (0, path::PathBuf::from("a1_0.txt")),
+                (2, path::PathBuf::from("a2_0.txt")),
+                (3, path::PathBuf::from("b_0.txt")),
+            ],
+            matches
+                .iter()
+                .map(|m| (m.root, m.relative.clone()))
+                .collect::<Vec<_>>()
+        );
+        assert!(matches.iter().all(|m| m.path.is_file()));
+
+        let matches = search(&Builder::new("../a1/*.txt"), &roots)?;
+        assert_eq!(2, matches.len());
+        assert!(search(&Builder::new("a["), &roots).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn search_overlay() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let roots = [format!("{root}/a/a1"), format!("{root}/a/a0"), root.clone()];
+
+        // none of the relative paths exists within more than one root
+        let matches = overlay(&Builder::new("*.txt"), &roots)?;
+        assert_eq!(1 + 3 + 1, matches.len());
+
+        let roots = [format!("{root}/a/a1"), format!("{root}/a/a2"), root];
+        let matches = search(&Builder::new("../a*/*.txt"), &roots)?;
+        assert_eq!(5 + 5, matches.len());
+
+        // all paths of the second root are shadowed by the first one
+        let matches = overlay(&Builder::new("../a*/*.txt"), &roots)?;
+        assert_eq!(5, matches.len());
+        assert!(matches.iter().all(|m| m.root == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn search_ascend() -> Result<(), String> {
+        let manifest = fs::canonicalize(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let start = manifest.join("test-files/c-simple/a/a0");
+
+        let limit = AscendLimit::default();
+        assert_eq!(
+            vec![manifest.join("Cargo.toml")],
+            ascend_with(&Builder::new("Cargo.toml"), &start, &limit)?
+        );
+        assert_eq!(
+            vec![manifest.join("test-files/c-simple/a/a0/A0_3.txt")],
+            ascend_with(&Builder::new("A*.txt"), &start, &limit)?
+        );
+        assert_eq!(
+            vec![
+                manifest.join("test-files/c-simple/.hidden/h_0.txt"),
+                manifest.join("test-files/c-simple/.hidden/h_1.txt"),
+            ],
+            ascend_with(&Builder::new(".hidden/*.txt"), &start, &limit)?
+        );
+
+        let limit = AscendLimit {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        assert!(ascend_with(&Builder::new("Cargo.toml"), &start, &limit)?.is_empty());
+        assert_eq!(
+            1,
+            ascend_with(&Builder::new("some_file.txt"), &start, &limit)?.len()
+        );
+
+        let limit = AscendLimit {
+            boundary: Some(manifest.join("test-files")),
+            ..Default::default()
+        };
+        assert!(ascend_with(&Builder::new("Cargo.toml"), &start, &limit)?.is_empty());
+
+        assert!(ascend_with(&Builder::new("../Cargo.toml"), &start, &limit).is_err());
+        assert!(ascend_with(&Builder::new(""), &start, &limit).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn search_from_ancestor() -> Result<(), String> {
+        let manifest = fs::canonicalize(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let start = manifest.join("test-files/c-simple/a/a0");
+
+        let paths: Vec<_> = build_from_ancestor(&Builder::new("src/*.rs"), &start, "Cargo.toml")?
+            .into_iter()
+            .flatten()
+            .collect();
+        assert!(paths.contains(&manifest.join("src/search.rs")));
+
+        let paths: Vec<_> = build_from_ancestor(&Builder::new("**/*.txt"), &start, "b/b_0.txt")?
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(6 + 2 + 1, paths.len());
+
+        let err = build_from_ancestor(&Builder::new("**"), &start, "does-not-exist")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("'does-not-exist': No ancestor of"));
+        Ok(())
+    }
+}
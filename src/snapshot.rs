@@ -0,0 +1,463 @@
+//! Snapshots of match results, to detect changes between runs.
+//!
+//! A [`Snapshot`] records a set of paths, e.g., as provided by
+//! [`wrappers::match_paths`](crate::wrappers::match_paths), together with the optional size,
+//! modification time and hash of each file. Snapshots can be written to and read from a file
+//! such that the next run of a tool can determine which paths have been added, removed or
+//! changed since the last run, e.g., for incremental formatters.
+//!
+//! ```
+//! use globmatch::snapshot::{Snapshot, SnapshotOptions};
+//!
+//! # fn example() -> Result<(), String> {
+//! let root = env!("CARGO_MANIFEST_DIR");
+//! let options = SnapshotOptions { size: true, modified: true };
+//!
+//! let paths = globmatch::Builder::new("test-files/c-simple/a/**/*.txt")
+//!     .build(root)?
+//!     .collect_sorted()
+//!     .map_err(|err| err.to_string())?;
+//! let previous = Snapshot::from_paths(&paths[1..], options).map_err(|err| err.to_string())?;
+//! let current = Snapshot::from_paths(&paths, options).map_err(|err| err.to_string())?;
+//!
+//! let diff = previous.diff(&current);
+//! assert_eq!(vec![paths[0].clone()], diff.added);
+//! assert!(diff.removed.is_empty() && diff.changed.is_empty());
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+//!
+//! # Format
+//!
+//! Snapshots are stored as UTF-8 text. The first line is the header `# globmatch snapshot v1`,
+//! followed by one line per path with the tab-separated fields `size`, `modified` (nanoseconds
+//! since the UNIX epoch), `hash` and `path`. Fields that have not been recorded are stored as
+//! `-`. Backslashes, tabs and line breaks within the hash and the path are escaped as `\\`,
+//! `\t`, `\n` and `\r`, a hash that is `-` itself is stored as `\-`. On Unix, bytes of paths
+//! that are not valid UTF-8 are escaped as `\xNN`. Paths are stored in sorted order.
+
+use std::{collections, fs, io, path, time};
+
+const HEADER: &str = "# globmatch snapshot v1";
+
+/// Defines which attributes are recorded by [`Snapshot::from_paths`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SnapshotOptions {
+    /// Record the size of each file.
+    pub size: bool,
+    /// Record the modification time of each file.
+    pub modified: bool,
+}
+
+/// A single path within a [`Snapshot`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SnapshotEntry {
+    /// The recorded path.
+    pub path: path::PathBuf,
+    /// Size of the file in bytes, if recorded.
+    pub size: Option<u64>,
+    /// Modification time in nanoseconds since the UNIX epoch, if recorded.
+    pub modified: Option<u128>,
+    /// Hash of the contents of the file, if recorded.
+    pub hash: Option<String>,
+}
+
+impl SnapshotEntry {
+    /// Creates a new entry for the given path without any attributes.
+    pub fn new<P>(path: P) -> SnapshotEntry
+    where
+        P: AsRef<path::Path>,
+    {
+        SnapshotEntry {
+            path: path::PathBuf::from(path.as_ref()),
+            size: None,
+            modified: None,
+            hash: None,
+        }
+    }
+
+    /// Checks whether the two entries differ in any attribute that is recorded by both entries.
+    fn differs(&self, other: &SnapshotEntry) -> bool {
+        fn differs<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+            matches!((a, b), (Some(a), Some(b)) if a != b)
+        }
+        differs(&self.size, &other.size)
+            || differs(&self.modified, &other.modified)
+            || differs(&self.hash, &other.hash)
+    }
+}
+
+/// Differences between two [`Snapshot`]s, see [`Snapshot::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SnapshotDiff {
+    /// Paths that only exist in the newer snapshot.
+    pub added: Vec<path::PathBuf>,
+    /// Paths that only exist in the older snapshot.
+    pub removed: Vec<path::PathBuf>,
+    /// Paths that exist in both snapshots but with different attributes.
+    pub changed: Vec<path::PathBuf>,
+}
+
+impl SnapshotDiff {
+    /// Checks whether the snapshots are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Set of paths and their attributes, refer to the [module documentation](self).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Snapshot {
+    entries: collections::BTreeMap<path::PathBuf, SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// Creates an empty snapshot.
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
+
+    /// Creates a snapshot for the given `paths`, recording the attributes selected by `options`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the metadata of any of the paths cannot be read.
+    pub fn from_paths<P>(paths: &[P], options: SnapshotOptions) -> io::Result<Snapshot>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut snapshot = Snapshot::new();
+        for p in paths {
+            let mut entry = SnapshotEntry::new(p);
+            if options.size || options.modified {
+                let meta = fs::metadata(p)?;
+                if options.size {
+                    entry.size = Some(meta.len());
+                }
+                if options.modified {
+                    let modified = meta.modified()?;
+                    entry.modified = modified
+                        .duration_since(time::UNIX_EPOCH)
+                        .ok()
+                        .map(|d| d.as_nanos());
+                }
+            }
+            snapshot.insert(entry);
+        }
+        Ok(snapshot)
+    }
+
+    /// Inserts the entry, replacing any existing entry for the same path.
+    pub fn insert(&mut self, entry: SnapshotEntry) -> &mut Snapshot {
+        self.entries.insert(entry.path.clone(), entry);
+        self
+    }
+
+    /// Provides the entry for the given path, if any.
+    pub fn get<P>(&self, path: P) -> Option<&SnapshotEntry>
+    where
+        P: AsRef<path::Path>,
+    {
+        self.entries.get(path.as_ref())
+    }
+
+    /// Provides all entries in sorted order.
+    pub fn entries(&self) -> impl Iterator<Item = &SnapshotEntry> {
+        self.entries.values()
+    }
+
+    /// Provides the number of entries within the snapshot.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether the snapshot does not contain any entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reports all paths that have been added, removed or changed in the `newer` snapshot.
+    ///
+    /// An entry has changed if any attribute that is recorded by both snapshots differs. All
+    /// lists are sorted.
+    pub fn diff(&self, newer: &Snapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+        for (p, entry) in &newer.entries {
+            match self.entries.get(p) {
+                None => diff.added.push(p.clone()),
+                Some(old) if old.differs(entry) => diff.changed.push(p.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .entries
+            .keys()
+            .filter(|p| !newer.entries.contains_key(*p))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Serializes the snapshot in the format described in the [module documentation](self).
+    pub fn to_text(&self) -> String {
+        fn field<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or("-".to_string(), |v| v.to_string())
+        }
+
+        let mut text = format!("{HEADER}\n");
+        for entry in self.entries.values() {
+            let hash = match entry.hash.as_deref() {
+                Some("-") => "\\-".to_string(),
+                hash => field(&hash.map(escape)),
+            };
+            text.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                field(&entry.size),
+                field(&entry.modified),
+                hash,
+                escape_path(&entry.path)
+            ));
+        }
+        text
+    }
+
+    /// Parses a snapshot in the format described in the [module documentation](self).
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the text is not a valid snapshot.
+    pub fn from_text(text: &str) -> io::Result<Snapshot> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid snapshot in line {line}: {msg}"),
+            )
+        };
+
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(invalid(1, "Unsupported header")),
+        }
+
+        let mut snapshot = Snapshot::new();
+        for (i, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<_> = line.splitn(4, '\t').collect();
+            let [size, modified, hash, p] = fields.as_slice() else {
+                return Err(invalid(i + 1, "Expected four fields"));
+            };
+
+            let mut entry = SnapshotEntry::new(unescape_path(p).ok_or_else(|| invalid(i + 1, p))?);
+            if *size != "-" {
+                entry.size = Some(size.parse().map_err(|_| invalid(i + 1, size))?);
+            }
+            if *modified != "-" {
+                entry.modified = Some(modified.parse().map_err(|_| invalid(i + 1, modified))?);
+            }
+            if *hash != "-" {
+                entry.hash = Some(unescape(hash).ok_or_else(|| invalid(i + 1, hash))?);
+            }
+            snapshot.insert(entry);
+        }
+        Ok(snapshot)
+    }
+
+    /// Writes the snapshot to the given file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be written.
+    pub fn write<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<path::Path>,
+    {
+        fs::write(path, self.to_text())
+    }
+
+    /// Reads a snapshot from the given file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read or if it does not contain a valid snapshot.
+    pub fn read<P>(path: P) -> io::Result<Snapshot>
+    where
+        P: AsRef<path::Path>,
+    {
+        Snapshot::from_text(&fs::read_to_string(path)?)
+    }
+}
+
+/// Escapes backslashes, tabs and line breaks within the given path.
+pub(crate) fn escape(p: &str) -> String {
+    let mut escaped = String::with_capacity(p.len());
+    for c in p.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the given path like [`escape`], where bytes that are not valid UTF-8 are escaped as
+/// `\xNN`.
+#[cfg(unix)]
+pub(crate) fn escape_path(p: &path::Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut escaped = String::new();
+    for chunk in p.as_os_str().as_bytes().utf8_chunks() {
+        escaped.push_str(&escape(chunk.valid()));
+        for byte in chunk.invalid() {
+            escaped.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+    escaped
+}
+
+/// Escapes the given path like [`escape`]. Paths that are not valid Unicode are converted
+/// lossily, since they are not representable as bytes on this platform.
+#[cfg(not(unix))]
+pub(crate) fn escape_path(p: &path::Path) -> String {
+    escape(&p.to_string_lossy())
+}
+
+/// Reverts [`escape`], returns `None` for invalid escape sequences.
+pub(crate) fn unescape(p: &str) -> Option<String> {
+    String::from_utf8(unescape_bytes(p)?).ok()
+}
+
+/// Reverts [`escape_path`], returns `None` for invalid escape sequences.
+pub(crate) fn unescape_path(p: &str) -> Option<path::PathBuf> {
+    let bytes = unescape_bytes(p)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(path::PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(bytes).ok().map(path::PathBuf::from)
+    }
+}
+
+/// Reverts all escape sequences, returns `None` for invalid escape sequences.
+fn unescape_bytes(p: &str) -> Option<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(p.len());
+    let mut chars = p.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(match chars.next()? {
+                '\\' => b'\\',
+                't' => b'\t',
+                'n' => b'\n',
+                'r' => b'\r',
+                '-' => b'-',
+                'x' => {
+                    let hex = [chars.next()?, chars.next()?];
+                    u8::from_str_radix(&String::from_iter(hex), 16).ok()?
+                }
+                _ => return None,
+            }),
+            c => unescaped.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_diff() -> io::Result<()> {
+        let mut old = Snapshot::new();
+        old.insert(SnapshotEntry::new("a.txt"))
+            .insert(SnapshotEntry {
+                size: Some(1),
+                ..SnapshotEntry::new("b.txt")
+            })
+            .insert(SnapshotEntry {
+                hash: Some("abc".to_string()),
+                ..SnapshotEntry::new("c.txt")
+            });
+
+        let mut new = Snapshot::new();
+        new.insert(SnapshotEntry {
+            size: Some(2),
+            ..SnapshotEntry::new("b.txt")
+        })
+        .insert(SnapshotEntry::new("c.txt"))
+        .insert(SnapshotEntry::new("d.txt"));
+
+        let diff = old.diff(&new);
+        assert_eq!(vec![path::PathBuf::from("d.txt")], diff.added);
+        assert_eq!(vec![path::PathBuf::from("a.txt")], diff.removed);
+        // the hash of `c.txt` has not been recorded in the newer snapshot
+        assert_eq!(vec![path::PathBuf::from("b.txt")], diff.changed);
+        assert!(old.diff(&old).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn snapshot_text() -> io::Result<()> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let options = SnapshotOptions {
+            size: true,
+            modified: true,
+        };
+        let mut snapshot =
+            Snapshot::from_paths(&[format!("{root}/some_file.txt"), root.clone()], options)?;
+        snapshot.insert(SnapshotEntry {
+            hash: Some("0123".to_string()),
+            ..SnapshotEntry::new("odd\\name\twith\nbreaks")
+        });
+
+        let text = snapshot.to_text();
+        assert!(text.starts_with("# globmatch snapshot v1\n"));
+        assert!(text.contains("-\t-\t0123\todd\\\\name\\twith\\nbreaks\n"));
+        assert_eq!(3, text.lines().count() - 1);
+        assert_eq!(snapshot, Snapshot::from_text(&text)?);
+
+        assert!(Snapshot::from_text("").is_err());
+        assert!(Snapshot::from_text("# globmatch snapshot v1\n1\t2\n").is_err());
+        assert!(Snapshot::from_text("# globmatch snapshot v1\nx\t-\t-\ta\n").is_err());
+        assert!(Snapshot::from_text("# globmatch snapshot v1\n-\t-\t-\ta\\y\n").is_err());
+        assert!(Snapshot::from_text("# globmatch snapshot v1\n-\t-\t-\ta\\xz\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn snapshot_text_escapes() -> io::Result<()> {
+        let mut snapshot = Snapshot::new();
+        snapshot
+            .insert(SnapshotEntry {
+                hash: Some("-".to_string()),
+                ..SnapshotEntry::new("a.txt")
+            })
+            .insert(SnapshotEntry {
+                hash: Some("with\ttab".to_string()),
+                ..SnapshotEntry::new("b.txt")
+            });
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let name = std::ffi::OsStr::from_bytes(b"c\xff.txt");
+            snapshot.insert(SnapshotEntry::new(name));
+        }
+
+        let text = snapshot.to_text();
+        assert!(text.contains("-\t-\t\\-\ta.txt\n"));
+        assert!(text.contains("-\t-\twith\\ttab\tb.txt\n"));
+        #[cfg(unix)]
+        assert!(text.contains("-\t-\t-\tc\\xff.txt\n"));
+        assert_eq!(snapshot, Snapshot::from_text(&text)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,488 @@
+use std::{fmt, fs, io, path, time, vec};
+
+use crate::error::Error;
+use crate::options::{CaseSensitivity, WalkOptions, WalkOrder};
+use crate::utils;
+
+/// Type of an entry provided by a [`WalkSource`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SourceFileType {
+    /// A regular file.
+    File,
+    /// A directory, whose entries are listed by [`WalkSource::read_dir`].
+    Dir,
+    /// A symbolic link.
+    Symlink,
+    /// Any other entry, e.g., a socket or a device.
+    Other,
+}
+
+impl From<fs::FileType> for SourceFileType {
+    fn from(file_type: fs::FileType) -> Self {
+        if file_type.is_dir() {
+            SourceFileType::Dir
+        } else if file_type.is_file() {
+            SourceFileType::File
+        } else if file_type.is_symlink() {
+            SourceFileType::Symlink
+        } else {
+            SourceFileType::Other
+        }
+    }
+}
+
+/// Metadata of an entry provided by a [`WalkSource`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SourceMetadata {
+    /// The type of the entry.
+    pub file_type: SourceFileType,
+    /// The size of the entry in bytes.
+    pub len: u64,
+    /// The time of the last modification, if known. Entries without a modification time are
+    /// rejected by the bounds of a [`MetadataFilter`](crate::MetadataFilter) for this time.
+    pub modified: Option<time::SystemTime>,
+}
+
+impl From<fs::Metadata> for SourceMetadata {
+    fn from(metadata: fs::Metadata) -> Self {
+        SourceMetadata {
+            file_type: metadata.file_type().into(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+/// Entry of a directory, provided by [`WalkSource::read_dir`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceEntry {
+    /// The path of the entry, i.e., the path of the directory joined with the name of the entry.
+    pub path: path::PathBuf,
+    /// The metadata of the entry itself, i.e., symbolic links are not followed.
+    pub metadata: SourceMetadata,
+}
+
+/// Tree of entries that is walked by an [`IterAll`](crate::IterAll), e.g., an archive, a mock
+/// file system for tests or a remote file system.
+///
+/// A source is used via [`Builder::build_source`](crate::Builder::build_source), which also
+/// resolves the root of the glob within the source. [`WalkDirSource`] is the implementation for
+/// the file system, which is walked by the iterators of a [`Matcher`](crate::Matcher) by
+/// default. Notice that symbolic links provided by a source are yielded like any other entry but
+/// never followed.
+///
+/// ```
+/// use std::{io, path::Path};
+/// use globmatch::{SourceEntry, SourceFileType, SourceMetadata, WalkSource};
+///
+/// /// A flat archive that only contains files.
+/// struct Archive(Vec<&'static str>);
+///
+/// impl WalkSource for Archive {
+///     fn read_dir(&self, dir: &Path) -> io::Result<Vec<SourceEntry>> {
+///         let entry = |name: &&str| SourceEntry { path: dir.join(name), metadata: file() };
+///         Ok(self.0.iter().map(entry).collect())
+///     }
+///
+///     fn metadata(&self, p: &Path) -> io::Result<SourceMetadata> {
+///         match p == Path::new("archive") {
+///             true => Ok(SourceMetadata { file_type: SourceFileType::Dir, ..file() }),
+///             false => match self.0.iter().any(|name| p == Path::new("archive").join(name)) {
+///                 true => Ok(file()),
+///                 false => Err(io::ErrorKind::NotFound.into()),
+///             },
+///         }
+///     }
+///
+///     fn symlink_metadata(&self, p: &Path) -> io::Result<SourceMetadata> {
+///         self.metadata(p)
+///     }
+/// }
+///
+/// fn file() -> SourceMetadata {
+///     SourceMetadata { file_type: SourceFileType::File, len: 0, modified: None }
+/// }
+///
+/// # fn example() -> Result<(), String> {
+/// let archive = Archive(vec!["a.txt", "b.md", "c.txt"]);
+/// let paths: Vec<_> = globmatch::Builder::new("*.txt")
+///     .build_source("archive", archive)?
+///     .flatten()
+///     .collect();
+///
+/// assert_eq!(2, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub trait WalkSource {
+    /// Provides all entries of the directory `dir`.
+    ///
+    /// The entries are walked in the provided order.
+    fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>>;
+
+    /// Queries the metadata of the entry, following symbolic links.
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] for paths that do not exist.
+    fn metadata(&self, p: &path::Path) -> io::Result<SourceMetadata>;
+
+    /// Queries the metadata of the entry without following symbolic links.
+    fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata>;
+}
+
+impl<S> WalkSource for &S
+where
+    S: WalkSource + ?Sized,
+{
+    fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>> {
+        (**self).read_dir(dir)
+    }
+
+    fn metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        (**self).metadata(p)
+    }
+
+    fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        (**self).symlink_metadata(p)
+    }
+}
+
+/// [`WalkSource`] for the file system, backed by `walkdir`.
+///
+/// This is the default source of the iterators of a [`Matcher`](crate::Matcher), which walk the
+/// file system recursively via `walkdir` instead of [`WalkSource::read_dir`], e.g., to follow
+/// symbolic links (see [`Builder::follow_links`](crate::Builder::follow_links)). Use this source
+/// explicitly to wrap the file system in sources that hide or add entries.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalkDirSource;
+
+impl WalkDirSource {
+    /// Creates the recursive walk of the given `root` for the iterators of a
+    /// [`Matcher`](crate::Matcher).
+    pub(crate) fn walk(
+        &self,
+        root: &path::Path,
+        options: &WalkOptions,
+        order: Option<WalkOrder>,
+    ) -> walkdir::IntoIter {
+        let mut walk = walkdir::WalkDir::new(root)
+            .follow_links(options.follow_links)
+            .same_file_system(options.same_file_system);
+        if let Some(depth) = options.min_depth {
+            walk = walk.min_depth(depth);
+        }
+        if let Some(depth) = options.max_depth {
+            walk = walk.max_depth(depth);
+        }
+        if let Some(order) = order {
+            walk = order.apply(walk);
+        }
+        walk.into_iter()
+    }
+}
+
+impl WalkSource for WalkDirSource {
+    fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>> {
+        walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .map(|entry| {
+                let entry = entry.map_err(io::Error::from)?;
+                Ok(SourceEntry {
+                    metadata: entry.metadata().map_err(io::Error::from)?.into(),
+                    path: entry.into_path(),
+                })
+            })
+            .collect()
+    }
+
+    fn metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        fs::metadata(p).map(SourceMetadata::from)
+    }
+
+    fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        fs::symlink_metadata(p).map(SourceMetadata::from)
+    }
+}
+
+/// Resolves the root of a glob within the file system.
+impl utils::Lookup for WalkDirSource {
+    fn exists(&self, p: &path::Path) -> bool {
+        p.exists()
+    }
+
+    fn check_exists(&self, p: &path::Path) -> io::Result<()> {
+        fs::metadata(p).map(drop)
+    }
+
+    fn is_dir(&self, p: &path::Path) -> bool {
+        p.is_dir()
+    }
+
+    fn canonicalize(&self, p: &path::Path) -> io::Result<path::PathBuf> {
+        p.canonicalize()
+    }
+
+    fn is_case_sensitive(&self, case: CaseSensitivity, root: &path::Path) -> bool {
+        case.is_sensitive(Some(root))
+    }
+}
+
+/// Resolves the root of a glob within any other [`WalkSource`], see [`utils::Lookup`].
+pub(crate) struct SourceLookup<'s, S>(pub(crate) &'s S);
+
+impl<S> utils::Lookup for SourceLookup<'_, S>
+where
+    S: WalkSource,
+{
+    fn exists(&self, p: &path::Path) -> bool {
+        self.0.symlink_metadata(p).is_ok()
+    }
+
+    fn is_dir(&self, p: &path::Path) -> bool {
+        self.0
+            .metadata(p)
+            .is_ok_and(|metadata| metadata.file_type == SourceFileType::Dir)
+    }
+
+    fn canonicalize(&self, p: &path::Path) -> io::Result<path::PathBuf> {
+        // symbolic links of a source are never followed
+        Ok(utils::normalize_path(p))
+    }
+
+    fn is_case_sensitive(&self, case: CaseSensitivity, _root: &path::Path) -> bool {
+        case.is_sensitive(None)
+    }
+}
+
+/// Walked entry of a [`SourceWalk`].
+#[derive(Clone, Debug)]
+pub(crate) struct SourceDirEntry {
+    pub(crate) path: path::PathBuf,
+    pub(crate) depth: usize,
+    pub(crate) metadata: SourceMetadata,
+}
+
+/// Recursive walk of a [`WalkSource`] used by an [`IterAll`](crate::IterAll), which walks
+/// directories before their contents like `walkdir`.
+pub struct SourceWalk<S> {
+    source: S,
+    /// The root, until it has been walked.
+    root: Option<path::PathBuf>,
+    /// Remaining entries of all directories that are currently walked, with their depth.
+    stack: Vec<(usize, vec::IntoIter<SourceEntry>)>,
+    /// The last walked directory, whose contents are walked next.
+    pending: Option<(usize, path::PathBuf)>,
+    min_depth: usize,
+    max_depth: usize,
+}
+
+impl<S> fmt::Debug for SourceWalk<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceWalk")
+            .field("root", &self.root)
+            .field("pending", &self.pending)
+            .field("min_depth", &self.min_depth)
+            .field("max_depth", &self.max_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> SourceWalk<S>
+where
+    S: WalkSource,
+{
+    pub(crate) fn new(
+        source: S,
+        root: path::PathBuf,
+        min_depth: Option<usize>,
+        max_depth: Option<usize>,
+    ) -> SourceWalk<S> {
+        SourceWalk {
+            source,
+            root: Some(root),
+            stack: vec![],
+            pending: None,
+            min_depth: min_depth.unwrap_or(0),
+            max_depth: max_depth.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Skips the contents of the last walked directory, see `walkdir::IntoIter::skip_current_dir`.
+    pub(crate) fn skip_current_dir(&mut self) {
+        self.pending = None;
+    }
+
+    /// Provides the next walked entry.
+    pub(crate) fn next_entry(&mut self) -> Option<Result<SourceDirEntry, Error>> {
+        loop {
+            match self.walk_next()? {
+                Ok(entry) if entry.depth < self.min_depth => continue,
+                next => return Some(next),
+            }
+        }
+    }
+
+    /// Provides the next walked entry, including entries below the minimum depth.
+    fn walk_next(&mut self) -> Option<Result<SourceDirEntry, Error>> {
+        if let Some(root) = self.root.take() {
+            let metadata = match self.source.metadata(&root) {
+                Ok(metadata) => metadata,
+                Err(err) => return Some(Err(Error::from_io(&root, 0, err))),
+            };
+            return Some(Ok(self.walked(root, 0, metadata)));
+        }
+
+        if let Some((depth, dir)) = self.pending.take() {
+            match self.source.read_dir(&dir) {
+                Ok(entries) => self.stack.push((depth + 1, entries.into_iter())),
+                Err(err) => return Some(Err(Error::from_io(&dir, depth, err))),
+            }
+        }
+
+        loop {
+            let (depth, entries) = self.stack.last_mut()?;
+            let depth = *depth;
+            match entries.next() {
+                Some(entry) => return Some(Ok(self.walked(entry.path, depth, entry.metadata))),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn walked(
+        &mut self,
+        path: path::PathBuf,
+        depth: usize,
+        metadata: SourceMetadata,
+    ) -> SourceDirEntry {
+        if metadata.file_type == SourceFileType::Dir && depth < self.max_depth {
+            self.pending = Some((depth, path.clone()));
+        }
+        SourceDirEntry {
+            path,
+            depth,
+            metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, FileType, MetadataFilter, VirtualTree};
+
+    #[test]
+    fn walk_source() -> Result<(), String> {
+        let tree: VirtualTree = [
+            "a/a0/a0_0.txt",
+            "a/a0/a0_1.txt",
+            "a/a0/a0_2.md",
+            "a/a1/a1_0.txt",
+            "b/b_0.txt",
+            "some_file.txt",
+        ]
+        .into_iter()
+        .collect();
+
+        let paths: Vec<_> = Builder::new("a/**/*.txt")
+            .build_source("", &tree)?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(3, paths.len());
+
+        // the walk is pruned and limited to the depth of the glob
+        let mut iter = Builder::new("a/*/a0_?.*").build_source("", &tree)?;
+        assert_eq!(3, iter.by_ref().flatten().count());
+
+        let paths: Vec<_> = Builder::new("*")
+            .file_type(FileType::Dirs)
+            .build_source("", &tree)?
+            .flatten()
+            .collect();
+        // the root itself is not matched
+        assert_eq!(["a", "b"].map(path::PathBuf::from).to_vec(), paths);
+
+        assert!(Builder::new("*").build_source("c", &tree).is_err());
+
+        // options that require the file system are rejected
+        let err = Builder::new("*").dedup_files(true).build_source("", &tree);
+        assert!(matches!(err, Err(Error::Unsupported(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn walk_source_metadata() -> Result<(), String> {
+        struct Sized;
+
+        impl WalkSource for Sized {
+            fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>> {
+                let entries = [("empty.txt", 0), ("small.txt", 10), ("large.txt", 100)];
+                let entries = entries.map(|(name, len)| SourceEntry {
+                    path: dir.join(name),
+                    metadata: SourceMetadata {
+                        file_type: SourceFileType::File,
+                        len,
+                        modified: None,
+                    },
+                });
+                Ok(entries.to_vec())
+            }
+
+            fn metadata(&self, _p: &path::Path) -> io::Result<SourceMetadata> {
+                Ok(SourceMetadata {
+                    file_type: SourceFileType::Dir,
+                    len: 0,
+                    modified: None,
+                })
+            }
+
+            fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+                self.metadata(p)
+            }
+        }
+
+        let paths: Vec<_> = Builder::new("*.txt")
+            .filter_metadata(MetadataFilter {
+                min_size: Some(1),
+                max_size: Some(50),
+                ..Default::default()
+            })
+            .build_source("", Sized)?
+            .flatten()
+            .collect();
+        assert_eq!(vec![path::PathBuf::from("small.txt")], paths);
+
+        // entries without a modification time are rejected by its bounds
+        let paths: Vec<_> = Builder::new("*.txt")
+            .filter_metadata(MetadataFilter {
+                modified_after: Some(time::UNIX_EPOCH),
+                ..Default::default()
+            })
+            .build_source("", Sized)?
+            .flatten()
+            .collect();
+        assert!(paths.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn walkdir_source() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let mut expected: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut paths: Vec<_> = Builder::new("**/*.txt")
+            .build_source(&root, WalkDirSource)?
+            .flatten()
+            .collect();
+        expected.sort();
+        paths.sort();
+        assert_eq!(expected, paths);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::{path, pin, task};
+
+use tokio::sync::mpsc;
+
+use crate::cancel::CancellationHandle;
+use crate::error::Error;
+use crate::iters::IterAll;
+
+type Match = Result<path::PathBuf, Error>;
+
+/// Number of matches that are buffered before the walk waits for the stream to be polled.
+const CAPACITY: usize = 64;
+
+/// Asynchronous stream of the paths matching the glob, see
+/// [`Matcher::into_stream`](crate::Matcher::into_stream). Requires the `async` feature.
+///
+/// The file system is walked by a blocking task of the `tokio` runtime, which yields the same
+/// items in the same order as the iterator provided by `into_iter`. Dropping the stream cancels
+/// the walk before the next entry is walked.
+#[derive(Debug)]
+pub struct MatchStream {
+    rx: mpsc::Receiver<Match>,
+    /// Cancels the walk once the stream is dropped.
+    cancel: CancellationHandle,
+}
+
+impl MatchStream {
+    pub(crate) fn new<P>(iter: IterAll<P>) -> MatchStream
+    where
+        P: AsRef<path::Path> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(CAPACITY);
+        let cancel = CancellationHandle::new();
+        let iter = iter.cancel_on(cancel.clone());
+        tokio::task::spawn_blocking(move || {
+            for item in iter {
+                // the receiver has been dropped, there is no one left to yield matches to
+                if tx.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        MatchStream { rx, cancel }
+    }
+}
+
+impl Drop for MatchStream {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+impl futures_core::Stream for MatchStream {
+    type Item = Match;
+
+    fn poll_next(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+    use futures_core::Stream;
+    use std::sync::Mutex;
+
+    /// Observer that forwards all errors of the walk.
+    struct Errors(Mutex<std::sync::mpsc::Sender<Error>>);
+
+    impl crate::WalkObserver for Errors {
+        fn on_error(&self, error: &Error) {
+            let _ = self.0.lock().unwrap().send(error.clone());
+        }
+    }
+
+    async fn collect(mut stream: MatchStream) -> Vec<Match> {
+        let mut items = vec![];
+        while let Some(item) =
+            std::future::poll_fn(|cx| pin::Pin::new(&mut stream).poll_next(cx)).await
+        {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    fn match_stream() -> Result<(), String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .map_err(|err| err.to_string())?;
+        let _guard = runtime.enter();
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        let expected: Vec<_> = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .into_iter()
+            .flatten()
+            .collect();
+        let stream = Builder::new("test-files/c-simple/**/*.txt")
+            .build(root)?
+            .into_stream();
+        let paths = runtime
+            .block_on(collect(stream))
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(9, paths.len());
+        assert_eq!(expected, paths);
+
+        // dropping the stream cancels the walk, which is paused until the stream is dropped
+        let (started_tx, started) = std::sync::mpsc::channel();
+        let (resume, resume_rx) = std::sync::mpsc::channel::<()>();
+        let (started_tx, resume_rx) = (Mutex::new(started_tx), Mutex::new(resume_rx));
+        let (errors_tx, errors) = std::sync::mpsc::channel();
+        let stream = Builder::new("test-files/**/*")
+            .build(root)?
+            .on_progress(1, move |progress| {
+                if progress.entries == 1 {
+                    started_tx.lock().unwrap().send(()).unwrap();
+                    resume_rx.lock().unwrap().recv().unwrap();
+                }
+            })
+            .observe(Errors(Mutex::new(errors_tx)))
+            .into_stream();
+        started.recv().unwrap();
+        drop(stream);
+        resume.send(()).unwrap();
+        let err = errors.recv_timeout(std::time::Duration::from_secs(10));
+        assert_eq!(Ok(Error::Cancelled), err);
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use std::path;
+
+/// Maximum edit distance for a path segment to be considered a typo.
+const MAX_TYPO_DISTANCE: usize = 1;
+
+/// Checks whether the provided glob segment contains any glob meta characters.
+pub(crate) fn is_literal(segment: &str) -> bool {
+    !segment.contains(['*', '?', '[', ']', '{', '}', '\\', '<'])
+}
+
+/// Computes the typo distance between two strings.
+///
+/// This is the optimal string alignment distance, i.e., the Levenshtein distance where the
+/// transposition of two adjacent characters (a typical typo) counts as a single edit.
+fn typo_distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().collect();
+    let b: Vec<_> = b.chars().collect();
+
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    (0..=a.len()).for_each(|i| d[i][0] = i);
+    (0..=b.len()).for_each(|j| d[0][j] = j);
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Finds entries within `dir` that are near misses for the provided `segment`.
+///
+/// An entry is a near miss if it only differs in case or if it is within a single edit of
+/// the provided segment. Entries that only differ in case are provided first.
+fn near_misses(dir: &path::Path, segment: &str) -> Vec<String> {
+    let entries = match dir.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    // entries that only differ in case are listed first, followed by the typos
+    let mut names: Vec<_> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .filter_map(|name| {
+            let distance = match name.to_lowercase() == segment.to_lowercase() {
+                true => 0,
+                false => typo_distance(&name, segment),
+            };
+            (distance <= MAX_TYPO_DISTANCE).then_some((distance, name))
+        })
+        .collect();
+    names.sort_unstable();
+    names.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Computes near-miss globs for the remaining pattern `rest` relative to `root`.
+///
+/// The literal segments of `rest` are walked starting from `root`. The first segment that
+/// does not exist is compared against the entries of its parent directory, see [`near_misses`].
+/// If all literal segments exist, the remaining pattern is matched case insensitive against
+/// all paths within `root`.
+///
+/// The suggestions are provided as remaining patterns (replacing `rest`), sorted and limited
+/// to `max` entries.
+pub(crate) fn suggest(root: &path::Path, rest: &str, max: usize) -> Vec<String> {
+    let segments: Vec<_> = rest.split('/').collect();
+    let mut dir = path::PathBuf::from(root);
+
+    for (i, segment) in segments.iter().enumerate() {
+        if !is_literal(segment) {
+            break;
+        }
+
+        if dir.join(segment).exists() {
+            dir.push(segment);
+            continue;
+        }
+
+        return near_misses(&dir, segment)
+            .into_iter()
+            .map(|name| {
+                let mut suggestion = segments.clone();
+                suggestion[i] = &name;
+                suggestion.join("/")
+            })
+            .take(max)
+            .collect();
+    }
+
+    // all literal segments exist, check for matches that only differ in case
+    let matcher = match globset::GlobBuilder::new(rest)
+        .literal_separator(true)
+        .case_insensitive(true)
+        .build()
+    {
+        Ok(glob) => glob.compile_matcher(),
+        Err(_) => return vec![],
+    };
+
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| {
+            let p = e.path().strip_prefix(root).ok()?;
+            match matcher.is_match(p) {
+                true => p.to_str().map(|p| p.replace('\\', "/")),
+                false => None,
+            }
+        })
+        .take(max)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos() {
+        assert_eq!(0, typo_distance("abc", "abc"));
+        assert_eq!(1, typo_distance("files", "fiels"));
+        assert_eq!(2, typo_distance("files", "flies_"));
+    }
+
+    #[test]
+    fn suggestions() {
+        let root = path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(vec!["test-files/**"], suggest(&root, "test-fiels/**", 5));
+        assert_eq!(vec!["test-files/**"], suggest(&root, "Test-Files/**", 5));
+        assert_eq!(
+            vec!["test-files/c-simple/a/*.txt"],
+            suggest(&root, "test-files/c-simple/aa/*.txt", 5)
+        );
+        assert!(suggest(&root, "test-files/c-simple/xyz/*.txt", 5).is_empty());
+
+        let root = root.join("test-files/c-simple/a");
+        assert_eq!(vec!["a0/A0_3.txt"], suggest(&root, "a0/a0_3.txt", 1));
+        assert_eq!(3, suggest(&root, "a0/a0_3.txt", 5).len());
+        assert_eq!(vec!["a0/a0_2.md"], suggest(&root, "a0/*.MD", 5));
+    }
+}
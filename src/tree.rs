@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::{io, path};
+
+use crate::options::{FileType, PathNormalization};
+use crate::source::{SourceEntry, SourceFileType, SourceMetadata, WalkSource};
+use crate::{utils, winpath, Matcher};
+
+/// In-memory tree of paths that globs can be matched against without accessing the file system,
+/// see [`Builder::match_virtual`](crate::Builder::match_virtual).
+///
+/// The tree is populated with paths, e.g., from the index of an archive or a generated list of
+/// files. All ancestors of an inserted path are implicitly inserted as directories. Paths are
+/// normalized lexically, i.e., `./src/../src/main.rs` and `src/main.rs` are the same path, and
+/// the empty path and `.` refer to the top level of the tree, which always exists.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let tree: globmatch::VirtualTree = ["src/main.rs", "src/net/tcp.rs", "README.md"]
+///     .into_iter()
+///     .collect();
+///
+/// let paths = globmatch::Builder::new("../src/**/*.rs").match_virtual(&tree, "src")?;
+/// assert_eq!(2, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VirtualTree {
+    /// Normalized paths of all entries, mapped to whether the entry is a directory.
+    entries: BTreeMap<path::PathBuf, bool>,
+}
+
+impl VirtualTree {
+    /// Creates an empty tree.
+    pub fn new() -> VirtualTree {
+        VirtualTree::default()
+    }
+
+    /// Inserts the file with the given path and all of its ancestors as directories.
+    ///
+    /// A file that has previously been inserted is converted into a directory if another path
+    /// is inserted below it.
+    pub fn insert<P>(&mut self, p: P) -> &mut VirtualTree
+    where
+        P: AsRef<path::Path>,
+    {
+        self.insert_entry(p.as_ref(), false)
+    }
+
+    /// Inserts the directory with the given path and all of its ancestors.
+    pub fn insert_dir<P>(&mut self, p: P) -> &mut VirtualTree
+    where
+        P: AsRef<path::Path>,
+    {
+        self.insert_entry(p.as_ref(), true)
+    }
+
+    fn insert_entry(&mut self, p: &path::Path, is_dir: bool) -> &mut VirtualTree {
+        let key = key(p);
+        if key.as_os_str().is_empty() {
+            return self;
+        }
+        for ancestor in key.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            self.entries.insert(path::PathBuf::from(ancestor), true);
+        }
+        let entry = self.entries.entry(key).or_insert(is_dir);
+        *entry |= is_dir;
+        self
+    }
+
+    /// Checks whether the tree contains the given path, either as file or as directory.
+    pub fn contains<P>(&self, p: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        let key = key(p.as_ref());
+        key.as_os_str().is_empty() || self.entries.contains_key(&key)
+    }
+
+    /// Checks whether the tree contains the given path as directory.
+    pub fn is_dir<P>(&self, p: P) -> bool
+    where
+        P: AsRef<path::Path>,
+    {
+        let key = key(p.as_ref());
+        key.as_os_str().is_empty() || self.entries.get(&key).is_some_and(|is_dir| *is_dir)
+    }
+
+    /// Provides the number of entries within the tree, including the implicit directories.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether the tree does not contain any entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Provides all entries of the tree in lexical order, including the implicit directories.
+    pub fn iter(&self) -> impl Iterator<Item = &path::Path> {
+        self.entries.keys().map(path::PathBuf::as_path)
+    }
+
+    /// Provides all paths of the tree that are matched by the [`Matcher`], in lexical order.
+    ///
+    /// Applies the same rules as the iterator of the [`Matcher`], except for options that
+    /// require the file system, e.g., `.gitignore` files.
+    pub(crate) fn matches(&self, matcher: &Matcher<'_, path::PathBuf>) -> Vec<path::PathBuf> {
+        let root = matcher.root_path();
+        let root_key = key(root);
+        let walk = &matcher.walk;
+
+        // the root itself is a file, match on its name as if walking its parent
+        if self.entries.get(&root_key).is_some_and(|is_dir| !is_dir) {
+            let name = path::Path::new(root.file_name().unwrap_or_default());
+            return match matcher.matcher.is_match(name)
+                && walk.file_type != FileType::Dirs
+                && !matcher.matcher.is_excluded(root)
+            {
+                true => vec![yielded_path(
+                    walk.relative_paths,
+                    walk.normalization,
+                    root,
+                    name,
+                )],
+                false => vec![],
+            };
+        }
+
+        let mut paths = vec![];
+        // all descendants of the root directly follow the root itself, see `skip_contents`
+        let mut from = root_key.clone();
+        while let Some((p, is_dir)) = self
+            .entries
+            .range::<path::Path, _>((Bound::Excluded(from.as_path()), Bound::Unbounded))
+            .next()
+        {
+            let Ok(relative) = p.strip_prefix(&root_key) else {
+                break;
+            };
+            from = p.clone();
+            let depth = relative.components().count();
+            let full = root.join(relative);
+
+            // like for the iterator, folders that cannot contain any match are not walked
+            if *is_dir {
+                if matcher.matcher.is_pruned(&full) {
+                    from = skip_contents(p);
+                    continue;
+                }
+                if walk.max_depth.is_some_and(|max| depth >= max)
+                    || !matcher.matcher.may_contain_match(relative)
+                {
+                    from = skip_contents(p);
+                }
+            }
+            if walk.min_depth.is_some_and(|min| depth < min)
+                || walk.max_depth.is_some_and(|max| depth > max)
+            {
+                continue;
+            }
+
+            let accepted = match walk.file_type {
+                FileType::All => true,
+                FileType::Files => !is_dir,
+                FileType::Dirs => *is_dir,
+            };
+            if accepted
+                && matcher.matcher.is_match(relative)
+                && !matcher.matcher.is_excluded(&full)
+                && is_allowed(matcher, &full, *is_dir)
+            {
+                paths.push(yielded_path(
+                    walk.relative_paths,
+                    walk.normalization,
+                    &full,
+                    relative,
+                ));
+            }
+        }
+        paths
+    }
+}
+
+impl<P> FromIterator<P> for VirtualTree
+where
+    P: AsRef<path::Path>,
+{
+    /// Creates a tree containing the given files, see [`VirtualTree::insert`].
+    fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> Self {
+        let mut tree = VirtualTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<P> Extend<P> for VirtualTree
+where
+    P: AsRef<path::Path>,
+{
+    /// Inserts the given files, see [`VirtualTree::insert`].
+    fn extend<I: IntoIterator<Item = P>>(&mut self, iter: I) {
+        for p in iter {
+            self.insert(p);
+        }
+    }
+}
+
+/// Walks the tree like the file system, e.g., via
+/// [`Builder::build_source`](crate::Builder::build_source). The tree does not contain any
+/// symbolic links, all entries have the size zero and no modification time.
+impl WalkSource for VirtualTree {
+    fn read_dir(&self, dir: &path::Path) -> io::Result<Vec<SourceEntry>> {
+        let key = key(dir);
+        if !VirtualTree::is_dir(self, &key) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        // all descendants of a directory directly follow the directory itself
+        let entries = self
+            .entries
+            .range::<path::Path, _>((Bound::Excluded(key.as_path()), Bound::Unbounded))
+            .take_while(|(p, _)| p.starts_with(&key))
+            .filter(|(p, _)| p.parent() == Some(key.as_path()))
+            .map(|(p, is_dir)| SourceEntry {
+                path: dir.join(p.file_name().unwrap_or_default()),
+                metadata: metadata(*is_dir),
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    fn metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        match self.contains(p) {
+            true => Ok(metadata(VirtualTree::is_dir(self, p))),
+            false => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+
+    fn symlink_metadata(&self, p: &path::Path) -> io::Result<SourceMetadata> {
+        self.metadata(p)
+    }
+}
+
+/// Provides the metadata of an entry of the tree.
+fn metadata(is_dir: bool) -> SourceMetadata {
+    SourceMetadata {
+        file_type: match is_dir {
+            true => SourceFileType::Dir,
+            false => SourceFileType::File,
+        },
+        len: 0,
+        modified: None,
+    }
+}
+
+/// Provides a key that is greater than all descendants of the directory `dir` but smaller than
+/// any of its following siblings, which allows to skip the contents of `dir`.
+///
+/// Paths are ordered by their components, i.e., the descendants `dir/*` directly follow `dir`
+/// and a sibling follows them if its name is greater than the name of `dir`. Appending a NUL,
+/// which is not allowed within file names, yields the smallest name that is greater.
+fn skip_contents(dir: &path::Path) -> path::PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push("\0");
+    dir.with_file_name(name)
+}
+
+/// Provides the key of the path within the tree, where the top level of the tree is empty.
+pub(crate) fn key(p: &path::Path) -> path::PathBuf {
+    let normalized = utils::normalize_path(&winpath::normalize(p));
+    match normalized.as_os_str() == "." {
+        true => path::PathBuf::new(),
+        false => normalized,
+    }
+}
+
+/// Provides the yielded path for a match, see `WalkOptions::yielded_path`.
+///
+/// Paths cannot be canonicalized within the tree, canonical paths are normalized lexically.
+fn yielded_path(
+    relative_paths: bool,
+    normalization: PathNormalization,
+    full: &path::Path,
+    relative: &path::Path,
+) -> path::PathBuf {
+    let p = match relative_paths {
+        true => relative,
+        false => full,
+    };
+    match normalization {
+        PathNormalization::Keep => path::PathBuf::from(p),
+        PathNormalization::Lexical | PathNormalization::Canonical => utils::normalize_path(p),
+    }
+}
+
+#[cfg(feature = "ignore")]
+fn is_allowed(matcher: &Matcher<'_, path::PathBuf>, full: &path::Path, is_dir: bool) -> bool {
+    matcher.matcher.is_allowed(full, is_dir)
+}
+
+#[cfg(not(feature = "ignore"))]
+fn is_allowed(_matcher: &Matcher<'_, path::PathBuf>, _full: &path::Path, _is_dir: bool) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+
+    fn tree() -> VirtualTree {
+        let mut tree: VirtualTree = [
+            "a/a0/a0_0.txt",
+            "a/a0/a0_1.txt",
+            "a/a0/a0_2.md",
+            "a/a1/a1_0.txt",
+            "b/b_0.txt",
+            "some_file.txt",
+        ]
+        .into_iter()
+        .collect();
+        tree.insert_dir("c/empty");
+        tree
+    }
+
+    #[test]
+    fn populate() {
+        let tree = tree();
+        assert_eq!(6 + 6, tree.len());
+        assert!(tree.contains("./a/../a/a0"));
+        assert!(tree.is_dir("a/a0/"));
+        assert!(!tree.is_dir("b/b_0.txt"));
+        assert!(tree.is_dir("."));
+        assert!(!tree.contains("b/b_1.txt"));
+
+        let mut tree = VirtualTree::new();
+        tree.insert("a").insert("a/b");
+        assert!(tree.is_dir("a"));
+    }
+
+    #[test]
+    fn match_virtual() -> Result<(), String> {
+        let tree = tree();
+        let paths = Builder::new("**/*.txt").match_virtual(&tree, ".")?;
+        assert_eq!(5, paths.len());
+
+        let paths = Builder::new("a/**/*.txt")
+            .exclude("a/a1/**")
+            .match_virtual(&tree, "")?;
+        assert_eq!(
+            vec![
+                path::PathBuf::from("a/a0/a0_0.txt"),
+                path::PathBuf::from("a/a0/a0_1.txt"),
+            ],
+            paths
+        );
+
+        // relative path prefixes are resolved within the tree
+        let paths = Builder::new("../../b/*")
+            .relative_paths(true)
+            .match_virtual(&tree, "a/a0")?;
+        assert_eq!(vec![path::PathBuf::from("b_0.txt")], paths);
+
+        let paths = Builder::new("*")
+            .file_type(FileType::Dirs)
+            .match_virtual(&tree, "c")?;
+        assert_eq!(vec![path::PathBuf::from("c/empty")], paths);
+
+        let paths = Builder::new("a/a0/a0_2.md").match_virtual(&tree, "")?;
+        assert_eq!(vec![path::PathBuf::from("a/a0/a0_2.md")], paths);
+
+        assert!(Builder::new("*.txt").match_virtual(&tree, "d").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn skip_dir_contents() {
+        let tree: VirtualTree = ["a/z/0.txt", "a b", "a.txt", "b"].into_iter().collect();
+        let skipped = skip_contents(path::Path::new("a"));
+        let next: Vec<_> = tree
+            .entries
+            .range::<path::Path, _>((Bound::Excluded(skipped.as_path()), Bound::Unbounded))
+            .map(|(p, _)| p.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(vec!["a b", "a.txt", "b"], next);
+
+        // the globs are only matched against the entries of walked folders
+        let paths = Builder::new("a*/*")
+            .max_depth(2)
+            .match_virtual(&tree, "")
+            .unwrap();
+        assert_eq!(vec![path::PathBuf::from("a/z")], paths);
+    }
+}
@@ -0,0 +1,134 @@
+use std::path;
+
+use camino::Utf8PathBuf;
+
+use crate::{Error, IterAll, IterFilter, Matcher};
+
+/// Iterator yielding [`Utf8PathBuf`]s, created via [`Matcher::into_utf8_iter`],
+/// [`IterAll::utf8`] or [`IterFilter::utf8`].
+///
+/// Matching paths that are not valid UTF-8 are yielded as [`Error::NotUtf8`], which contains the
+/// original path such that no information is lost. Requires the `camino` feature.
+#[derive(Debug)]
+pub struct IterUtf8<I> {
+    iter: I,
+}
+
+impl<I> Iterator for IterUtf8<I>
+where
+    I: Iterator<Item = Result<path::PathBuf, Error>>,
+{
+    type Item = Result<Utf8PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|item| item.and_then(|p| Utf8PathBuf::from_path_buf(p).map_err(Error::NotUtf8)))
+    }
+}
+
+impl<'a, P> Matcher<'a, P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Transforms the matcher into an iterator yielding [`Utf8PathBuf`]s, see [`IterUtf8`].
+    ///
+    /// Since [`Utf8Path`](camino::Utf8Path) implements `AsRef<Path>`, UTF-8 roots can be passed
+    /// to [`Builder::build`](crate::Builder::build) directly. Requires the `camino` feature.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let root = camino::Utf8Path::new(env!("CARGO_MANIFEST_DIR"));
+    /// let paths: Vec<camino::Utf8PathBuf> = globmatch::Builder::new("test-files/c-simple/*.txt")
+    ///     .build(root)?
+    ///     .into_utf8_iter()
+    ///     .flatten()
+    ///     .collect();
+    ///
+    /// assert_eq!(1, paths.len());
+    /// assert_eq!(Some("txt"), paths[0].extension());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn into_utf8_iter(self) -> IterUtf8<IterAll<P>> {
+        self.into_iter().utf8()
+    }
+}
+
+impl<P> IterAll<P>
+where
+    P: AsRef<path::Path>,
+{
+    /// Transforms the iterator into an iterator yielding [`Utf8PathBuf`]s, see [`IterUtf8`].
+    ///
+    /// Requires the `camino` feature.
+    pub fn utf8(self) -> IterUtf8<Self> {
+        IterUtf8 { iter: self }
+    }
+}
+
+impl<PreDir, P> IterFilter<walkdir::IntoIter, P, PreDir>
+where
+    PreDir: FnMut(&walkdir::DirEntry) -> bool,
+    P: AsRef<path::Path>,
+{
+    /// Transforms the iterator into an iterator yielding [`Utf8PathBuf`]s, see [`IterUtf8`].
+    ///
+    /// Requires the `camino` feature.
+    pub fn utf8(self) -> IterUtf8<Self> {
+        IterUtf8 { iter: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Builder};
+
+    #[test]
+    fn utf8_root() -> Result<(), String> {
+        let root = Utf8PathBuf::from(format!(
+            "{}/test-files/c-simple",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+        let paths: Vec<_> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_utf8_iter()
+            .collect::<Result<_, _>>()?;
+        assert_eq!(6 + 3, paths.len());
+        assert!(paths.iter().all(|p| p.starts_with(&root)));
+
+        let filtered: Vec<Utf8PathBuf> = Builder::new("**/*.txt")
+            .build(&root)?
+            .into_iter()
+            .filter_entry(|p| !crate::is_hidden_path(p))
+            .utf8()
+            .flatten()
+            .collect();
+        assert_eq!(6 + 1, filtered.len());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn utf8_lossless() -> Result<(), String> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = utils::TempDir::new("utf8")?;
+        let invalid = root.write(std::ffi::OsStr::from_bytes(b"inv\xffalid.txt"), "")?;
+        root.write("valid.txt", "")?;
+
+        let items: Vec<_> = Builder::new("*.txt")
+            .build(&root)?
+            .into_utf8_iter()
+            .collect();
+
+        assert_eq!(2, items.len());
+        assert!(items.contains(&Ok(
+            Utf8PathBuf::from_path_buf(root.join("valid.txt")).unwrap()
+        )));
+        assert!(items.contains(&Err(Error::NotUtf8(invalid))));
+        Ok(())
+    }
+}
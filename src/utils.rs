@@ -1,6 +1,13 @@
+use std::borrow;
+use std::cmp;
+use std::ffi;
+use std::fs;
 use std::io;
 use std::path;
 
+use crate::options::{CaseSensitivity, RootSymlinks};
+use crate::winpath;
+
 /// Resolves the root for the pattern and the given path prefix.
 ///
 /// E.g., for the prefix `/home/some/folder` and pattern `../../*.c` this function will resolve
@@ -8,28 +15,96 @@ use std::path;
 /// the pattern, resulting in the remainder `*.c`.
 ///
 /// Both, the resolved root path and the remaining pattern are provided as tuple `Some(root, rest)`.
-/// If the provided `prefix` is not a valid path this function returns an `io::Error`.
+/// If the provided `prefix` is not a valid path this function returns an `io::Error`. Equivalent
+/// spellings of the prefix, e.g., with trailing separators or `.` components, are normalized and
+/// result in the same root.
 #[allow(clippy::needless_lifetimes)]
 pub fn resolve_root<'a, P>(
     prefix: P,
     pattern: &'a str,
 ) -> Result<(path::PathBuf, &'a str), io::Error>
+where
+    P: AsRef<path::Path>,
+{
+    resolve_root_with(prefix, pattern, &ResolveOptions::default())
+}
+
+/// Queries of the file system that are required to resolve the root of a pattern.
+///
+/// Implemented by the [`WalkDirSource`](crate::WalkDirSource) of the file system and by the
+/// [`SourceLookup`](crate::source::SourceLookup) of any other [`WalkSource`](crate::WalkSource),
+/// which allows to resolve patterns within trees other than the file system.
+pub(crate) trait Lookup {
+    /// Checks whether the path exists.
+    fn exists(&self, p: &path::Path) -> bool;
+    /// Checks whether the path exists, providing the reason if it does not.
+    fn check_exists(&self, p: &path::Path) -> io::Result<()> {
+        match self.exists(p) {
+            true => Ok(()),
+            false => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+    /// Checks whether the path exists and is a directory.
+    fn is_dir(&self, p: &path::Path) -> bool;
+    /// Resolves all symbolic links and relative path components of the path.
+    fn canonicalize(&self, p: &path::Path) -> io::Result<path::PathBuf>;
+    /// Resolves the case sensitivity for the tree containing the given root.
+    fn is_case_sensitive(&self, case: CaseSensitivity, root: &path::Path) -> bool;
+    /// Provides the value of an environment variable, see [`expand_env`].
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+/// Options for [`resolve_root_with`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ResolveOptions {
+    /// Allows relative path components in the remaining pattern, see [`normalize_parent_dirs`].
+    pub(crate) parent_dirs: bool,
+    /// Policy for symbolic links within the resolved root.
+    pub(crate) root_symlinks: RootSymlinks,
+}
+
+/// Resolves the root for the pattern and the given path prefix using the provided options.
+///
+/// Refer to [`resolve_root`], which uses the default options.
+#[allow(clippy::needless_lifetimes)]
+pub(crate) fn resolve_root_with<'a, P>(
+    prefix: P,
+    pattern: &'a str,
+    options: &ResolveOptions,
+) -> Result<(path::PathBuf, &'a str), io::Error>
+where
+    P: AsRef<path::Path>,
+{
+    resolve_root_in(prefix, pattern, options, &crate::WalkDirSource)
+}
+
+/// Resolves the root for the pattern within the tree of the given [`Lookup`].
+///
+/// Refer to [`resolve_root`], which resolves the root within the file system.
+#[allow(clippy::needless_lifetimes)]
+pub(crate) fn resolve_root_in<'a, P>(
+    prefix: P,
+    pattern: &'a str,
+    options: &ResolveOptions,
+    lookup: &dyn Lookup,
+) -> Result<(path::PathBuf, &'a str), io::Error>
 where
     P: AsRef<path::Path>,
 {
     // TODO: is there such a thing as Cow for Path?
-    let mut root = path::PathBuf::from(prefix.as_ref());
+    // normalizes the root, e.g., `dir/`, `dir/.` and `dir` are the same
+    let mut root = winpath::normalize(prefix.as_ref());
     let mut rest = path::PathBuf::new();
 
     if pattern.is_empty() {
         return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty pattern"));
     }
 
-    if !root.as_path().exists() {
-        return Err(io::Error::from(io::ErrorKind::NotFound));
-    }
+    lookup.check_exists(&root)?;
 
-    if path::Path::new(pattern).is_absolute() {
+    if is_absolute_pattern(pattern) {
         return Err(io::Error::new(
             io::ErrorKind::Unsupported,
             format!("'{pattern}' is an absolute path"),
@@ -43,14 +118,17 @@ where
     // only lead to zero matches during the matching procedure.
 
     // println!("resolve root for {:?} -> {}", prefix.as_ref(), pattern);
+    // all leading literal components are moved to the root, such that the walk starts as deep
+    // as possible. components containing wildcards are kept in the pattern, even if a path with
+    // the same name exists, e.g., a folder named `[ab]` must not prevent matching `a` and `b`.
     let mut push_root = true;
     path::Path::new(pattern).components().for_each(|c| {
         if push_root {
-            root.push(c);
+            winpath::push(&mut root, c);
 
             // notice that a path exists even if the number of "../" is beyond the root.
             // thus all superfluous "../" will simply be consumed by this iterator.
-            if !root.exists() {
+            if !lookup.exists(&root) || !is_literal(c) {
                 root.pop();
                 rest.push(c);
                 push_root = false;
@@ -74,14 +152,18 @@ where
     // do not canonicalize the root directory, the user can decide to do this. otherwise
     // matching against patterns that also use relative paths will be impossible.
     // let root = root.canonicalize()?;
+    if options.root_symlinks == RootSymlinks::Resolve {
+        root = lookup.canonicalize(&root)?;
+    }
     // println!(" -- root {:?}\n    rest {}", root, rest.to_str().unwrap());
 
     // patterns can have no relative paths (after selectors) since it would be possible to
     // "move" out of the pattern using "../" and changing into a directory outside of root.
-    // do not allow such patterns (though the levels could be checked).
-    if rest
-        .components()
-        .any(|c| matches!(c, path::Component::ParentDir))
+    // do not allow such patterns unless explicitly requested (levels are checked separately).
+    if !options.parent_dirs
+        && rest
+            .components()
+            .any(|c| matches!(c, path::Component::ParentDir))
     {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
@@ -92,11 +174,681 @@ where
         ));
     }
 
+    // the remainder is matched against paths that are relative to the root, thus a remainder
+    // that starts with a prefix or root would never match
+    if has_prefix_or_root(rest.as_path()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "pattern remainder '{}' is an absolute path",
+                rest.to_string_lossy()
+            ),
+        ));
+    }
+
     // notice that calling unwrap() is safe since we created the PathBuf from the pattern,
     let rest = &pattern[pattern.len() - rest.to_str().unwrap().len()..];
     Ok((root, rest))
 }
 
+/// Lexically resolves the relative path components `..` within the remaining pattern.
+///
+/// Each `..` removes the preceding component of the pattern, even if it is a wildcard, e.g.,
+/// `build/*/../src/*.c` is resolved to `build/src/*.c`. Notice that this does not check whether
+/// any path matches the removed wildcard. An error is returned if the pattern would escape the
+/// root, e.g., for `*/../../*.c`, or if the resulting pattern is empty.
+pub(crate) fn normalize_parent_dirs(rest: &str) -> Result<String, io::Error> {
+    let mut components: Vec<&str> = vec![];
+    for c in rest.split('/') {
+        match c {
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("pattern remainder '{rest}' escapes the root directory"),
+                    ));
+                }
+            }
+            "." | "" => {}
+            c => components.push(c),
+        }
+    }
+
+    if components.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("pattern remainder '{rest}' resolves to an empty pattern"),
+        ));
+    }
+    Ok(components.join("/"))
+}
+
+/// Lexically normalizes the path, i.e., removes all `.` components and resolves `..` components
+/// without accessing the file system.
+///
+/// Each `..` removes the preceding component, e.g., `a/b/../c` is normalized to `a/c`. Leading
+/// `..` components of relative paths are kept, `..` components directly after the root are
+/// removed since the parent of the root is the root itself.
+pub(crate) fn normalize_path(p: &path::Path) -> path::PathBuf {
+    let mut normalized = path::PathBuf::new();
+    for c in p.components() {
+        match c {
+            path::Component::CurDir => {}
+            path::Component::ParentDir => match normalized.components().next_back() {
+                Some(path::Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(path::Component::RootDir) => {}
+                _ => normalized.push(c),
+            },
+            c => normalized.push(c),
+        }
+    }
+    if normalized.as_os_str().is_empty() && !p.as_os_str().is_empty() {
+        normalized.push(".");
+    }
+    normalized
+}
+
+/// Provides the longest common ancestor of both paths, lexically.
+///
+/// Returns `None` if the paths do not have any component in common, e.g., for an absolute and a
+/// relative path.
+pub(crate) fn common_ancestor(a: &path::Path, b: &path::Path) -> Option<path::PathBuf> {
+    let common: path::PathBuf = a
+        .components()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c)
+        .collect();
+    match common.as_os_str().is_empty() {
+        true => None,
+        false => Some(common),
+    }
+}
+
+/// Checks whether the path component of a pattern is a literal, i.e., contains no wildcards,
+/// alternates, character classes or regular expression segments.
+fn is_literal(c: path::Component<'_>) -> bool {
+    match c.as_os_str().to_str() {
+        Some(c) => !c.contains(['*', '?', '[', ']', '{', '}', '\\']) && !c.starts_with("<re:"),
+        None => true,
+    }
+}
+
+/// Checks if the pattern is an absolute path, i.e., if it would replace the root when pushed.
+///
+/// On windows a path is only absolute if it has a prefix and a root, but patterns such as
+/// `\path` or `C:path` would still replace the root.
+pub(crate) fn is_absolute_pattern(pattern: &str) -> bool {
+    path::Path::new(pattern).is_absolute() || has_prefix_or_root(pattern)
+}
+
+/// Checks if any of the components of the provided path is a prefix (windows) or root.
+fn has_prefix_or_root<P>(p: P) -> bool
+where
+    P: AsRef<path::Path>,
+{
+    p.as_ref()
+        .components()
+        .any(|c| matches!(c, path::Component::Prefix(_) | path::Component::RootDir))
+}
+
+/// Parses the content of a numeric range `start..end` within braces.
+///
+/// Returns the bounds and the width for zero-padded values (e.g., `01..10`), or `None` if the
+/// content is not a numeric range.
+fn parse_range(content: &str) -> Option<(i64, i64, usize)> {
+    let (start, end) = content.split_once("..")?;
+    fn digits(s: &str) -> &str {
+        s.strip_prefix('-').unwrap_or(s)
+    }
+    let is_number =
+        |s: &str| !digits(s).is_empty() && digits(s).chars().all(|c| c.is_ascii_digit());
+    if !is_number(start) || !is_number(end) {
+        return None;
+    }
+
+    let is_padded = |s: &str| digits(s).len() > 1 && digits(s).starts_with('0');
+    let width = match is_padded(start) || is_padded(end) {
+        true => start.len().max(end.len()),
+        false => 0,
+    };
+    Some((start.parse().ok()?, end.parse().ok()?, width))
+}
+
+/// Expands all numeric ranges `{start..end}` within the pattern.
+///
+/// E.g., the pattern `report_{2019..2021}/*.csv` is expanded to the patterns `report_2019/*.csv`,
+/// `report_2020/*.csv` and `report_2021/*.csv`. Ranges can be descending (`{3..1}`) and are
+/// zero-padded if any of the bounds has a leading zero (`{01..10}`). Braces that do not contain a
+/// numeric range are left untouched and thus are handled as alternates by `globset`. Backslashes
+/// only escape braces if `escape` is set, see [`token_end`].
+pub(crate) fn expand_ranges(pattern: &str, escape: bool) -> Vec<String> {
+    match find_range(pattern, escape) {
+        None => vec![pattern.to_string()],
+        Some((open, close, range)) => range_values(range)
+            .flat_map(|v| {
+                let expanded = format!("{}{}{}", &pattern[..open], v, &pattern[close + 1..]);
+                expand_ranges(&expanded, escape)
+            })
+            .collect(),
+    }
+}
+
+/// Replaces all numeric ranges `{start..end}` within the pattern by alternates of their values,
+/// e.g., `report_{08..10}.csv` by `report_{08,09,10}.csv`, see [`expand_ranges`].
+#[cfg(feature = "regex")]
+pub(crate) fn ranges_to_alternates(pattern: &str, escape: bool) -> String {
+    match find_range(pattern, escape) {
+        None => pattern.to_string(),
+        Some((open, close, range)) => format!(
+            "{}{{{}}}{}",
+            &pattern[..open],
+            range_values(range).collect::<Vec<_>>().join(","),
+            ranges_to_alternates(&pattern[close + 1..], escape)
+        ),
+    }
+}
+
+/// Provides the formatted values of a parsed numeric range.
+fn range_values((start, end, width): (i64, i64, usize)) -> impl Iterator<Item = String> {
+    let values: Vec<i64> = match start <= end {
+        true => (start..=end).collect(),
+        false => (end..=start).rev().collect(),
+    };
+    values.into_iter().map(move |v| format!("{v:0width$}"))
+}
+
+/// Counts the number of patterns that [`expand_ranges`] creates, without expanding the ranges.
+pub(crate) fn count_ranges(pattern: &str, escape: bool) -> usize {
+    match find_range(pattern, escape) {
+        None => 1,
+        Some((_, close, (start, end, _))) => {
+            let values = usize::try_from(start.abs_diff(end)).unwrap_or(usize::MAX);
+            values
+                .saturating_add(1)
+                .saturating_mul(count_ranges(&pattern[close + 1..], escape))
+        }
+    }
+}
+
+/// Provides the index of the last byte of the token that starts at byte `i` of the pattern.
+///
+/// Tokens that span multiple bytes are escaped characters (only if `escape` is set, see
+/// [`Builder::backslash_escape`](crate::Builder::backslash_escape)), character classes `[...]`
+/// and regular expression segments `<re:...>`, which always span an entire path component. All
+/// other tokens, including unclosed classes and segments, consist of a single byte.
+pub(crate) fn token_end(pattern: &str, i: usize, escape: bool) -> usize {
+    let bytes = pattern.as_bytes();
+    match bytes[i] {
+        b'\\' if escape && i + 1 < bytes.len() => i + 1,
+        b'[' => {
+            // a closing bracket directly after the opening bracket or negation is a member
+            let mut j = i + 1;
+            if matches!(bytes.get(j), Some(b'!' | b'^')) {
+                j += 1;
+            }
+            if bytes.get(j) == Some(&b']') {
+                j += 1;
+            }
+            pattern
+                .get(j..)
+                .and_then(|rest| rest.find(']'))
+                .map_or(i, |close| j + close)
+        }
+        b'<' if (i == 0 || bytes[i - 1] == b'/') && pattern[i..].starts_with("<re:") => {
+            let end = pattern[i..].find('/').map_or(bytes.len(), |end| i + end);
+            match bytes[end - 1] == b'>' {
+                true => end - 1,
+                false => i,
+            }
+        }
+        _ => i,
+    }
+}
+
+/// Finds the first numeric range within the pattern.
+///
+/// Provides the positions of the opening and closing braces and the parsed range.
+fn find_range(pattern: &str, escape: bool) -> Option<(usize, usize, (i64, i64, usize))> {
+    let bytes = pattern.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'{' {
+            let close = pattern[i..].find('}')? + i;
+            if let Some(range) = parse_range(&pattern[i + 1..close]) {
+                return Some((i, close, range));
+            }
+        }
+        i = token_end(pattern, i, escape) + 1;
+    }
+    None
+}
+
+/// Expands all alternates `{a,b}` within the pattern, including nested alternates.
+///
+/// E.g., the pattern `{src,include}/**/*.{c,h}` is expanded to the patterns `src/**/*.c`,
+/// `src/**/*.h`, `include/**/*.c` and `include/**/*.h`. Each expanded pattern thus has its own
+/// literal prefix, which allows to skip all folders that are not part of any alternative. The
+/// number of patterns grows exponentially with the number of alternates, see [`count_braces`],
+/// and thus the expansion is only used to restrict the walk, whereas the compiled globs keep the
+/// alternates, see [`flatten_braces`]. Braces without a comma, e.g., numeric ranges, escaped
+/// braces and braces within character classes or regular expression segments are left
+/// untouched.
+///
+/// Empty alternatives, e.g., in `{a,}/*.rs`, are only expanded if `empty_alternates` is set, in
+/// the same way as `globset` ignores empty alternatives by default. Backslashes only escape
+/// braces if `escape` is set, see [`token_end`].
+pub(crate) fn expand_braces(pattern: &str, empty_alternates: bool, escape: bool) -> Vec<String> {
+    match find_alternates(pattern, empty_alternates, escape) {
+        None => vec![pattern.to_string()],
+        Some((open, close, alternatives)) => alternatives
+            .into_iter()
+            .flat_map(|alternative| {
+                let expanded = format!(
+                    "{}{}{}",
+                    &pattern[..open],
+                    alternative,
+                    &pattern[close + 1..]
+                );
+                expand_braces(&expanded, empty_alternates, escape)
+            })
+            .collect(),
+    }
+}
+
+/// Counts the number of patterns that [`expand_braces`] creates, without expanding the braces.
+pub(crate) fn count_braces(pattern: &str, empty_alternates: bool, escape: bool) -> usize {
+    match find_alternates(pattern, empty_alternates, escape) {
+        None => 1,
+        Some((_, close, alternatives)) => alternatives
+            .into_iter()
+            .map(|alternative| count_braces(alternative, empty_alternates, escape))
+            .fold(0, usize::saturating_add)
+            .saturating_mul(count_braces(
+                &pattern[close + 1..],
+                empty_alternates,
+                escape,
+            )),
+    }
+}
+
+/// Flattens nested alternates, e.g., `{a,b{c,d}}` to `{a,bc,bd}`, since `globset` does not
+/// support nested alternates.
+///
+/// Alternates that are not nested are left untouched. Provides `None` if a group of nested
+/// alternates expands to more than `max` alternatives.
+pub(crate) fn flatten_braces(
+    pattern: &str,
+    empty_alternates: bool,
+    escape: bool,
+    max: usize,
+) -> Option<String> {
+    let mut flattened = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some((open, close, alternatives)) = find_alternates(rest, empty_alternates, escape) {
+        flattened.push_str(&rest[..open]);
+        let nested = alternatives
+            .iter()
+            .any(|alternative| find_alternates(alternative, empty_alternates, escape).is_some());
+        if nested {
+            let count = alternatives
+                .iter()
+                .map(|alternative| count_braces(alternative, empty_alternates, escape))
+                .fold(0, usize::saturating_add);
+            if count > max {
+                return None;
+            }
+            let expanded: Vec<_> = alternatives
+                .iter()
+                .flat_map(|alternative| expand_braces(alternative, empty_alternates, escape))
+                .collect();
+            flattened.push('{');
+            flattened.push_str(&expanded.join(","));
+            flattened.push('}');
+        } else {
+            flattened.push_str(&rest[open..=close]);
+        }
+        rest = &rest[close + 1..];
+    }
+    flattened.push_str(rest);
+    Some(flattened)
+}
+
+/// Finds the first group of alternates within the pattern.
+///
+/// Provides the positions of the opening and closing braces and the alternatives, which may
+/// contain nested alternates themselves. Empty alternatives are removed unless `empty_alternates`
+/// is set, but at least one alternative is provided.
+fn find_alternates(
+    pattern: &str,
+    empty_alternates: bool,
+    escape: bool,
+) -> Option<(usize, usize, Vec<&str>)> {
+    let bytes = pattern.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'{' {
+            let mut depth = 0;
+            let mut nested = false;
+            let mut start = i + 1;
+            let mut alternatives = vec![];
+            let mut j = i;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'{' => {
+                        depth += 1;
+                        nested |= depth > 1;
+                    }
+                    b'}' if depth == 1 => {
+                        // a group without comma is only expanded if it contains alternates
+                        if alternatives.is_empty() && !nested {
+                            break;
+                        }
+                        alternatives.push(&pattern[start..j]);
+                        if !empty_alternates {
+                            alternatives.retain(|a| !a.is_empty());
+                            if alternatives.is_empty() {
+                                alternatives.push("");
+                            }
+                        }
+                        return Some((i, j, alternatives));
+                    }
+                    b'}' => depth -= 1,
+                    b',' if depth == 1 => {
+                        alternatives.push(&pattern[start..j]);
+                        start = j + 1;
+                    }
+                    _ => {}
+                }
+                j = token_end(pattern, j, escape) + 1;
+            }
+        }
+        i = token_end(pattern, i, escape) + 1;
+    }
+    None
+}
+
+/// Counts the globstars `**` within the pattern, including globstars within alternates.
+pub(crate) fn count_globstar_segments(pattern: &str) -> usize {
+    pattern
+        .split(['/', '{', ',', '}'])
+        .filter(|component| *component == "**")
+        .count()
+}
+
+/// Expands each globstar `**` within the pattern to at most `max_depth` path components.
+///
+/// A globstar at the start or within the pattern, e.g., `**/*.txt`, is replaced by zero up to
+/// `max_depth` components `*`. A trailing globstar, e.g., `src/**`, matches at least one
+/// component (as in `globset`) and thus is replaced by one up to `max_depth` components.
+pub(crate) fn expand_globstar(pattern: &str, max_depth: usize) -> Vec<String> {
+    let components: Vec<_> = pattern.split('/').collect();
+    if !components.contains(&"**") {
+        return vec![pattern.to_string()];
+    }
+
+    let last = components.len() - 1;
+    let mut expanded: Vec<Vec<&str>> = vec![vec![]];
+    for (i, component) in components.into_iter().enumerate() {
+        if component != "**" {
+            expanded.iter_mut().for_each(|e| e.push(component));
+            continue;
+        }
+
+        let min_depth = usize::from(i == last);
+        expanded = expanded
+            .into_iter()
+            .flat_map(|e| {
+                (min_depth..=max_depth.max(min_depth)).map(move |depth| {
+                    let mut e = e.clone();
+                    e.resize(e.len() + depth, "*");
+                    e
+                })
+            })
+            .collect();
+    }
+    expanded.into_iter().map(|e| e.join("/")).collect()
+}
+
+/// Counts the number of patterns that [`expand_globstar`] creates, without expanding the pattern.
+pub(crate) fn count_globstar(pattern: &str, max_depth: usize) -> usize {
+    let components: Vec<_> = pattern.split('/').collect();
+    let last = components.len() - 1;
+    components
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c == "**")
+        .map(|(i, _)| match i == last {
+            true => max_depth.max(1),
+            false => max_depth.saturating_add(1),
+        })
+        .fold(1, usize::saturating_mul)
+}
+
+/// Computes the maximum number of path components of any path that matches the pattern.
+///
+/// Provides `None` if the number of components is unbounded, i.e., if the pattern contains a
+/// globstar `**` and `globstar_max_depth` is not configured. Alternates and character classes
+/// that contain a path separator, e.g., `{a,b/c}`, are not analyzed and also result in `None`.
+pub(crate) fn max_depth(
+    pattern: &str,
+    globstar_max_depth: Option<usize>,
+    escape: bool,
+) -> Option<usize> {
+    if has_nested_separator(pattern, escape) {
+        return None;
+    }
+
+    pattern
+        .split('/')
+        .map(|c| match c {
+            "**" => globstar_max_depth.map(|depth| depth.max(1)),
+            _ => Some(1),
+        })
+        .try_fold(0, |sum: usize, depth| Some(sum.saturating_add(depth?)))
+}
+
+/// Checks whether any alternate or character class of the pattern contains a path separator.
+///
+/// Such patterns cannot be split into path components at the path separators. Backslashes only
+/// escape braces and brackets if `escape` is set, see [`token_end`].
+pub(crate) fn has_nested_separator(pattern: &str, escape: bool) -> bool {
+    let bytes = pattern.as_bytes();
+    let mut braces = 0_usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = token_end(pattern, i, escape);
+        match bytes[i] {
+            b'[' if end > i && pattern[i..end].contains('/') => return true,
+            b'{' => braces += 1,
+            b'}' => braces = braces.saturating_sub(1),
+            b'/' if braces > 0 => return true,
+            _ => {}
+        }
+        i = end + 1;
+    }
+    false
+}
+
+/// Probes whether the file system of the given directory is case sensitive.
+///
+/// Looks up the entries of the directory with a different case of their names, no files are
+/// created. Symbolic links are skipped since they may point to a different file system. Provides
+/// `None` if no entry with a name that contains letters is found.
+pub(crate) fn probe_case_sensitive(dir: &path::Path) -> Option<bool> {
+    let swap_case = |name: &str| -> Option<String> {
+        let swapped: String = name
+            .chars()
+            .map(|c| match c.is_uppercase() {
+                true => c.to_ascii_lowercase(),
+                false => c.to_ascii_uppercase(),
+            })
+            .collect();
+        (swapped != name).then_some(swapped)
+    };
+
+    let mut entries = fs::read_dir(dir).ok()?.flatten();
+    entries.find_map(|entry| {
+        if entry.file_type().ok()?.is_symlink() {
+            return None;
+        }
+        let candidate = entry.path();
+        let swapped = candidate.with_file_name(swap_case(candidate.file_name()?.to_str()?)?);
+        Some(match (file_id(&candidate, true), file_id(&swapped, true)) {
+            (Some(original), Some(swapped)) => original != swapped,
+            (Some(_), None) => true,
+            _ => return None,
+        })
+    })
+}
+
+/// Identifier of the file underlying a path, used to detect hard links and paths that resolve to
+/// the same file.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum FileId {
+    /// Device and inode of the file.
+    #[cfg(unix)]
+    Inode(u64, u64),
+    /// Canonical path of the file, used on platforms without access to file identifiers.
+    #[cfg(not(unix))]
+    Path(path::PathBuf),
+}
+
+/// Provides the identifier of the file at the given path, where `follow` resolves symbolic links
+/// to their targets instead of identifying the links themselves.
+///
+/// Provides `None` if the file cannot be accessed, e.g., for followed broken symbolic links.
+#[cfg(unix)]
+pub(crate) fn file_id(p: &path::Path, follow: bool) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = match follow {
+        true => fs::metadata(p).ok()?,
+        false => fs::symlink_metadata(p).ok()?,
+    };
+    Some(FileId::Inode(meta.dev(), meta.ino()))
+}
+
+/// Provides the identifier of the file at the given path, where `follow` resolves symbolic links
+/// to their targets instead of identifying the links themselves.
+///
+/// Without access to file identifiers, the canonical path is used, i.e., hard links are not
+/// detected. Provides `None` if the file cannot be accessed.
+#[cfg(not(unix))]
+pub(crate) fn file_id(p: &path::Path, follow: bool) -> Option<FileId> {
+    if follow {
+        return fs::canonicalize(p).ok().map(FileId::Path);
+    }
+    // only the parent is resolved, such that the link itself is identified
+    fs::symlink_metadata(p).ok()?;
+    let parent = p.parent().filter(|parent| !parent.as_os_str().is_empty());
+    let parent = fs::canonicalize(parent.unwrap_or(path::Path::new("."))).ok()?;
+    Some(FileId::Path(parent.join(p.file_name()?)))
+}
+
+/// Checks whether the path `p` resolves to a path within the canonical `root`, following all
+/// symbolic links.
+///
+/// Paths that cannot be resolved, e.g., broken symbolic links, are never within the root.
+pub(crate) fn is_within(p: &path::Path, root: &path::Path) -> bool {
+    fs::canonicalize(p).is_ok_and(|p| p.starts_with(root))
+}
+
+/// Checks whether the glob contains a `..` component, i.e., may refer to a parent directory.
+pub(crate) fn has_parent_dir(glob: &str) -> bool {
+    glob.split('/').any(|component| component == "..")
+}
+
+/// Expands a leading tilde `~` and all environment variables `$NAME` or `${NAME}` in the string.
+///
+/// The values are provided by the `lookup` function, `~` is looked up as `HOME` (`USERPROFILE`
+/// on Windows). A `$` that is not followed by a variable name is kept. Provides the name of the
+/// first variable that is not set as error.
+pub(crate) fn expand_env<F>(s: &str, lookup: F) -> Result<borrow::Cow<'_, str>, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if !s.starts_with('~') && !s.contains('$') {
+        return Ok(borrow::Cow::Borrowed(s));
+    }
+
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+    if let Some(after) = s.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            let home = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+            expanded.push_str(&lookup(home).ok_or_else(|| home.to_string())?);
+            rest = after;
+        }
+    }
+
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, tail) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after.find(|c| !is_name(c)).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+
+        if name.is_empty() || !name.chars().all(is_name) {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        expanded.push_str(&lookup(name).ok_or_else(|| name.to_string())?);
+        rest = tail;
+    }
+    expanded.push_str(rest);
+    Ok(borrow::Cow::Owned(expanded))
+}
+
+/// Splits an absolute glob into the root of the file system and the relative remainder, e.g.,
+/// `/home/user/**/*.rs` is split into `/` and `home/user/**/*.rs`.
+///
+/// Provides `None` if the glob is relative.
+pub(crate) fn split_absolute(glob: &str) -> Option<(path::PathBuf, String)> {
+    if !path::Path::new(glob).is_absolute() {
+        return None;
+    }
+
+    // the remainder is taken from the glob as is, since the components of a path omit `.`
+    // components and trailing separators
+    let mut anchor = path::PathBuf::new();
+    let mut offset = 0;
+    for c in path::Path::new(glob).components() {
+        match c {
+            path::Component::Prefix(prefix) => {
+                anchor.push(c);
+                offset += prefix.as_os_str().len();
+            }
+            path::Component::RootDir => {
+                anchor.push(c);
+                break;
+            }
+            _ => break,
+        }
+    }
+    let rest = glob[offset..].trim_start_matches(path::is_separator);
+    Some((anchor, rest.to_string()))
+}
+
+/// Shortens the provided glob for error messages, e.g., for globs exceeding a length limit.
+pub(crate) fn shorten(glob: &str) -> String {
+    const MAX_CHARS: usize = 64;
+    match glob.char_indices().nth(MAX_CHARS) {
+        None => glob.to_string(),
+        Some((i, _)) => format!("{}...", &glob[..i]),
+    }
+}
+
 /// Transforms the first character of a string to uppercase.
 pub(crate) fn to_upper(s: String) -> String {
     let mut c = s.chars();
@@ -106,25 +858,64 @@ pub(crate) fn to_upper(s: String) -> String {
     }
 }
 
+/// Compares the names in natural order, i.e., runs of digits are compared by their numeric value
+/// such that `file2.txt` is ordered before `file10.txt`. All other bytes are compared
+/// lexicographically, equal numbers with leading zeros are ordered after the shorter number.
+pub(crate) fn natural_cmp(a: &ffi::OsStr, b: &ffi::OsStr) -> cmp::Ordering {
+    // provides the end of the run of digits, the number without leading zeros and the zeros
+    fn digits(s: &[u8], start: usize) -> (usize, &[u8], usize) {
+        let end = s[start..]
+            .iter()
+            .position(|c| !c.is_ascii_digit())
+            .map_or(s.len(), |n| start + n);
+        let run = &s[start..end];
+        let zeros = run.iter().take_while(|c| **c == b'0').count();
+        (end, &run[zeros..], zeros)
+    }
+
+    let (a, b) = (a.as_encoded_bytes(), b.as_encoded_bytes());
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let (end_a, num_a, zeros_a) = digits(a, i);
+            let (end_b, num_b, zeros_b) = digits(b, j);
+            let ord = num_a
+                .len()
+                .cmp(&num_b.len())
+                .then_with(|| num_a.cmp(num_b))
+                .then_with(|| zeros_a.cmp(&zeros_b));
+            if ord != cmp::Ordering::Equal {
+                return ord;
+            }
+            (i, j) = (end_a, end_b);
+        } else {
+            match a[i].cmp(&b[j]) {
+                cmp::Ordering::Equal => (i, j) = (i + 1, j + 1),
+                ord => return ord,
+            }
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
 /// Checks if the provided path is a hidden "entry".
 ///
 /// An entry is hidden if its final path component (filename or directory name) starts with a dot,
 /// e.g., `.git` or `.clang-format` are hidden entries.
 ///
 /// This function can be used in [IterAll::filter_entry](./struct.IterAll.html#method.filter_entry)
-/// to avoid iterating through hidden paths.
+/// to avoid iterating through hidden paths. Use a [`HiddenPolicy`](crate::HiddenPolicy) for
+/// other conventions, e.g., to also hide `__pycache__` folders.
 pub fn is_hidden_entry<P>(path: P) -> bool
 where
     P: AsRef<path::Path>,
 {
-    let is_hidden = path
+    let name = path
         .as_ref()
         .file_name()
-        .unwrap_or_else(|| path.as_ref().as_os_str())
-        .to_str()
-        .map(|s| s.starts_with('.'))
-        .unwrap_or(false);
-    is_hidden
+        .unwrap_or_else(|| path.as_ref().as_os_str());
+    starts_with_dot(name)
 }
 
 /// Checks if the provided path has a hidden path component.
@@ -134,21 +925,88 @@ pub fn is_hidden_path<P>(path: P) -> bool
 where
     P: AsRef<path::Path>,
 {
-    let has_hidden = path.as_ref().components().find(|c| {
-        c.as_os_str()
-            .to_str()
-            .map(|s| s.starts_with('.'))
-            .unwrap_or(false)
-    });
+    path.as_ref()
+        .components()
+        .any(|c| starts_with_dot(c.as_os_str()))
+}
+
+/// Checks whether the name starts with a dot, also for names that are not valid unicode.
+fn starts_with_dot(name: &ffi::OsStr) -> bool {
+    name.as_encoded_bytes().first() == Some(&b'.')
+}
+
+/// Temporary directory for tests, which is removed including its contents when dropped.
+#[cfg(test)]
+pub(crate) struct TempDir(path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    /// Creates the empty directory `globmatch-<name>-<pid>` within the temporary directory.
+    pub(crate) fn new(name: &str) -> Result<TempDir, String> {
+        let p = std::env::temp_dir().join(format!("globmatch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&p);
+        fs::create_dir_all(&p).map_err(|err| err.to_string())?;
+        Ok(TempDir(p))
+    }
+
+    /// Creates the directory at the relative path `name`, including all parent directories.
+    pub(crate) fn mkdir<P>(&self, name: P) -> Result<path::PathBuf, String>
+    where
+        P: AsRef<path::Path>,
+    {
+        let p = self.0.join(name);
+        fs::create_dir_all(&p).map_err(|err| err.to_string())?;
+        Ok(p)
+    }
+
+    /// Writes the file at the relative path `name`, including all parent directories.
+    pub(crate) fn write<P, C>(&self, name: P, content: C) -> Result<path::PathBuf, String>
+    where
+        P: AsRef<path::Path>,
+        C: AsRef<[u8]>,
+    {
+        let p = self.0.join(name);
+        if let Some(parent) = p.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(&p, content).map_err(|err| err.to_string())?;
+        Ok(p)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempDir {
+    type Target = path::Path;
+
+    fn deref(&self) -> &path::Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<path::Path> for TempDir {
+    fn as_ref(&self) -> &path::Path {
+        &self.0
+    }
+}
 
-    has_hidden.is_some()
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     // use super::*;
 
-    use super::resolve_root;
+    use super::{
+        common_ancestor, count_braces, count_globstar, count_ranges, expand_braces, expand_env,
+        expand_globstar, expand_ranges, flatten_braces, has_prefix_or_root, max_depth, natural_cmp,
+        normalize_parent_dirs, normalize_path, probe_case_sensitive, resolve_root, split_absolute,
+        TempDir,
+    };
     use std::{io, path};
 
     #[test]
@@ -255,6 +1113,337 @@ mod tests {
             "test-files/c-simple/a/a0",
             "a0_0.txt",
         )?;
+
+        // all leading literal components are moved to the root
+        tst(
+            "",
+            "test-files/c-simple/a/a1/**/*.txt",
+            "test-files/c-simple/a/a1",
+            "**/*.txt",
+        )?;
+        tst("", "test-files/c-*/a/*.txt", "test-files", "c-*/a/*.txt")?;
+        Ok(())
+    }
+
+    #[test]
+    fn literal_prefix() -> Result<(), String> {
+        let root = TempDir::new("literal")?;
+        root.mkdir("[ab]")?;
+
+        // a folder with the name of a character class does not replace the class
+        let (resolved, rest) = resolve_root(&root, "[ab]/*.txt").map_err(|err| err.to_string())?;
+        assert_eq!(root.to_path_buf(), resolved);
+        assert_eq!("[ab]/*.txt", rest);
+        Ok(())
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(vec!["*.txt"], expand_ranges("*.txt", true));
+        assert_eq!(vec!["{a,b}/*.txt"], expand_ranges("{a,b}/*.txt", true));
+        assert_eq!(vec!["{1..}/*.txt"], expand_ranges("{1..}/*.txt", true));
+        assert_eq!(vec!["a1", "a2", "a3"], expand_ranges("a{1..3}", true));
+        assert_eq!(vec!["a3", "a2", "a1"], expand_ranges("a{3..1}", true));
+        assert_eq!(vec!["a-1", "a0", "a1"], expand_ranges("a{-1..1}", true));
+        assert_eq!(vec!["09", "10"], expand_ranges("{09..10}", true));
+        assert_eq!(vec![r"\{1..2}"], expand_ranges(r"\{1..2}", true));
+        assert_eq!(vec![r"\1", r"\2"], expand_ranges(r"\{1..2}", false));
+        assert_eq!(vec!["[{]1..2}"], expand_ranges("[{]1..2}", true));
+        assert_eq!(
+            vec![
+                "a1/b{x,y}/c0",
+                "a1/b{x,y}/c1",
+                "a2/b{x,y}/c0",
+                "a2/b{x,y}/c1"
+            ],
+            expand_ranges("a{1..2}/b{x,y}/c{0..1}", true)
+        );
+    }
+
+    #[test]
+    fn braces() {
+        assert_eq!(vec!["*.txt"], expand_braces("*.txt", false, true));
+        assert_eq!(
+            vec!["a/*.txt", "b/*.txt"],
+            expand_braces("{a,b}/*.txt", false, true)
+        );
+        assert_eq!(vec!["*.md"], expand_braces("*.{md,}", false, true));
+        assert_eq!(vec!["a{1..3}/b"], expand_braces("a{1..3}/b", false, true));
+        assert_eq!(vec!["{a}/b"], expand_braces("{a}/b", false, true));
+        assert_eq!(vec![r"\{a,b}/c"], expand_braces(r"\{a,b}/c", false, true));
+        assert_eq!(
+            vec![r"\a/c", r"\b/c"],
+            expand_braces(r"\{a,b}/c", false, false)
+        );
+        assert_eq!(vec!["[{,]/c"], expand_braces("[{,]/c", false, true));
+        assert_eq!(vec!["[]{,]/c"], expand_braces("[]{,]/c", false, true));
+        assert_eq!(vec!["[!]{,]/c"], expand_braces("[!]{,]/c", false, true));
+        assert_eq!(
+            vec!["<re:a{1,2}>/c"],
+            expand_braces("<re:a{1,2}>/c", false, true)
+        );
+        assert_eq!(
+            vec!["a/c", "bc/c", "bd/c", "/c"],
+            expand_braces("{a,b{c,d},}/c", true, true)
+        );
+        assert_eq!(
+            vec!["*a", "*bc", "*bd", "*"],
+            expand_braces("*{a,b{c,d},}", true, true)
+        );
+        assert_eq!(vec!["a/c", "b/c"], expand_braces("{a,,b,}/c", false, true));
+        assert_eq!(vec!["/c"], expand_braces("{,}/c", false, true));
+        assert_eq!(
+            vec!["src/*.c", "src/*.h", "inc/*.c", "inc/*.h"],
+            expand_braces("{src,inc}/*.{c,h}", false, true)
+        );
+        assert_eq!(
+            vec!["a/x/*.txt", "b/y/*.txt"],
+            expand_braces("{a/x,b/y}/*.txt", false, true)
+        );
+    }
+
+    #[test]
+    fn flattened_braces() {
+        let flatten = |pattern| flatten_braces(pattern, false, true, 8);
+        assert_eq!(Some("*.{c,h}"), flatten("*.{c,h}").as_deref());
+        assert_eq!(Some("{a,bc,bd}/*"), flatten("{a,b{c,d}}/*").as_deref());
+        assert_eq!(
+            Some("{a,b}/{x,y}/{c,d}{e,f}"),
+            flatten("{a,b}/{x,y}/{c,d}{e,f}").as_deref()
+        );
+        assert_eq!(
+            Some("{ace,acf,ade,adf}"),
+            flatten("{a{c,d}{e,f}}").as_deref()
+        );
+        assert_eq!(
+            Some("{a,b}"),
+            flatten_braces("{a,{b,}}", false, true, 8).as_deref()
+        );
+        assert_eq!(
+            Some("{a,b,}"),
+            flatten_braces("{a,{b,}}", true, true, 8).as_deref()
+        );
+        assert_eq!(None, flatten("{a{0,1}{0,1}{0,1}{0,1}}"));
+    }
+
+    #[test]
+    fn globstar() {
+        assert_eq!(vec!["*.txt"], expand_globstar("*.txt", 2));
+        assert_eq!(vec!["*.txt"], expand_globstar("**/*.txt", 0));
+        assert_eq!(
+            vec!["*.txt", "*/*.txt", "*/*/*.txt"],
+            expand_globstar("**/*.txt", 2)
+        );
+        assert_eq!(vec!["a/*", "a/*/*"], expand_globstar("a/**", 2));
+        assert_eq!(vec!["a/*"], expand_globstar("a/**", 0));
+        assert_eq!(vec!["a/b/*", "a/*/b/*"], expand_globstar("a/**/b/**", 1));
+    }
+
+    #[test]
+    fn depth() {
+        assert_eq!(Some(1), max_depth("*.yaml", None, true));
+        assert_eq!(Some(3), max_depth("src/*/Cargo.{toml,lock}", None, true));
+        assert_eq!(None, max_depth("src/**/*.rs", None, true));
+        assert_eq!(Some(2 + 2), max_depth("src/**/*.rs", Some(2), true));
+        assert_eq!(Some(2), max_depth("a**b/*.rs", None, true));
+        assert_eq!(None, max_depth("{a,b/c}/*.rs", None, true));
+        assert_eq!(None, max_depth("[/]/*.rs", None, true));
+        assert_eq!(Some(2), max_depth("\\{/*.rs", None, true));
+        assert_eq!(None, max_depth("\\{/*.rs}", None, false));
+        assert_eq!(None, max_depth("[]/]/*.rs", None, true));
+        assert_eq!(None, max_depth("a/[!]/]", None, true));
+        assert_eq!(Some(2), max_depth("[]{]/*.rs", None, true));
+    }
+
+    #[test]
+    fn absolute_patterns() {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        assert!(!has_prefix_or_root("test-files/*.txt"));
+        assert!(has_prefix_or_root("/test-files/*.txt"));
+        assert!(resolve_root(root, "/test-files/*.txt").is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn absolute_patterns_windows() {
+        let root = env!("CARGO_MANIFEST_DIR");
+
+        // neither of these paths is absolute on windows, but would replace the root
+        assert!(!std::path::Path::new(r"\test-files\*.txt").is_absolute());
+        assert!(has_prefix_or_root(r"\test-files\*.txt"));
+        assert!(resolve_root(root, r"\test-files\*.txt").is_err());
+
+        assert!(!std::path::Path::new("C:test-files/*.txt").is_absolute());
+        assert!(has_prefix_or_root("C:test-files/*.txt"));
+        assert!(resolve_root(root, "C:test-files/*.txt").is_err());
+    }
+
+    #[test]
+    fn parent_dirs() -> Result<(), io::Error> {
+        assert_eq!(
+            "build/src/*.c",
+            normalize_parent_dirs("build/*/../src/*.c")?
+        );
+        assert_eq!("src/*.c", normalize_parent_dirs("*/*/../../src/*.c")?);
+        assert_eq!("*.c", normalize_parent_dirs("./*/../*.c")?);
+        assert!(normalize_parent_dirs("*/../../*.c").is_err());
+        assert!(normalize_parent_dirs("*/..").is_err());
         Ok(())
     }
+
+    #[test]
+    fn ancestor() {
+        let ancestor = |a: &str, b: &str| common_ancestor(a.as_ref(), b.as_ref());
+        assert_eq!(
+            Some(path::PathBuf::from("x/y")),
+            ancestor("x/y/a", "x/y/b/c")
+        );
+        assert_eq!(Some(path::PathBuf::from("x/y")), ancestor("x/y", "x/y"));
+        assert_eq!(None, ancestor("a", "b"));
+        #[cfg(unix)]
+        assert_eq!(None, ancestor("/a", "a"));
+    }
+
+    #[test]
+    fn normalize() {
+        let normalized = |p: &str| normalize_path(path::Path::new(p));
+        assert_eq!(path::PathBuf::from("a/c"), normalized("./a/b/../c"));
+        assert_eq!(path::PathBuf::from("../../a"), normalized("../x/../../a/."));
+        assert_eq!(path::PathBuf::from("."), normalized("a/.."));
+        #[cfg(unix)]
+        assert_eq!(path::PathBuf::from("/a"), normalized("/../a/b/.."));
+    }
+
+    #[test]
+    fn env() {
+        let lookup = |name: &str| match name {
+            "HOME" | "USERPROFILE" => Some("/home/user".to_string()),
+            "WS" => Some("ws".to_string()),
+            _ => None,
+        };
+        let expand = |s| expand_env(s, lookup).map(|s| s.into_owned());
+
+        assert_eq!(Ok("a/*.txt".to_string()), expand("a/*.txt"));
+        assert_eq!(Ok("/home/user/a/**".to_string()), expand("~/a/**"));
+        assert_eq!(Ok("/home/user".to_string()), expand("~"));
+        assert_eq!(Ok("~a/$/*.txt".to_string()), expand("~a/$/*.txt"));
+        assert_eq!(
+            Ok("ws/src/ws_1/**".to_string()),
+            expand("$WS/src/${WS}_1/**")
+        );
+        assert_eq!(Ok("a/${/b".to_string()), expand("a/${/b"));
+        assert_eq!(Err("OTHER".to_string()), expand("$OTHER/**"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn absolute() {
+        assert_eq!(None, split_absolute("a/**/*.rs"));
+        assert_eq!(
+            Some((path::PathBuf::from("/"), "home/user/**/*.rs".to_string())),
+            split_absolute("/home/user/**/*.rs")
+        );
+        assert_eq!(
+            Some((path::PathBuf::from("/"), "a/./b/*/".to_string())),
+            split_absolute("//a/./b/*/")
+        );
+    }
+
+    #[test]
+    fn case_probe() {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let probed = probe_case_sensitive(path::Path::new(&root));
+        assert!(probed.is_some());
+        if cfg!(target_os = "linux") {
+            assert_eq!(Some(true), probed);
+        }
+        assert_eq!(
+            None,
+            probe_case_sensitive(path::Path::new("does/not/exist"))
+        );
+    }
+
+    #[test]
+    fn normalized_root() -> Result<(), io::Error> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let expected = resolve_root(&root, "a/*.txt")?;
+
+        for prefix in ["/", "/.", "/./", "//", "/./."] {
+            let root = format!("{root}{prefix}");
+            assert_eq!(expected, resolve_root(&root, "a/*.txt")?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn counts() {
+        for pattern in ["*.txt", "a{1..3}", "a{3..1}/{01..10}", "{a,b}{-2..2}"] {
+            assert_eq!(
+                expand_ranges(pattern, true).len(),
+                count_ranges(pattern, true)
+            );
+        }
+        assert_eq!(
+            usize::MAX,
+            count_ranges("{0..9223372036854775807}{0..10}", true)
+        );
+
+        let patterns = [
+            "*.txt",
+            "{a,b}",
+            "{a,b{c,d}}/{x,y}",
+            "{a,b{c,d}}",
+            "{1..3}{a,b,}/c",
+        ];
+        for pattern in patterns {
+            for empty in [false, true] {
+                assert_eq!(
+                    expand_braces(pattern, empty, true).len(),
+                    count_braces(pattern, empty, true)
+                );
+            }
+        }
+
+        for pattern in ["*.txt", "**/*.txt", "a/**", "**/b/**/c/**"] {
+            for depth in 0..3 {
+                assert_eq!(
+                    expand_globstar(pattern, depth).len(),
+                    count_globstar(pattern, depth)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn natural_order() {
+        let mut names = vec![
+            "file10.txt",
+            "file2.txt",
+            "file02.txt",
+            "file1.txt",
+            "file.txt",
+            "file1b.txt",
+            "File3.txt",
+            "file100",
+        ];
+        names.sort_by(|a, b| natural_cmp(a.as_ref(), b.as_ref()));
+        assert_eq!(
+            vec![
+                "File3.txt",
+                "file.txt",
+                "file1.txt",
+                "file1b.txt",
+                "file2.txt",
+                "file02.txt",
+                "file10.txt",
+                "file100",
+            ],
+            names
+        );
+        assert_eq!(
+            std::cmp::Ordering::Equal,
+            natural_cmp("a007".as_ref(), "a007".as_ref())
+        );
+    }
 }
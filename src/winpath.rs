@@ -0,0 +1,161 @@
+//! Handling of Windows specific paths, i.e., verbatim (`\\?\C:\...`) and UNC (`\\server\share`)
+//! roots.
+//!
+//! Verbatim paths are passed to the file system without any normalization, thus `.` and `..`
+//! components are not resolved by the file system and must be resolved lexically. Since the same
+//! directory can be specified with and without the verbatim prefix, e.g., `\\?\C:\dir` and
+//! `C:\dir`, paths are compared without their verbatim prefix. Long paths do not need any special
+//! handling since the standard library uses verbatim paths for long paths internally.
+//!
+//! On all other platforms paths never contain a prefix and all functions behave like their
+//! counterparts of `std::path`.
+
+use std::path;
+
+/// Checks whether the path starts with a verbatim prefix, e.g., `\\?\C:\` or `\\?\UNC\server`.
+pub(crate) fn is_verbatim(p: &path::Path) -> bool {
+    match p.components().next() {
+        Some(path::Component::Prefix(prefix)) => prefix.kind().is_verbatim(),
+        _ => false,
+    }
+}
+
+/// Appends the component to the path.
+///
+/// For verbatim paths `.` is skipped and `..` removes the last component of the path, unless the
+/// path only consists of its prefix and root.
+pub(crate) fn push(p: &mut path::PathBuf, c: path::Component<'_>) {
+    if is_verbatim(p) {
+        match c {
+            path::Component::CurDir => return,
+            path::Component::ParentDir => {
+                if let Some(path::Component::Normal(_)) = p.components().next_back() {
+                    p.pop();
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
+    p.push(c);
+}
+
+/// Normalizes the spelling of the path, e.g., `dir/`, `dir/.` and `dir` are the same path.
+///
+/// Relative path components `..` are only resolved for verbatim paths, see [`push`].
+pub(crate) fn normalize(p: &path::Path) -> path::PathBuf {
+    let mut normalized = path::PathBuf::new();
+    for c in p.components() {
+        push(&mut normalized, c);
+    }
+    normalized
+}
+
+/// Removes the verbatim prefix of the path, if possible.
+///
+/// E.g., `\\?\C:\dir` is converted to `C:\dir` and `\\?\UNC\server\share\dir` is converted to
+/// `\\server\share\dir`. Other verbatim paths, e.g., `\\?\Volume{..}\dir`, are kept.
+fn simplify(p: &path::Path) -> path::PathBuf {
+    let mut components = p.components();
+    let prefix = match components.next() {
+        Some(path::Component::Prefix(prefix)) => match prefix.kind() {
+            path::Prefix::VerbatimDisk(disk) => format!("{}:", disk as char),
+            path::Prefix::VerbatimUNC(server, share) => format!(
+                r"\\{}\{}",
+                server.to_string_lossy(),
+                share.to_string_lossy()
+            ),
+            _ => return path::PathBuf::from(p),
+        },
+        _ => return path::PathBuf::from(p),
+    };
+
+    let mut simplified = path::PathBuf::from(prefix);
+    simplified.extend(components);
+    simplified
+}
+
+/// Removes the `root` from the start of the path, see `Path::strip_prefix`.
+///
+/// In contrast to `Path::strip_prefix` the verbatim prefixes of both paths are ignored, e.g.,
+/// `C:\dir` is stripped from `\\?\C:\dir\file.txt`, resulting in `file.txt`.
+pub(crate) fn strip_prefix<'p>(p: &'p path::Path, root: &path::Path) -> Option<&'p path::Path> {
+    if let Ok(relative) = p.strip_prefix(root) {
+        return Some(relative);
+    }
+    if !is_verbatim(p) && !is_verbatim(root) {
+        return None;
+    }
+
+    let simplified = simplify(p);
+    let relative = simplified.strip_prefix(simplify(root)).ok()?;
+
+    // the relative path consists of the trailing components of the original path
+    Some(suffix(p, relative.components().count()))
+}
+
+/// Provides the trailing `n` components of the path.
+fn suffix(p: &path::Path, n: usize) -> &path::Path {
+    let mut components = p.components();
+    for _ in n..p.components().count() {
+        components.next();
+    }
+    components.as_path()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip() {
+        let p = path::Path::new("dir/sub/file.txt");
+        assert_eq!(
+            Some(path::Path::new("sub/file.txt")),
+            strip_prefix(p, "dir".as_ref())
+        );
+        assert_eq!(None, strip_prefix(p, "other".as_ref()));
+        assert_eq!(path::Path::new("dir/sub"), normalize("dir/./sub/".as_ref()));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn verbatim() {
+        let root = path::Path::new(r"\\?\C:\dir\..\other\.\sub");
+        assert!(is_verbatim(root));
+        assert_eq!(path::Path::new(r"\\?\C:\other\sub"), normalize(root));
+        assert_eq!(
+            path::Path::new(r"\\?\C:\"),
+            normalize(r"\\?\C:\..\..".as_ref())
+        );
+        assert!(!is_verbatim(r"C:\dir".as_ref()));
+
+        let p = path::Path::new(r"\\?\C:\dir\file.txt");
+        assert_eq!(
+            Some(path::Path::new("file.txt")),
+            strip_prefix(p, r"C:\dir".as_ref())
+        );
+        let p = path::Path::new(r"C:\dir\sub\file.txt");
+        assert_eq!(
+            Some(path::Path::new(r"sub\file.txt")),
+            strip_prefix(p, r"\\?\C:\dir".as_ref())
+        );
+        assert_eq!(None, strip_prefix(p, r"\\?\D:\dir".as_ref()));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn unc() {
+        let p = path::Path::new(r"\\?\UNC\server\share\dir\file.txt");
+        assert!(is_verbatim(p));
+        assert_eq!(path::Path::new(r"\\server\share\dir\file.txt"), simplify(p));
+        assert_eq!(
+            Some(path::Path::new("file.txt")),
+            strip_prefix(p, r"\\server\share\dir".as_ref())
+        );
+
+        let mut root = path::PathBuf::from(r"\\server\share");
+        push(&mut root, path::Component::Normal("dir".as_ref()));
+        assert_eq!(path::Path::new(r"\\server\share\dir"), root);
+    }
+}
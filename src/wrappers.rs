@@ -4,6 +4,20 @@
 //! determine paths, and a common set of filters that are applied to all of these globs. The
 //! functions in this module cover this common usecase as demonstrated by the example below.
 //!
+//! # Inline flags
+//!
+//! Each pattern passed to the functions in this module may start with an inline flag that
+//! overrides the case sensitivity for this single pattern: `(?i)` matches case insensitive,
+//! `(?-i)` matches case sensitive, e.g., `(?i)assets/**/*.png`. This allows to mix case
+//! sensitive and case insensitive globs within the same list of patterns.
+//!
+//! # Negations
+//!
+//! Patterns that start with `!` are negations, e.g., `!**/generated/*.rs`. As for `.gitignore`
+//! files, the last pattern in the list that matches a path decides: a path that is matched by a
+//! negation is excluded unless it is matched again by a later pattern. Negations precede the
+//! inline flags, e.g., `!(?i)**/*.BAK`.
+//!
 //! # Example
 //!
 //! ```
@@ -62,18 +76,15 @@
 //! # example_usecase().unwrap();
 //! ```
 
-use std::path;
+use std::{collections, path};
 
-use crate::{utils, Builder, GlobSet, Matcher};
+use crate::{pattern, utils, Builder, Error, GlobSet, HiddenPolicy, Matcher, PathMatcher};
 
-fn extract_patterns<T>(candidates: Vec<Result<T, String>>) -> Result<Vec<T>, String> {
-    let failures: Vec<_> = candidates
-        .iter()
-        .filter_map(|f| match f {
-            Ok(_) => None,
-            Err(e) => Some(e),
-        })
-        .collect();
+pub(crate) fn extract_patterns<T, E>(candidates: Vec<Result<T, E>>) -> Result<Vec<T>, String>
+where
+    E: std::fmt::Display,
+{
+    let failures: Vec<_> = candidates.iter().filter_map(|f| f.as_ref().err()).collect();
 
     if !failures.is_empty() {
         return Err(format!(
@@ -88,12 +99,140 @@ fn extract_patterns<T>(candidates: Vec<Result<T, String>>) -> Result<Vec<T>, Str
     Ok(candidates.into_iter().flatten().collect())
 }
 
+/// Splits a leading negation `!` from the provided pattern.
+pub(crate) fn split_negation(pattern: &str) -> (bool, &str) {
+    match pattern.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    }
+}
+
+/// Splits an inline case sensitivity flag from the provided pattern.
+///
+/// Returns `Some(false)` for the prefix `(?i)`, `Some(true)` for the prefix `(?-i)` and `None` if
+/// the pattern does not start with an inline flag. The remaining pattern is returned without
+/// the flag.
+pub(crate) fn split_case_flag(pattern: &str) -> (Option<bool>, &str) {
+    if let Some(rest) = pattern.strip_prefix("(?i)") {
+        (Some(false), rest)
+    } else if let Some(rest) = pattern.strip_prefix("(?-i)") {
+        (Some(true), rest)
+    } else {
+        (None, pattern)
+    }
+}
+
+/// Normalizes the spelling of a single pattern, see [`normalize_patterns`].
+fn normalize_pattern(pattern: &str) -> String {
+    let (negated, pattern) = split_negation(pattern.trim());
+    let (flag, pattern) = split_case_flag(pattern);
+    let flag = match (negated, flag) {
+        (false, Some(false)) => "(?i)",
+        (false, Some(true)) => "(?-i)",
+        (false, None) => "",
+        (true, Some(false)) => "!(?i)",
+        (true, Some(true)) => "!(?-i)",
+        (true, None) => "!",
+    };
+
+    let mut components: Vec<&str> = vec![];
+    for (i, component) in pattern.split('/').enumerate() {
+        match component {
+            // keep the leading separator of absolute paths
+            "" if i == 0 => components.push(component),
+            "" | "." => {}
+            "**" if components.last() == Some(&"**") => {}
+            _ => components.push(component),
+        }
+    }
+
+    match components.as_slice() {
+        [] => format!("{flag}."),
+        [""] => format!("{flag}/"),
+        _ => format!("{flag}{}", components.join("/")),
+    }
+}
+
+/// Normalizes and deduplicates a list of patterns.
+///
+/// This is useful for tools that merge globs from multiple sources, e.g., configuration layers.
+/// Each pattern is trimmed and normalized to a common spelling, i.e., current directory
+/// components (`./a/**`), repeated or trailing separators (`a//b/`) and consecutive globstars
+/// (`**/**`) are removed. Patterns that are empty or that are equivalent to a previous pattern
+/// are removed. The order of the remaining patterns is preserved.
+///
+/// Use [`normalize_patterns_report`] to find out which patterns have been removed.
+///
+/// ```
+/// let patterns = globmatch::normalize_patterns(&["./a/**", " a/** ", "a//**/**/*.txt", ""]);
+/// assert_eq!(vec!["a/**", "a/**/*.txt"], patterns);
+/// ```
+pub fn normalize_patterns(patterns: &[&str]) -> Vec<String> {
+    normalize_patterns_report(patterns).0
+}
+
+/// Normalizes and deduplicates a list of patterns, reporting the removed patterns.
+///
+/// Behaves exactly like [`normalize_patterns`] but additionally provides a message for each
+/// pattern that has been removed, e.g., `"'./a/**': Equivalent to 'a/**'"`.
+pub fn normalize_patterns_report(patterns: &[&str]) -> (Vec<String>, Vec<String>) {
+    let mut normalized: Vec<String> = vec![];
+    let mut removed = vec![];
+
+    for pattern in patterns {
+        if pattern.trim().is_empty() {
+            removed.push(format!("'{pattern}': Empty glob"));
+            continue;
+        }
+
+        let candidate = normalize_pattern(pattern);
+        match normalized.iter().find(|p| **p == candidate) {
+            Some(existing) if existing == pattern => {
+                removed.push(format!("'{pattern}': Duplicate glob"));
+            }
+            Some(existing) => {
+                removed.push(format!("'{pattern}': Equivalent to '{existing}'"));
+            }
+            None => normalized.push(candidate),
+        }
+    }
+    (normalized, removed)
+}
+
 /// Builds a set of [`Matcher`]s for the list of `globs` relative to `root`.
 ///
 /// This function creates multiple [`Matcher`]s by calling the [`Builder::build`] for each of the
 /// provided globs. It then checks if any failures have occurred while building the [`Matcher`]
 /// instances; if for any of the provided globs the build fails an error is returned.
 ///
+/// Notice that each [`Matcher`] walks its root on its own. Use the [`crate::MultiMatcher`] to
+/// match many globs with a single walk.
+///
+/// Globs are matched case sensitive on all platforms except for Windows, unless the glob starts
+/// with an inline flag (see the [module documentation](self#inline-flags)). Use the [`Builder`]
+/// with [`CaseSensitivity::Auto`](crate::CaseSensitivity::Auto) to match globs according to the
+/// file system of the root.
+///
+/// Negated globs `!glob` do not create a [`Matcher`]. Instead, the paths that are matched by a
+/// negation are excluded from the [`Matcher`]s of all previous globs, unless a later glob matches
+/// the path again (see the [module documentation](self#negations)).
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+/// let globs = ["a/**/*.txt", "!a/a0/*", "a/a0/a0_1.txt"];
+///
+/// let matchers = globmatch::wrappers::build_matchers(&globs, &root)?;
+/// assert_eq!(2, matchers.len());
+///
+/// let paths: Vec<_> = matchers.into_iter().flat_map(|m| m.into_iter().flatten()).collect();
+/// // a1_0.txt, a2_0.txt and a0_1.txt, which is matched by both globs
+/// assert_eq!(4, paths.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+///
 /// # Errors
 ///
 /// Refer to [`Builder::build`]. Error checks are performed for each glob.
@@ -104,12 +243,53 @@ pub fn build_matchers<'a, P>(
 where
     P: AsRef<path::Path>,
 {
-    let candidates: Vec<Result<_, String>> = globs
+    build_matchers_with(globs, root, !cfg!(windows))
+}
+
+/// Implementation of [`build_matchers`] using the given default case sensitivity.
+fn build_matchers_with<'a, P>(
+    globs: &[&'a str],
+    root: P,
+    case_sensitive: bool,
+) -> Result<Vec<Matcher<'a, path::PathBuf>>, String>
+where
+    P: AsRef<path::Path>,
+{
+    let builder_for = |pattern: &'a str| {
+        let (flag, pattern) = split_case_flag(pattern);
+        let mut builder = Builder::new(pattern);
+        builder.case_sensitive(flag.unwrap_or(case_sensitive));
+        builder
+    };
+
+    // the rules of all globs, negations exclude the paths matched by a glob
+    let rules: Vec<Result<_, Error>> = globs
         .iter()
         .map(|pattern| {
-            Builder::new(pattern)
-                .case_sensitive(!cfg!(windows))
-                .build(root.as_ref())
+            let (negated, pattern) = split_negation(pattern);
+            let (base, rule) = builder_for(pattern).exclusion_for(root.as_ref())?;
+            Ok((base, rule, negated))
+        })
+        .collect();
+    let rules = extract_patterns(rules)?;
+
+    let candidates: Vec<Result<_, Error>> = globs
+        .iter()
+        .enumerate()
+        .filter(|(_, pattern)| !split_negation(pattern).0)
+        .map(|(index, pattern)| {
+            let mut matcher = builder_for(pattern).build(root.as_ref())?;
+            // only the globs following this glob decide whether a path is excluded
+            let later = &rules[index + 1..];
+            if later.iter().any(|(_, _, negated)| *negated) {
+                matcher.matcher.exclusions = Some(pattern::Exclusions::new(
+                    later
+                        .iter()
+                        .map(|(base, rule, negated)| (*base, rule.clone(), *negated))
+                        .collect(),
+                ));
+            }
+            Ok(matcher)
         })
         .collect();
 
@@ -117,12 +297,57 @@ where
     Ok(candidates)
 }
 
+/// Skips the empty or blank globs, providing the remaining globs and a warning for each skipped
+/// glob. Globs that only consist of a negation or an inline flag are considered to be empty.
+fn skip_empty<'a>(globs: &[&'a str]) -> (Vec<&'a str>, Vec<String>) {
+    let mut warnings = vec![];
+    let globs = globs
+        .iter()
+        .enumerate()
+        .filter(|(i, pattern)| {
+            let pattern = split_negation(pattern.trim()).1;
+            match split_case_flag(pattern).1.trim().is_empty() {
+                true => {
+                    warnings.push(format!("Skipped empty pattern at index {i}"));
+                    false
+                }
+                false => true,
+            }
+        })
+        .map(|(_, pattern)| *pattern)
+        .collect();
+    (globs, warnings)
+}
+
+/// Builds a set of [`Matcher`]s for the list of `globs` relative to `root`, skipping empty globs.
+///
+/// Behaves like [`build_matchers`] but does not fail for empty or blank globs, e.g., for
+/// artifacts of trailing commas in configuration files. Instead, such globs are skipped and a
+/// warning is provided for each skipped glob in addition to the list of [`Matcher`]s.
+///
+/// # Errors
+///
+/// Refer to [`build_matchers`]. Empty globs are not considered to be an error.
+pub fn build_matchers_lenient<'a, P>(
+    globs: &[&'a str],
+    root: P,
+) -> Result<(Vec<Matcher<'a, path::PathBuf>>, Vec<String>), String>
+where
+    P: AsRef<path::Path>,
+{
+    let (globs, warnings) = skip_empty(globs);
+    Ok((build_matchers(&globs, root)?, warnings))
+}
+
 /// Builds a set of [`GlobSet`]s for the list of provided `paths`.
 ///
 /// This function creates multiple [`GlobSet`]s by calling the [`Builder::build_glob_set`] function
 /// for each provided glob. It then checks if any failures have occurred while building the
 /// [`GlobSet`] instances; if for any of the provided paths the build fails an error is returned.
 ///
+/// The provided `case_sensitive` setting is used for all globs that do not start with an inline
+/// flag (see the [module documentation](self#inline-flags)).
+///
 /// # Errors
 ///
 /// Refer to [`Builder::build_glob_set`]. Error checks are performed for each glob.
@@ -133,12 +358,16 @@ pub fn build_glob_set<'a>(
     let paths = match paths {
         None => None,
         Some(paths_) => {
-            let candidates: Vec<Result<_, String>> = paths_
+            let candidates: Vec<Result<_, Error>> = paths_
                 .iter()
                 .map(|pattern| {
-                    Builder::new(pattern)
-                        .case_sensitive(case_sensitive)
-                        .build_glob_set()
+                    let (negated, pattern) = split_negation(pattern);
+                    let (flag, pattern) = split_case_flag(pattern);
+                    let mut set = Builder::new(pattern)
+                        .case_sensitive(flag.unwrap_or(case_sensitive))
+                        .build_glob_set()?;
+                    set.negated = negated;
+                    Ok(set)
                 })
                 .collect();
             Some(extract_patterns(candidates)?)
@@ -147,17 +376,321 @@ pub fn build_glob_set<'a>(
     Ok(paths)
 }
 
+/// Builds a set of [`GlobSet`]s for the list of provided `paths`, skipping empty globs.
+///
+/// Behaves like [`build_glob_set`] but skips empty or blank globs instead of failing, providing a
+/// warning for each skipped glob, see [`build_matchers_lenient`].
+///
+/// # Errors
+///
+/// Refer to [`build_glob_set`]. Empty globs are not considered to be an error.
+pub fn build_glob_set_lenient<'a>(
+    paths: &Option<Vec<&'a str>>,
+    case_sensitive: bool,
+) -> Result<(Option<Vec<GlobSet<'a>>>, Vec<String>), String> {
+    let (paths, warnings) = match paths {
+        None => (None, vec![]),
+        Some(paths) => {
+            let (paths, warnings) = skip_empty(paths);
+            (Some(paths), warnings)
+        }
+    };
+    Ok((build_glob_set(&paths, case_sensitive)?, warnings))
+}
+
+/// Checks whether the path is matched by the list of `patterns`, honoring negations.
+///
+/// The last [`GlobSet`] in the list that matches the path decides: the path is matched unless
+/// this [`GlobSet`] is negated (see [`GlobSet::is_negated`]). An empty list never matches any
+/// path. This is the matching used by the filters of [`match_paths`].
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let patterns = Some(vec!["**/*.txt", "!**/a0/*", "**/a0_1.txt"]);
+/// let patterns = globmatch::wrappers::build_glob_set(&patterns, true)?.unwrap();
+///
+/// assert!(globmatch::wrappers::is_match_ordered(&patterns, "a/a1/a1_0.txt"));
+/// assert!(!globmatch::wrappers::is_match_ordered(&patterns, "a/a0/a0_0.txt"));
+/// assert!(globmatch::wrappers::is_match_ordered(&patterns, "a/a0/a0_1.txt"));
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub fn is_match_ordered<P>(patterns: &[GlobSet<'_>], p: P) -> bool
+where
+    P: AsRef<path::Path>,
+{
+    patterns
+        .iter()
+        .rev()
+        .find(|pattern| pattern.is_match(p.as_ref()))
+        .is_some_and(|pattern| !pattern.is_negated())
+}
+
+/// Provides the indices of all `patterns` that match the path, in ascending order.
+///
+/// In contrast to [`is_match_ordered`] negations are not applied, i.e., the indices of negated
+/// [`GlobSet`]s that match the path are provided as well. Use [`GlobSet::is_negated`] to tell
+/// them apart, e.g., to report which pattern of a filter included or excluded a path.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let patterns = Some(vec!["**/*.txt", "!**/a0/*", "**/a0_1.txt"]);
+/// let patterns = globmatch::wrappers::build_glob_set(&patterns, true)?.unwrap();
+///
+/// assert_eq!(vec![0, 1], globmatch::wrappers::matches(&patterns, "a/a0/a0_0.txt"));
+/// assert_eq!(vec![0, 1, 2], globmatch::wrappers::matches(&patterns, "a/a0/a0_1.txt"));
+/// assert!(globmatch::wrappers::matches(&patterns, "b/b_1.md").is_empty());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub fn matches<P>(patterns: &[GlobSet<'_>], p: P) -> Vec<usize>
+where
+    P: AsRef<path::Path>,
+{
+    patterns
+        .iter()
+        .enumerate()
+        .filter(|(_, pattern)| pattern.is_match(p.as_ref()))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Provides the globs of all `patterns` that match the path, see [`matches()`].
+///
+/// The globs are provided as returned by [`GlobSet::glob`], i.e., without a leading negation
+/// `!` or inline flag.
+pub fn matched_globs<'s, P>(patterns: &'s [GlobSet<'_>], p: P) -> Vec<&'s str>
+where
+    P: AsRef<path::Path>,
+{
+    matches(patterns, p)
+        .into_iter()
+        .map(|idx| patterns[idx].glob())
+        .collect()
+}
+
+/// Globs and filters for [`match_paths`], e.g., as stored in a configuration file.
+///
+/// With the `serde` feature this struct can be deserialized, e.g., from the following JSON
+/// document. All fields except for `globs` are optional.
+///
+/// ```json
+/// {
+///   "globs": ["../../some/text-files/**/*.txt", "other/inputs/*.md"],
+///   "filter_pre": [".*"],
+///   "filter_post": ["**/generated/*.*"],
+///   "case_sensitive": false
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PatternConfig {
+    /// The globs passed to [`build_matchers`].
+    pub globs: Vec<String>,
+    /// The globs for the `filter_entry` parameter of [`match_paths`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filter_pre: Option<Vec<String>>,
+    /// The globs for the `filter_post` parameter of [`match_paths`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filter_post: Option<Vec<String>>,
+    /// The case sensitivity for all globs and filters without inline flags, see the
+    /// [module documentation](self#inline-flags). Defaults to a case sensitive match on all
+    /// platforms except for Windows.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case_sensitive: Option<bool>,
+}
+
+/// [`Matcher`]s and filters built from a [`PatternConfig`], see [`PatternConfig::build`].
+#[derive(Debug)]
+pub struct ConfigMatchers {
+    /// The [`Matcher`]s for the globs of the configuration.
+    pub matchers: Vec<Matcher<'static, path::PathBuf>>,
+    /// The filters that are applied before matching the paths.
+    pub filter_pre: Option<Vec<GlobSet<'static>>>,
+    /// The filters that are applied after matching the paths.
+    pub filter_post: Option<Vec<GlobSet<'static>>>,
+}
+
+impl ConfigMatchers {
+    /// Collects all paths using [`match_paths`], providing the matching and the filtered paths.
+    pub fn match_paths(self) -> (Vec<path::PathBuf>, Vec<path::PathBuf>) {
+        match_paths(self.matchers, self.filter_pre, self.filter_post)
+    }
+}
+
+impl PatternConfig {
+    /// Builds the [`Matcher`]s for all globs relative to `root`, and the [`GlobSet`]s for the
+    /// configured filters.
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`build_matchers`] and [`build_glob_set`].
+    pub fn build<P>(&self, root: P) -> Result<ConfigMatchers, String>
+    where
+        P: AsRef<path::Path>,
+    {
+        fn as_str(globs: &Option<Vec<String>>) -> Option<Vec<&str>> {
+            globs
+                .as_ref()
+                .map(|globs| globs.iter().map(String::as_str).collect())
+        }
+        fn into_owned(sets: Option<Vec<GlobSet<'_>>>) -> Option<Vec<GlobSet<'static>>> {
+            sets.map(|sets| sets.into_iter().map(GlobSet::into_owned).collect())
+        }
+
+        let case_sensitive = self.case_sensitive.unwrap_or(!cfg!(windows));
+        let globs: Vec<_> = self.globs.iter().map(String::as_str).collect();
+        let matchers = build_matchers_with(&globs, root, case_sensitive)?
+            .into_iter()
+            .map(Matcher::into_owned)
+            .collect();
+
+        let filter_pre = build_glob_set(&as_str(&self.filter_pre), case_sensitive)?;
+        let filter_post = build_glob_set(&as_str(&self.filter_post), case_sensitive)?;
+        Ok(ConfigMatchers {
+            matchers,
+            filter_pre: into_owned(filter_pre),
+            filter_post: into_owned(filter_post),
+        })
+    }
+
+    /// Collects all paths for the globs relative to `root`, applying the configured filters.
+    ///
+    /// Builds the [`Matcher`]s and [`GlobSet`]s (see [`PatternConfig::build`]) and passes them to
+    /// [`match_paths`], which provides the matching and the filtered paths.
+    ///
+    /// ```
+    /// # fn example() -> Result<(), String> {
+    /// let config = globmatch::wrappers::PatternConfig {
+    ///     globs: vec!["test-files/c-simple/**/*.txt".to_string()],
+    ///     filter_post: Some(vec!["**/a0/*".to_string()]),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let (paths, filtered) = config.match_paths(env!("CARGO_MANIFEST_DIR"))?;
+    /// assert_eq!(2 + 1 + 1, paths.len());
+    /// assert_eq!(3, filtered.len());
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Refer to [`build_matchers`] and [`build_glob_set`].
+    pub fn match_paths<P>(
+        &self,
+        root: P,
+    ) -> Result<(Vec<path::PathBuf>, Vec<path::PathBuf>), String>
+    where
+        P: AsRef<path::Path>,
+    {
+        Ok(self.build(root)?.match_paths())
+    }
+}
+
+/// Loads a [`PatternConfig`] from a JSON or TOML file and builds its [`ConfigMatchers`].
+///
+/// The format is determined by the extension of the file, i.e., `.json` or `.toml`. The globs
+/// of the configuration are relative to the directory containing the configuration file, such
+/// that the paths can be resolved independent of the current working directory.
+///
+/// ```no_run
+/// # fn example() -> Result<(), String> {
+/// let config = globmatch::wrappers::from_config_file("path/to/globs.toml")?;
+/// let (paths, filtered) = config.match_paths();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Requires the `config` feature.
+///
+/// # Errors
+///
+/// Fails if the file cannot be read or parsed, or if the extension is not supported. Refer to
+/// [`PatternConfig::build`] for the errors of the globs.
+#[cfg(feature = "config")]
+pub fn from_config_file<P>(path: P) -> Result<ConfigMatchers, String>
+where
+    P: AsRef<path::Path>,
+{
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read '{}': {}", path.display(), err))?;
+
+    let config: PatternConfig = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&content).map_err(|err| err.to_string()),
+        Some("toml") => toml::from_str(&content).map_err(|err| err.to_string()),
+        _ => Err("Unsupported file extension, expected 'json' or 'toml'".to_string()),
+    }
+    .map_err(|err| format!("Failed to parse '{}': {}", path.display(), err))?;
+
+    // relative paths without a parent folder are relative to the working directory
+    let root = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => path::Path::new("."),
+    };
+    config.build(root)
+}
+
+/// Ordering of the paths provided by [`match_paths_ordered`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PathOrder {
+    /// Sorts all paths and removes duplicates. This is the order used by [`match_paths`].
+    #[default]
+    Sorted,
+    /// Keeps the paths in the order in which they have been discovered, including duplicates
+    /// that are found by more than one [`Matcher`].
+    Discovered,
+    /// Keeps the order of discovery but only keeps the first occurrence of each path.
+    DiscoveredUnique,
+    /// Keeps the order of discovery but only keeps the first path of each underlying file, e.g.,
+    /// for overlapping roots or hard links, see [`Builder::dedup_files`].
+    DiscoveredUniqueFiles,
+}
+
+impl PathOrder {
+    /// Applies the ordering to the provided list of paths.
+    fn apply(&self, mut paths: Vec<path::PathBuf>) -> Vec<path::PathBuf> {
+        match self {
+            PathOrder::Sorted => {
+                paths.sort_unstable();
+                paths.dedup();
+            }
+            PathOrder::Discovered => {}
+            PathOrder::DiscoveredUnique => {
+                let mut seen = collections::HashSet::new();
+                paths.retain(|p| seen.insert(p.clone()));
+            }
+            PathOrder::DiscoveredUniqueFiles => {
+                let mut seen = collections::HashSet::new();
+                paths.retain(|p| match utils::file_id(p, true) {
+                    Some(id) => seen.insert(id),
+                    None => true,
+                });
+            }
+        }
+        paths
+    }
+}
+
 /// Collects all paths using a set of [`Matcher`]s and optional filters.
 ///
 /// This function iterates over all `candidates` to resolve the paths for each [`Matcher`] in the
 /// list of candidates. A common set of filters is applied to each candidate.
 ///
+/// The resulting paths are sorted and duplicates are removed, use [`match_paths_ordered`] to
+/// retain the order in which the paths have been discovered.
+///
 /// # Filters
 ///
 /// The optional `filter_entry` will be passed to the [`crate::IterAll::filter_entry`] call,
 /// filtering files and folders *before* matching any of the paths of each candidate. If no
 /// `filter_entry` is provided this function filters all hidden paths by applying the
-/// [`crate::is_hidden_entry`] utility function.
+/// [`crate::is_hidden_entry`] utility function, use [`match_paths_report_with`] to apply a
+/// different [`HiddenPolicy`].
 ///
 /// The optional `filter_post` is used to apply a filter *after* matching the paths.
 pub fn match_paths<P>(
@@ -168,62 +701,300 @@ pub fn match_paths<P>(
 where
     P: AsRef<path::Path>,
 {
-    let mut filtered = vec![];
+    match_paths_ordered(candidates, filter_entry, filter_post, PathOrder::Sorted)
+}
 
-    let paths = candidates
-        .into_iter()
-        .flat_map(|m| {
-            m.into_iter()
-                .filter_entry(|path| {
-                    match &filter_entry {
-                        // yield all entries if no pattern have been provided
-                        // but try_for_each yields all elements for an empty vector (see test)
-                        // Some(patterns) if patterns.is_empty() => true,
-                        // Some(patterns) if !patterns.is_empty() => {
-                        Some(patterns) => {
-                            let do_filter = patterns
-                                .iter()
-                                .try_for_each(|glob| match glob.is_match(path) {
-                                    true => None,      // path is a match, abort on first match
-                                    false => Some(()), // path is not a match, continue with 'ok'
-                                })
-                                .is_none(); // the value remains "Some" if no match was encountered
-                            !do_filter
-                        }
-                        _ => !utils::is_hidden_entry(path), // yield entries that are not hidden
-                    }
-                })
-                .flatten()
-                .collect::<Vec<_>>()
-        })
-        // .filter(|path| path.as_path().is_file()) // accept only files
-        .filter(|path| match &filter_post {
-            None => true,
-            Some(patterns) => {
-                let do_filter = patterns
-                    .iter()
-                    .try_for_each(|glob| match glob.is_match(path) {
-                        true => None,      // path is a match, abort on first match in filter_post
-                        false => Some(()), // path is not a match, continue with 'ok'
-                    })
-                    .is_none(); // the value remains "Some" if no match was encountered
-                if do_filter {
-                    filtered.push(path::PathBuf::from(path));
-                }
-                !do_filter
+/// Collects all paths using a set of [`Matcher`]s and optional filters in the given `order`.
+///
+/// Behaves exactly like [`match_paths`] but allows to keep the order in which the paths have been
+/// discovered, e.g., to process parent directories before their children. The `order` is applied
+/// to both, the matched and the filtered paths.
+pub fn match_paths_ordered<P>(
+    candidates: Vec<Matcher<'_, P>>,
+    filter_entry: Option<Vec<GlobSet<'_>>>,
+    filter_post: Option<Vec<GlobSet<'_>>>,
+    order: PathOrder,
+) -> (Vec<path::PathBuf>, Vec<path::PathBuf>)
+where
+    P: AsRef<path::Path>,
+{
+    let report = match_paths_report(candidates, filter_entry, filter_post, order);
+    (report.paths, report.filtered)
+}
+
+/// Collects all paths using a set of [`Matcher`]s and filters that implement [`PathMatcher`].
+///
+/// Behaves like [`match_paths`], but accepts any [`PathMatcher`] as filters, e.g., matchers that
+/// have been combined using [`PathMatcher::and`] or [`PathMatcher::not`]. Entries that match the
+/// `filter_entry` are skipped, including all of their sub-paths, and paths that match the
+/// `filter_post` are removed after matching. Hidden paths are not skipped unless the
+/// `filter_entry` matches them.
+///
+/// ```
+/// use globmatch::PathMatcher;
+///
+/// # fn example() -> Result<(), String> {
+/// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+/// let candidates = globmatch::wrappers::build_matchers(&["**/*.txt"], &root)?;
+///
+/// let hidden = |p: &std::path::Path| globmatch::is_hidden_path(p);
+/// let a0 = globmatch::Builder::new("**/a0").build_glob()?;
+/// let b = globmatch::Builder::new("**/b_*").build_glob_set()?;
+///
+/// let (paths, filtered) = globmatch::wrappers::match_paths_by(candidates, hidden.or(a0), b);
+/// assert_eq!(3, paths.len());
+/// assert_eq!(1, filtered.len());
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub fn match_paths_by<P, E, F>(
+    candidates: Vec<Matcher<'_, P>>,
+    filter_entry: E,
+    filter_post: F,
+) -> (Vec<path::PathBuf>, Vec<path::PathBuf>)
+where
+    P: AsRef<path::Path>,
+    E: PathMatcher,
+    F: PathMatcher,
+{
+    let mut paths = vec![];
+    for m in candidates {
+        let iter = m.into_iter().filter_entry(|p| !filter_entry.is_match(p));
+        paths.extend(iter.flatten());
+    }
+    let (filtered, paths) = paths.into_iter().partition(|p| filter_post.is_match(p));
+    (
+        PathOrder::Sorted.apply(paths),
+        PathOrder::Sorted.apply(filtered),
+    )
+}
+
+/// Result of [`match_paths_report`], providing diagnostics in addition to the paths.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchReport {
+    /// The matching paths, see [`match_paths`].
+    pub paths: Vec<path::PathBuf>,
+    /// The paths that have been removed by the `filter_post`, see [`match_paths`].
+    pub filtered: Vec<path::PathBuf>,
+    /// The number of paths matched by each [`Matcher`], in the order of the candidates. Paths
+    /// that are skipped by the `filter_entry` are not counted, whereas paths that are removed by
+    /// the `filter_post` are.
+    pub counts: Vec<usize>,
+    /// The original globs of all [`Matcher`]s that did not match any path.
+    pub unmatched: Vec<String>,
+    /// The number of errors that occurred while walking, e.g., for unreadable folders.
+    pub errors: usize,
+    /// The total number of file system entries that have been walked by all [`Matcher`]s,
+    /// including the entries that are skipped by the `filter_entry`.
+    pub entries: usize,
+    /// The time needed to walk and filter all paths.
+    pub elapsed: std::time::Duration,
+}
+
+/// Collects all paths using a set of [`Matcher`]s and optional filters, providing diagnostics.
+///
+/// Behaves exactly like [`match_paths_ordered`] but provides a [`MatchReport`], e.g., to inform
+/// the users of a tool about globs that did not match any path.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+/// let candidates = globmatch::wrappers::build_matchers(&["**/*.txt", "**/*.rs"], &root)?;
+///
+/// let report = globmatch::wrappers::match_paths_report(
+///     candidates,
+///     None,
+///     None,
+///     globmatch::wrappers::PathOrder::Sorted,
+/// );
+/// assert_eq!(vec![7, 0], report.counts);
+/// assert_eq!(vec!["**/*.rs"], report.unmatched);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub fn match_paths_report<P>(
+    candidates: Vec<Matcher<'_, P>>,
+    filter_entry: Option<Vec<GlobSet<'_>>>,
+    filter_post: Option<Vec<GlobSet<'_>>>,
+    order: PathOrder,
+) -> MatchReport
+where
+    P: AsRef<path::Path>,
+{
+    let hidden = HiddenPolicy::default();
+    match_paths_report_with(candidates, filter_entry, filter_post, order, &hidden)
+}
+
+/// Collects all paths using a set of [`Matcher`]s and optional filters, skipping the entries
+/// that are hidden by the given [`HiddenPolicy`].
+///
+/// Behaves exactly like [`match_paths_report`], but the `hidden` policy replaces the default
+/// filter that is applied if no `filter_entry` is provided, e.g., to also skip `__pycache__`
+/// folders or temporary files.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+/// let candidates = globmatch::wrappers::build_matchers(&["**/*.txt"], &root)?;
+///
+/// let mut hidden = globmatch::HiddenPolicy::default();
+/// hidden.name("b");
+/// let report = globmatch::wrappers::match_paths_report_with(
+///     candidates,
+///     None,
+///     None,
+///     globmatch::wrappers::PathOrder::Sorted,
+///     &hidden,
+/// );
+/// assert_eq!(vec![6], report.counts);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub fn match_paths_report_with<P>(
+    candidates: Vec<Matcher<'_, P>>,
+    filter_entry: Option<Vec<GlobSet<'_>>>,
+    filter_post: Option<Vec<GlobSet<'_>>>,
+    order: PathOrder,
+    hidden: &HiddenPolicy,
+) -> MatchReport
+where
+    P: AsRef<path::Path>,
+{
+    let start = std::time::Instant::now();
+    let mut report = MatchReport::default();
+    let mut paths = vec![];
+
+    for m in candidates {
+        let glob = m.glob().to_string();
+        let mut count = 0;
+        report.entries += walk_filtered(m, &filter_entry, hidden, |item| match item {
+            Ok(path) => {
+                count += 1;
+                paths.push(path);
             }
+            Err(_) => report.errors += 1,
         });
+        report.counts.push(count);
+        if count == 0 {
+            report.unmatched.push(glob);
+        }
+    }
 
-    let mut paths: Vec<_> = paths.collect();
-    paths.sort_unstable();
-    paths.dedup();
+    // .filter(|path| path.as_path().is_file()) // accept only files
+    let filtered = apply_filter_post(&mut paths, &filter_post, |path| path);
 
-    filtered.sort_unstable();
-    filtered.dedup();
+    report.paths = order.apply(paths);
+    report.filtered = order.apply(filtered);
+    report.elapsed = start.elapsed();
+    report
+}
 
+/// Collects all paths using a set of [`Matcher`]s and optional filters, attributing each path to
+/// the [`Matcher`]s that yielded it.
+///
+/// Behaves like [`match_paths`] but provides the indices of all `candidates` that matched a path
+/// together with the path itself, e.g., to apply different rules depending on the glob. The
+/// indices are sorted in ascending order and the paths are sorted and free of duplicates. The
+/// filtered paths are provided without any attribution.
+///
+/// ```
+/// # fn example() -> Result<(), String> {
+/// let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+/// let candidates = globmatch::wrappers::build_matchers(&["**/b_0.txt", "b/*.txt"], &root)?;
+///
+/// let (paths, _) = globmatch::wrappers::match_paths_attributed(candidates, None, None);
+/// assert_eq!(1, paths.len());
+/// assert_eq!(vec![0, 1], paths[0].1);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn match_paths_attributed<P>(
+    candidates: Vec<Matcher<'_, P>>,
+    filter_entry: Option<Vec<GlobSet<'_>>>,
+    filter_post: Option<Vec<GlobSet<'_>>>,
+) -> (Vec<(path::PathBuf, Vec<usize>)>, Vec<path::PathBuf>)
+where
+    P: AsRef<path::Path>,
+{
+    let hidden = HiddenPolicy::default();
+    let mut attributed = collections::BTreeMap::<path::PathBuf, Vec<usize>>::new();
+
+    for (idx, m) in candidates.into_iter().enumerate() {
+        walk_filtered(m, &filter_entry, &hidden, |item| {
+            if let Ok(path) = item {
+                let indices = attributed.entry(path).or_default();
+                // a single matcher never yields the same path twice
+                if indices.last() != Some(&idx) {
+                    indices.push(idx);
+                }
+            }
+        });
+    }
+
+    let mut paths: Vec<_> = attributed.into_iter().collect();
+    let filtered = apply_filter_post(&mut paths, &filter_post, |(path, _)| path);
     (paths, filtered)
 }
 
+/// Walks all paths of the [`Matcher`], passing each item to `f`, and provides the number of
+/// walked entries.
+///
+/// Entries that match the `filter_entry` are skipped, or the entries that are hidden by the
+/// `hidden` policy if no `filter_entry` is provided, see [`match_paths`].
+fn walk_filtered<P, F>(
+    m: Matcher<'_, P>,
+    filter_entry: &Option<Vec<GlobSet<'_>>>,
+    hidden: &HiddenPolicy,
+    mut f: F,
+) -> usize
+where
+    P: AsRef<path::Path>,
+    F: FnMut(Result<path::PathBuf, Error>),
+{
+    let mut iter = m.into_iter().filter_entry(|path| {
+        match filter_entry {
+            // an empty list of patterns does not match any path, i.e., all entries
+            // are yielded
+            Some(patterns) => !is_match_ordered(patterns, path),
+            _ => !hidden.is_hidden_entry(path), // yield entries that are not hidden
+        }
+    });
+    for item in iter.by_ref() {
+        f(item);
+    }
+    iter.entries()
+}
+
+/// Removes all items whose path matches the `filter_post` and provides the removed paths, see
+/// [`match_paths`].
+fn apply_filter_post<T, F>(
+    items: &mut Vec<T>,
+    filter_post: &Option<Vec<GlobSet<'_>>>,
+    path_of: F,
+) -> Vec<path::PathBuf>
+where
+    F: Fn(&T) -> &path::Path,
+{
+    let mut filtered = vec![];
+    if let Some(patterns) = filter_post {
+        items.retain(|item| {
+            let path = path_of(item);
+            let do_filter = is_match_ordered(patterns, path);
+            if do_filter {
+                filtered.push(path::PathBuf::from(path));
+            }
+            !do_filter
+        });
+    }
+    filtered
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,4 +1078,308 @@ mod tests {
         assert_eq!(5, filtered.len());
         Ok(())
     }
+
+    #[test]
+    fn test_order() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        // the second pattern matches a subset of the paths of the first pattern
+        let patterns = vec!["test-files/c-simple/a/**", "test-files/c-simple/**/*.txt"];
+
+        let (sorted, _) = match_paths_ordered(
+            build_matchers(&patterns, root)?,
+            None,
+            None,
+            PathOrder::Sorted,
+        );
+        let (discovered, _) = match_paths_ordered(
+            build_matchers(&patterns, root)?,
+            None,
+            None,
+            PathOrder::Discovered,
+        );
+        let (unique, _) = match_paths_ordered(
+            build_matchers(&patterns, root)?,
+            None,
+            None,
+            PathOrder::DiscoveredUnique,
+        );
+
+        // walkdir yields parent folders before their children
+        let pos = |p: &str| {
+            unique
+                .iter()
+                .position(|u| u.ends_with(p))
+                .expect("path not found")
+        };
+        assert!(pos("a/a0") < pos("a/a0/a0_0.txt"));
+
+        assert!(discovered.len() > unique.len());
+        assert_eq!(sorted.len(), unique.len());
+
+        let mut unique_sorted = unique.clone();
+        unique_sorted.sort_unstable();
+        assert_eq!(sorted, unique_sorted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_case_flag() -> Result<(), String> {
+        assert_eq!((Some(false), "*.txt"), split_case_flag("(?i)*.txt"));
+        assert_eq!((Some(true), "*.txt"), split_case_flag("(?-i)*.txt"));
+        assert_eq!((None, "*.txt"), split_case_flag("*.txt"));
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let patterns = vec![
+            "(?-i)test-files/c-simple/a/a0/a*.txt",
+            "(?i)test-files/c-simple/a/a0/a*.md",
+        ];
+        let (paths, _) = match_paths(build_matchers(&patterns, root)?, None, None);
+        assert_eq!(3, paths.len());
+
+        let patterns = vec!["(?i)test-files/c-simple/a/a0/a*.txt"];
+        let (paths, _) = match_paths(build_matchers(&patterns, root)?, None, None);
+        assert_eq!(3, paths.len());
+
+        let filter = build_glob_set(&Some(vec!["(?i)A0_*.txt"]), true)?.unwrap();
+        assert!(filter[0].is_match("a/a0/a0_0.txt"));
+        let filter = build_glob_set(&Some(vec!["(?-i)A0_*.txt"]), false)?.unwrap();
+        assert!(!filter[0].is_match("a/a0/a0_0.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_negation() -> Result<(), String> {
+        assert_eq!((true, "(?i)*.txt"), split_negation("!(?i)*.txt"));
+        assert_eq!((false, "*.txt"), split_negation("*.txt"));
+
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let patterns = vec!["**/*.txt", "!a/**", "a/a0/a0_*", "!(?i)**/A0_1.*"];
+        let candidates = build_matchers(&patterns, &root)?;
+        assert_eq!(2, candidates.len());
+
+        let (paths, _) = match_paths(candidates, Some(vec![]), None);
+        let paths: Vec<_> = paths
+            .iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                ".hidden/h_0.txt",
+                ".hidden/h_1.txt",
+                "a/a0/a0_0.txt",
+                "a/a0/a0_2.md",
+                "b/b_0.txt",
+                "some_file.txt"
+            ],
+            paths
+        );
+
+        // a negation without a previous glob does not match anything
+        assert!(build_matchers(&["!**/*.txt"], &root)?.is_empty());
+        assert!(build_matchers(&["**/*.txt", "!"], &root).is_err());
+
+        let filter_post = build_glob_set(&Some(vec!["a/**", "!**/a0/*.txt"]), true)?;
+        assert!(filter_post.as_ref().unwrap()[1].is_negated());
+        let candidates = build_matchers(&["a/**/*.txt"], &root)?;
+        let (paths, filtered) = match_paths(candidates, None, filter_post);
+        assert_eq!(3, paths.len());
+        assert_eq!(2, filtered.len());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_config() -> Result<(), String> {
+        let config: PatternConfig = serde_json::from_str(
+            r#"{
+                "globs": ["test-files/c-simple/**/a*.txt", "!**/a1/*"],
+                "filter_post": ["**/A0_*"],
+                "case_sensitive": false
+            }"#,
+        )
+        .map_err(|err| err.to_string())?;
+        assert_eq!(None, config.filter_pre);
+
+        let (paths, filtered) = config.match_paths(env!("CARGO_MANIFEST_DIR"))?;
+        assert_eq!(1, paths.len());
+        assert_eq!(3, filtered.len());
+
+        let json = serde_json::to_string(&config).map_err(|err| err.to_string())?;
+        let parsed: PatternConfig = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        assert_eq!(config, parsed);
+
+        assert!(serde_json::from_str::<PatternConfig>(r#"{"filter_pre": []}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "config")]
+    fn test_config_file() -> Result<(), String> {
+        let root = utils::TempDir::new("config")?;
+        root.write("src/main.rs", "")?;
+        root.write("src/lib.rs", "")?;
+        root.write("src/generated/out.rs", "")?;
+        root.write(
+            "globs.json",
+            r#"{"globs": ["src/**/*.rs"], "filter_post": ["**/generated/*"]}"#,
+        )?;
+        root.write("globs.toml", "globs = [\"src/*.rs\", \"!**/lib.rs\"]\n")?;
+        root.write("globs.yaml", "")?;
+
+        let (paths, filtered) = from_config_file(root.join("globs.json"))?.match_paths();
+        assert_eq!((2, 1), (paths.len(), filtered.len()));
+
+        let (paths, _) = from_config_file(root.join("globs.toml"))?.match_paths();
+        assert_eq!(vec![root.join("src/main.rs")], paths);
+
+        let yaml = from_config_file(root.join("globs.yaml")).unwrap_err();
+        let missing = from_config_file(root.join("missing.json")).unwrap_err();
+
+        assert!(yaml.contains("Unsupported file extension"));
+        assert!(missing.starts_with("Failed to read"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_matched_globs() -> Result<(), String> {
+        let patterns = Some(vec!["**/*.txt", "!a/**", "(?i)**/A0_*"]);
+        let patterns = build_glob_set(&patterns, true)?.unwrap();
+
+        assert_eq!(vec![0, 1, 2], matches(&patterns, "a/a0/a0_1.txt"));
+        assert_eq!(
+            vec!["a/**", "**/A0_*"],
+            matched_globs(&patterns, "a/a0/a0_2.md")
+        );
+        assert_eq!(vec!["**/*.txt"], matched_globs(&patterns, "b/b_0.txt"));
+        assert!(matches(&patterns, "b/b_1.md").is_empty());
+        assert!(matches(&[], "b/b_0.txt").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_report() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let patterns = vec!["a/**/*.txt", "**/*.md", "b/*.md", "**/*.txt"];
+        let filter_post = build_glob_set(&Some(vec!["**/a0/*"]), true)?;
+
+        let report = match_paths_report(
+            build_matchers(&patterns, &root)?,
+            Some(vec![]),
+            filter_post,
+            PathOrder::Sorted,
+        );
+        assert_eq!(vec![5, 1, 0, 9], report.counts);
+        assert_eq!(vec!["b/*.md"], report.unmatched);
+        assert_eq!((6, 4), (report.paths.len(), report.filtered.len()));
+        assert_eq!(0, report.errors);
+        assert!(report.entries > 0);
+
+        let (paths, filtered) = match_paths(build_matchers(&patterns, &root)?, Some(vec![]), None);
+        let report = match_paths_report(
+            build_matchers(&patterns, &root)?,
+            Some(vec![]),
+            None,
+            PathOrder::Sorted,
+        );
+        assert_eq!((paths, filtered), (report.paths, report.filtered));
+        Ok(())
+    }
+
+    #[test]
+    fn test_attributed() -> Result<(), String> {
+        let root = format!("{}/test-files/c-simple", env!("CARGO_MANIFEST_DIR"));
+        let patterns = vec!["a/**/*.txt", "**/*.md", "**/a0/*.txt"];
+        let filter_post = build_glob_set(&Some(vec!["**/*.md"]), true)?;
+
+        let (paths, filtered) =
+            match_paths_attributed(build_matchers(&patterns, &root)?, None, filter_post);
+        let (expected, _) = match_paths(
+            build_matchers(&patterns, &root)?,
+            None,
+            build_glob_set(&Some(vec!["**/*.md"]), true)?,
+        );
+
+        assert_eq!(
+            expected,
+            paths.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(1, filtered.len());
+        for (path, indices) in paths {
+            let in_a0 = path.parent().unwrap().ends_with("a0");
+            assert_eq!(if in_a0 { vec![0, 2] } else { vec![0] }, indices);
+        }
+
+        // hidden paths are skipped like for the other wrappers
+        let patterns = vec!["**/*.txt"];
+        let (paths, _) = match_paths_attributed(build_matchers(&patterns, &root)?, None, None);
+        let report = match_paths_report(
+            build_matchers(&patterns, &root)?,
+            None,
+            None,
+            PathOrder::Sorted,
+        );
+        assert_eq!(
+            report.paths,
+            paths.into_iter().map(|(p, _)| p).collect::<Vec<_>>()
+        );
+        assert!(report.paths.iter().all(|p| !utils::is_hidden_path(p)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient() -> Result<(), String> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let patterns = vec!["", "test-files/c-simple/*.txt", "(?i)", "  ", "! "];
+
+        assert!(build_matchers(&patterns, root).is_err());
+
+        let (candidates, warnings) = build_matchers_lenient(&patterns, root)?;
+        assert_eq!(1, candidates.len());
+        assert_eq!(
+            vec![
+                "Skipped empty pattern at index 0",
+                "Skipped empty pattern at index 2",
+                "Skipped empty pattern at index 3",
+                "Skipped empty pattern at index 4"
+            ],
+            warnings
+        );
+
+        let (paths, _) = match_paths(candidates, None, None);
+        assert_eq!(1, paths.len());
+
+        let patterns = Some(vec!["  ", "*.txt", ""]);
+        assert!(build_glob_set(&patterns, true).is_err());
+        let (sets, warnings) = build_glob_set_lenient(&patterns, true)?;
+        assert_eq!(1, sets.map_or(0, |sets| sets.len()));
+        assert_eq!(2, warnings.len());
+        assert!(matches!(build_glob_set_lenient(&None, true)?, (None, w) if w.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!("a/**", normalize_pattern("./a/**"));
+        assert_eq!("a/**", normalize_pattern("a/./**/"));
+        assert_eq!("../a/*.txt", normalize_pattern("  .././a//*.txt\t"));
+        assert_eq!("**/*.rs", normalize_pattern("**/**/**/*.rs"));
+        assert_eq!("(?i)a/**", normalize_pattern("(?i)./a/**"));
+        assert_eq!("/a", normalize_pattern("/a/"));
+        assert_eq!(".", normalize_pattern("./"));
+        assert_eq!("!(?i)a/**", normalize_pattern(" !(?i)./a/**"));
+
+        let (patterns, removed) = normalize_patterns_report(&[
+            "./a/**", "a/**", "a/**", " ", "(?i)a/**", "b/*.md", "b//*.md",
+        ]);
+        assert_eq!(vec!["a/**", "(?i)a/**", "b/*.md"], patterns);
+        assert_eq!(
+            vec![
+                "'a/**': Duplicate glob",
+                "'a/**': Duplicate glob",
+                "' ': Empty glob",
+                "'b//*.md': Equivalent to 'b/*.md'",
+            ],
+            removed
+        );
+    }
 }